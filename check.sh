@@ -6,6 +6,8 @@ cargo check --workspace --all-features --all-targets
 cargo clippy --workspace --all-features --all-targets -- -D warnings -W clippy::all
 cargo test --workspace --all-features --all-targets
 cargo test --workspace --all-features --doc
+cargo run --features render-test -- --render-test
+cargo run -- --self-test
 cargo fmt --all -- --check
 
 cargo check --workspace --all-features --all-targets --target wasm32-unknown-unknown
@@ -1,25 +1,77 @@
+mod ast_tree;
+#[cfg(not(target_arch = "wasm32"))]
+mod capture_worker;
+mod clipboard;
 mod color;
+mod completion;
+mod crash;
+mod descriptions;
+mod engine_info;
+mod escaped_bytes;
+mod explain;
+mod export;
+mod hir_tree;
+mod journal;
+mod library;
+mod lint;
 mod loop_vec;
 mod parsing;
+mod refactor;
+#[cfg(all(feature = "render-test", not(target_arch = "wasm32")))]
+mod render_test;
+mod replacement;
+#[cfg(any(test, feature = "self-test"))]
+mod self_test;
 mod shape;
+#[cfg(any(target_arch = "wasm32", test))]
+mod share;
+mod snippets;
 mod state;
+mod syntax_docs;
 mod text;
+mod timings;
+mod tutorial;
 mod ui;
+mod verbose;
 
+pub use self::crash::install_panic_hook;
+#[cfg(all(feature = "render-test", not(target_arch = "wasm32")))]
+pub use self::render_test::{run_render_test, RenderTestFixture, RENDER_TEST_FIXTURES};
+#[cfg(feature = "self-test")]
+pub use self::self_test::{run_self_test, run_self_test_case, SelfTestCase, SELF_TEST_CASES};
+
+#[cfg(not(target_arch = "wasm32"))]
+use self::ui::{apply_zoom, zoom_shortcuts};
 use self::{
+    crash::{take_crash_report, CrashRecovery, SNAPSHOT_INTERVAL, SNAPSHOT_KEY},
     state::AppState,
-    ui::{create_font_definitions, update_style},
+    ui::{
+        apply_ui_prefs, create_font_definitions, recovery::recovery, shortcuts::shortcuts,
+        update_style,
+    },
 };
 use eframe::{App, CreationContext, Frame, Storage};
-use egui::Context;
+use egui::{Context, Event};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(Default, Deserialize, Serialize)]
 #[serde(default)] // If we add new fields, give them default values when deserializing old state
 pub struct Application {
-    #[serde(skip)]
     state: AppState,
+    /// Information for recovering from a crash, if the app crashed the last time it was ran
+    #[serde(skip)]
+    recovery: Option<CrashRecovery>,
+    /// The time at which the last crash recovery snapshot was saved
+    #[serde(skip)]
+    last_snapshot_time: f64,
+    /// Whether there has been any input since the last crash recovery snapshot was saved
+    #[serde(skip)]
+    snapshot_outdated: bool,
+    /// The scale of the UI that the regex and input text were last laid out at
+    #[serde(skip)]
+    laid_out_pixels_per_point: f32,
 }
 
 impl Application {
@@ -33,9 +85,76 @@ impl Application {
             .set_style(update_style(cc.egui_ctx.style().as_ref().clone()));
 
         // Load previous app state (if any).
-        cc.storage
+        let mut app: Self = cc
+            .storage
             .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
-            .unwrap_or_default()
+            .unwrap_or_default();
+
+        // Opening a shared link reproduces the session that it was shared from
+        #[cfg(target_arch = "wasm32")]
+        if let Some(session) = share::take_session_from_location() {
+            session.apply(&mut app.state.widgets);
+        }
+
+        // The theme has to be restored before the text is laid out, as the highlighting colors depend on it
+        apply_ui_prefs(&cc.egui_ctx, &app.state.widgets.ui_prefs);
+        #[cfg(not(target_arch = "wasm32"))]
+        apply_zoom(
+            &cc.egui_ctx,
+            &app.state.widgets.ui_prefs,
+            cc.integration_info.native_pixels_per_point,
+        );
+        app.state.recompile(&cc.egui_ctx.style());
+        app.laid_out_pixels_per_point = cc.egui_ctx.pixels_per_point();
+
+        // Offer to recover from a crash if the app crashed the last time it was ran
+        app.recovery = take_crash_report().map(|report| CrashRecovery {
+            report,
+            snapshot: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, SNAPSHOT_KEY)),
+        });
+
+        app
+    }
+
+    /// Saves a snapshot of the widget state, separately from the normal persistence, to be restored after a crash
+    ///
+    /// Snapshots are only saved if there has been input since the last one, and at most once every `SNAPSHOT_INTERVAL`
+    fn save_snapshot(&mut self, ctx: &Context, frame: &mut Frame) {
+        let input = ctx.input();
+        let time = input.time;
+        self.snapshot_outdated |= input
+            .events
+            .iter()
+            .any(|event| !matches!(event, Event::PointerMoved(_)));
+        drop(input);
+
+        if !self.snapshot_outdated {
+            return;
+        }
+
+        if time - self.last_snapshot_time >= SNAPSHOT_INTERVAL {
+            if let Some(storage) = frame.storage_mut() {
+                eframe::set_value(storage, SNAPSHOT_KEY, &self.state.widgets);
+                storage.flush();
+            }
+            self.last_snapshot_time = time;
+            self.snapshot_outdated = false;
+        } else {
+            // Make sure the snapshot is saved even if there is no more input
+            ctx.request_repaint_after(Duration::from_secs_f64(SNAPSHOT_INTERVAL));
+        }
+    }
+
+    /// Lays out the regex and input text again if the scale of the UI has changed since they were last laid out,
+    /// as the positions of their glyphs, and so the connecting lines between them, depend on it
+    fn relayout_on_rescale(&mut self, ctx: &Context) {
+        let pixels_per_point = ctx.pixels_per_point();
+        if pixels_per_point != self.laid_out_pixels_per_point {
+            self.state.recompile(&ctx.style());
+            self.laid_out_pixels_per_point = pixels_per_point;
+        }
     }
 }
 
@@ -48,12 +167,33 @@ impl App for Application {
     /// Called each time the UI needs repainting, which may be many times per second. (Native)
     #[cfg(not(target_arch = "wasm32"))]
     fn update(&mut self, ctx: &Context, frame: &mut Frame) {
-        ui::native::root(ctx, &mut self.state, || frame.close());
+        self.save_snapshot(ctx, frame);
+        zoom_shortcuts(ctx, &mut self.state.widgets.ui_prefs);
+        apply_zoom(
+            ctx,
+            &self.state.widgets.ui_prefs,
+            frame.info().native_pixels_per_point,
+        );
+        self.relayout_on_rescale(ctx);
+        if self.recovery.is_some() {
+            recovery(ctx, &mut self.recovery, &mut self.state);
+        } else {
+            shortcuts(ctx, &mut self.state);
+            ui::native::root(ctx, &mut self.state, || frame.close());
+        }
     }
 
     /// Called each time the UI needs repainting, which may be many times per second. (Wasm)
     #[cfg(target_arch = "wasm32")]
-    fn update(&mut self, ctx: &Context, _: &mut Frame) {
-        ui::wasm::root(ctx, &mut self.state);
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+        self.save_snapshot(ctx, frame);
+        // The browser's own zoom is used on the web, but the text still has to be laid out again when it changes
+        self.relayout_on_rescale(ctx);
+        if self.recovery.is_some() {
+            recovery(ctx, &mut self.recovery, &mut self.state);
+        } else {
+            shortcuts(ctx, &mut self.state);
+            ui::wasm::root(ctx, &mut self.state);
+        }
     }
 }
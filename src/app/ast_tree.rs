@@ -0,0 +1,161 @@
+use super::text::GetRangeExt;
+use regex_syntax::ast::{
+    AssertionKind, Ast, GroupKind, LiteralKind, RepetitionKind, RepetitionRange,
+};
+use std::{cmp::Reverse, ops::Range};
+
+/// A node of a tree that describes the structure of a regex, built from its AST
+pub struct AstNode {
+    /// A human-readable description of the node
+    pub label: String,
+    /// The byte range of the pattern that the node was parsed from
+    pub span: Range<usize>,
+    pub children: Vec<AstNode>,
+}
+
+impl AstNode {
+    /// Builds a tree describing the given AST, which must have been parsed from the given pattern
+    pub fn from_ast(ast: &Ast, pattern: &str) -> Self {
+        Self {
+            label: describe_ast(ast, pattern),
+            span: ast.span().range(),
+            children: children(ast)
+                .iter()
+                .map(|ast| Self::from_ast(ast, pattern))
+                .collect(),
+        }
+    }
+}
+
+/// The span of each node of an AST along with its description, for finding the node at a position in the pattern
+#[derive(Default)]
+pub struct AstSpans {
+    /// The span, depth and description of each node, with parents before their children
+    spans: Vec<(Range<usize>, usize, String)>,
+}
+
+impl AstSpans {
+    /// Describes every node of the given AST, which must have been parsed from the given pattern
+    pub fn from_ast(ast: &Ast, pattern: &str) -> Self {
+        let mut spans = Self::default();
+        spans.visit_ast(ast, pattern, 0);
+        spans
+    }
+
+    /// Returns the span and description of the innermost node that contains the given byte offset, if any do
+    ///
+    /// Of nodes with the same span, the deepest one is the innermost
+    pub fn node_at(&self, offset: usize) -> Option<(&Range<usize>, &str)> {
+        self.spans
+            .iter()
+            .filter(|(span, _, _)| span.contains(&offset))
+            .min_by_key(|(span, depth, _)| (span.len(), Reverse(*depth)))
+            .map(|(span, _, description)| (span, description.as_str()))
+    }
+
+    fn visit_ast(&mut self, ast: &Ast, pattern: &str, depth: usize) {
+        self.spans
+            .push((ast.span().range(), depth, describe_ast(ast, pattern)));
+        for child in children(ast) {
+            self.visit_ast(child, pattern, depth + 1);
+        }
+    }
+}
+
+/// Returns the nodes directly inside of the given node of an AST
+pub fn children(ast: &Ast) -> &[Ast] {
+    match ast {
+        Ast::Repetition(repetition) => std::slice::from_ref(&*repetition.ast),
+        Ast::Group(group) => std::slice::from_ref(&*group.ast),
+        Ast::Alternation(alternation) => &alternation.asts,
+        Ast::Concat(concat) => &concat.asts,
+        _ => &[],
+    }
+}
+
+/// Returns a human-readable description of the given node of an AST, which must have been parsed from the given pattern,
+/// without describing the nodes inside of it
+pub fn describe_ast(ast: &Ast, pattern: &str) -> String {
+    let source = pattern.get(ast.span().range()).unwrap_or_default();
+    match ast {
+        Ast::Empty(_) => "Empty".into(),
+        Ast::Flags(_) => format!("Flags: {}", source),
+        Ast::Literal(literal) => match literal.kind {
+            LiteralKind::Verbatim => format!("Literal: {:?}", literal.c),
+            _ => format!("Escape: {} ({:?})", source, literal.c),
+        },
+        Ast::Dot(_) => "Any character: .".into(),
+        Ast::Assertion(assertion) => {
+            let description = match assertion.kind {
+                AssertionKind::StartLine => "start of line",
+                AssertionKind::EndLine => "end of line",
+                AssertionKind::StartText => "start of text",
+                AssertionKind::EndText => "end of text",
+                AssertionKind::WordBoundary => "word boundary",
+                AssertionKind::NotWordBoundary => "not a word boundary",
+            };
+            format!("Assertion: {}", description)
+        }
+        Ast::Class(_) => format!("Class: {}", source),
+        Ast::Repetition(repetition) => {
+            let count = match &repetition.op.kind {
+                RepetitionKind::ZeroOrOne => "zero or one time".into(),
+                RepetitionKind::ZeroOrMore => "zero or more times".into(),
+                RepetitionKind::OneOrMore => "one or more times".into(),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                    format!("exactly {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                    format!("at least {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::Bounded(n, m)) => {
+                    format!("between {} and {} times", n, m)
+                }
+            };
+            let greediness = if repetition.greedy { "greedy" } else { "lazy" };
+            format!("Repetition: {}, {}", count, greediness)
+        }
+        Ast::Group(group) => match &group.kind {
+            GroupKind::CaptureIndex(index) => format!("Capture group {}", index),
+            GroupKind::CaptureName(name) => {
+                format!("Capture group {} ({})", name.index, name.name)
+            }
+            GroupKind::NonCapturing(flags) if flags.items.is_empty() => {
+                "Non-capturing group".into()
+            }
+            GroupKind::NonCapturing(flags) => format!(
+                "Non-capturing group with flags {}",
+                pattern.get(flags.span.range()).unwrap_or_default()
+            ),
+        },
+        Ast::Alternation(alternation) => {
+            format!("Alternation of {} branches", alternation.asts.len())
+        }
+        Ast::Concat(concat) => format!("Sequence of {} items", concat.asts.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::parse_regex;
+
+    /// The innermost node is found, so the operator of a repetition explains the repetition rather than what it repeats
+    #[test]
+    fn innermost_node_at_offset() {
+        let pattern = "(a){2,5}b";
+        let ast = parse_regex(pattern, Default::default()).unwrap();
+        let spans = AstSpans::from_ast(&ast, pattern);
+
+        for (offset, expected) in [
+            (0, Some("Capture group 1")),
+            (1, Some("Literal: 'a'")),
+            (3, Some("Repetition: between 2 and 5 times, greedy")),
+            (8, Some("Literal: 'b'")),
+            (9, None),
+        ] {
+            let description = spans.node_at(offset).map(|(_, description)| description);
+            assert_eq!(description, expected, "Byte {}", offset);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::text::{CaptureSearch, CaptureTable};
+use regex::Regex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver},
+    Arc,
+};
+
+/// How many matches the worker finds between checking whether it has been cancelled and reporting its progress
+const CAPTURES_PER_STEP: usize = 1000;
+
+/// A message sent from the worker thread to the UI thread
+enum WorkerUpdate {
+    /// The byte offset in the text that the search has reached, along with the captures of the matches found since
+    /// the previous update
+    Progress(usize, CaptureTable),
+    Finished(CaptureSearch),
+}
+
+/// A search for captures that is continued on a background thread, so that a slow search doesn't stall the UI
+///
+/// The search is cancelled when the worker is dropped, such as when the regex or input text changes again
+pub struct CaptureWorker {
+    receiver: Receiver<WorkerUpdate>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CaptureWorker {
+    /// Continues the given search for captures of the given regex in the given text on a new thread
+    pub fn spawn(mut search: CaptureSearch, regex: Regex, text: String) -> Self {
+        let (sender, receiver) = channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let worker_cancelled = cancelled.clone();
+        std::thread::spawn(move || {
+            while !worker_cancelled.load(Ordering::Relaxed) {
+                let found = search.captures.len();
+                let finished = search.advance(&regex, &text, CAPTURES_PER_STEP);
+                let update = if finished {
+                    WorkerUpdate::Finished(std::mem::take(&mut search))
+                } else {
+                    WorkerUpdate::Progress(search.resume_offset, search.captures[found..].to_vec())
+                };
+
+                // The receiver is only dropped once the worker is, which cancels the search anyway
+                if sender.send(update).is_err() || finished {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Receives any updates from the worker thread, bringing the given search up to date with it,
+    /// and returns whether it has finished
+    ///
+    /// The search must be the one that the worker was spawned with, or an earlier version of it brought up to date
+    pub fn poll(&mut self, search: &mut CaptureSearch) -> bool {
+        for update in self.receiver.try_iter() {
+            match update {
+                WorkerUpdate::Progress(offset, captures) => {
+                    search.resume_offset = offset;
+                    search.captures.extend(captures);
+                }
+                WorkerUpdate::Finished(finished) => {
+                    *search = finished;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl Drop for CaptureWorker {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
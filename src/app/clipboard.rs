@@ -0,0 +1,85 @@
+use egui::Context;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+/// A read of the text on the system clipboard, which finishes straight away on native,
+/// but only once the browser has asked the user for permission on the web
+pub struct ClipboardRead {
+    receiver: Receiver<Result<String, String>>,
+}
+
+impl ClipboardRead {
+    /// Starts reading the clipboard, requesting a repaint of the given context once it has been read
+    pub fn start(ctx: &Context) -> Self {
+        let (sender, receiver) = channel();
+        read_clipboard(ctx.clone(), sender);
+        Self { receiver }
+    }
+
+    /// Returns the text on the clipboard, or why it couldn't be read, once the read has finished
+    pub fn poll(&self) -> Option<Result<String, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("The clipboard couldn't be read".into())),
+        }
+    }
+}
+
+/// Reads the text on the clipboard and sends it to the given sender (Native)
+#[cfg(not(target_arch = "wasm32"))]
+fn read_clipboard(ctx: Context, sender: Sender<Result<String, String>>) {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| err.to_string());
+    // The receiver is still alive, as it's only returned once this has finished
+    let _ = sender.send(text);
+    ctx.request_repaint();
+}
+
+/// Starts reading the text on the clipboard with the asynchronous clipboard API,
+/// sending it to the given sender once the browser has read it (Wasm)
+///
+/// `navigator.clipboard` is looked up dynamically, as `web-sys` only binds it with unstable APIs enabled
+#[cfg(target_arch = "wasm32")]
+fn read_clipboard(ctx: Context, sender: Sender<Result<String, String>>) {
+    use js_sys::{Function, Promise, Reflect};
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+    let describe = |err: JsValue| err.as_string().unwrap_or_else(|| format!("{:?}", err));
+    let promise = Reflect::get(&js_sys::global(), &"navigator".into())
+        .and_then(|navigator| Reflect::get(&navigator, &"clipboard".into()))
+        .and_then(|clipboard| {
+            let read_text = Reflect::get(&clipboard, &"readText".into())?;
+            read_text.dyn_into::<Function>()?.call0(&clipboard)
+        })
+        .and_then(|promise| promise.dyn_into::<Promise>());
+    let promise = match promise {
+        Ok(promise) => promise,
+        Err(_) => {
+            let _ = sender.send(Err(
+                "This browser doesn't allow the clipboard to be read".into()
+            ));
+            return;
+        }
+    };
+
+    let error_sender = sender.clone();
+    let error_ctx = ctx.clone();
+    let on_read = Closure::once(move |text: JsValue| {
+        let _ = sender.send(
+            text.as_string()
+                .ok_or_else(|| "The clipboard doesn't contain text".into()),
+        );
+        ctx.request_repaint();
+    });
+    let on_error = Closure::once(move |err: JsValue| {
+        let _ = error_sender.send(Err(describe(err)));
+        error_ctx.request_repaint();
+    });
+    let _ = promise.then2(&on_read, &on_error);
+
+    // The closures have to outlive this function to be called, and only one of them ever is,
+    // so they are leaked rather than dropped, which is a small cost for a button press
+    on_read.forget();
+    on_error.forget();
+}
@@ -1,28 +1,232 @@
 #![allow(dead_code)]
 
-use egui::{Color32, FontId, TextFormat};
+use super::parsing::TokenKind;
+use egui::{color::Hsva, Color32, FontId, Stroke, TextFormat};
+use serde::{Deserialize, Serialize};
 
 pub const FG_BLUE: Color32 = Color32::from_rgb(23, 159, 255);
 pub const FG_YELLOW: Color32 = Color32::from_rgb(255, 215, 0);
 pub const FG_PINK: Color32 = Color32::from_rgb(218, 112, 214);
 
-pub const FOREGROUND_COLORS: [Color32; 3] = [FG_BLUE, FG_YELLOW, FG_PINK];
-
 pub const BG_BLUE: Color32 = Color32::from_rgb(38, 77, 109);
 pub const BG_YELLOW: Color32 = Color32::from_rgb(108, 94, 32);
 pub const BG_PINK: Color32 = Color32::from_rgb(97, 63, 97);
 
-pub const BACKGROUND_COLORS: [Color32; 3] = [BG_BLUE, BG_YELLOW, BG_PINK];
+/// The colors of the first groups, which the generated colors are modelled on
+const ORIGINAL_GROUP_COLORS: [GroupColor; 3] = [
+    GroupColor::new(BG_BLUE, FG_BLUE),
+    GroupColor::new(BG_YELLOW, FG_YELLOW),
+    GroupColor::new(BG_PINK, FG_PINK),
+];
+
+/// The hue of the first group's color, as a fraction of a full turn
+const FIRST_HUE: f32 = 205.0 / 360.0;
+
+/// The fraction of a full turn that the hue is rotated backwards by between one group's color and the next,
+/// which spreads any number of hues as evenly as possible, and happens to pass close to yellow and then pink
+const GOLDEN_ANGLE: f32 = 137.507_77 / 360.0;
 
 pub const FG_RED: Color32 = Color32::RED;
 pub const BG_RED: Color32 = Color32::from_rgb(104, 41, 47);
 
+pub const FG_DARK_RED: Color32 = Color32::from_rgb(180, 20, 20);
+pub const BG_PALE_RED: Color32 = Color32::from_rgb(255, 205, 205);
+
+/// The color of text on a pale background
+pub const DARK_TEXT: Color32 = Color32::from_gray(30);
+
+/// The colors that a capture group is highlighted with
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct GroupColor {
+    /// The color that the text matched by the group is filled in with,
+    /// which is dark in the dark theme and pale in the light theme
+    pub background: Color32,
+    /// A brighter variant of the background, for lines and underlines drawn over the editor's background
+    pub foreground: Color32,
+}
+
+impl GroupColor {
+    /// The color of the implicit group of the whole match, which isn't highlighted
+    pub const NONE: Self = Self::new(Color32::TRANSPARENT, Color32::TRANSPARENT);
+
+    const fn new(background: Color32, foreground: Color32) -> Self {
+        Self {
+            background,
+            foreground,
+        }
+    }
+}
+
+/// Returns the color at the given index of an endless palette of group colors, each of which has a hue as far as
+/// possible from those of the colors before it, with variants that suit the given theme
+pub fn group_color(index: usize, dark_mode: bool) -> GroupColor {
+    let original = ORIGINAL_GROUP_COLORS.get(index);
+    let hue = match original {
+        Some(original) => Hsva::from(original.background).h,
+        None => (FIRST_HUE - index as f32 * GOLDEN_ANGLE).rem_euclid(1.0),
+    };
+
+    match original {
+        Some(&original) if dark_mode => original,
+        _ if dark_mode => GroupColor {
+            background: Hsva::new(hue, 0.65, 0.42, 1.0).into(),
+            foreground: Hsva::new(hue, 0.85, 1.0, 1.0).into(),
+        },
+        _ => GroupColor {
+            background: Hsva::new(hue, 0.22, 0.98, 1.0).into(),
+            foreground: Hsva::new(hue, 1.0, 0.7, 1.0).into(),
+        },
+    }
+}
+
+/// Returns the color of the text of an error message or an erroneous pattern
+pub fn error_color(dark_mode: bool) -> Color32 {
+    if dark_mode {
+        FG_RED
+    } else {
+        FG_DARK_RED
+    }
+}
+
+/// Returns the format that the erroneous part of some text is highlighted with
+pub fn error_highlight(font_id: FontId, dark_mode: bool) -> TextFormat {
+    let background = if dark_mode { BG_RED } else { BG_PALE_RED };
+    TextFormat::background(font_id, background)
+}
+
+/// Returns the format that a part of some text that is related to an error is highlighted with,
+/// which is underlined rather than filled in so that it can be told apart from the erroneous part itself
+pub fn error_aux_highlight(font_id: FontId, dark_mode: bool) -> TextFormat {
+    let color = error_color(dark_mode);
+    TextFormat {
+        underline: Stroke::new(1.5, color),
+        ..TextFormat::simple(font_id, color)
+    }
+}
+
+/// Returns whether the given color is dark enough that light text is easier to read on it than dark text
+pub fn is_dark(color: Color32) -> bool {
+    let [r, g, b, _] = color.to_array().map(f32::from);
+    0.299 * r + 0.587 * g + 0.114 * b < 128.0
+}
+
+/// The colors that the user has chosen for capture groups, in place of those of the generated palette
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CustomGroupColors {
+    /// The background chosen in place of the color at each index of the palette, if one was chosen
+    backgrounds: Vec<Option<Color32>>,
+}
+
+impl CustomGroupColors {
+    /// Returns the color at the given index of the palette,
+    /// which is made from the background chosen for it if there is one, otherwise see `group_color`
+    pub fn get(&self, index: usize, dark_mode: bool) -> GroupColor {
+        match self.chosen(index) {
+            Some(background) => {
+                // Lines are drawn in a brighter or darker shade of the chosen color, depending on the theme
+                let Hsva { h, s, .. } = Hsva::from(background);
+                let value = if dark_mode { 1.0 } else { 0.7 };
+                GroupColor {
+                    background,
+                    foreground: Hsva::new(h, s.max(0.5), value, 1.0).into(),
+                }
+            }
+            None => group_color(index, dark_mode),
+        }
+    }
+
+    /// Returns the background chosen for the color at the given index of the palette, if one was chosen
+    pub fn chosen(&self, index: usize) -> Option<Color32> {
+        self.backgrounds.get(index).copied().flatten()
+    }
+
+    /// Chooses the background of the color at the given index of the palette, or goes back to the generated one
+    pub fn choose(&mut self, index: usize, background: Option<Color32>) {
+        if self.backgrounds.len() <= index {
+            self.backgrounds.resize(index + 1, None);
+        }
+        self.backgrounds[index] = background;
+
+        while let Some(None) = self.backgrounds.last() {
+            self.backgrounds.pop();
+        }
+    }
+
+    /// Returns one more than the index in the palette of the last color that was chosen, or 0 if none were
+    pub fn len(&self) -> usize {
+        self.backgrounds.len()
+    }
+
+    /// Returns whether no colors have been chosen
+    pub fn is_empty(&self) -> bool {
+        self.backgrounds.is_empty()
+    }
+}
+
+/// Returns the first `count` colors of the palette of group colors, see `group_color`
+pub fn group_palette(count: usize, dark_mode: bool) -> Vec<GroupColor> {
+    (0..count)
+        .map(|index| group_color(index, dark_mode))
+        .collect()
+}
+
+/// Returns the color that tokens of the given kind are highlighted with in a pattern,
+/// which is lighter on dark backgrounds and darker on light backgrounds
+pub fn token_color(kind: TokenKind, dark_background: bool) -> Color32 {
+    let (dark, light) = match kind {
+        TokenKind::Literal => ((220, 220, 220), (40, 40, 40)),
+        TokenKind::Escape => ((215, 186, 125), (140, 90, 0)),
+        TokenKind::Class => ((78, 201, 176), (0, 128, 110)),
+        TokenKind::Quantifier => ((197, 134, 192), (150, 40, 170)),
+        TokenKind::Anchor => ((86, 156, 214), (0, 80, 200)),
+        TokenKind::Group => ((255, 215, 0), (150, 110, 0)),
+        TokenKind::Alternation => ((255, 140, 60), (200, 80, 0)),
+        TokenKind::Flags => ((156, 220, 254), (0, 100, 160)),
+    };
+
+    let (r, g, b) = if dark_background { dark } else { light };
+    Color32::from_rgb(r, g, b)
+}
+
+/// Returns a more contrasting and underlined variant of the given format, that the selected match is highlighted with
+/// to set it apart from the other matches, including the parts of it that no capture group matched
+pub fn emphasised_format(format: &TextFormat, dark_mode: bool) -> TextFormat {
+    // Dark backgrounds are brightened and pale ones are darkened, to stand out more against the editor's background
+    let [r, g, b, _] = format.background.to_array();
+    let (background, underline) = match (format.background.a(), dark_mode) {
+        (0, true) => (Color32::from_white_alpha(24), Color32::WHITE),
+        (0, false) => (Color32::from_black_alpha(16), DARK_TEXT),
+        _ if is_dark(format.background) => {
+            let lighten = |channel: u8| channel + ((255 - channel) as f32 * 0.3) as u8;
+            (
+                Color32::from_rgb(lighten(r), lighten(g), lighten(b)),
+                Color32::WHITE,
+            )
+        }
+        _ => {
+            let darken = |channel: u8| (channel as f32 * 0.85) as u8;
+            (
+                Color32::from_rgb(darken(r), darken(g), darken(b)),
+                DARK_TEXT,
+            )
+        }
+    };
+
+    TextFormat {
+        background,
+        underline: Stroke::new(1.0, underline),
+        ..format.clone()
+    }
+}
+
 pub trait FromBackgroundExt {
     fn background(font_id: FontId, background: Color32) -> Self;
 }
 
 impl FromBackgroundExt for TextFormat {
-    /// A parallel to the `TextFormat::simple` function, but for specifying the background color instead of the foreground color
+    /// A parallel to the `TextFormat::simple` function, but for specifying the background color instead of the foreground color,
+    /// with white text on dark backgrounds and dark text on pale ones
     fn background(font_id: FontId, background: Color32) -> Self {
         if background.a() == 0 {
             Self {
@@ -33,9 +237,53 @@ impl FromBackgroundExt for TextFormat {
             Self {
                 font_id,
                 background,
-                color: Color32::WHITE,
+                color: if is_dark(background) {
+                    Color32::WHITE
+                } else {
+                    DARK_TEXT
+                },
                 ..Default::default()
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_palette_starts_with_the_original_colors() {
+        assert_eq!(group_color(0, true).background, BG_BLUE);
+        assert_eq!(group_color(2, true).foreground, FG_PINK);
+    }
+
+    #[test]
+    fn group_palette_colors_are_distinct() {
+        for dark_mode in [true, false] {
+            let palette = group_palette(12, dark_mode);
+            for (index, group) in palette.iter().enumerate() {
+                assert!(
+                    !palette[..index].iter().any(|other| {
+                        other.background == group.background || other.foreground == group.foreground
+                    }),
+                    "Color {} is repeated",
+                    index
+                );
+            }
+
+            // Adjacent groups are the most likely to be confused, so their hues should be far apart
+            for (index, pair) in palette.windows(2).enumerate() {
+                let [a, b] = [pair[0], pair[1]].map(|color| Hsva::from(color.background).h);
+                let distance = (a - b).abs().min(1.0 - (a - b).abs()) * 360.0;
+                assert!(
+                    distance >= 60.0,
+                    "Colors {} and {} are only {:.0} degrees apart",
+                    index,
+                    index + 1,
+                    distance
+                );
+            }
+        }
+    }
+}
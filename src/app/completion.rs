@@ -0,0 +1,296 @@
+use super::syntax_docs::ASCII_CHARACTER_CLASSES;
+use std::ops::Range;
+
+/// The names of the Unicode general categories, scripts and binary properties that can be matched with `\p{name}`,
+/// offered as completions
+///
+/// This isn't every name that the regex crate supports, just the most commonly used ones
+pub const UNICODE_PROPERTY_NAMES: &[&str] = &[
+    // General categories, with their short names before their long names,
+    // except for those that the regex crate doesn't recognise, such as `Sc`, which it mistakes for the `sc` property
+    "L",
+    "Letter",
+    "Cased_Letter",
+    "Lu",
+    "Uppercase_Letter",
+    "Ll",
+    "Lowercase_Letter",
+    "Lt",
+    "Titlecase_Letter",
+    "Lm",
+    "Modifier_Letter",
+    "Lo",
+    "Other_Letter",
+    "M",
+    "Mark",
+    "Mn",
+    "Nonspacing_Mark",
+    "Mc",
+    "Spacing_Mark",
+    "Me",
+    "Enclosing_Mark",
+    "N",
+    "Number",
+    "Nd",
+    "Decimal_Number",
+    "Nl",
+    "Letter_Number",
+    "No",
+    "Other_Number",
+    "P",
+    "Punctuation",
+    "Pc",
+    "Connector_Punctuation",
+    "Pd",
+    "Dash_Punctuation",
+    "Ps",
+    "Open_Punctuation",
+    "Pe",
+    "Close_Punctuation",
+    "Pi",
+    "Initial_Punctuation",
+    "Pf",
+    "Final_Punctuation",
+    "Po",
+    "Other_Punctuation",
+    "S",
+    "Symbol",
+    "Sm",
+    "Math_Symbol",
+    "Currency_Symbol",
+    "Sk",
+    "Modifier_Symbol",
+    "So",
+    "Other_Symbol",
+    "Z",
+    "Separator",
+    "Zs",
+    "Space_Separator",
+    "Zl",
+    "Line_Separator",
+    "Zp",
+    "Paragraph_Separator",
+    "C",
+    "Other",
+    "Cc",
+    "Control",
+    "Cf",
+    "Format",
+    "Co",
+    "Private_Use",
+    "Cn",
+    "Unassigned",
+    // Scripts
+    "Arabic",
+    "Armenian",
+    "Bengali",
+    "Bopomofo",
+    "Braille",
+    "Cherokee",
+    "Common",
+    "Cyrillic",
+    "Devanagari",
+    "Ethiopic",
+    "Georgian",
+    "Greek",
+    "Gujarati",
+    "Gurmukhi",
+    "Han",
+    "Hangul",
+    "Hebrew",
+    "Hiragana",
+    "Inherited",
+    "Kannada",
+    "Katakana",
+    "Khmer",
+    "Lao",
+    "Latin",
+    "Malayalam",
+    "Mongolian",
+    "Myanmar",
+    "Ogham",
+    "Oriya",
+    "Runic",
+    "Sinhala",
+    "Syriac",
+    "Tamil",
+    "Telugu",
+    "Thaana",
+    "Thai",
+    "Tibetan",
+    "Yi",
+    // Binary properties
+    "Alphabetic",
+    "Any",
+    "ASCII",
+    "Assigned",
+    "Emoji",
+    "Lowercase",
+    "Math",
+    "Uppercase",
+    "White_Space",
+];
+
+/// Names that could complete the partial name before the cursor in a regex
+pub struct Completion {
+    /// The byte range of the partial name, including any part of it after the cursor, which is replaced by the chosen name
+    pub range: Range<usize>,
+    pub candidates: Vec<&'static str>,
+    /// The text that ends the syntax that the name is in, which is inserted after the name if it isn't already there
+    pub closing: &'static str,
+}
+
+impl Completion {
+    /// Returns the given pattern with the partial name replaced with the given name, followed by the closing text,
+    /// along with the byte offset after the closing text, for the cursor to be placed at
+    pub fn apply(&self, pattern: &str, name: &str) -> (String, usize) {
+        let rest = &pattern[self.range.end..];
+        let closing = if rest.starts_with(self.closing) {
+            ""
+        } else {
+            self.closing
+        };
+
+        let completed = format!(
+            "{}{}{}{}",
+            &pattern[..self.range.start],
+            name,
+            closing,
+            rest
+        );
+        (
+            completed,
+            self.range.start + name.len() + self.closing.len(),
+        )
+    }
+}
+
+/// Returns the names that could complete the partial name at the given byte offset of the given pattern,
+/// if it is in a Unicode class (`\p{...}`) or an ASCII class (`[[:...:]]`)
+pub fn completion_at(pattern: &str, cursor: usize) -> Option<Completion> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let before = pattern.get(..cursor)?;
+    let start = before
+        .rfind(|c| !is_name_char(c))
+        .map_or(0, |index| index + 1);
+    let end = cursor
+        + pattern[cursor..]
+            .find(|c| !is_name_char(c))
+            .unwrap_or(pattern.len() - cursor);
+    let prefix = &before[..start];
+    let partial = &before[start..].to_ascii_lowercase();
+
+    let (names, closing) = if let Some(escape) = prefix
+        .strip_suffix(r"\p{")
+        .or_else(|| prefix.strip_suffix(r"\P{"))
+    {
+        // The backslash could be escaped itself, in which case there's no class
+        let backslashes = escape.len() - escape.trim_end_matches('\\').len();
+        if backslashes % 2 == 1 {
+            return None;
+        }
+        (UNICODE_PROPERTY_NAMES.to_vec(), "}")
+    } else {
+        let class = prefix
+            .strip_suffix("[:^")
+            .or_else(|| prefix.strip_suffix("[:"))?;
+        if !in_class(class) {
+            return None;
+        }
+        let names = ASCII_CHARACTER_CLASSES
+            .iter()
+            .filter_map(|entry| entry.syntax.strip_prefix("[[:")?.strip_suffix(":]]"))
+            .collect::<Vec<_>>();
+        (names, ":]")
+    };
+
+    let candidates = names
+        .into_iter()
+        .filter(|name| name.to_ascii_lowercase().starts_with(partial))
+        .collect::<Vec<_>>();
+    (!candidates.is_empty()).then_some(Completion {
+        range: start..end,
+        candidates,
+        closing,
+    })
+}
+
+/// Returns whether the end of the given part of a pattern is inside of a character class
+fn in_class(pattern: &str) -> bool {
+    let mut depth = 0usize;
+    // A `]` at the start of a class is a literal
+    let mut class_start = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                depth += 1;
+                class_start = true;
+                continue;
+            }
+            '^' if class_start => continue,
+            ']' if depth > 0 && !class_start => depth -= 1,
+            _ => {}
+        }
+        class_start = false;
+    }
+    depth > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::compile_regex;
+
+    #[test]
+    fn unicode_property_names_compile() {
+        for name in UNICODE_PROPERTY_NAMES {
+            let pattern = format!(r"\p{{{}}}", name);
+            assert!(
+                compile_regex(&pattern, Default::default()).is_ok(),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn completions() {
+        // The cursor is at the `|`
+        let cases: &[(&str, Option<(&str, &str)>)] = &[
+            (r"\p{Gre|", Some(("Greek", r"\p{Greek}|"))),
+            (r"a\P{gre|ek}b", Some(("Greek", r"a\P{Greek}|b"))),
+            (r"[[:al|", Some(("alnum", "[[:alnum:]|"))),
+            (r"[a[:^dig|:]]", Some(("digit", "[a[:^digit:]|]"))),
+            (r"\\p{Gre|", None),
+            (r"[:al|", None),
+            (r"[\]][:al|", None),
+            (r"[]][:al|", None),
+            (r"\p{Zzz|", None),
+        ];
+        for &(text, expected) in cases {
+            let cursor = text.find('|').unwrap();
+            let pattern = text.replacen('|', "", 1);
+            let completion = completion_at(&pattern, cursor);
+            let (name, completed) = match expected {
+                Some(expected) => expected,
+                None => {
+                    assert!(completion.is_none(), "{:?} was completed", text);
+                    continue;
+                }
+            };
+            let completion = completion.unwrap_or_else(|| panic!("{:?} wasn't completed", text));
+
+            // The best match is offered first
+            assert_eq!(completion.candidates.first(), Some(&name), "{:?}", text);
+            let (pattern, cursor) = completion.apply(&pattern, name);
+            assert_eq!(
+                format!("{}|{}", &pattern[..cursor], &pattern[cursor..]),
+                completed
+            );
+        }
+    }
+}
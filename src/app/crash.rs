@@ -0,0 +1,114 @@
+use super::state::WidgetState;
+use std::backtrace::Backtrace;
+
+/// The storage key under which snapshots of the widget state are saved, to be restored after a crash
+pub const SNAPSHOT_KEY: &str = "crash_recovery_snapshot";
+
+/// The minimum number of seconds between each snapshot of the widget state
+pub const SNAPSHOT_INTERVAL: f64 = 5.0;
+
+/// The maximum number of bytes of a crash report to include when prefilling an issue, to keep the URL a sensible length
+const MAX_ISSUE_REPORT_LEN: usize = 4000;
+
+/// Information for recovering from the app having crashed the last time it was ran
+pub struct CrashRecovery {
+    /// The panic message and backtrace of the crash
+    pub report: String,
+    /// The most recent snapshot of the widget state from before the crash, if there is one
+    pub snapshot: Option<WidgetState>,
+}
+
+impl CrashRecovery {
+    /// Returns a URL for opening a new issue on the repository, prefilled with the crash report
+    pub fn issue_url(&self) -> String {
+        let mut end = self.report.len().min(MAX_ISSUE_REPORT_LEN);
+        while !self.report.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let body = format!(
+            "The app crashed (Version {}).\n\n```\n{}\n```",
+            env!("CARGO_PKG_VERSION"),
+            &self.report[..end]
+        );
+
+        format!(
+            "{}issues/new?title={}&body={}",
+            env!("CARGO_PKG_REPOSITORY"),
+            percent_encode("Crash report"),
+            percent_encode(&body)
+        )
+    }
+}
+
+/// Encodes the given text to be used as a URL query parameter
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Installs a panic hook that records the panic message and backtrace,
+/// so that they can be shown the next time the app is launched
+///
+/// The previously installed panic hook is still called afterwards
+pub fn install_panic_hook() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        write_crash_report(&format!("{}\n\n{}", info, Backtrace::force_capture()));
+        previous_hook(info);
+    }));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn crash_report_path() -> std::path::PathBuf {
+    std::env::temp_dir().join("regex_visualiser_crash_report.txt")
+}
+
+/// Records a crash report to be retrieved by `take_crash_report` (Native)
+#[cfg(not(target_arch = "wasm32"))]
+fn write_crash_report(report: &str) {
+    // There isn't anything useful that can be done if this fails, as the app is already crashing
+    let _ = std::fs::write(crash_report_path(), report);
+}
+
+/// Retrieves and removes the crash report recorded by the panic hook, if there is one (Native)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn take_crash_report() -> Option<String> {
+    let path = crash_report_path();
+    let report = std::fs::read_to_string(&path).ok()?;
+    let _ = std::fs::remove_file(path);
+    Some(report)
+}
+
+/// The local storage key under which crash reports are recorded
+#[cfg(target_arch = "wasm32")]
+const CRASH_REPORT_KEY: &str = "regex_visualiser_crash_report";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Records a crash report to be retrieved by `take_crash_report` (Wasm)
+#[cfg(target_arch = "wasm32")]
+fn write_crash_report(report: &str) {
+    // There isn't anything useful that can be done if this fails, as the app is already crashing
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(CRASH_REPORT_KEY, report);
+    }
+}
+
+/// Retrieves and removes the crash report recorded by the panic hook, if there is one (Wasm)
+#[cfg(target_arch = "wasm32")]
+pub fn take_crash_report() -> Option<String> {
+    let storage = local_storage()?;
+    let report = storage.get_item(CRASH_REPORT_KEY).ok()??;
+    let _ = storage.remove_item(CRASH_REPORT_KEY);
+    Some(report)
+}
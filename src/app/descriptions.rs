@@ -0,0 +1,133 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Identifies a capture group by its name if it has one, or by its index otherwise
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub enum GroupKey {
+    Name(String),
+    Index(usize),
+}
+
+impl GroupKey {
+    pub fn new(index: usize, name: Option<&str>) -> Self {
+        match name {
+            Some(name) => Self::Name(name.into()),
+            None => Self::Index(index),
+        }
+    }
+}
+
+/// User-written descriptions of the capture groups in the regex, for documentation purposes
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GroupDescriptions {
+    #[serde(serialize_with = "serialize_entries")]
+    #[serde(deserialize_with = "deserialize_entries")]
+    descriptions: BTreeMap<GroupKey, String>,
+    /// The names of the capture groups as of the last time they were updated, used to detect renames
+    names: Vec<Option<String>>,
+    /// A message explaining why descriptions were discarded, if any were
+    #[serde(skip)]
+    pub notice: Option<String>,
+}
+
+impl GroupDescriptions {
+    /// Gets the description of the capture group with the given index and name, if it has one
+    pub fn get(&self, index: usize, name: Option<&str>) -> Option<&str> {
+        self.descriptions
+            .get(&GroupKey::new(index, name))
+            .map(String::as_str)
+            .filter(|description| !description.is_empty())
+    }
+
+    /// Lists the description of each capture group with the given names that has one, in order of their indexes,
+    /// as lines such as `Group 1 (year): Four-digit year`, for including in exports
+    pub fn describe_groups<'a>(&self, names: impl Iterator<Item = Option<&'a str>>) -> Vec<String> {
+        names
+            .enumerate()
+            .filter_map(|(index, name)| {
+                let description = self.get(index, name)?;
+                Some(match name {
+                    Some(name) => format!("Group {} ({}): {}", index, name, description),
+                    None => format!("Group {}: {}", index, description),
+                })
+            })
+            .collect()
+    }
+
+    /// Gets a mutable reference to the description of the capture group with the given index and name,
+    /// inserting an empty description if it doesn't have one
+    pub fn get_mut(&mut self, index: usize, name: Option<&str>) -> &mut String {
+        self.descriptions
+            .entry(GroupKey::new(index, name))
+            .or_default()
+    }
+
+    /// Updates which descriptions belong to which capture groups, given the names of the capture groups in the new regex
+    ///
+    /// Descriptions of named groups follow those groups if they are renamed, whereas descriptions of unnamed groups
+    /// are discarded if the number of capture groups changes, as their indexes can no longer be relied upon
+    pub fn update_groups<'a>(&mut self, names: impl Iterator<Item = Option<&'a str>>) {
+        let names = names.map(|name| name.map(Into::into)).collect::<Vec<_>>();
+        if names == self.names {
+            return;
+        }
+
+        if names.len() == self.names.len() {
+            // A named group is considered to have been renamed if its old name is no longer in use,
+            // and a group with the same index now has a name that doesn't have a description yet
+            for (old, new) in self.names.iter().zip(&names) {
+                if let (Some(old), Some(new)) = (old, new) {
+                    let old_key = GroupKey::Name(old.clone());
+                    let new_key = GroupKey::Name(new.clone());
+                    if names.contains(&Some(old.clone()))
+                        || self.descriptions.contains_key(&new_key)
+                    {
+                        continue;
+                    }
+
+                    if let Some(description) = self.descriptions.remove(&old_key) {
+                        self.descriptions.insert(new_key, description);
+                    }
+                }
+            }
+        } else {
+            let dropped = self
+                .descriptions
+                .iter()
+                .filter(|(key, description)| {
+                    matches!(key, GroupKey::Index(_)) && !description.is_empty()
+                })
+                .count();
+
+            self.descriptions
+                .retain(|key, _| matches!(key, GroupKey::Name(_)));
+
+            if dropped > 0 {
+                self.notice = Some(format!(
+                    "Discarded {} description(s) of unnamed groups, as the number of capture groups changed",
+                    dropped
+                ));
+            }
+        }
+
+        self.names = names;
+    }
+}
+
+/// Serializes the descriptions as a list of entries rather than as a map,
+/// as JSON (Which shared sessions are encoded in) only allows maps to have string keys
+fn serialize_entries<S: Serializer>(
+    descriptions: &BTreeMap<GroupKey, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(descriptions)
+}
+
+/// Deserializes the descriptions from the list of entries that `serialize_entries` writes them as
+fn deserialize_entries<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<GroupKey, String>, D::Error> {
+    Vec::<(GroupKey, String)>::deserialize(deserializer)
+        .map(|entries| entries.into_iter().collect())
+}
@@ -0,0 +1,205 @@
+use super::escaped_bytes::escape_bytes;
+use regex::{bytes, RegexBuilder};
+use regex_syntax::hir::{literal::Literals, Hir};
+
+/// The size limit that regexes are compiled with by default, which is the most that a compiled size is searched up to
+const DEFAULT_SIZE_LIMIT: usize = 10 * (1 << 20);
+
+/// The smallest size limit that a compiled size is searched from
+const MIN_SIZE_LIMIT: usize = 256;
+
+/// Facts about how the regex engine sees a regex, derived from its HIR
+pub struct EngineInfo {
+    /// The approximate number of bytes that the compiled program of the regex takes up,
+    /// or `None` if it couldn't be measured
+    pub compiled_size: Option<usize>,
+    /// Whether every match must start at the start of the text
+    pub anchored_start: bool,
+    /// Whether every match must end at the end of the text
+    pub anchored_end: bool,
+    /// Whether the regex can match the empty string
+    pub match_empty: bool,
+    /// Literals that every match starts with one of, which the engine can search for before running the regex
+    pub prefixes: Literals,
+    /// Literals that every match ends with one of
+    pub suffixes: Literals,
+}
+
+impl EngineInfo {
+    pub fn from_hir(hir: &Hir) -> Self {
+        Self {
+            compiled_size: compiled_size(hir),
+            anchored_start: hir.is_anchored_start(),
+            anchored_end: hir.is_anchored_end(),
+            match_empty: hir.is_match_empty(),
+            prefixes: Literals::prefixes(hir),
+            suffixes: Literals::suffixes(hir),
+        }
+    }
+}
+
+/// Describes the given set of literals, showing each literal as a string, with bytes that aren't valid UTF-8 escaped,
+/// marked with `…` if it is only the start (or end, for suffixes) of what a match has there
+pub fn describe_literals(literals: &Literals, suffixes: bool) -> String {
+    if literals.literals().is_empty() {
+        return "None".into();
+    }
+    literals
+        .literals()
+        .iter()
+        .map(|literal| {
+            let text = match std::str::from_utf8(literal) {
+                Ok(text) => format!("{:?}", text),
+                Err(_) => format!("\"{}\"", escape_bytes(literal)),
+            };
+            match (literal.is_cut(), suffixes) {
+                (false, _) => text,
+                (true, false) => format!("{}…", text),
+                (true, true) => format!("…{}", text),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Measures the compiled size of the regex of the given HIR, by searching for the smallest size limit
+/// that it can be compiled within
+///
+/// The `regex` crate doesn't report the size of what it compiles, but it does check it against the size limit,
+/// so this compiles the regex a few dozen times, and should only be done once it's needed
+fn compiled_size(hir: &Hir) -> Option<usize> {
+    // The HIR is printed as a pattern that has the same meaning without any flags being set
+    let pattern = hir.to_string();
+    let utf8 = hir.is_always_utf8();
+    let compiles = |limit| {
+        if utf8 {
+            RegexBuilder::new(&pattern)
+                .size_limit(limit)
+                .build()
+                .is_ok()
+        } else {
+            bytes::RegexBuilder::new(&pattern)
+                .size_limit(limit)
+                .build()
+                .is_ok()
+        }
+    };
+
+    if !compiles(DEFAULT_SIZE_LIMIT) {
+        return None;
+    }
+
+    // Doubles the limit until it's enough, then narrows it down between that and the last limit that wasn't
+    let mut low = 0;
+    let mut high = MIN_SIZE_LIMIT;
+    while !compiles(high) {
+        low = high;
+        high = (high * 2).min(DEFAULT_SIZE_LIMIT);
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if compiles(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    Some(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::{parse_regex, translate_regex, RegexFlags};
+
+    fn engine_info(pattern: &str, flags: RegexFlags, bytes: bool) -> EngineInfo {
+        let ast = parse_regex(pattern, flags).unwrap();
+        EngineInfo::from_hir(&translate_regex(pattern, &ast, flags, bytes).unwrap())
+    }
+
+    #[test]
+    fn anchors_and_literals() {
+        let case_insensitive = RegexFlags {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        let ascii = RegexFlags {
+            unicode: false,
+            ..Default::default()
+        };
+
+        // The pattern, its flags, whether it matches bytes, whether it is anchored at the start and end,
+        // whether it matches the empty string, and its literal prefixes and suffixes
+        let cases = [
+            (
+                "^abc$",
+                Default::default(),
+                false,
+                (true, true, false),
+                r#""abc"…"#,
+                r#"…"abc""#,
+            ),
+            (
+                r"foo\d+|bar",
+                Default::default(),
+                false,
+                (false, false, false),
+                r#""foo"…, "bar""#,
+                "None",
+            ),
+            (
+                "a*",
+                Default::default(),
+                false,
+                (false, false, true),
+                "None",
+                "None",
+            ),
+            (
+                r"\Ax?",
+                Default::default(),
+                false,
+                (true, false, true),
+                "None",
+                "None",
+            ),
+            (
+                "ab",
+                case_insensitive,
+                false,
+                (false, false, false),
+                r#""AB", "aB", "Ab", "ab""#,
+                r#""AB", "Ab", "aB", "ab""#,
+            ),
+            (
+                r"\xFFz",
+                ascii,
+                true,
+                (false, false, false),
+                r#""\xFFz""#,
+                r#""\xFFz""#,
+            ),
+        ];
+        for (pattern, flags, bytes, anchors, prefixes, suffixes) in cases {
+            let info = engine_info(pattern, flags, bytes);
+            assert_eq!(
+                (
+                    (info.anchored_start, info.anchored_end, info.match_empty),
+                    describe_literals(&info.prefixes, false).as_str(),
+                    describe_literals(&info.suffixes, true).as_str(),
+                ),
+                (anchors, prefixes, suffixes),
+                "{:?}",
+                pattern
+            );
+            assert!(info.compiled_size.is_some(), "{:?}", pattern);
+        }
+    }
+
+    #[test]
+    fn bigger_patterns_compile_to_bigger_programs() {
+        let small = engine_info("a", Default::default(), false).compiled_size;
+        let large = engine_info(r"\w{50}", Default::default(), false).compiled_size;
+        assert!(small < large, "{:?} isn't less than {:?}", small, large);
+    }
+}
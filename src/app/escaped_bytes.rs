@@ -0,0 +1,141 @@
+use super::text::CaptureTable;
+use regex::bytes::Regex;
+use std::{fmt::Write, ops::Range};
+
+/// Writes bytes as text that can be displayed and edited, with each byte that isn't part of valid UTF-8 written as
+/// a `\xNN` escape sequence, and backslashes written as `\\` so that they can't be mistaken for escape sequences
+pub fn escape_bytes(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(valid) => (valid, &[][..]),
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                // The error has no length if the bytes end part way through a char, in which case the rest are invalid
+                let invalid = &after[..err.error_len().unwrap_or(after.len())];
+                (std::str::from_utf8(valid).unwrap(), invalid)
+            }
+        };
+
+        text.push_str(&valid.replace('\\', "\\\\"));
+        for byte in invalid {
+            // Writing to a string can't fail
+            let _ = write!(text, "\\x{:02X}", byte);
+        }
+
+        rest = &rest[valid.len() + invalid.len()..];
+    }
+    text
+}
+
+/// The bytes that are written in text by `escape_bytes`, along with where in the text each of them is written
+#[derive(Default)]
+pub struct EscapedBytes {
+    pub bytes: Vec<u8>,
+    /// The byte offset in the text at which the char or escape sequence that each byte is written with starts
+    starts: Vec<usize>,
+    /// The byte offset in the text at which the char or escape sequence that each byte is written with ends
+    ends: Vec<usize>,
+    /// The length of the text in bytes
+    text_len: usize,
+}
+
+impl EscapedBytes {
+    /// Reads the bytes that are written in the given text
+    ///
+    /// Backslashes that don't start a `\xNN` or `\\` escape sequence are read as they are written,
+    /// so that text that was edited by hand is never rejected
+    pub fn unescape(text: &str) -> Self {
+        let mut escaped = Self {
+            bytes: Vec::with_capacity(text.len()),
+            starts: Vec::with_capacity(text.len()),
+            ends: Vec::with_capacity(text.len()),
+            text_len: text.len(),
+        };
+
+        let mut offset = 0;
+        while let Some(c) = text[offset..].chars().next() {
+            let rest = &text[offset + c.len_utf8()..];
+            let hex_byte = rest
+                .strip_prefix('x')
+                .and_then(|hex| hex.get(..2))
+                .filter(|hex| hex.bytes().all(|digit| digit.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+            let (bytes, len) = match (c, hex_byte) {
+                ('\\', Some(byte)) => (vec![byte], "\\xNN".len()),
+                ('\\', None) if rest.starts_with('\\') => (vec![b'\\'], "\\\\".len()),
+                _ => (c.to_string().into_bytes(), c.len_utf8()),
+            };
+
+            for byte in bytes {
+                escaped.bytes.push(byte);
+                escaped.starts.push(offset);
+                escaped.ends.push(offset + len);
+            }
+            offset += len;
+        }
+
+        escaped
+    }
+
+    /// Converts a range of the bytes into the range of the text that they are written in,
+    /// widening it to include the whole of any char that it only covers some of the bytes of
+    pub fn to_text_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self
+            .starts
+            .get(range.start)
+            .copied()
+            .unwrap_or(self.text_len);
+        let end = if range.end > range.start {
+            self.ends[range.end - 1]
+        } else {
+            start
+        };
+        start..end
+    }
+
+    /// Converts a byte offset in the text into the offset of the byte that is written there
+    pub fn to_byte_offset(&self, text_offset: usize) -> usize {
+        self.starts.partition_point(|&start| start < text_offset)
+    }
+
+    /// Finds the match of the given regex that starts at the given byte offset in the text, returning the ranges of the
+    /// text that each of its capture groups matched, or `None` if there is no match there
+    pub fn captures_at(
+        &self,
+        regex: &Regex,
+        text_offset: usize,
+    ) -> Option<Vec<Option<Range<usize>>>> {
+        let mut locations = regex.capture_locations();
+        regex.captures_read_at(
+            &mut locations,
+            &self.bytes,
+            self.to_byte_offset(text_offset),
+        )?;
+        Some(
+            (0..locations.len())
+                .map(|group| {
+                    locations
+                        .get(group)
+                        .map(|(start, end)| self.to_text_range(start..end))
+                })
+                .collect(),
+        )
+    }
+
+    /// Finds every match of the given regex in the bytes, along with the ranges matched by each capture group,
+    /// as ranges of the text that the bytes are written in
+    pub fn find_captures(&self, regex: &Regex) -> CaptureTable {
+        regex
+            .captures_iter(&self.bytes)
+            .map(|captures| {
+                captures
+                    .iter()
+                    .map(|r#match| r#match.map(|r#match| self.to_text_range(r#match.range())))
+                    .collect()
+            })
+            .collect()
+    }
+}
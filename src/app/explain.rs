@@ -0,0 +1,410 @@
+use super::{color::GroupColor, text::GetRangeExt};
+use egui::Color32;
+use regex_syntax::ast::{
+    AssertionKind, Ast, Class, ClassAsciiKind, ClassBracketed, ClassPerl, ClassPerlKind, ClassSet,
+    ClassSetBinaryOpKind, ClassSetItem, ClassUnicode, ClassUnicodeKind, Flag, Flags, FlagsItemKind,
+    GroupKind, Literal, LiteralKind, RepetitionKind, RepetitionRange,
+};
+use std::ops::Range;
+
+/// A line of a plain-English explanation of a regex, describing one part of it,
+/// along with the lines describing the parts inside of it
+pub struct ExplainLine {
+    pub text: String,
+    /// The highlight color of the capture group that the line describes, if it describes one
+    pub color: Option<Color32>,
+    /// The byte range of the pattern that the line describes
+    pub span: Range<usize>,
+    /// Whether the line is one of several alternatives, and so is shown as a bullet point
+    pub bullet: bool,
+    pub children: Vec<ExplainLine>,
+}
+
+impl ExplainLine {
+    fn new(text: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+            span,
+            bullet: false,
+            children: vec![],
+        }
+    }
+
+    fn with_children(mut self, children: Vec<ExplainLine>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+/// Explains the given AST in plain English, with each part of a sequence on its own line,
+/// where each capture group is marked with the color at its index in `capture_group_colors`
+pub fn explain(ast: &Ast, capture_group_colors: &[GroupColor]) -> Vec<ExplainLine> {
+    match ast {
+        // Every part of a sequence is explained on its own line, except for runs of plain characters
+        Ast::Concat(concat) => {
+            let mut lines = Vec::new();
+            let mut run: Option<(String, Range<usize>)> = None;
+            for ast in &concat.asts {
+                match ast {
+                    Ast::Literal(literal) if literal.kind == LiteralKind::Verbatim => {
+                        let span = literal.span.range();
+                        match &mut run {
+                            Some((text, range)) => {
+                                text.push(literal.c);
+                                range.end = span.end;
+                            }
+                            None => run = Some((literal.c.to_string(), span)),
+                        }
+                    }
+                    ast => {
+                        lines.extend(run.take().map(text_line));
+                        lines.extend(explain(ast, capture_group_colors));
+                    }
+                }
+            }
+            lines.extend(run.map(text_line));
+            lines
+        }
+        Ast::Empty(_) => vec![],
+        ast => vec![explain_node(ast, capture_group_colors)],
+    }
+}
+
+/// Explains a run of plain characters as a whole
+fn text_line((text, span): (String, Range<usize>)) -> ExplainLine {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => ExplainLine::new(format!("The character {:?}", c), span),
+        _ => ExplainLine::new(format!("The text {:?}", text), span),
+    }
+}
+
+/// Explains a single node of an AST that isn't a sequence
+fn explain_node(ast: &Ast, capture_group_colors: &[GroupColor]) -> ExplainLine {
+    let span = ast.span().range();
+    match ast {
+        Ast::Empty(_) => ExplainLine::new("Nothing, which always matches", span),
+        Ast::Flags(flags) => ExplainLine::new(
+            format!("From here on, {}", describe_flags(&flags.flags)),
+            span,
+        ),
+        Ast::Literal(literal) => ExplainLine::new(describe_literal(literal), span),
+        Ast::Dot(_) => ExplainLine::new(
+            "Any character except a newline, or any character at all with the s flag",
+            span,
+        ),
+        Ast::Assertion(assertion) => {
+            let text = match assertion.kind {
+                AssertionKind::StartLine => "The start of the text, or of any line with the m flag",
+                AssertionKind::EndLine => "The end of the text, or of any line with the m flag",
+                AssertionKind::StartText => "The start of the text",
+                AssertionKind::EndText => "The end of the text",
+                AssertionKind::WordBoundary => "A word boundary",
+                AssertionKind::NotWordBoundary => "Anywhere that isn't a word boundary",
+            };
+            ExplainLine::new(text, span)
+        }
+        Ast::Class(Class::Perl(class)) => ExplainLine::new(describe_perl_class(class), span),
+        Ast::Class(Class::Unicode(class)) => ExplainLine::new(describe_unicode_class(class), span),
+        Ast::Class(Class::Bracketed(class)) => explain_bracketed_class(class),
+        Ast::Repetition(repetition) => {
+            let count = match &repetition.op.kind {
+                RepetitionKind::ZeroOrOne => "zero or one time".into(),
+                RepetitionKind::ZeroOrMore => "zero or more times".into(),
+                RepetitionKind::OneOrMore => "one or more times".into(),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                    format!("exactly {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                    format!("at least {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::Bounded(n, m)) => {
+                    format!("between {} and {} times", n, m)
+                }
+            };
+            // Greediness makes no difference to a fixed number of repetitions
+            let greediness = match (&repetition.op.kind, repetition.greedy) {
+                (RepetitionKind::Range(RepetitionRange::Exactly(_)), _) => "",
+                (RepetitionKind::ZeroOrOne, true) => ", preferring to match it",
+                (RepetitionKind::ZeroOrOne, false) => ", preferring to skip it",
+                (_, true) => ", as many times as possible",
+                (_, false) => ", as few times as possible",
+            };
+
+            // A repetition of something that fits on one line is explained on that same line
+            let mut inner = explain(&repetition.ast, capture_group_colors);
+            match inner.as_mut_slice() {
+                [line] if line.children.is_empty() && line.color.is_none() => {
+                    let text = format!("{}, {}{}", line.text, count, greediness);
+                    ExplainLine::new(text, span)
+                }
+                _ => ExplainLine::new(format!("Repeated {}{}:", count, greediness), span)
+                    .with_children(inner),
+            }
+        }
+        Ast::Group(group) => {
+            let (text, index) = match &group.kind {
+                GroupKind::CaptureIndex(index) => {
+                    (format!("Capture group {}:", index), Some(*index))
+                }
+                GroupKind::CaptureName(name) => (
+                    format!("Capture group {} ({}):", name.index, name.name),
+                    Some(name.index),
+                ),
+                GroupKind::NonCapturing(flags) if flags.items.is_empty() => ("Group:".into(), None),
+                GroupKind::NonCapturing(flags) => {
+                    (format!("Group where {}:", describe_flags(flags)), None)
+                }
+            };
+
+            let mut line = ExplainLine::new(text, span)
+                .with_children(explain(&group.ast, capture_group_colors));
+            line.color = index
+                .and_then(|index| capture_group_colors.get(index as usize))
+                .map(|color| color.background);
+            line
+        }
+        Ast::Alternation(alternation) => {
+            let branches = alternation
+                .asts
+                .iter()
+                .map(|ast| {
+                    let mut lines = explain(ast, capture_group_colors);
+                    let mut line = if lines.len() == 1 {
+                        lines.remove(0)
+                    } else if lines.is_empty() {
+                        ExplainLine::new("Nothing, which always matches", ast.span().range())
+                    } else {
+                        ExplainLine::new("In sequence:", ast.span().range()).with_children(lines)
+                    };
+                    line.bullet = true;
+                    line
+                })
+                .collect();
+            ExplainLine::new("Either:", span).with_children(branches)
+        }
+        Ast::Concat(_) => {
+            ExplainLine::new("In sequence:", span).with_children(explain(ast, capture_group_colors))
+        }
+    }
+}
+
+/// Describes which flags the given flags turn on and off
+fn describe_flags(flags: &Flags) -> String {
+    let mut on = Vec::new();
+    let mut off = Vec::new();
+    let mut negated = false;
+    for item in &flags.items {
+        match &item.kind {
+            FlagsItemKind::Negation => negated = true,
+            FlagsItemKind::Flag(flag) => {
+                let description = match flag {
+                    Flag::CaseInsensitive => "letters match regardless of case",
+                    Flag::MultiLine => "^ and $ match at the start and end of lines",
+                    Flag::DotMatchesNewLine => ". matches newlines",
+                    Flag::SwapGreed => "repetitions are lazy unless marked with ?",
+                    Flag::Unicode => "classes are Unicode-aware",
+                    Flag::IgnoreWhitespace => "whitespace and # comments are ignored",
+                };
+                if negated {
+                    off.push(description);
+                } else {
+                    on.push(description);
+                }
+            }
+        }
+    }
+
+    match (on.is_empty(), off.is_empty()) {
+        (false, true) => on.join(", "),
+        (true, false) => format!("it is no longer the case that {}", off.join(", or that ")),
+        (false, false) => format!(
+            "{}, but it is no longer the case that {}",
+            on.join(", "),
+            off.join(", or that ")
+        ),
+        (true, true) => "nothing changes".into(),
+    }
+}
+
+fn describe_literal(literal: &Literal) -> String {
+    match literal.kind {
+        LiteralKind::Verbatim | LiteralKind::Punctuation => {
+            format!("The character {:?}", literal.c)
+        }
+        _ => format!("The character {:?} (U+{:04X})", literal.c, literal.c as u32),
+    }
+}
+
+fn describe_perl_class(class: &ClassPerl) -> String {
+    let kind = match class.kind {
+        ClassPerlKind::Digit => "digit",
+        ClassPerlKind::Space => "whitespace character",
+        ClassPerlKind::Word => "word character (a letter, digit or underscore)",
+    };
+    if class.negated {
+        format!("Any character that isn't a {}", kind)
+    } else {
+        format!("A {}", kind)
+    }
+}
+
+fn describe_unicode_class(class: &ClassUnicode) -> String {
+    let name = match &class.kind {
+        ClassUnicodeKind::OneLetter(letter) => letter.to_string(),
+        ClassUnicodeKind::Named(name) => name.clone(),
+        ClassUnicodeKind::NamedValue { name, value, .. } => format!("{}={}", name, value),
+    };
+    if class.negated {
+        format!("Any character that isn't in the Unicode class {}", name)
+    } else {
+        format!("A character in the Unicode class {}", name)
+    }
+}
+
+fn describe_ascii_class(kind: &ClassAsciiKind, negated: bool) -> String {
+    let kind = match kind {
+        ClassAsciiKind::Alnum => "ASCII letter or digit",
+        ClassAsciiKind::Alpha => "ASCII letter",
+        ClassAsciiKind::Ascii => "ASCII character",
+        ClassAsciiKind::Blank => "space or tab",
+        ClassAsciiKind::Cntrl => "ASCII control character",
+        ClassAsciiKind::Digit => "ASCII digit",
+        ClassAsciiKind::Graph => "visible ASCII character",
+        ClassAsciiKind::Lower => "lowercase ASCII letter",
+        ClassAsciiKind::Print => "printable ASCII character",
+        ClassAsciiKind::Punct => "ASCII punctuation character",
+        ClassAsciiKind::Space => "ASCII whitespace character",
+        ClassAsciiKind::Upper => "uppercase ASCII letter",
+        ClassAsciiKind::Word => "ASCII word character",
+        ClassAsciiKind::Xdigit => "hexadecimal digit",
+    };
+    if negated {
+        format!("Any character that isn't a {}", kind)
+    } else {
+        format!("A {}", kind)
+    }
+}
+
+/// Explains a bracketed class, listing each of the characters or classes in it
+fn explain_bracketed_class(class: &ClassBracketed) -> ExplainLine {
+    let span = class.span.range();
+    let text = if class.negated {
+        "Any character except:"
+    } else {
+        "Any one of:"
+    };
+
+    match &class.kind {
+        ClassSet::Item(ClassSetItem::Union(union)) => ExplainLine::new(text, span)
+            .with_children(union.items.iter().map(explain_class_set_item).collect()),
+        ClassSet::Item(item) => {
+            ExplainLine::new(text, span).with_children(vec![explain_class_set_item(item)])
+        }
+        ClassSet::BinaryOp(op) => {
+            let operation = match op.kind {
+                ClassSetBinaryOpKind::Intersection => "Characters that are in both of:",
+                ClassSetBinaryOpKind::Difference => {
+                    "Characters in the first but not the second of:"
+                }
+                ClassSetBinaryOpKind::SymmetricDifference => "Characters that are in only one of:",
+            };
+            let sets = [&op.lhs, &op.rhs]
+                .into_iter()
+                .map(|set| match &**set {
+                    ClassSet::Item(item) => explain_class_set_item(item),
+                    ClassSet::BinaryOp(op) => {
+                        ExplainLine::new("A combination of classes", op.span.range())
+                    }
+                })
+                .collect();
+            ExplainLine::new(text, span).with_children(vec![ExplainLine::new(
+                operation,
+                op.span.range(),
+            )
+            .with_children(sets)])
+        }
+    }
+}
+
+fn explain_class_set_item(item: &ClassSetItem) -> ExplainLine {
+    match item {
+        ClassSetItem::Empty(span) => ExplainLine::new("Nothing", span.range()),
+        ClassSetItem::Literal(literal) => {
+            ExplainLine::new(describe_literal(literal), literal.span.range())
+        }
+        ClassSetItem::Range(range) => ExplainLine::new(
+            format!("A character from {:?} to {:?}", range.start.c, range.end.c),
+            range.span.range(),
+        ),
+        ClassSetItem::Ascii(class) => ExplainLine::new(
+            describe_ascii_class(&class.kind, class.negated),
+            class.span.range(),
+        ),
+        ClassSetItem::Unicode(class) => {
+            ExplainLine::new(describe_unicode_class(class), class.span.range())
+        }
+        ClassSetItem::Perl(class) => {
+            ExplainLine::new(describe_perl_class(class), class.span.range())
+        }
+        ClassSetItem::Bracketed(class) => explain_bracketed_class(class),
+        ClassSetItem::Union(union) => ExplainLine::new("Any one of:", union.span.range())
+            .with_children(union.items.iter().map(explain_class_set_item).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{color::group_color, parsing::parse_regex};
+
+    #[test]
+    fn explanation() {
+        let pattern = r"^(?P<year>\d{4})-(a|b)[^a-z]$";
+        let ast = parse_regex(pattern, Default::default()).unwrap();
+        let colors = [GroupColor::NONE, group_color(0, true), group_color(1, true)];
+        let lines = explain(&ast, &colors);
+
+        let texts = lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            [
+                "The start of the text, or of any line with the m flag",
+                "Capture group 1 (year):",
+                "The character '-'",
+                "Capture group 2:",
+                "Any character except:",
+                "The end of the text, or of any line with the m flag",
+            ]
+        );
+
+        // Each capture group is marked with its own highlight color
+        assert_eq!(lines[1].color, Some(colors[1].background));
+        assert_eq!(lines[3].color, Some(colors[2].background));
+
+        // Each part is explained along with the span of the pattern that it was parsed from
+        for (line, expected) in [
+            (
+                &lines[1].children[0],
+                (r"\d{4}", "A digit, exactly 4 times"),
+            ),
+            (
+                &lines[3].children[0].children[1],
+                ("b", "The character 'b'"),
+            ),
+            (
+                &lines[4].children[0],
+                ("a-z", "A character from 'a' to 'z'"),
+            ),
+        ] {
+            assert_eq!((&pattern[line.span.clone()], line.text.as_str()), expected);
+        }
+
+        // The branches of an alternation are bullet points
+        assert!(lines[3].children[0].children.iter().all(|line| line.bullet));
+    }
+}
@@ -0,0 +1,1493 @@
+use super::{
+    ast_tree::{self, describe_ast},
+    descriptions::GroupDescriptions,
+    parsing::{parse_regex, RegexFlags},
+    state::LogicState,
+    text::GetRangeExt,
+    verbose::compact_verbose,
+};
+use regex_syntax::ast::{
+    AssertionKind, Ast, Class, ClassPerlKind, ClassSet, ClassSetBinaryOpKind, ClassSetItem,
+    ClassUnicodeKind, Flag, Flags, FlagsItemKind, GroupKind, RepetitionKind, RepetitionRange,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::{fmt::Write, ops::Range};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// A part of an output template
+#[derive(Clone, PartialEq, Eq)]
+pub enum TemplateToken {
+    /// Text that is output as-is, with any escape sequences already resolved
+    Literal(String),
+    /// `{match}`, the whole text of the match
+    Match,
+    /// `{match.index}`, the index of the match, starting from 0
+    MatchIndex,
+    /// `{match.start}`, the byte offset that the match starts at in the input text
+    MatchStart,
+    /// `{match.end}`, the byte offset that the match ends at in the input text
+    MatchEnd,
+    /// `{group:N}`, the text matched by the capture group with the given index
+    GroupIndex(usize),
+    /// `{group.name}`, the text matched by the capture group with the given name
+    GroupName(String),
+}
+
+/// An error in an output template, along with the span of the template that caused it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TemplateError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl TemplateError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Describes where in the given template this error occurred, along with the error itself
+    pub fn location(&self, template: &str) -> String {
+        format!(
+            "at character {}: {}",
+            template[..self.span.start].chars().count(),
+            self.message
+        )
+    }
+}
+
+/// Parses an output template into literal text and placeholders
+///
+/// Placeholders are enclosed in braces, and `\n`, `\t`, `\\`, `\{` and `\}` are escape sequences
+pub fn parse_template(template: &str) -> Result<Vec<TemplateToken>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some((_, 'n')) => '\n',
+                    Some((_, 't')) => '\t',
+                    Some((_, c @ ('\\' | '{' | '}'))) => c,
+                    Some((offset, c)) => {
+                        return Err(TemplateError::new(
+                            format!("Unknown escape sequence `\\{}`", c),
+                            start..offset + c.len_utf8(),
+                        ))
+                    }
+                    None => {
+                        return Err(TemplateError::new(
+                            "Expected an escaped character after `\\`",
+                            start..start + 1,
+                        ))
+                    }
+                };
+                literal.push(escaped);
+            }
+            '{' => {
+                let end = match template[start..].find('}') {
+                    Some(end) => start + end,
+                    None => {
+                        return Err(TemplateError::new(
+                            "Unclosed placeholder, expected a `}`",
+                            start..template.len(),
+                        ))
+                    }
+                };
+
+                let span = start..end + 1;
+                let token = parse_placeholder(&template[start + 1..end], span.clone())?;
+                if !literal.is_empty() {
+                    tokens.push(TemplateToken::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(token);
+
+                while chars.next_if(|&(offset, _)| offset < span.end).is_some() {}
+            }
+            '}' => {
+                return Err(TemplateError::new(
+                    "Unmatched `}`, use `\\}` for a literal brace",
+                    start..start + 1,
+                ))
+            }
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        tokens.push(TemplateToken::Literal(literal));
+    }
+    Ok(tokens)
+}
+
+/// Parses the contents of a placeholder, which spans the given range of the template including its braces
+fn parse_placeholder(contents: &str, span: Range<usize>) -> Result<TemplateToken, TemplateError> {
+    let token = match contents {
+        "match" => TemplateToken::Match,
+        "match.index" => TemplateToken::MatchIndex,
+        "match.start" => TemplateToken::MatchStart,
+        "match.end" => TemplateToken::MatchEnd,
+        _ => {
+            if let Some(name) = contents
+                .strip_prefix("group.")
+                .filter(|name| !name.is_empty())
+            {
+                TemplateToken::GroupName(name.into())
+            } else if let Some(index) = contents.strip_prefix("group:") {
+                match index.parse() {
+                    Ok(index) => TemplateToken::GroupIndex(index),
+                    Err(_) => {
+                        return Err(TemplateError::new(
+                            format!("`{}` is not a valid group index", index),
+                            span,
+                        ))
+                    }
+                }
+            } else {
+                return Err(TemplateError::new(
+                    format!(
+                        "Unknown placeholder `{{{}}}`, expected `{{match}}`, `{{match.index}}`, `{{match.start}}`, \
+                        `{{match.end}}`, `{{group:N}}` or `{{group.name}}`",
+                        contents
+                    ),
+                    span,
+                ));
+            }
+        }
+    };
+    Ok(token)
+}
+
+/// Evaluates the given template once for each match in the selector of the given logic state,
+/// and concatenates the results
+///
+/// These are the matches that are highlighted, so in line mode each line is matched separately, and only the first
+/// `match_limit` matches are formatted. Capture groups that don't exist or didn't participate in a match are output
+/// as empty text
+pub fn format_matches(tokens: &[TemplateToken], logic: &LogicState) -> String {
+    let text = &logic.selector.text;
+    let names = logic.regex.capture_names().collect::<Vec<_>>();
+    let mut output = String::new();
+    for index in 0..logic.selector.matches.len() {
+        // The selector may only have the whole match of each match, if capture groups are extracted lazily
+        let groups = logic.match_groups(index);
+        let whole = match groups.first().cloned().flatten() {
+            Some(whole) => whole,
+            None => continue,
+        };
+        let group_text = |group: Option<usize>| {
+            group
+                .and_then(|group| groups.get(group)?.clone())
+                .map_or("", |range| &text[range])
+        };
+
+        for token in tokens {
+            // Writing to a string can't fail
+            let _ = match token {
+                TemplateToken::Literal(literal) => output.write_str(literal),
+                TemplateToken::Match => output.write_str(&text[whole.clone()]),
+                TemplateToken::MatchIndex => write!(output, "{}", index),
+                TemplateToken::MatchStart => write!(output, "{}", whole.start),
+                TemplateToken::MatchEnd => write!(output, "{}", whole.end),
+                TemplateToken::GroupIndex(group) => output.write_str(group_text(Some(*group))),
+                TemplateToken::GroupName(name) => output.write_str(group_text(
+                    names.iter().position(|group| *group == Some(name.as_str())),
+                )),
+            };
+        }
+    }
+    output
+}
+
+/// Saves output to a file with the given name in the user's downloads directory, returning the path of the file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_output(output: &str, file_name: &str) -> std::io::Result<PathBuf> {
+    let dir = directories_next::UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(Into::into))
+        .unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(file_name);
+    std::fs::write(&path, output)?;
+    Ok(path)
+}
+
+/// Has the browser download output as a file with the given name
+#[cfg(target_arch = "wasm32")]
+pub fn download_output(output: &str, file_name: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let describe = |err: wasm_bindgen::JsValue| format!("{:?}", err);
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("There is no document to download from")?;
+
+    let parts = js_sys::Array::of1(&output.into());
+    let blob = web_sys::Blob::new_with_str_sequence(&parts).map_err(describe)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(describe)?;
+
+    let anchor = document
+        .create_element("a")
+        .map_err(describe)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "Failed to create a download link")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(describe)
+}
+
+/// The file formats that matches can be exported in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    /// The name of the file that matches exported in this format are saved to
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "regex_visualiser_matches.json",
+            Self::Csv => "regex_visualiser_matches.csv",
+        }
+    }
+}
+
+/// A match as it is exported to JSON
+#[derive(Deserialize, Serialize)]
+pub struct ExportedMatch {
+    /// The index of the match, starting from 0
+    pub index: usize,
+    /// The byte range of the input text that the match spans
+    pub range: Range<usize>,
+    pub text: String,
+    /// Every capture group of the regex, including the ones that didn't participate in the match
+    pub groups: Vec<ExportedGroup>,
+}
+
+/// A capture group of a match as it is exported to JSON
+#[derive(Deserialize, Serialize)]
+pub struct ExportedGroup {
+    pub index: usize,
+    pub name: Option<String>,
+    /// The description that the user wrote for the group, if it has one
+    pub description: Option<String>,
+    /// The byte range of the input text that the group matched, or `None` if it didn't participate in the match
+    pub range: Option<Range<usize>>,
+    pub text: Option<String>,
+}
+
+/// Collects every match of the given logic state along with all of its capture groups, excluding the whole match,
+/// and the given descriptions of those groups
+///
+/// In byte mode the ranges and text are those of the escaped text that the bytes are written in
+pub fn exported_matches(
+    logic: &LogicState,
+    descriptions: &GroupDescriptions,
+) -> Vec<ExportedMatch> {
+    let text = &logic.selector.text;
+    // With other engines `regex` only has the capture groups of the pattern, which is all that's needed for their names
+    let names = logic.regex.capture_names().collect::<Vec<_>>();
+    (0..logic.selector.matches.len())
+        .filter_map(|index| {
+            let groups = logic.match_groups(index);
+            let (whole, groups) = groups.split_first()?;
+            let range = whole.clone()?;
+            Some(ExportedMatch {
+                index,
+                text: text[range.clone()].to_owned(),
+                range,
+                groups: groups
+                    .iter()
+                    .enumerate()
+                    .map(|(group, range)| {
+                        let name = names.get(group + 1).copied().flatten();
+                        ExportedGroup {
+                            index: group + 1,
+                            name: name.map(str::to_owned),
+                            description: descriptions.get(group + 1, name).map(str::to_owned),
+                            text: range.clone().map(|range| text[range].to_owned()),
+                            range: range.clone(),
+                        }
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Formats every match of the given logic state as pretty-printed JSON, with the given descriptions of its groups
+pub fn to_json(logic: &LogicState, descriptions: &GroupDescriptions) -> String {
+    // Only strings, numbers and options are serialized, which can't fail
+    serde_json::to_string_pretty(&exported_matches(logic, descriptions)).unwrap_or_default()
+}
+
+/// Formats every match of the given logic state as CSV, with a row for each match and a column for each capture group
+///
+/// The header names each group by its name if it has one, or its index if it doesn't,
+/// and groups that didn't participate in a match are left empty
+pub fn to_csv(logic: &LogicState) -> String {
+    let mut header = vec!["index".to_owned(), "start".to_owned(), "end".to_owned()];
+    header.extend(
+        logic
+            .regex
+            .capture_names()
+            .enumerate()
+            .map(|(index, name)| match (index, name) {
+                (0, _) => "match".to_owned(),
+                (_, Some(name)) => name.to_owned(),
+                (index, None) => index.to_string(),
+            }),
+    );
+
+    // Descriptions aren't values of the matches, so they have no place in the table
+    let mut csv = csv_row(&header);
+    for exported in exported_matches(logic, &GroupDescriptions::default()) {
+        let mut row = vec![
+            exported.index.to_string(),
+            exported.range.start.to_string(),
+            exported.range.end.to_string(),
+            exported.text,
+        ];
+        row.extend(
+            exported
+                .groups
+                .into_iter()
+                .map(|group| group.text.unwrap_or_default()),
+        );
+        csv.push_str(&csv_row(&row));
+    }
+    csv
+}
+
+/// Joins fields into a line of CSV, ending with a CRLF line break as RFC 4180 specifies
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|field| escape_csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quotes a CSV field if it contains a comma, quote or line break, doubling any quotes inside it
+pub fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// How the columns of a table of matches are separated
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TableBorders {
+    /// Lines drawn with box-drawing characters
+    BoxDrawing,
+    /// Padding with spaces, with the header underlined with dashes
+    Spaces,
+}
+
+/// Options for `to_aligned_table`
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TableOptions {
+    /// The widest that a cell can be, in columns of monospace text, beyond which it is truncated with an ellipsis
+    pub max_width: usize,
+    pub borders: TableBorders,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 40,
+            borders: TableBorders::BoxDrawing,
+        }
+    }
+}
+
+/// Formats the matches of the given logic state as a plain text table, with a row for each match and a column for each
+/// capture group
+///
+/// The columns are aligned when displayed in a monospace font, with wide characters (Such as CJK characters) taking up
+/// two columns, so that the table can be shared in places that only support plain text.
+/// In byte mode the cells are the escaped text that the bytes are written in.
+/// The given descriptions of the capture groups are listed below the table
+pub fn to_aligned_table(
+    logic: &LogicState,
+    options: TableOptions,
+    descriptions: &GroupDescriptions,
+) -> String {
+    let mut header = vec!["#".to_owned()];
+    header.extend(
+        logic
+            .regex
+            .capture_names()
+            .enumerate()
+            .map(|(index, name)| match (index, name) {
+                (0, _) => "Match".to_owned(),
+                (_, Some(name)) => name.to_owned(),
+                (index, None) => index.to_string(),
+            }),
+    );
+
+    let mut rows = vec![header];
+    let text = &logic.selector.text;
+    for index in 0..logic.selector.matches.len() {
+        // The selector may only have the whole match of each match, if capture groups are extracted lazily
+        let mut row = vec![(index + 1).to_string()];
+        row.extend(
+            logic
+                .match_groups(index)
+                .into_iter()
+                .map(|range| range.map_or("", |range| &text[range]).to_owned()),
+        );
+        rows.push(row);
+    }
+
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| fit_cell(cell, options.max_width))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let columns = logic.regex.captures_len() + 1;
+    let widths = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.width())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut table = String::new();
+    match options.borders {
+        TableBorders::BoxDrawing => {
+            table.push_str(&border_line(&widths, '┌', '┬', '┐'));
+            for (index, row) in rows.iter().enumerate() {
+                table.push('│');
+                for (column, width) in widths.iter().enumerate() {
+                    let cell = row.get(column).map_or("", String::as_str);
+                    table.push(' ');
+                    table.push_str(&pad_cell(cell, *width));
+                    table.push_str(" │");
+                }
+                table.push('\n');
+
+                if index == 0 {
+                    table.push_str(&border_line(&widths, '├', '┼', '┤'));
+                }
+            }
+            table.push_str(&border_line(&widths, '└', '┴', '┘'));
+        }
+        TableBorders::Spaces => {
+            for (index, row) in rows.iter().enumerate() {
+                let line = widths
+                    .iter()
+                    .enumerate()
+                    .map(|(column, width)| {
+                        pad_cell(row.get(column).map_or("", String::as_str), *width)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                table.push_str(line.trim_end());
+                table.push('\n');
+
+                if index == 0 {
+                    let underline = widths
+                        .iter()
+                        .map(|width| "-".repeat(*width))
+                        .collect::<Vec<_>>()
+                        .join("  ");
+                    table.push_str(&underline);
+                    table.push('\n');
+                }
+            }
+        }
+    }
+
+    let descriptions = descriptions.describe_groups(logic.regex.capture_names());
+    if !descriptions.is_empty() {
+        table.push('\n');
+        for description in descriptions {
+            table.push_str(&description);
+            table.push('\n');
+        }
+    }
+    table
+}
+
+/// Makes a value fit on one line of a table cell, replacing line breaks with `⏎`
+/// and truncating it with an ellipsis if it is wider than `max_width`
+pub fn fit_cell(value: &str, max_width: usize) -> String {
+    let value = value.replace("\r\n", "⏎").replace('\n', "⏎");
+    let max_width = max_width.max(1);
+    if value.width() <= max_width {
+        return value;
+    }
+
+    let mut fitted = String::new();
+    let mut width = 0;
+    for c in value.chars() {
+        let char_width = c.width().unwrap_or_default();
+        if width + char_width > max_width - 1 {
+            break;
+        }
+        fitted.push(c);
+        width += char_width;
+    }
+    fitted.push('…');
+    fitted
+}
+
+/// Pads a cell with spaces on the right until it is the given width
+fn pad_cell(cell: &str, width: usize) -> String {
+    format!("{}{}", cell, " ".repeat(width.saturating_sub(cell.width())))
+}
+
+/// Draws a horizontal border of a table with box-drawing characters, using the given characters for its corners
+/// and the places where it meets the borders between columns
+fn border_line(widths: &[usize], left: char, middle: char, right: char) -> String {
+    let mut line = String::new();
+    line.push(left);
+    for (index, width) in widths.iter().enumerate() {
+        if index > 0 {
+            line.push(middle);
+        }
+        line.push_str(&"─".repeat(width + 2));
+    }
+    line.push(right);
+    line.push('\n');
+    line
+}
+
+/// The programming languages that the regex can be copied as code for
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    /// A string literal to pass to `Regex::new`, with any flags set inline
+    Rust,
+    /// A call to `re.compile`
+    Python,
+    /// A regex literal
+    JavaScript,
+}
+
+impl CodeLanguage {
+    pub const ALL: [Self; 3] = [Self::Rust, Self::Python, Self::JavaScript];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "Rust",
+            Self::Python => "Python",
+            Self::JavaScript => "JavaScript",
+        }
+    }
+
+    /// The token that starts a line comment in this language
+    fn comment(self) -> &'static str {
+        match self {
+            Self::Rust | Self::JavaScript => "//",
+            Self::Python => "#",
+        }
+    }
+}
+
+/// Formats the given pattern as code in the given language that creates a regex with the same flags,
+/// preceded by the given descriptions of its capture groups as comments (See `GroupDescriptions::describe_groups`)
+///
+/// Syntax that the language writes differently is translated, and anything that can't be translated is left as it is,
+/// with a warning comment before the code saying so
+pub fn regex_as_code(
+    pattern: &str,
+    flags: RegexFlags,
+    language: CodeLanguage,
+    descriptions: &[String],
+) -> String {
+    let (pattern, warnings) = translate_pattern(pattern, flags, language);
+    let mut code = String::new();
+    for description in descriptions {
+        let _ = writeln!(code, "{} {}", language.comment(), description);
+    }
+    for warning in warnings {
+        let _ = writeln!(code, "{} Warning: {}", language.comment(), warning);
+    }
+
+    match language {
+        CodeLanguage::Rust => {
+            code.push_str(&rust_string_literal(&format!(
+                "{}{}",
+                rust_inline_flags(flags),
+                pattern
+            )));
+        }
+        CodeLanguage::Python => {
+            let names = [
+                ("re.IGNORECASE", flags.case_insensitive),
+                ("re.MULTILINE", flags.multi_line),
+                ("re.DOTALL", flags.dot_matches_new_line),
+                // Patterns are always Unicode aware in Python 3 unless told otherwise
+                ("re.ASCII", !flags.unicode),
+            ]
+            .into_iter()
+            .filter_map(|(name, set)| set.then_some(name))
+            .collect::<Vec<_>>();
+
+            let literal = python_string_literal(&pattern);
+            if names.is_empty() {
+                let _ = write!(code, "re.compile({})", literal);
+            } else {
+                let _ = write!(code, "re.compile({}, {})", literal, names.join(" | "));
+            }
+        }
+        CodeLanguage::JavaScript => {
+            let letters = [
+                ('i', flags.case_insensitive),
+                ('m', flags.multi_line),
+                ('s', flags.dot_matches_new_line),
+                ('u', flags.unicode),
+            ]
+            .into_iter()
+            .filter_map(|(letter, set)| set.then_some(letter))
+            .collect::<String>();
+
+            let _ = write!(code, "/{}/{}", javascript_regex_body(&pattern), letters);
+        }
+    }
+    code
+}
+
+/// Rewrites the given pattern into the syntax of the given language, returning it along with a warning
+/// for each part of it that can't be rewritten, which is left as it is
+///
+/// Neither language has the `U` flag, so the greediness of each repetition it applies to is swapped instead.
+/// Verbose mode is removed with `compact_verbose`, as JavaScript doesn't have it,
+/// and Python doesn't ignore whitespace in classes the way that Rust does.
+/// Patterns that don't parse are left as they are, as there's no way to tell what their parts are.
+fn translate_pattern(
+    pattern: &str,
+    flags: RegexFlags,
+    language: CodeLanguage,
+) -> (String, Vec<String>) {
+    if language == CodeLanguage::Rust {
+        return (pattern.into(), Vec::new());
+    }
+
+    let compact = compact_verbose(pattern, flags.ignore_whitespace);
+    let flags = RegexFlags {
+        ignore_whitespace: false,
+        ..flags
+    };
+    let ast = match parse_regex(&compact, flags) {
+        Ok(ast) => ast,
+        Err(_) => return (pattern.into(), Vec::new()),
+    };
+
+    let mut translator = PatternTranslator {
+        pattern: &compact,
+        language,
+        swap_greed: flags.swap_greed,
+        edits: Vec::new(),
+        warnings: Vec::new(),
+    };
+    translator.visit(&ast);
+
+    // The edits are made in the order that the AST is visited, which is the order that they appear in the pattern
+    let mut translated = String::with_capacity(compact.len());
+    let mut end = 0;
+    for (range, replacement) in translator.edits {
+        translated.push_str(&compact[end..range.start]);
+        translated.push_str(&replacement);
+        end = range.end;
+    }
+    translated.push_str(&compact[end..]);
+    (translated, translator.warnings)
+}
+
+/// Collects the edits that rewrite a pattern into the syntax of another language, see `translate_pattern`
+struct PatternTranslator<'a> {
+    pattern: &'a str,
+    language: CodeLanguage,
+    /// Whether the `U` flag is set where the node being visited is
+    swap_greed: bool,
+    /// Byte ranges of the pattern to replace, along with what to replace them with
+    edits: Vec<(Range<usize>, String)>,
+    warnings: Vec<String>,
+}
+
+impl PatternTranslator<'_> {
+    fn visit(&mut self, ast: &Ast) {
+        match ast {
+            Ast::Flags(set_flags) => {
+                self.translate_flags(&set_flags.flags, Some(set_flags.span.range()));
+            }
+            Ast::Group(group) => {
+                // Flags set within a group only last until the end of it
+                let outer_swap_greed = self.swap_greed;
+                match &group.kind {
+                    GroupKind::CaptureName(name) if self.language == CodeLanguage::JavaScript => {
+                        let start = group.span.start.offset;
+                        self.edits
+                            .push((start..name.span.start.offset, "(?<".into()));
+                    }
+                    GroupKind::NonCapturing(flags) => self.translate_flags(flags, None),
+                    _ => {}
+                }
+                self.visit(&group.ast);
+                self.swap_greed = outer_swap_greed;
+            }
+            Ast::Repetition(repetition) => {
+                self.visit(&repetition.ast);
+                if self.swap_greed {
+                    // A lazy repetition's operator ends with the `?` that makes it lazy
+                    let end = repetition.op.span.end.offset;
+                    if repetition.greedy {
+                        self.edits.push((end..end, "?".into()));
+                    } else {
+                        self.edits.push((end - 1..end, String::new()));
+                    }
+                }
+            }
+            Ast::Assertion(assertion) => {
+                let replacement = match (self.language, &assertion.kind) {
+                    (CodeLanguage::Python, AssertionKind::EndText) => r"\Z",
+                    (CodeLanguage::JavaScript, AssertionKind::StartText) => r"(?<![\s\S])",
+                    (CodeLanguage::JavaScript, AssertionKind::EndText) => r"(?![\s\S])",
+                    _ => return,
+                };
+                self.edits
+                    .push((assertion.span.range(), replacement.into()));
+            }
+            _ => {
+                for ast in ast_tree::children(ast) {
+                    self.visit(ast);
+                }
+            }
+        }
+    }
+
+    /// Applies the given inline flags, and removes the `U` flag from them, as greediness is swapped where it applies
+    ///
+    /// `set_span` is the span of the whole of a group that only sets flags, such as `(?i)`,
+    /// which is removed if it only set the `U` flag
+    fn translate_flags(&mut self, flags: &Flags, set_span: Option<Range<usize>>) {
+        let mut negated = false;
+        let mut turns_off_unicode = false;
+        let mut kept = String::new();
+        for item in &flags.items {
+            match item.kind {
+                FlagsItemKind::Negation => negated = true,
+                FlagsItemKind::Flag(Flag::SwapGreed) => {
+                    self.swap_greed = !negated;
+                    continue;
+                }
+                FlagsItemKind::Flag(Flag::Unicode) => turns_off_unicode |= negated,
+                FlagsItemKind::Flag(_) => {}
+            }
+            kept.push_str(&self.pattern[item.span.range()]);
+        }
+        let kept = kept.trim_end_matches('-');
+
+        let unsupported = match self.language {
+            CodeLanguage::Rust => None,
+            CodeLanguage::Python if turns_off_unicode => {
+                Some("Python can't turn off Unicode support for part of a pattern")
+            }
+            CodeLanguage::Python if set_span.as_ref().map_or(false, |span| span.start > 0) => {
+                Some("Python only allows flags outside of a group at the start of the pattern")
+            }
+            CodeLanguage::Python => None,
+            CodeLanguage::JavaScript if !kept.is_empty() => {
+                Some("JavaScript doesn't support inline flags")
+            }
+            CodeLanguage::JavaScript => None,
+        };
+
+        let bare = set_span.is_some();
+        let flags_span = flags.span.range();
+        match set_span {
+            Some(span) if kept.is_empty() => self.edits.push((span, String::new())),
+            _ if kept.len() < flags_span.len() => self.edits.push((flags_span, kept.into())),
+            _ => {}
+        }
+
+        if let Some(reason) = unsupported {
+            let source = if bare {
+                format!("(?{})", kept)
+            } else {
+                format!("(?{}:", kept)
+            };
+            self.warnings
+                .push(format!("{}, so {} is left as it is", reason, source));
+        }
+    }
+}
+
+/// The inline flag group that sets the given flags in a Rust regex, or an empty string if they are all the default
+fn rust_inline_flags(flags: RegexFlags) -> String {
+    let mut set = [
+        ('i', flags.case_insensitive),
+        ('m', flags.multi_line),
+        ('s', flags.dot_matches_new_line),
+        ('U', flags.swap_greed),
+        ('x', flags.ignore_whitespace),
+    ]
+    .into_iter()
+    .filter_map(|(letter, set)| set.then_some(letter))
+    .collect::<String>();
+
+    if !flags.unicode {
+        set.push_str("-u");
+    }
+
+    if set.is_empty() {
+        set
+    } else {
+        format!("(?{})", set)
+    }
+}
+
+/// Formats text as a Rust raw string literal, or as an escaped string literal if it contains a `"`,
+/// which would end a raw string without any `#`s
+fn rust_string_literal(text: &str) -> String {
+    if text.contains('"') {
+        escaped_string_literal(text, '"')
+    } else {
+        format!("r\"{}\"", text)
+    }
+}
+
+/// Formats text as a Python raw string literal, or as an escaped string literal if it can't be written raw
+fn python_string_literal(text: &str) -> String {
+    // A raw string can't contain its own quote or a line break, and can't end with an odd number of backslashes
+    let escapes_quote = (text.len() - text.trim_end_matches('\\').len()) % 2 == 1;
+    let can_be_raw = |quote| !text.contains([quote, '\n', '\r']) && !escapes_quote;
+
+    if can_be_raw('\'') {
+        format!("r'{}'", text)
+    } else if can_be_raw('"') {
+        format!("r\"{}\"", text)
+    } else {
+        escaped_string_literal(text, '\'')
+    }
+}
+
+/// Formats text as a string literal delimited by the given quote, escaping backslashes, quotes and line breaks
+fn escaped_string_literal(text: &str, quote: char) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push(quote);
+    for c in text.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c == quote => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push(quote);
+    literal
+}
+
+/// Escapes a pattern so that it can be the body of a JavaScript regex literal,
+/// by escaping any `/` that isn't already escaped, and writing line breaks as escape sequences
+fn javascript_regex_body(pattern: &str) -> String {
+    // `//` would start a comment instead
+    if pattern.is_empty() {
+        return "(?:)".into();
+    }
+
+    let mut body = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                body.push(c);
+                match chars.next() {
+                    Some('\n') => body.push('n'),
+                    Some('\r') => body.push('r'),
+                    Some(escaped) => body.push(escaped),
+                    None => {}
+                }
+            }
+            '/' => body.push_str("\\/"),
+            '\n' => body.push_str("\\n"),
+            '\r' => body.push_str("\\r"),
+            c => body.push(c),
+        }
+    }
+    body
+}
+
+/// The file formats that the AST of a regex can be exported in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AstFormat {
+    /// A Graphviz graph
+    Dot,
+    Json,
+}
+
+impl AstFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dot => "DOT",
+            Self::Json => "JSON",
+        }
+    }
+
+    /// The name of the file that an AST exported in this format is saved to
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Dot => "regex_visualiser_ast.dot",
+            Self::Json => "regex_visualiser_ast.json",
+        }
+    }
+
+    /// Formats the given AST, which must have been parsed from the given pattern, in this format
+    pub fn export(self, ast: &Ast, pattern: &str) -> String {
+        match self {
+            Self::Dot => ast_to_dot(ast, pattern),
+            Self::Json => ast_to_json(ast, pattern),
+        }
+    }
+}
+
+/// Formats the given AST, which must have been parsed from the given pattern, as a Graphviz graph,
+/// with a node for each node of the AST labelled with its description, and an edge to each of its children
+pub fn ast_to_dot(ast: &Ast, pattern: &str) -> String {
+    let mut dot = String::from("digraph ast {\n    node [shape=box, fontname=monospace];\n");
+    dot_node(&mut dot, ast, pattern, &mut 0);
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes the given node of an AST and all of its children to a Graphviz graph, returning the id of the node
+///
+/// Nodes are given ids in the order that they are written, counting up from `next_id`
+fn dot_node(dot: &mut String, ast: &Ast, pattern: &str, next_id: &mut usize) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+
+    let label = describe_ast(ast, pattern)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    let _ = writeln!(dot, "    n{} [label=\"{}\"];", id, label);
+    for child in ast_tree::children(ast) {
+        let child_id = dot_node(dot, child, pattern, next_id);
+        let _ = writeln!(dot, "    n{} -> n{};", id, child_id);
+    }
+    id
+}
+
+/// A node of the AST of a regex as it is exported to JSON
+#[derive(Serialize)]
+pub struct ExportedAstNode {
+    /// What kind of node this is, such as `literal` or `repetition`
+    pub kind: &'static str,
+    /// The byte range of the pattern that the node was parsed from
+    pub span: Range<usize>,
+    /// The properties that are particular to this kind of node, such as the bounds of a repetition
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub attributes: Map<String, Value>,
+    pub children: Vec<ExportedAstNode>,
+}
+
+impl ExportedAstNode {
+    /// Simplifies the given AST, which must have been parsed from the given pattern
+    pub fn from_ast(ast: &Ast, pattern: &str) -> Self {
+        let source = |span: &regex_syntax::ast::Span| {
+            pattern.get(span.range()).unwrap_or_default().to_owned()
+        };
+
+        let (kind, attributes) = match ast {
+            Ast::Empty(_) => ("empty", json!({})),
+            Ast::Flags(flags) => ("flags", json!({ "flags": source(&flags.flags.span) })),
+            Ast::Literal(literal) => ("literal", json!({ "char": literal.c })),
+            Ast::Dot(_) => ("dot", json!({})),
+            Ast::Assertion(assertion) => {
+                let assertion = match assertion.kind {
+                    AssertionKind::StartLine => "start_line",
+                    AssertionKind::EndLine => "end_line",
+                    AssertionKind::StartText => "start_text",
+                    AssertionKind::EndText => "end_text",
+                    AssertionKind::WordBoundary => "word_boundary",
+                    AssertionKind::NotWordBoundary => "not_word_boundary",
+                };
+                ("assertion", json!({ "assertion": assertion }))
+            }
+            Ast::Class(Class::Perl(class)) => {
+                let name = match class.kind {
+                    ClassPerlKind::Digit => "digit",
+                    ClassPerlKind::Space => "space",
+                    ClassPerlKind::Word => "word",
+                };
+                (
+                    "perl_class",
+                    json!({ "name": name, "negated": class.negated }),
+                )
+            }
+            Ast::Class(Class::Unicode(class)) => {
+                let name = match &class.kind {
+                    ClassUnicodeKind::OneLetter(letter) => letter.to_string(),
+                    ClassUnicodeKind::Named(name) => name.clone(),
+                    ClassUnicodeKind::NamedValue { name, value, .. } => {
+                        format!("{}={}", name, value)
+                    }
+                };
+                (
+                    "unicode_class",
+                    json!({ "name": name, "negated": class.negated }),
+                )
+            }
+            Ast::Class(Class::Bracketed(class)) => {
+                // The items of the class are listed as they were written, rather than as nodes of their own
+                let mut attributes = json!({ "negated": class.negated });
+                match &class.kind {
+                    ClassSet::Item(ClassSetItem::Union(union)) => {
+                        attributes["items"] =
+                            union.items.iter().map(|item| source(item.span())).collect();
+                    }
+                    ClassSet::Item(item) => attributes["items"] = json!([source(item.span())]),
+                    ClassSet::BinaryOp(op) => {
+                        let operation = match op.kind {
+                            ClassSetBinaryOpKind::Intersection => "intersection",
+                            ClassSetBinaryOpKind::Difference => "difference",
+                            ClassSetBinaryOpKind::SymmetricDifference => "symmetric_difference",
+                        };
+                        attributes["operation"] = operation.into();
+                        attributes["items"] = json!([source(op.lhs.span()), source(op.rhs.span())]);
+                    }
+                }
+                ("bracketed_class", attributes)
+            }
+            Ast::Repetition(repetition) => {
+                let (min, max) = match &repetition.op.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => (*n, Some(*n)),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (*n, None),
+                    RepetitionKind::Range(RepetitionRange::Bounded(n, m)) => (*n, Some(*m)),
+                };
+                (
+                    "repetition",
+                    json!({ "min": min, "max": max, "greedy": repetition.greedy }),
+                )
+            }
+            Ast::Group(group) => match &group.kind {
+                GroupKind::CaptureIndex(index) => ("group", json!({ "index": index })),
+                GroupKind::CaptureName(name) => {
+                    ("group", json!({ "index": name.index, "name": name.name }))
+                }
+                GroupKind::NonCapturing(flags) if flags.items.is_empty() => {
+                    ("non_capturing_group", json!({}))
+                }
+                GroupKind::NonCapturing(flags) => (
+                    "non_capturing_group",
+                    json!({ "flags": source(&flags.span) }),
+                ),
+            },
+            Ast::Alternation(_) => ("alternation", json!({})),
+            Ast::Concat(_) => ("concat", json!({})),
+        };
+
+        Self {
+            kind,
+            span: ast.span().range(),
+            attributes: match attributes {
+                Value::Object(attributes) => attributes,
+                _ => Map::new(),
+            },
+            children: ast_tree::children(ast)
+                .iter()
+                .map(|ast| Self::from_ast(ast, pattern))
+                .collect(),
+        }
+    }
+}
+
+/// Formats the given AST, which must have been parsed from the given pattern, as pretty-printed JSON
+pub fn ast_to_json(ast: &Ast, pattern: &str) -> String {
+    // Only strings, numbers, booleans and maps of them are serialized, which can't fail
+    serde_json::to_string_pretty(&ExportedAstNode::from_ast(ast, pattern)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::state::{HighlightMode, LogicOptions};
+    use egui::Style;
+
+    #[test]
+    fn format_matches_uses_the_highlighted_matches() {
+        let tokens = parse_template("{match.index}:{group:1}{group.b};").unwrap();
+        let input = "ab\nab\nab";
+        for (line_mode, match_limit, expected) in [
+            (false, None, "0:a;1:b;"),
+            (true, None, "0:a;1:b;2:a;3:b;4:a;5:b;"),
+            (true, Some(3), "0:a;1:b;2:a;"),
+        ] {
+            let logic = LogicState::new(
+                "^(a)|(?P<b>b)$",
+                &Style::default(),
+                "",
+                input,
+                LogicOptions {
+                    line_mode,
+                    match_limit,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(format_matches(&tokens, &logic), expected);
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_escaped() {
+        let fields = [
+            ("plain", "plain"),
+            ("", ""),
+            ("a,b", "\"a,b\""),
+            ("say \"hi\"", "\"say \"\"hi\"\"\""),
+            ("two\nlines", "\"two\nlines\""),
+            ("cr\r", "\"cr\r\""),
+        ];
+        for (field, expected) in fields {
+            assert_eq!(escape_csv_field(field), expected);
+        }
+    }
+
+    /// Every capture group gets a column, even if it didn't match
+    #[test]
+    fn csv_export_has_every_group() {
+        let logic = LogicState::new(
+            "(?P<key>[^=;]+)=(?P<value>[^;!]*)(!)?",
+            &Style::default(),
+            "",
+            "a,b=\"x\";c=1\n2!",
+            LogicOptions {
+                highlight_mode: HighlightMode::Captures,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            to_csv(&logic),
+            "index,start,end,match,key,value,3\r\n\
+            0,0,7,\"a,b=\"\"x\"\"\",\"a,b\",\"\"\"x\"\"\",\r\n\
+            1,8,14,\"c=1\n2!\",c,\"1\n2\",!\r\n"
+        );
+
+        let groups = exported_matches(&logic, &Default::default())
+            .iter()
+            .flat_map(|exported| &exported.groups)
+            .map(|group| (group.index, group.name.clone(), group.range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [
+                (1, Some("key".to_owned()), Some(0..3)),
+                (2, Some("value".to_owned()), Some(4..7)),
+                (3, None, None),
+                (1, Some("key".to_owned()), Some(8..9)),
+                (2, Some("value".to_owned()), Some(10..13)),
+                (3, None, Some(13..14)),
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_as_code_translates_rust_syntax() {
+        use CodeLanguage::{JavaScript, Python};
+
+        let swap_greed = RegexFlags {
+            swap_greed: true,
+            ..Default::default()
+        };
+        let verbose = RegexFlags {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let cases = [
+            (r"a+b*?", swap_greed, Python, r"re.compile(r'a+?b*')"),
+            (r"a+b*?", swap_greed, JavaScript, r"/a+?b*/u"),
+            (r"a+(?-U:b+)c+", swap_greed, Python, r"re.compile(r'a+?(?:b+)c+?')"),
+            (r"(?iU:a+)", Default::default(), Python, r"re.compile(r'(?i:a+?)')"),
+            (r"a(?U)b*", Default::default(), JavaScript, r"/ab*?/u"),
+            ("a b # comment\n+", verbose, JavaScript, r"/ab+/u"),
+            (r"[ a]", verbose, Python, r"re.compile(r'[a]')"),
+            (r"\Aa\z", Default::default(), Python, r"re.compile(r'\Aa\Z')"),
+            (
+                r"\A(?P<word>\w+)\z",
+                Default::default(),
+                JavaScript,
+                r"/(?<![\s\S])(?<word>\w+)(?![\s\S])/u",
+            ),
+            (
+                r"(?i:a)b",
+                Default::default(),
+                JavaScript,
+                "// Warning: JavaScript doesn't support inline flags, so (?i: is left as it is\n/(?i:a)b/u",
+            ),
+            (
+                r"a(?i)b",
+                Default::default(),
+                Python,
+                "# Warning: Python only allows flags outside of a group at the start of the pattern, \
+                so (?i) is left as it is\nre.compile(r'a(?i)b')",
+            ),
+            (
+                r"(?-u:\w)",
+                Default::default(),
+                Python,
+                "# Warning: Python can't turn off Unicode support for part of a pattern, \
+                so (?-u: is left as it is\nre.compile(r'(?-u:\\w)')",
+            ),
+        ];
+        for (pattern, flags, language, expected) in cases {
+            assert_eq!(
+                regex_as_code(pattern, flags, language, &[]),
+                expected,
+                "{:?} as {} code",
+                pattern,
+                language.name()
+            );
+        }
+    }
+
+    #[test]
+    fn exports_include_group_descriptions() {
+        let logic = LogicState::new(
+            r"(?P<year>\d{4})-(\d\d)",
+            &Style::default(),
+            "",
+            "2022-10",
+            Default::default(),
+        )
+        .unwrap();
+        let mut descriptions = GroupDescriptions::default();
+        descriptions.update_groups(logic.regex.capture_names());
+        *descriptions.get_mut(1, Some("year")) = "Four-digit year".into();
+        *descriptions.get_mut(2, None) = "Month".into();
+
+        let json = serde_json::from_str::<Value>(&to_json(&logic, &descriptions)).unwrap();
+        assert_eq!(json[0]["groups"][0]["description"], "Four-digit year");
+        assert_eq!(json[0]["groups"][1]["description"], "Month");
+
+        let options = TableOptions {
+            borders: TableBorders::Spaces,
+            ..Default::default()
+        };
+        assert!(to_aligned_table(&logic, options, &descriptions)
+            .ends_with("\nGroup 1 (year): Four-digit year\nGroup 2: Month\n"));
+
+        let lines = descriptions.describe_groups(logic.regex.capture_names());
+        assert_eq!(
+            regex_as_code("a", Default::default(), CodeLanguage::Python, &lines),
+            "# Group 1 (year): Four-digit year\n# Group 2: Month\nre.compile(r'a')"
+        );
+    }
+
+    #[test]
+    fn aligned_tables_line_up_wide_chars() {
+        let logic = LogicState::new(
+            r"(?P<word>[a-z日本]+)(\d)?",
+            &Style::default(),
+            "",
+            "日本 ab1",
+            Default::default(),
+        )
+        .unwrap();
+        let descriptions = GroupDescriptions::default();
+        assert_eq!(
+            to_aligned_table(&logic, TableOptions::default(), &descriptions),
+            "┌───┬───────┬──────┬───┐\n\
+             │ # │ Match │ word │ 2 │\n\
+             ├───┼───────┼──────┼───┤\n\
+             │ 1 │ 日本  │ 日本 │   │\n\
+             │ 2 │ ab1   │ ab   │ 1 │\n\
+             └───┴───────┴──────┴───┘\n"
+        );
+
+        let options = TableOptions {
+            max_width: 3,
+            borders: TableBorders::Spaces,
+        };
+        assert_eq!(
+            to_aligned_table(&logic, options, &descriptions),
+            "#  Ma…  wo…  2\n\
+             -  ---  ---  -\n\
+             1  日…  日…\n\
+             2  ab1  ab   1\n"
+        );
+    }
+
+    #[test]
+    fn fit_cell_truncates_to_the_width() {
+        assert_eq!(fit_cell("a\r\nb\nc", 10), "a⏎b⏎c");
+        assert_eq!(fit_cell("abc", 3), "abc");
+        assert_eq!(fit_cell("abcd", 3), "ab…");
+        // A wide char that would only half fit is left out
+        assert_eq!(fit_cell("a日本", 4), "a日…");
+        assert_eq!(fit_cell("abc", 0), "…");
+    }
+
+    #[cfg(feature = "backend-fancy")]
+    #[test]
+    fn aligned_table_finds_groups_with_fancy_regex() {
+        let logic = LogicState::new(
+            r"(\w)(?=b)",
+            &Style::default(),
+            "",
+            "ab cd",
+            LogicOptions {
+                engine: crate::app::state::RegexEngine::Fancy,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let options = TableOptions {
+            borders: TableBorders::Spaces,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_aligned_table(&logic, options, &GroupDescriptions::default()),
+            "#  Match  1\n-  -----  -\n1  a      a\n"
+        );
+    }
+
+    #[test]
+    fn regex_as_code_quotes_the_pattern() {
+        use CodeLanguage::{JavaScript, Python, Rust};
+
+        let flags = RegexFlags {
+            case_insensitive: true,
+            unicode: false,
+            ..Default::default()
+        };
+        let cases = [
+            (r"\d+", Default::default(), Rust, r#"r"\d+""#),
+            (r"\d+", flags, Rust, r#"r"(?i-u)\d+""#),
+            (
+                r#"say "\w+""#,
+                Default::default(),
+                Rust,
+                r#""say \"\\w+\"""#,
+            ),
+            (r"\d+", Default::default(), Python, r"re.compile(r'\d+')"),
+            (
+                r"\d+",
+                flags,
+                Python,
+                r"re.compile(r'\d+', re.IGNORECASE | re.ASCII)",
+            ),
+            (
+                r"it's",
+                Default::default(),
+                Python,
+                r#"re.compile(r"it's")"#,
+            ),
+            (
+                r#"'\w+' or "\w+""#,
+                Default::default(),
+                Python,
+                r#"re.compile('\'\\w+\' or "\\w+"')"#,
+            ),
+            (r"a\\", Default::default(), Python, r"re.compile(r'a\\')"),
+            // A raw string can't end with an odd number of backslashes
+            (r"a\", Default::default(), Python, r"re.compile('a\\')"),
+            (r"a/b\/c", Default::default(), JavaScript, r"/a\/b\/c/u"),
+            (r"[/]", flags, JavaScript, r"/[\/]/i"),
+            ("", Default::default(), JavaScript, "/(?:)/u"),
+        ];
+        for (pattern, flags, language, expected) in cases {
+            assert_eq!(
+                regex_as_code(pattern, flags, language, &[]),
+                expected,
+                "{:?} as {} code",
+                pattern,
+                language.name()
+            );
+        }
+    }
+
+    #[test]
+    fn ast_exports_have_every_node() {
+        use crate::app::ast_tree::AstNode;
+
+        fn count_nodes(node: &AstNode) -> usize {
+            1 + node.children.iter().map(count_nodes).sum::<usize>()
+        }
+        fn count_json_nodes(node: &Value) -> usize {
+            let children = node["children"].as_array().map(Vec::as_slice);
+            1 + children
+                .unwrap_or_default()
+                .iter()
+                .map(count_json_nodes)
+                .sum::<usize>()
+        }
+
+        for pattern in [
+            "",
+            "abc",
+            r"^(?P<word>\w+)\s*(?:a|b|)$",
+            r"[^a-z\d[:alpha:]]{2,}?",
+            r#"(?i)\p{Greek}|\B."\\"#,
+        ] {
+            let ast = parse_regex(pattern, Default::default()).unwrap();
+            let expected = count_nodes(&AstNode::from_ast(&ast, pattern));
+
+            // Every node but the root is connected to its parent by an edge
+            let dot = ast_to_dot(&ast, pattern);
+            let nodes = dot.lines().filter(|line| line.contains("[label=")).count();
+            let edges = dot.lines().filter(|line| line.contains(" -> ")).count();
+            assert_eq!((nodes, edges + 1), (expected, expected), "{:?}", pattern);
+
+            let json = serde_json::from_str::<Value>(&ast_to_json(&ast, pattern)).unwrap();
+            assert_eq!(count_json_nodes(&json), expected, "{:?}", pattern);
+            assert_eq!(json["span"]["end"], pattern.len());
+        }
+    }
+
+    #[test]
+    fn ast_export_labels_and_attributes() {
+        // Quotes and backslashes in labels are escaped
+        let pattern = r#""\\"#;
+        let dot = ast_to_dot(&parse_regex(pattern, Default::default()).unwrap(), pattern);
+        assert!(dot.contains(r#"[label="Literal: '\"'"]"#), "{}", dot);
+        assert!(
+            dot.contains(r#"[label="Escape: \\\\ ('\\\\')"]"#),
+            "{}",
+            dot
+        );
+
+        // The attributes of each kind of node are included
+        let pattern = "(?P<n>a{2,}?)";
+        let ast = parse_regex(pattern, Default::default()).unwrap();
+        let json = serde_json::from_str::<Value>(&ast_to_json(&ast, pattern)).unwrap();
+        assert_eq!(json["attributes"].to_string(), r#"{"index":1,"name":"n"}"#);
+        assert_eq!(
+            json["children"][0]["attributes"].to_string(),
+            r#"{"greedy":false,"max":null,"min":2}"#
+        );
+    }
+}
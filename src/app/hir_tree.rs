@@ -0,0 +1,228 @@
+use regex_syntax::hir::{
+    Anchor, Class, GroupKind, Hir, HirKind, Literal, RepetitionKind, RepetitionRange, WordBoundary,
+};
+
+/// How many of the ranges of a class are listed in its description before the rest are left out
+const CLASS_SUMMARY_RANGES: usize = 4;
+
+/// A node of a tree that describes the HIR of a regex, which is what the regex engine compiles,
+/// after classes such as `\w` and flags such as `i` have been expanded
+///
+/// Unlike the AST, the HIR of `regex-syntax` 0.6 doesn't record which part of the pattern each node came from
+pub struct HirNode {
+    /// A human-readable description of the node
+    pub label: String,
+    pub children: Vec<HirNode>,
+}
+
+impl HirNode {
+    /// Builds a tree describing the given HIR
+    pub fn from_hir(hir: &Hir) -> Self {
+        Self {
+            label: describe_hir(hir),
+            children: children(hir).iter().map(Self::from_hir).collect(),
+        }
+    }
+}
+
+/// Returns the nodes directly inside of the given node of a HIR
+fn children(hir: &Hir) -> &[Hir] {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => std::slice::from_ref(&*repetition.hir),
+        HirKind::Group(group) => std::slice::from_ref(&*group.hir),
+        HirKind::Alternation(hirs) | HirKind::Concat(hirs) => hirs,
+        _ => &[],
+    }
+}
+
+/// Returns a human-readable description of the given node of a HIR, without describing the nodes inside of it
+pub fn describe_hir(hir: &Hir) -> String {
+    match hir.kind() {
+        HirKind::Empty => "Empty".into(),
+        HirKind::Literal(Literal::Unicode(c)) => format!("Literal: {:?}", c),
+        HirKind::Literal(Literal::Byte(b)) => format!("Byte: \\x{:02X}", b),
+        HirKind::Class(Class::Unicode(class)) => describe_class(
+            "Class",
+            class
+                .ranges()
+                .iter()
+                .map(|range| (range.start(), range.end())),
+            describe_char,
+        ),
+        HirKind::Class(Class::Bytes(class)) => describe_class(
+            "Byte class",
+            class
+                .ranges()
+                .iter()
+                .map(|range| (range.start(), range.end())),
+            describe_byte,
+        ),
+        HirKind::Anchor(anchor) => {
+            let description = match anchor {
+                Anchor::StartLine => "start of line",
+                Anchor::EndLine => "end of line",
+                Anchor::StartText => "start of text",
+                Anchor::EndText => "end of text",
+            };
+            format!("Anchor: {}", description)
+        }
+        HirKind::WordBoundary(boundary) => {
+            let description = match boundary {
+                WordBoundary::Unicode => "word boundary",
+                WordBoundary::UnicodeNegate => "not a word boundary",
+                WordBoundary::Ascii => "ASCII word boundary",
+                WordBoundary::AsciiNegate => "not an ASCII word boundary",
+            };
+            format!("Assertion: {}", description)
+        }
+        HirKind::Repetition(repetition) => {
+            let count = match &repetition.kind {
+                RepetitionKind::ZeroOrOne => "zero or one time".into(),
+                RepetitionKind::ZeroOrMore => "zero or more times".into(),
+                RepetitionKind::OneOrMore => "one or more times".into(),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                    format!("exactly {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                    format!("at least {} times", n)
+                }
+                RepetitionKind::Range(RepetitionRange::Bounded(n, m)) => {
+                    format!("between {} and {} times", n, m)
+                }
+            };
+            let greediness = if repetition.greedy { "greedy" } else { "lazy" };
+            format!("Repetition: {}, {}", count, greediness)
+        }
+        HirKind::Group(group) => match &group.kind {
+            GroupKind::CaptureIndex(index) => format!("Capture group {}", index),
+            GroupKind::CaptureName { name, index } => {
+                format!("Capture group {} ({})", index, name)
+            }
+            GroupKind::NonCapturing => "Non-capturing group".into(),
+        },
+        HirKind::Alternation(hirs) => format!("Alternation of {} branches", hirs.len()),
+        HirKind::Concat(hirs) => format!("Sequence of {} items", hirs.len()),
+    }
+}
+
+/// Summarises a class by how many ranges it has and what the first few of them are,
+/// as a class such as `\w` can have hundreds of them
+fn describe_class<T: Copy + PartialEq>(
+    kind: &str,
+    ranges: impl ExactSizeIterator<Item = (T, T)>,
+    describe: impl Fn(T) -> String,
+) -> String {
+    let count = ranges.len();
+    if count == 0 {
+        return format!("{}: empty, matches nothing", kind);
+    }
+
+    let mut listed = ranges
+        .take(CLASS_SUMMARY_RANGES)
+        .map(|(start, end)| {
+            if start == end {
+                describe(start)
+            } else {
+                format!("{}-{}", describe(start), describe(end))
+            }
+        })
+        .collect::<Vec<_>>();
+    let includes = if count > CLASS_SUMMARY_RANGES {
+        listed.push("…".into());
+        "includes "
+    } else {
+        ""
+    };
+    let plural = if count == 1 { "range" } else { "ranges" };
+    format!(
+        "{}: {} {}, {}{}",
+        kind,
+        count,
+        plural,
+        includes,
+        listed.join(", ")
+    )
+}
+
+/// Writes a char of a class as itself, unless it would be invisible or mistaken for part of the description
+fn describe_char(c: char) -> String {
+    match c {
+        '-' | ',' => format!("\\{}", c),
+        '\t' | '\n' | '\r' => c.escape_default().to_string(),
+        c if c.is_control() || c.is_whitespace() => format!("\\x{{{:X}}}", c as u32),
+        c => c.to_string(),
+    }
+}
+
+/// Writes a byte of a class as the ASCII char that it is, or as an escape sequence if it isn't a printable one
+fn describe_byte(b: u8) -> String {
+    if b.is_ascii_graphic() {
+        describe_char(b as char)
+    } else {
+        format!("\\x{:02X}", b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::{parse_regex, translate_regex, RegexFlags};
+
+    /// Describes the tree with one node on each line, indented by its depth
+    fn describe_tree(node: &HirNode, depth: usize, labels: &mut Vec<String>) {
+        labels.push(format!("{}{}", "  ".repeat(depth), node.label));
+        for child in &node.children {
+            describe_tree(child, depth + 1, labels);
+        }
+    }
+
+    #[test]
+    fn hir_tree_labels() {
+        let ascii = RegexFlags {
+            unicode: false,
+            ..Default::default()
+        };
+        let cases = [
+            (
+                "(?i)k",
+                Default::default(),
+                false,
+                "Class: 3 ranges, K, k, \u{212A}",
+            ),
+            (
+                "(?P<x>a)|[-,]+?",
+                Default::default(),
+                false,
+                "Alternation of 2 branches\n  Capture group 1 (x)\n    Literal: 'a'\n  \
+                Repetition: one or more times, lazy\n    Class: 1 range, \\,-\\-",
+            ),
+            (
+                r"^\bx{2,}",
+                ascii,
+                false,
+                "Sequence of 3 items\n  Anchor: start of text\n  Assertion: ASCII word boundary\n  \
+                Repetition: at least 2 times, greedy\n    Literal: 'x'",
+            ),
+            (
+                r"[\x00-\x10 \xFF]",
+                ascii,
+                true,
+                r"Byte class: 3 ranges, \x00-\x10, \x20, \xFF",
+            ),
+            (
+                r"[\w!]",
+                ascii,
+                false,
+                "Byte class: 5 ranges, includes !, 0-9, A-Z, _, …",
+            ),
+        ];
+
+        for (pattern, flags, bytes, expected) in cases {
+            let ast = parse_regex(pattern, flags).unwrap();
+            let hir = translate_regex(pattern, &ast, flags, bytes).unwrap();
+            let mut labels = Vec::new();
+            describe_tree(&HirNode::from_hir(&hir), 0, &mut labels);
+            assert_eq!(labels.join("\n"), expected, "{:?}", pattern);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+/// A pattern that compiled successfully, recorded in the pattern journal
+#[derive(Clone, Deserialize, Serialize)]
+pub struct JournalEntry {
+    /// When the pattern was compiled, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub pattern: String,
+    /// How many times the pattern matched the input text when it was compiled,
+    /// or `None` if the matches weren't counted because the input text was too large
+    pub match_count: Option<usize>,
+}
+
+impl JournalEntry {
+    /// Formats the timestamp of this entry as a UTC date and time, such as "2022-09-30 14:05:09"
+    pub fn format_timestamp(&self) -> String {
+        let seconds = self.timestamp / 1000;
+        let (days, time) = (seconds / 86400, seconds % 86400);
+
+        // Converts days since the Unix epoch to a civil date, from http://howardhinnant.github.io/date_algorithms.html
+        let days = days as i64 + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )
+    }
+}
+
+/// An opt-in record of every pattern that compiled successfully, with the time it was compiled
+#[derive(Default)]
+pub struct Journal {
+    /// The entries shown in the journal viewer, which are only loaded once it is first opened
+    pub entries: Option<Vec<JournalEntry>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    writer: Option<std::sync::mpsc::Sender<native::WriteCommand>>,
+}
+
+impl Journal {
+    /// Records that the given pattern compiled successfully
+    pub fn record(&mut self, pattern: &str, match_count: Option<usize>) {
+        self.append(JournalEntry {
+            timestamp: now(),
+            pattern: pattern.to_owned(),
+            match_count,
+        });
+    }
+
+    /// Returns the entries in the journal, loading them if they haven't been loaded yet
+    pub fn entries(&mut self) -> &[JournalEntry] {
+        self.entries.get_or_insert_with(load)
+    }
+}
+
+/// Returns the current time in milliseconds since the Unix epoch (Native)
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Returns the current time in milliseconds since the Unix epoch (Wasm)
+#[cfg(target_arch = "wasm32")]
+fn now() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use native::load;
+
+#[cfg(target_arch = "wasm32")]
+use wasm::load;
+
+/// The journal is kept in a JSON Lines file in the app's data directory, which is written to on a background thread
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::{Journal, JournalEntry};
+    use std::{
+        fs::{File, OpenOptions},
+        io::{BufRead, BufReader, BufWriter, Write},
+        path::PathBuf,
+        sync::mpsc::{channel, Receiver},
+    };
+
+    pub enum WriteCommand {
+        Append(JournalEntry),
+        Clear,
+    }
+
+    fn journal_path() -> Option<PathBuf> {
+        directories_next::ProjectDirs::from("", "", "regex_visualiser")
+            .map(|dirs| dirs.data_dir().join("pattern_journal.jsonl"))
+    }
+
+    pub fn load() -> Vec<JournalEntry> {
+        let file = match journal_path().and_then(|path| File::open(path).ok()) {
+            Some(file) => file,
+            None => return Vec::new(),
+        };
+
+        // Malformed lines are skipped, so that one bad write doesn't make the rest of the journal unreadable
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect()
+    }
+
+    impl Journal {
+        pub(super) fn append(&mut self, entry: JournalEntry) {
+            if let Some(entries) = &mut self.entries {
+                entries.push(entry.clone());
+            }
+            self.send(WriteCommand::Append(entry));
+        }
+
+        /// Removes every entry from the journal
+        pub fn clear(&mut self) {
+            self.entries = Some(Vec::new());
+            self.send(WriteCommand::Clear);
+        }
+
+        fn send(&mut self, command: WriteCommand) {
+            let writer = self.writer.get_or_insert_with(|| {
+                let (sender, receiver) = channel();
+                std::thread::spawn(move || write_commands(receiver));
+                sender
+            });
+
+            // The writer thread only stops if it can't find somewhere to write the journal,
+            // in which case there isn't anything more useful to do with the entry
+            let _ = writer.send(command);
+        }
+    }
+
+    /// Writes the commands sent to the journal as they arrive, flushing the file whenever the queue is empty
+    fn write_commands(receiver: Receiver<WriteCommand>) {
+        let path = match journal_path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+
+        let open = || OpenOptions::new().create(true).append(true).open(&path);
+        let mut file = match open() {
+            Ok(file) => BufWriter::new(file),
+            Err(_) => return,
+        };
+
+        while let Ok(command) = receiver.recv() {
+            for command in std::iter::once(command).chain(receiver.try_iter()) {
+                match command {
+                    WriteCommand::Append(entry) => {
+                        if let Ok(line) = serde_json::to_string(&entry) {
+                            let _ = writeln!(file, "{}", line);
+                        }
+                    }
+                    WriteCommand::Clear => {
+                        file = match File::create(&path) {
+                            Ok(file) => BufWriter::new(file),
+                            Err(_) => return,
+                        };
+                    }
+                }
+            }
+            let _ = file.flush();
+        }
+    }
+}
+
+/// The journal is kept in local storage as JSON Lines, as a ring of the most recent entries
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use super::{Journal, JournalEntry};
+
+    /// The local storage key under which the journal is kept
+    const JOURNAL_KEY: &str = "regex_visualiser_pattern_journal";
+
+    /// The maximum number of entries to keep, as local storage is small
+    const MAX_ENTRIES: usize = 1000;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn load() -> Vec<JournalEntry> {
+        local_storage()
+            .and_then(|storage| storage.get_item(JOURNAL_KEY).ok()?)
+            .map(|journal| {
+                journal
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn store(entries: &[JournalEntry]) {
+        let journal = entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(JOURNAL_KEY, &journal);
+        }
+    }
+
+    impl Journal {
+        pub(super) fn append(&mut self, entry: JournalEntry) {
+            let entries = self.entries.get_or_insert_with(load);
+            entries.push(entry);
+            if entries.len() > MAX_ENTRIES {
+                entries.drain(..entries.len() - MAX_ENTRIES);
+            }
+            store(entries);
+        }
+
+        /// Removes every entry from the journal
+        pub fn clear(&mut self) {
+            self.entries = Some(Vec::new());
+            store(&[]);
+        }
+    }
+}
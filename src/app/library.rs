@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A pattern saved in the pattern library, along with the replacement text and input text that it was saved with
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LibraryEntry {
+    pub pattern: String,
+    pub replace_text: String,
+    pub input: String,
+}
+
+/// Patterns that have been saved under a name, so that they can be loaded again later
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PatternLibrary {
+    /// The saved entries, ordered by name
+    entries: BTreeMap<String, LibraryEntry>,
+}
+
+impl PatternLibrary {
+    pub fn get(&self, name: &str) -> Option<&LibraryEntry> {
+        self.entries.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Saves an entry under the given name, replacing any entry that was already saved under it
+    pub fn insert(&mut self, name: String, entry: LibraryEntry) {
+        self.entries.insert(name, entry);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    /// Returns each entry along with its name, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &LibraryEntry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+}
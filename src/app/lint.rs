@@ -0,0 +1,397 @@
+use super::{ast_tree::children, text::GetRangeExt};
+use regex_syntax::{
+    ast::{Alternation, Ast, Repetition, RepetitionKind, RepetitionRange},
+    hir::{self, translate::TranslatorBuilder, Hir, HirKind},
+};
+use std::ops::Range;
+
+/// How many times a repetition has to be able to repeat before it is linted as large
+pub const LARGE_REPETITION: u32 = 1000;
+
+/// How likely a lint is to make matching slow
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::High => "High",
+        }
+    }
+}
+
+/// The kinds of pattern that can make matching slow, especially with engines that backtrack
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LintKind {
+    /// An unbounded repetition of something that contains another unbounded repetition, such as `(a+)+`
+    NestedRepetition,
+    /// A repetition of an alternation whose alternatives can start with the same character, such as `(a|ab)*`
+    OverlappingAlternation,
+    /// A repetition that can repeat more than `LARGE_REPETITION` times, such as `a{1,99999}`
+    LargeRepetition,
+}
+
+impl LintKind {
+    /// A short identifier for this kind of lint
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::NestedRepetition => "nested-repetition",
+            Self::OverlappingAlternation => "overlapping-alternation",
+            Self::LargeRepetition => "large-repetition",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::NestedRepetition => Severity::High,
+            Self::OverlappingAlternation => Severity::Medium,
+            Self::LargeRepetition => Severity::Low,
+        }
+    }
+}
+
+/// A part of a regex that could make matching slow
+#[derive(Debug)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub description: String,
+    /// The byte ranges of the pattern that the lint is about, starting with the one that is linted
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Finds the parts of the given AST, which must have been parsed from the given pattern,
+/// that could make matching slow, in the order that they appear in the pattern
+///
+/// `bytes` is whether the regex matches bytes, in which case it can match bytes that aren't valid UTF-8.
+/// The `regex` crate never backtracks, so these mostly matter for patterns that are meant for engines that do
+pub fn lint_ast(ast: &Ast, pattern: &str, bytes: bool) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut stack = vec![ast];
+    while let Some(ast) = stack.pop() {
+        if let Ast::Repetition(repetition) = ast {
+            lint_repetition(repetition, pattern, bytes, &mut lints);
+        }
+        stack.extend(children(ast).iter().rev());
+    }
+    lints
+}
+
+fn lint_repetition(repetition: &Repetition, pattern: &str, bytes: bool, lints: &mut Vec<Lint>) {
+    let span = repetition.span.range();
+    let (_, max) = repetition_bounds(&repetition.op.kind);
+    let source = |span: &Range<usize>| pattern.get(span.clone()).unwrap_or_default();
+
+    let count = match repetition.op.kind {
+        RepetitionKind::Range(
+            RepetitionRange::Exactly(count)
+            | RepetitionRange::AtLeast(count)
+            | RepetitionRange::Bounded(_, count),
+        ) => count,
+        _ => 0,
+    };
+    if count > LARGE_REPETITION {
+        lints.push(Lint {
+            kind: LintKind::LargeRepetition,
+            description: format!(
+                "{} repeats {} times, which makes the regex large and slow to compile",
+                source(&repetition.op.span.range()),
+                count
+            ),
+            spans: vec![span.clone()],
+        });
+    }
+
+    if max.is_none() {
+        if let Some(inner) = find_unbounded_repetition(&repetition.ast) {
+            let inner = inner.span.range();
+            lints.push(Lint {
+                kind: LintKind::NestedRepetition,
+                description: format!(
+                    "{} is repeated without a limit inside of another such repetition, \
+                    so a backtracking engine can try exponentially many ways of matching the same text",
+                    source(&inner)
+                ),
+                spans: vec![span.clone(), inner],
+            });
+        }
+    }
+
+    if !matches!(max, Some(max) if max <= 1) {
+        if let Some(alternation) = repeated_alternation(&repetition.ast) {
+            if let Some((first, second)) = overlapping_alternatives(alternation, pattern, bytes) {
+                lints.push(Lint {
+                    kind: LintKind::OverlappingAlternation,
+                    description: format!(
+                        "The repeated alternatives {} and {} can start with the same character, \
+                        so a backtracking engine can try both of them at every repetition",
+                        source(&first),
+                        source(&second)
+                    ),
+                    spans: vec![span, first, second],
+                });
+            }
+        }
+    }
+}
+
+/// Returns the least and the most number of times that the given kind of repetition repeats,
+/// where `None` is without a limit
+fn repetition_bounds(kind: &RepetitionKind) -> (u32, Option<u32>) {
+    match kind {
+        RepetitionKind::ZeroOrOne => (0, Some(1)),
+        RepetitionKind::ZeroOrMore => (0, None),
+        RepetitionKind::OneOrMore => (1, None),
+        RepetitionKind::Range(RepetitionRange::Exactly(n)) => (*n, Some(*n)),
+        RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (*n, None),
+        RepetitionKind::Range(RepetitionRange::Bounded(n, m)) => (*n, Some(*m)),
+    }
+}
+
+/// Finds the outermost repetition without a limit in the given AST, if it has one
+fn find_unbounded_repetition(ast: &Ast) -> Option<&Repetition> {
+    match ast {
+        Ast::Repetition(repetition) if repetition_bounds(&repetition.op.kind).1.is_none() => {
+            Some(repetition)
+        }
+        _ => children(ast).iter().find_map(find_unbounded_repetition),
+    }
+}
+
+/// Returns the alternation that is the whole of what a repetition repeats, inside of any groups, if it is one
+fn repeated_alternation(ast: &Ast) -> Option<&Alternation> {
+    match ast {
+        Ast::Group(group) => repeated_alternation(&group.ast),
+        Ast::Alternation(alternation) => Some(alternation),
+        _ => None,
+    }
+}
+
+/// Finds the spans of the first two alternatives of the given alternation that can start with the same character
+///
+/// Alternatives are considered on their own, so flags that are set before the alternation are ignored
+fn overlapping_alternatives(
+    alternation: &Alternation,
+    pattern: &str,
+    bytes: bool,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let mut translator = TranslatorBuilder::new().allow_invalid_utf8(bytes).build();
+    let firsts = alternation
+        .asts
+        .iter()
+        .map(|ast| {
+            let hir = translator.translate(pattern, ast).ok()?;
+            Some((ast.span().range(), first_chars(&hir).0))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    firsts
+        .iter()
+        .enumerate()
+        .find_map(|(index, (span, chars))| {
+            firsts[index + 1..]
+                .iter()
+                .find(|(_, other_chars)| chars.overlaps(other_chars))
+                .map(|(other_span, _)| (span.clone(), other_span.clone()))
+        })
+}
+
+/// The characters that a match can start with
+///
+/// Bytes that aren't ASCII, which a regex that matches bytes can match on their own, aren't characters,
+/// so they are kept apart from the characters, as the first byte of the UTF-8 encoding of a character
+#[derive(Clone)]
+struct FirstChars {
+    chars: hir::ClassUnicode,
+    /// Only bytes from `0x80` up, as ASCII bytes are the same as ASCII characters
+    bytes: hir::ClassBytes,
+}
+
+impl FirstChars {
+    fn none() -> Self {
+        Self {
+            chars: hir::ClassUnicode::empty(),
+            bytes: hir::ClassBytes::empty(),
+        }
+    }
+
+    fn from_bytes(ranges: impl Iterator<Item = (u8, u8)>) -> Self {
+        let mut first = Self::none();
+        for (start, end) in ranges {
+            if start <= 0x7F {
+                let end = end.min(0x7F);
+                first
+                    .chars
+                    .push(hir::ClassUnicodeRange::new(start.into(), end.into()));
+            }
+            if end >= 0x80 {
+                let start = start.max(0x80);
+                first.bytes.push(hir::ClassBytesRange::new(start, end));
+            }
+        }
+        first
+    }
+
+    fn union(&mut self, other: &Self) {
+        self.chars.union(&other.chars);
+        self.bytes.union(&other.bytes);
+    }
+
+    /// Whether a match starting with one of these could start with the same character or byte as one of `other`
+    fn overlaps(&self, other: &Self) -> bool {
+        let intersects_chars = {
+            let mut chars = self.chars.clone();
+            chars.intersect(&other.chars);
+            !chars.ranges().is_empty()
+        };
+        let intersects_bytes = |bytes: &hir::ClassBytes, other: &hir::ClassBytes| {
+            let mut bytes = bytes.clone();
+            bytes.intersect(other);
+            !bytes.ranges().is_empty()
+        };
+        intersects_chars
+            || intersects_bytes(&self.bytes, &other.bytes)
+            || intersects_bytes(&self.bytes, &other.lead_bytes())
+            || intersects_bytes(&self.lead_bytes(), &other.bytes)
+    }
+
+    /// The bytes that the UTF-8 encodings of the characters that aren't ASCII start with
+    ///
+    /// The first byte of the encoding of a character only ever increases along with the character,
+    /// so a range of characters starts with the range of bytes between those of its first and last characters
+    fn lead_bytes(&self) -> hir::ClassBytes {
+        let lead = |c: char| c.encode_utf8(&mut [0; 4]).as_bytes()[0];
+        hir::ClassBytes::new(
+            self.chars
+                .ranges()
+                .iter()
+                .filter(|range| !range.end().is_ascii())
+                .map(|range| {
+                    let start = range.start().max('\u{80}');
+                    hir::ClassBytesRange::new(lead(start), lead(range.end()))
+                }),
+        )
+    }
+}
+
+/// Returns the characters that a match of the given HIR can start with, and whether it can match the empty string,
+/// in which case it can also start with whatever comes after it
+fn first_chars(hir: &Hir) -> (FirstChars, bool) {
+    let none = FirstChars::none;
+    match hir.kind() {
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => (none(), true),
+        HirKind::Literal(hir::Literal::Unicode(c)) => (
+            FirstChars {
+                chars: hir::ClassUnicode::new([hir::ClassUnicodeRange::new(*c, *c)]),
+                ..none()
+            },
+            false,
+        ),
+        HirKind::Literal(hir::Literal::Byte(b)) => {
+            (FirstChars::from_bytes(std::iter::once((*b, *b))), false)
+        }
+        HirKind::Class(hir::Class::Unicode(class)) => (
+            FirstChars {
+                chars: class.clone(),
+                ..none()
+            },
+            false,
+        ),
+        HirKind::Class(hir::Class::Bytes(class)) => {
+            let ranges = class
+                .ranges()
+                .iter()
+                .map(|range| (range.start(), range.end()));
+            (FirstChars::from_bytes(ranges), false)
+        }
+        HirKind::Repetition(repetition) => {
+            let (chars, empty) = first_chars(&repetition.hir);
+            (chars, empty || repetition.is_match_empty())
+        }
+        HirKind::Group(group) => first_chars(&group.hir),
+        HirKind::Concat(hirs) => {
+            let mut chars = none();
+            for hir in hirs {
+                let (first, empty) = first_chars(hir);
+                chars.union(&first);
+                if !empty {
+                    return (chars, false);
+                }
+            }
+            (chars, true)
+        }
+        HirKind::Alternation(hirs) => hirs.iter().map(first_chars).fold(
+            (none(), false),
+            |(mut chars, empty), (first, first_empty)| {
+                chars.union(&first);
+                (chars, empty || first_empty)
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::parse_regex;
+    use LintKind::*;
+
+    fn lints(pattern: &str, bytes: bool) -> Vec<(LintKind, Range<usize>)> {
+        let ast = parse_regex(pattern, Default::default()).unwrap();
+        lint_ast(&ast, pattern, bytes)
+            .into_iter()
+            .map(|lint| (lint.kind, lint.spans[0].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn nested_repetition() {
+        assert_eq!(lints("(a+)+", false), [(NestedRepetition, 0..5)]);
+        assert_eq!(lints(r"x(?:\w+\s*)*", false), [(NestedRepetition, 1..12)]);
+        assert_eq!(lints("a+b+(c+)?", false), []);
+    }
+
+    #[test]
+    fn overlapping_alternation() {
+        assert_eq!(lints("(a|ab)*", false), [(OverlappingAlternation, 0..7)]);
+        assert_eq!(
+            lints(r"(?:\d|[0-9a-f]){2,3}", false),
+            [(OverlappingAlternation, 0..20)]
+        );
+        assert_eq!(lints("(?:^a|a)+", false), [(OverlappingAlternation, 0..9)]);
+        assert_eq!(lints("(a|b|c)*", false), []);
+        assert_eq!(lints("(a|ab)?", false), []);
+    }
+
+    #[test]
+    fn overlapping_alternation_of_bytes() {
+        // `é` is encoded as `\xC3\xA9`, so it starts with the same byte as `\xC3`, but not as `\xE9`
+        assert_eq!(
+            lints(r"(?:(?-u:\xC3)|é)+", true),
+            [(OverlappingAlternation, 0..18)]
+        );
+        assert_eq!(lints(r"(?:(?-u:\xE9)|é)+", true), []);
+        assert_eq!(
+            lints(r"(?:(?-u:[\x00-\xFF])|(?-u:\xE9))+", true),
+            [(OverlappingAlternation, 0..33)]
+        );
+    }
+
+    #[test]
+    fn large_repetition() {
+        assert_eq!(lints("a{1000}", false), []);
+        assert_eq!(
+            lints("a{1001}b{5,99999}", false),
+            [(LargeRepetition, 0..7), (LargeRepetition, 7..17)]
+        );
+        assert_eq!(
+            lints("((a|a)+){2000}", false),
+            [(LargeRepetition, 0..14), (OverlappingAlternation, 1..7)]
+        );
+    }
+}
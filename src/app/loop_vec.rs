@@ -29,6 +29,12 @@ impl<T> FromIterator<T> for LoopVec<T> {
     }
 }
 
+impl<T> Extend<T> for LoopVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.vec.extend(iter)
+    }
+}
+
 impl<T> LoopVec<T> {
     pub fn new() -> Self {
         Self {
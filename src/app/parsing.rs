@@ -1,6 +1,13 @@
-use super::text::GetRangeExt;
-use regex::Regex;
-use regex_syntax::ast::{parse::Parser, Alternation, Ast, Concat};
+use super::text::{GetRangeExt, TextIndex};
+use regex::{bytes, Regex, RegexBuilder};
+use regex_syntax::{
+    ast::{
+        parse::ParserBuilder, Alternation, Ast, Class, ClassSet, ClassSetItem, Concat, ErrorKind,
+        GroupKind, LiteralKind, Span,
+    },
+    hir::{self, translate::TranslatorBuilder, Hir},
+};
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Formatter},
     ops::Range,
@@ -10,6 +17,9 @@ use std::{
 pub enum RegexError {
     Parse(regex_syntax::ast::Error),
     Compile(regex::Error),
+    /// An error from fancy-regex, which doesn't say where in the pattern it occurred
+    #[cfg(feature = "backend-fancy")]
+    Fancy(fancy_regex::Error),
 }
 
 impl From<regex_syntax::ast::Error> for RegexError {
@@ -24,42 +34,517 @@ impl From<regex::Error> for RegexError {
     }
 }
 
+#[cfg(feature = "backend-fancy")]
+impl From<fancy_regex::Error> for RegexError {
+    fn from(err: fancy_regex::Error) -> Self {
+        Self::Fancy(err)
+    }
+}
+
+impl RegexError {
+    /// The span of the regex that the error occurred at, if it is known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RegexError::Parse(err) => Some(*err.span()),
+            RegexError::Compile(_) => None,
+            #[cfg(feature = "backend-fancy")]
+            RegexError::Fancy(_) => None,
+        }
+    }
+
+    /// A secondary span of the regex that is relevant to the error, such as the original name of a duplicated group name
+    pub fn auxiliary_span(&self) -> Option<Span> {
+        match self {
+            RegexError::Parse(err) => err.auxiliary_span().copied(),
+            RegexError::Compile(_) => None,
+            #[cfg(feature = "backend-fancy")]
+            RegexError::Fancy(_) => None,
+        }
+    }
+
+    /// The span and auxiliary span of the error, if they are known, each with a label describing what it points to
+    pub fn labeled_spans(&self) -> Vec<(Span, &'static str)> {
+        let span = self.span().map(|span| (span, "error here"));
+        let aux = self.auxiliary_span().map(|aux| (aux, "related to here"));
+        span.into_iter().chain(aux).collect()
+    }
+
+    /// A description of the error, without the regex itself or any indication of where the error is
+    pub fn message(&self) -> String {
+        match self {
+            RegexError::Parse(err) => err.kind().to_string(),
+            RegexError::Compile(err) => err.to_string(),
+            #[cfg(feature = "backend-fancy")]
+            RegexError::Fancy(err) => err.to_string(),
+        }
+    }
+
+    /// Returns a human-readable description of where in the given regex the error occurred,
+    /// with the character and byte offsets of the span and auxiliary span, if they are known
+    pub fn location(&self, regex: &str) -> Option<String> {
+        let index = TextIndex::new(regex);
+        let describe = |span: Span| {
+            format!(
+                "character {}, byte {}",
+                index.byte_to_char(span.start.offset).unwrap_or_default(),
+                span.start.offset
+            )
+        };
+
+        match (self.span(), self.auxiliary_span()) {
+            (Some(span), Some(aux)) => Some(format!(
+                "at {}: {} (see also {})",
+                describe(span),
+                self.message(),
+                describe(aux)
+            )),
+            (Some(span), None) => Some(format!("at {}: {}", describe(span), self.message())),
+            (None, Some(aux)) => Some(format!("related to {}: {}", describe(aux), self.message())),
+            (None, None) => None,
+        }
+    }
+
+    /// Recognizes syntax from other regex flavours that the given pattern failed to parse because of,
+    /// and explains how to do without it
+    pub fn diagnose(&self, pattern: &str) -> Option<Diagnosis> {
+        let err = match self {
+            RegexError::Parse(err) => err,
+            _ => return None,
+        };
+
+        #[cfg(feature = "backend-fancy")]
+        let fancy = Some(Suggestion::UseFancyEngine);
+        #[cfg(not(feature = "backend-fancy"))]
+        let fancy = None;
+
+        let span = err.span().range();
+        let source = pattern.get(span.clone()).unwrap_or_default();
+        let diagnosis = match err.kind() {
+            ErrorKind::UnsupportedBackreference => Diagnosis {
+                explanation: "Backreferences can't be matched in linear time, so the regex crate doesn't support them. \
+                    If the group only ever matches the same text, repeat that text instead.",
+                suggestion: fancy,
+            },
+            ErrorKind::UnsupportedLookAround => Diagnosis {
+                explanation: "Lookahead and lookbehind can't be matched in linear time, so the regex crate doesn't support them. \
+                    Consider matching the surrounding text as well, and capturing the part you want in a group.",
+                suggestion: fancy,
+            },
+            ErrorKind::EscapeUnrecognized if source == r"\k" => Diagnosis {
+                explanation: "Named backreferences can't be matched in linear time, so the regex crate doesn't support them.",
+                suggestion: fancy,
+            },
+            ErrorKind::FlagUnrecognized if source == "<" => Diagnosis {
+                explanation: "Named groups are written as (?P<name>exp) in this regex flavour.",
+                suggestion: Some(Suggestion::Edit {
+                    range: span,
+                    replacement: "P<",
+                    label: "Replace (?< with (?P<",
+                }),
+            },
+            ErrorKind::FlagUnrecognized if source == ">" => Diagnosis {
+                explanation: "Atomic groups are unnecessary in the regex crate, which never backtracks, so it doesn't support them.",
+                suggestion: fancy,
+            },
+            ErrorKind::FlagUnrecognized if source == "#" => Diagnosis {
+                explanation: "Inline comments aren't supported. \
+                    With the x flag set, # starts a comment that lasts until the end of the line instead.",
+                suggestion: pattern[span.end..].find(')').map(|end| Suggestion::Edit {
+                    range: span.start.saturating_sub(2)..span.end + end + 1,
+                    replacement: "",
+                    label: "Remove the comment",
+                }),
+            },
+            _ => return None,
+        };
+        Some(diagnosis)
+    }
+}
+
 impl Display for RegexError {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             RegexError::Parse(err) => err.fmt(fmt),
             RegexError::Compile(err) => err.fmt(fmt),
+            #[cfg(feature = "backend-fancy")]
+            RegexError::Fancy(err) => err.fmt(fmt),
         }
     }
 }
 
-/// Parses and compiles a regular expression, returning the parsed AST and compiled regex.
-pub fn compile_regex(pattern: &str) -> Result<(Ast, Regex), RegexError> {
-    Ok((Parser::new().parse(pattern)?, Regex::new(pattern)?))
+/// An explanation of why a pattern uses syntax that the regex crate doesn't support,
+/// along with a change that would make it compile, if there is one
+pub struct Diagnosis {
+    pub explanation: &'static str,
+    pub suggestion: Option<Suggestion>,
 }
 
-/// Finds all capture groups in the given AST and returns the depth and span of each one
+/// A change that makes a pattern with unsupported syntax compile
+#[derive(Clone, PartialEq, Eq)]
+pub enum Suggestion {
+    /// Replace the given byte range of the pattern with the given text
+    Edit {
+        range: Range<usize>,
+        replacement: &'static str,
+        label: &'static str,
+    },
+    /// Match with the fancy-regex engine, which supports the syntax
+    #[cfg(feature = "backend-fancy")]
+    UseFancyEngine,
+}
+
+impl Suggestion {
+    /// A short description of the change, for a button that applies it
+    pub fn label(&self) -> &'static str {
+        match self {
+            Suggestion::Edit { label, .. } => label,
+            #[cfg(feature = "backend-fancy")]
+            Suggestion::UseFancyEngine => "Use the fancy-regex engine",
+        }
+    }
+}
+
+/// Flags that apply to the whole of a regex, as an alternative to setting them inline with `(?flags)`
+///
+/// Inline flags in the pattern still take precedence over these for the part of the pattern that they apply to
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RegexFlags {
+    /// `i`: Letters match both upper and lower case
+    pub case_insensitive: bool,
+    /// `m`: `^` and `$` match at the start and end of each line
+    pub multi_line: bool,
+    /// `s`: `.` matches `\n`
+    pub dot_matches_new_line: bool,
+    /// `U`: The meanings of `x*` and `x*?` are swapped
+    pub swap_greed: bool,
+    /// `u`: Unicode support
+    pub unicode: bool,
+    /// `x`: Whitespace is ignored, and `#` starts a comment that lasts until the end of the line
+    pub ignore_whitespace: bool,
+}
+
+impl Default for RegexFlags {
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+            multi_line: false,
+            dot_matches_new_line: false,
+            swap_greed: false,
+            unicode: true,
+            ignore_whitespace: false,
+        }
+    }
+}
+
+impl RegexFlags {
+    /// Returns each flag, along with the letter used to set it inline and a description of what it does
+    pub fn toggles(&mut self) -> [(&'static str, &'static str, &mut bool); 6] {
+        [
+            (
+                "i",
+                "Case insensitive: letters match both upper and lower case",
+                &mut self.case_insensitive,
+            ),
+            (
+                "m",
+                "Multi-line: ^ and $ match at the start and end of each line",
+                &mut self.multi_line,
+            ),
+            (
+                "s",
+                "Dot matches newline: . matches \\n as well",
+                &mut self.dot_matches_new_line,
+            ),
+            (
+                "U",
+                "Swap greed: x* is lazy and x*? is greedy",
+                &mut self.swap_greed,
+            ),
+            (
+                "u",
+                "Unicode: classes such as \\w and \\d match any Unicode character of their kind, \
+                rather than only ASCII characters",
+                &mut self.unicode,
+            ),
+            (
+                "x",
+                "Ignore whitespace: whitespace in the pattern is ignored, and # starts a comment",
+                &mut self.ignore_whitespace,
+            ),
+        ]
+    }
+}
+
+/// Parses and compiles a regular expression with the given flags, returning the parsed AST and compiled regex.
+pub fn compile_regex(pattern: &str, flags: RegexFlags) -> Result<(Ast, Regex), RegexError> {
+    let ast = parse_regex(pattern, flags)?;
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
+        .swap_greed(flags.swap_greed)
+        .unicode(flags.unicode)
+        .ignore_whitespace(flags.ignore_whitespace)
+        .build()?;
+
+    Ok((ast, regex))
+}
+
+/// Parses and compiles a regular expression with the given flags for matching bytes, rather than UTF-8 text,
+/// returning the parsed AST and compiled regex.
+pub fn compile_bytes_regex(
+    pattern: &str,
+    flags: RegexFlags,
+) -> Result<(Ast, bytes::Regex), RegexError> {
+    let ast = parse_regex(pattern, flags)?;
+    let regex = bytes::RegexBuilder::new(pattern)
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
+        .swap_greed(flags.swap_greed)
+        .unicode(flags.unicode)
+        .ignore_whitespace(flags.ignore_whitespace)
+        .build()?;
+
+    Ok((ast, regex))
+}
+
+/// Compiles a regular expression with the given flags using fancy-regex, which supports lookaround and backreferences,
+/// returning the compiled regex along with the parsed AST, if `regex-syntax` is able to parse the pattern
+///
+/// fancy-regex is always Unicode-aware, so the `u` flag has no effect
+#[cfg(feature = "backend-fancy")]
+pub fn compile_fancy_regex(
+    pattern: &str,
+    flags: RegexFlags,
+) -> Result<(Option<Ast>, fancy_regex::Regex), RegexError> {
+    // fancy-regex has no options for these flags, so they are set inline instead
+    let inline_flags = [
+        ('i', flags.case_insensitive),
+        ('m', flags.multi_line),
+        ('s', flags.dot_matches_new_line),
+        ('U', flags.swap_greed),
+        ('x', flags.ignore_whitespace),
+    ]
+    .into_iter()
+    .filter_map(|(letter, enabled)| enabled.then_some(letter))
+    .collect::<String>();
+
+    let regex = if inline_flags.is_empty() {
+        fancy_regex::Regex::new(pattern)?
+    } else {
+        fancy_regex::Regex::new(&format!("(?{}){}", inline_flags, pattern))?
+    };
+
+    Ok((parse_regex(pattern, flags).ok(), regex))
+}
+
+/// Parses a regular expression with the given flags, without compiling it
+pub fn parse_regex(pattern: &str, flags: RegexFlags) -> Result<Ast, RegexError> {
+    // Whitespace and comments are only parsed differently by the parser; the other flags don't change the syntax
+    Ok(ParserBuilder::new()
+        .ignore_whitespace(flags.ignore_whitespace)
+        .build()
+        .parse(pattern)?)
+}
+
+/// Translates the AST of a regex, which must have been parsed from the given pattern with the given flags,
+/// to the high-level intermediate representation that the regex is compiled from
+///
+/// `bytes` is whether the regex matches bytes, in which case the HIR is allowed to match invalid UTF-8
+pub fn translate_regex(
+    pattern: &str,
+    ast: &Ast,
+    flags: RegexFlags,
+    bytes: bool,
+) -> Result<Hir, hir::Error> {
+    TranslatorBuilder::new()
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
+        .swap_greed(flags.swap_greed)
+        .unicode(flags.unicode)
+        .allow_invalid_utf8(bytes)
+        .build()
+        .translate(pattern, ast)
+}
+
+/// Compiles a regex with capture groups of the given names, but which matches nothing else, for the parts of the app
+/// that only need to know about the capture groups of a regex that is matched with something other than `Regex`,
+/// such as a regex that matches bytes
+///
+/// The names are those of every capture group of the regex, including the 0th, which is the whole match
+pub fn capture_group_skeleton<'a>(names: impl Iterator<Item = Option<&'a str>>) -> Regex {
+    let skeleton = names
+        .skip(1) // The first (0th) capture group always corresponds to the entire match, not any 'real' capture groups
+        .map(|name| match name {
+            Some(name) => format!("(?P<{}>)", name),
+            None => "()".to_owned(),
+        })
+        .collect::<String>();
+
+    // The names were valid in the original pattern, though fancy-regex allows a few names that `Regex` doesn't,
+    // so those groups are left unnamed instead
+    Regex::new(&skeleton).unwrap_or_else(|_| {
+        let unnamed = "()".repeat(skeleton.matches("(").count());
+        Regex::new(&unnamed).unwrap()
+    })
+}
+
+/// Builds an AST of only the groups in a pattern that `regex-syntax` can't parse, such as one with lookaround,
+/// so that its capture groups can still be highlighted, with everything else in the pattern left out of the AST
+///
+/// The pattern must have been compiled successfully by fancy-regex, so its parentheses are balanced
+#[cfg(feature = "backend-fancy")]
+pub fn capture_group_ast(pattern: &str) -> Ast {
+    use regex_syntax::ast::{CaptureName, Flags, Group, Position};
+
+    let position = |offset: usize| {
+        let before = &pattern[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+        Position::new(offset, line, column)
+    };
+    let span = |range: Range<usize>| Span::new(position(range.start), position(range.end));
+
+    // Each group that is still open, along with where it starts, what kind of group it is, and the groups inside of it
+    let mut open: Vec<(usize, GroupKind, Vec<Ast>)> = Vec::new();
+    let mut top_level = Vec::new();
+    let mut capture_index = 0;
+    let mut class_depth = 0;
+    let mut chars = pattern.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => class_depth += 1,
+            ']' if class_depth > 0 => class_depth -= 1,
+            _ if class_depth > 0 => {}
+            '(' => {
+                let rest = &pattern[offset + 1..];
+                let name = ["?P<", "?<"].into_iter().find_map(|prefix| {
+                    let after = rest.strip_prefix(prefix)?;
+                    // `(?<=` and `(?<!` start lookbehind rather than a named group
+                    if after.starts_with(['=', '!']) {
+                        return None;
+                    }
+                    let (name, _) = after.split_once('>')?;
+                    Some((offset + 1 + prefix.len(), name))
+                });
+                let kind = match name {
+                    Some((start, name)) => {
+                        capture_index += 1;
+                        GroupKind::CaptureName(CaptureName {
+                            span: span(start..start + name.len()),
+                            name: name.to_owned(),
+                            index: capture_index,
+                        })
+                    }
+                    None if rest.starts_with('?') => GroupKind::NonCapturing(Flags {
+                        span: span(offset + 1..offset + 1),
+                        items: vec![],
+                    }),
+                    None => {
+                        capture_index += 1;
+                        GroupKind::CaptureIndex(capture_index)
+                    }
+                };
+                open.push((offset, kind, vec![]));
+            }
+            ')' => {
+                if let Some((start, kind, asts)) = open.pop() {
+                    let inner = span(start + 1..offset);
+                    let group = Ast::Group(Group {
+                        span: span(start..offset + 1),
+                        kind,
+                        ast: Box::new(Ast::Concat(Concat { span: inner, asts })),
+                    });
+                    match open.last_mut() {
+                        Some((_, _, parent)) => parent.push(group),
+                        None => top_level.push(group),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ast::Concat(Concat {
+        span: span(0..pattern.len()),
+        asts: top_level,
+    })
+}
+
+/// Returns whether the given AST is empty, or only sets flags, in which case no matching is performed with it
+pub fn ast_is_empty(ast: &Ast) -> bool {
+    match ast {
+        Ast::Empty(_) | Ast::Flags(_) => true,
+        Ast::Concat(concat) => concat.asts.iter().all(ast_is_empty),
+        _ => false,
+    }
+}
+
+/// Returns whether the given AST contains a newline character written literally, or in a character class
+pub fn ast_contains_newline(ast: &Ast) -> bool {
+    let mut stack = vec![ast];
+    while let Some(ast) = stack.pop() {
+        match ast {
+            Ast::Literal(literal) if literal.c == '\n' => return true,
+            Ast::Class(Class::Bracketed(class)) if !class.negated => {
+                if let ClassSet::Item(item) = &class.kind {
+                    if class_item_contains_newline(item) {
+                        return true;
+                    }
+                }
+            }
+            Ast::Repetition(repetition) => stack.push(&repetition.ast),
+            Ast::Group(group) => stack.push(&group.ast),
+            Ast::Alternation(Alternation { asts, .. }) | Ast::Concat(Concat { asts, .. }) => {
+                stack.extend(asts)
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Returns whether the given item of a character class contains a newline character
+fn class_item_contains_newline(item: &ClassSetItem) -> bool {
+    match item {
+        ClassSetItem::Literal(literal) => literal.c == '\n',
+        ClassSetItem::Range(range) => (range.start.c..=range.end.c).contains(&'\n'),
+        ClassSetItem::Union(union) => union.items.iter().any(class_item_contains_newline),
+        ClassSetItem::Bracketed(class) if !class.negated => match &class.kind {
+            ClassSet::Item(item) => class_item_contains_newline(item),
+            ClassSet::BinaryOp(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// Finds all capture groups in the given AST and returns the depth and span of each one,
+/// ordered by capture index so that the group with index `i` is at position `i - 1`
 pub fn ast_find_capture_groups(ast: &Ast) -> (Vec<usize>, Vec<Range<usize>>) {
     let mut stack = vec![(0, ast)];
-    let mut depths = Vec::new();
-    let mut ranges = Vec::new();
+    let mut groups = Vec::new();
     while let Some((depth, ast)) = stack.pop() {
         match ast {
             Ast::Repetition(repetition) => stack.push((depth + 1, &repetition.ast)),
             Ast::Group(group) => {
                 if let Some(index) = group.capture_index() {
-                    assert_eq!(
-                        depths.len() + 1,
-                        index as usize,
-                        "Regex capture group indexes are not consecutive (Expected: {}, Got: {})",
-                        depths.len() + 1,
-                        index
-                    );
-
-                    depths.push(depth);
-                    ranges.push(group.span.range());
-                    stack.push((depth + 1, &group.ast))
+                    groups.push((index, depth, group.span.range()));
                 }
+
+                // Non-capturing groups can still contain capturing ones
+                stack.push((depth + 1, &group.ast))
             }
             Ast::Alternation(Alternation { asts, .. }) | Ast::Concat(Concat { asts, .. }) => {
                 stack.extend(asts.iter().rev().map(|ast| (depth + 1, ast)))
@@ -67,5 +552,300 @@ pub fn ast_find_capture_groups(ast: &Ast) -> (Vec<usize>, Vec<Range<usize>>) {
             _ => {}
         }
     }
-    (depths, ranges)
+
+    // The groups aren't necessarily visited in the order of their indexes
+    groups.sort_unstable_by_key(|&(index, _, _)| index);
+    groups
+        .into_iter()
+        .map(|(_, depth, range)| (depth, range))
+        .unzip()
+}
+
+/// Finds the name of each capture group in the given AST, if it has one,
+/// ordered by capture index like `ast_find_capture_groups`
+pub fn ast_find_capture_names(ast: &Ast) -> Vec<Option<String>> {
+    let mut stack = vec![ast];
+    let mut names = Vec::new();
+    while let Some(ast) = stack.pop() {
+        match ast {
+            Ast::Repetition(repetition) => stack.push(&repetition.ast),
+            Ast::Group(group) => {
+                if let Some(index) = group.capture_index() {
+                    let name = match &group.kind {
+                        GroupKind::CaptureName(name) => Some(name.name.clone()),
+                        _ => None,
+                    };
+                    names.push((index, name));
+                }
+                stack.push(&group.ast)
+            }
+            Ast::Alternation(Alternation { asts, .. }) | Ast::Concat(Concat { asts, .. }) => {
+                stack.extend(asts.iter())
+            }
+            _ => {}
+        }
+    }
+
+    names.sort_unstable_by_key(|&(index, _)| index);
+    names.into_iter().map(|(_, name)| name).collect()
+}
+
+/// The kinds of tokens that a pattern is made up of, for syntax highlighting
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    /// A character that matches itself
+    Literal,
+    /// An escape sequence that matches a single character, such as `\n` or `\*`
+    Escape,
+    /// Any syntax that matches one of a set of characters, such as `.`, `\d` or `[a-z]`
+    Class,
+    /// A repetition operator, such as `+` or `{2,3}`
+    Quantifier,
+    /// An assertion that matches the empty string, such as `^` or `\b`
+    Anchor,
+    /// The delimiters of a group, including its name or flags, such as `(?P<name>` and `)`
+    Group,
+    /// The `|` between alternatives
+    Alternation,
+    /// Flags that apply to the rest of the pattern, such as `(?i)`
+    Flags,
+}
+
+/// Finds the tokens that make up the pattern that the given AST was parsed from, and returns the kind and span of each
+///
+/// The spans don't overlap, but they aren't in any particular order, and parts of the pattern that aren't tokens
+/// (Such as whitespace ignored by the `x` flag) aren't covered by any span
+pub fn ast_find_tokens(ast: &Ast) -> Vec<(TokenKind, Range<usize>)> {
+    let mut stack = vec![ast];
+    let mut tokens = Vec::new();
+    while let Some(ast) = stack.pop() {
+        match ast {
+            Ast::Empty(_) => {}
+            Ast::Flags(flags) => tokens.push((TokenKind::Flags, flags.span.range())),
+            Ast::Literal(literal) => {
+                let kind = match literal.kind {
+                    LiteralKind::Verbatim => TokenKind::Literal,
+                    _ => TokenKind::Escape,
+                };
+                tokens.push((kind, literal.span.range()));
+            }
+            Ast::Dot(span) => tokens.push((TokenKind::Class, span.range())),
+            Ast::Assertion(assertion) => tokens.push((TokenKind::Anchor, assertion.span.range())),
+            Ast::Class(class) => tokens.push((TokenKind::Class, class.span().range())),
+            Ast::Repetition(repetition) => {
+                tokens.push((TokenKind::Quantifier, repetition.op.span.range()));
+                stack.push(&repetition.ast);
+            }
+            Ast::Group(group) => {
+                // The delimiters are whatever is either side of the contents of the group
+                let inner = group.ast.span().range();
+                tokens.push((TokenKind::Group, group.span.start.offset..inner.start));
+                tokens.push((TokenKind::Group, inner.end..group.span.end.offset));
+                stack.push(&group.ast);
+            }
+            Ast::Alternation(Alternation { asts, .. }) => {
+                tokens.extend(asts.windows(2).map(|pair| {
+                    (
+                        TokenKind::Alternation,
+                        pair[0].span().end.offset..pair[1].span().start.offset,
+                    )
+                }));
+                stack.extend(asts);
+            }
+            Ast::Concat(Concat { asts, .. }) => stack.extend(asts),
+        }
+    }
+    tokens
+}
+
+/// A reference to a capture group in a replacement string
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GroupRef<'a> {
+    Index(usize),
+    Name(&'a str),
+}
+
+/// A part of a replacement string, as interpreted by `Regex::replace`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReplacementToken<'a> {
+    /// Text that is inserted as-is, including a `$` escaped as `$$`
+    Literal(&'a str),
+    /// A reference to a capture group, that is replaced with the text matched by that group
+    Group(GroupRef<'a>),
+}
+
+/// Splits a replacement string into literal text and references to capture groups,
+/// following the same rules as `Regex::replace`
+pub fn tokenize_replacement(replacement: &str) -> Vec<ReplacementToken<'_>> {
+    tokenize_replacement_spans(replacement)
+        .into_iter()
+        .map(|(_, token)| token)
+        .collect()
+}
+
+/// Like `tokenize_replacement`, but also returns the byte range of the replacement string that each token came from
+pub fn tokenize_replacement_spans(replacement: &str) -> Vec<(Range<usize>, ReplacementToken<'_>)> {
+    let mut tokens = Vec::new();
+    let mut rest = replacement;
+    let offset = |rest: &str| replacement.len() - rest.len();
+    while let Some(dollar) = rest.find('$') {
+        let start = offset(rest);
+        if dollar > 0 {
+            tokens.push((
+                start..start + dollar,
+                ReplacementToken::Literal(&rest[..dollar]),
+            ));
+        }
+        rest = &rest[dollar..];
+        let start = offset(rest);
+
+        let after = &rest[1..];
+        if let Some(escaped) = after.strip_prefix('$') {
+            tokens.push((start..start + 2, ReplacementToken::Literal("$")));
+            rest = escaped;
+            continue;
+        }
+
+        // Braced names may contain any characters (Even none at all) and end at the first `}`,
+        // whereas unbraced names take the longest run of name characters, so `$1a` refers to a group named `1a`
+        let reference = match after.strip_prefix('{') {
+            Some(braced) => braced.find('}').map(|end| (&braced[..end], end + 2)),
+            None => {
+                let end = after
+                    .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                    .unwrap_or(after.len());
+                (end > 0).then(|| (&after[..end], end))
+            }
+        };
+
+        let (name, consumed) = match reference {
+            Some(reference) => reference,
+            None => {
+                // A `$` that isn't followed by a valid reference is inserted as-is
+                tokens.push((start..start + 1, ReplacementToken::Literal("$")));
+                rest = after;
+                continue;
+            }
+        };
+
+        // Group numbers are parsed the same way as by `Regex::replace`, so numbers too large for a `u32` are names
+        let group = match name.parse::<u32>() {
+            Ok(index) => GroupRef::Index(index as usize),
+            Err(_) => GroupRef::Name(name),
+        };
+        tokens.push((start..start + 1 + consumed, ReplacementToken::Group(group)));
+        rest = &after[consumed..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push((
+            offset(rest)..replacement.len(),
+            ReplacementToken::Literal(rest),
+        ));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_groups_are_ordered_by_index() {
+        let check = |pattern, depths: &[usize], spans: &[Range<usize>]| {
+            let (ast, regex) = compile_regex(pattern, Default::default()).unwrap();
+            assert_eq!(
+                ast_find_capture_groups(&ast),
+                (depths.to_vec(), spans.to_vec()),
+                "{:?}",
+                pattern
+            );
+            assert_eq!(spans.len() + 1, regex.captures_len());
+        };
+
+        check("((a)(b(c)))", &[0, 2, 2, 4], &[0..11, 1..4, 4..10, 6..9]);
+        // The groups of the first branch are inside of a concatenation, which makes them deeper
+        check("(a)(b(c))|(d)", &[2, 2, 4, 1], &[0..3, 3..9, 5..8, 10..13]);
+        check(
+            "(?:(a)|b)(?i:c(d))(?:e)+(f)",
+            &[3, 3, 1],
+            &[3..6, 14..17, 24..27],
+        );
+        check("(?:a|b)+", &[], &[]);
+    }
+
+    #[test]
+    fn newlines_are_found_in_literals_and_classes() {
+        let check = |pattern, expected| {
+            let (ast, _) = compile_regex(pattern, Default::default()).unwrap();
+            assert_eq!(ast_contains_newline(&ast), expected, "{:?}", pattern);
+        };
+
+        check(r"a\nb", true);
+        check(r"(?:x|\n)+", true);
+        check(r"[\n]", true);
+        check(r"[\x00-\x7f]", true);
+        check(r"[a[\n]]", true);
+        check(r"[a[b[\n]]]", true);
+        check(r"[^\n]", false);
+        check(r"[a[^\n]]", false);
+        check(r"abc[de]", false);
+    }
+
+    /// Each pattern is diagnosed, and the fixed pattern is given for those with an edit to suggest
+    #[test]
+    fn unsupported_syntax_is_diagnosed() {
+        #[cfg(feature = "backend-fancy")]
+        let fancy = Some(Suggestion::UseFancyEngine);
+        #[cfg(not(feature = "backend-fancy"))]
+        let fancy = None;
+
+        for (pattern, fixed) in [
+            (r"(?<year>\d{4})-\d", Some(r"(?P<year>\d{4})-\d")),
+            (r"a(?# a comment )b", Some("ab")),
+            (r"a(?#", None),
+            (r"(a)\1", None),
+            (r"a(?=b)", None),
+            (r"(?<!a)b", None),
+            (r"(?P<n>a)\k<n>", None),
+            (r"(?>a)", None),
+        ] {
+            let err = compile_regex(pattern, Default::default()).unwrap_err();
+            let diagnosis = err.diagnose(pattern).unwrap();
+
+            match (diagnosis.suggestion, fixed) {
+                (
+                    Some(Suggestion::Edit {
+                        range, replacement, ..
+                    }),
+                    Some(fixed),
+                ) => {
+                    let mut edited = pattern.to_owned();
+                    edited.replace_range(range, replacement);
+                    assert_eq!(edited, fixed);
+                    assert!(compile_regex(&edited, Default::default()).is_ok());
+                }
+                (suggestion, None) => assert!(
+                    suggestion.is_none() || suggestion == fancy,
+                    "{:?} was diagnosed with an edit",
+                    pattern
+                ),
+                (_, Some(_)) => panic!("{:?} wasn't diagnosed with an edit", pattern),
+            }
+        }
+
+        // Errors that are just mistakes are left as they are
+        for pattern in ["[a", "a{2,1}", r"(?P<n>a)(?P<n>b)"] {
+            let err = compile_regex(pattern, Default::default()).unwrap_err();
+            assert!(err.diagnose(pattern).is_none(), "{:?}", pattern);
+        }
+    }
+
+    #[cfg(feature = "backend-fancy")]
+    #[test]
+    fn fancy_capture_groups_are_found_around_lookaround() {
+        let pattern = r"(?<=a\()(?<n>b[(])(?:(c))";
+        let (_, spans) = ast_find_capture_groups(&capture_group_ast(pattern));
+        assert_eq!(spans, [8..18, 21..24]);
+    }
 }
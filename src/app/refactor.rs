@@ -0,0 +1,441 @@
+use super::{
+    ast_tree::children,
+    parsing::{compile_regex, GroupRef, RegexFlags},
+    replacement::{resolve_references, rewrite_references},
+    text::GetRangeExt,
+};
+use regex_syntax::{
+    ast::{Ast, Class, Group},
+    is_meta_character,
+};
+use std::{fmt::Write, ops::Range};
+
+/// The kinds of group that a selection of the regex can be wrapped in
+pub enum WrapKind {
+    NonCapturing,
+    Capturing,
+    Named(String),
+    /// A non-capturing group containing an alternation between the selection and an empty alternative,
+    /// as a scaffold for adding another alternative
+    Alternation,
+}
+
+impl WrapKind {
+    /// Returns the text to insert before and after the selection
+    fn delimiters(&self) -> (String, &'static str) {
+        match self {
+            Self::NonCapturing => ("(?:".into(), ")"),
+            Self::Capturing => ("(".into(), ")"),
+            Self::Named(name) => (format!("(?P<{}>", name), ")"),
+            Self::Alternation => ("(?:".into(), "|)"),
+        }
+    }
+}
+
+/// Checks that the given byte range of the pattern with the given AST can be wrapped in a group,
+/// returning a message explaining why not if it can't
+///
+/// A range can't be wrapped if it starts or ends partway through a token, such as an escape sequence or a class,
+/// or if it only contains one of the parentheses of a group, or only part of an alternative of an alternation
+pub fn check_wrap_range(ast: &Ast, range: &Range<usize>) -> Result<(), &'static str> {
+    if range.is_empty() {
+        return Err("Select part of the regex to wrap it in a group");
+    }
+
+    let mut stack = vec![ast];
+    while let Some(ast) = stack.pop() {
+        let span = ast.span().range();
+        let disjoint = range.end <= span.start || range.start >= span.end;
+        let contains = range.start <= span.start && span.end <= range.end;
+        if disjoint || contains {
+            continue;
+        }
+
+        let within = |inner: Range<usize>| inner.start <= range.start && range.end <= inner.end;
+        match ast {
+            Ast::Concat(concat) => stack.extend(&concat.asts),
+            Ast::Alternation(alternation) => {
+                let spans = alternation.asts.iter().map(|ast| ast.span().range());
+                if let Some(inner) = alternation
+                    .asts
+                    .iter()
+                    .find(|ast| within(ast.span().range()))
+                {
+                    stack.push(inner);
+                } else if !spans.clone().any(|span| span.start == range.start)
+                    || !spans.into_iter().any(|span| span.end == range.end)
+                {
+                    return Err("The selection splits an alternative of an alternation");
+                }
+            }
+            Ast::Group(group) if within(group.ast.span().range()) => stack.push(&group.ast),
+            Ast::Group(_) => return Err("The selection splits the parentheses of a group"),
+            Ast::Repetition(repetition) if within(repetition.ast.span().range()) => {
+                stack.push(&repetition.ast)
+            }
+            Ast::Repetition(_) => {
+                return Err("The selection splits a repetition from what it repeats")
+            }
+            Ast::Class(Class::Bracketed(_)) => {
+                return Err("The selection starts or ends inside a character class")
+            }
+            Ast::Literal(_) | Ast::Class(_) => {
+                return Err("The selection starts or ends in the middle of an escape sequence")
+            }
+            Ast::Flags(_) => return Err("The selection starts or ends inside a flag group"),
+            Ast::Empty(_) | Ast::Dot(_) | Ast::Assertion(_) => {
+                return Err("The selection starts or ends in the middle of a token")
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Wraps the given byte range of the pattern with the given AST in the given kind of group, which must still compile
+/// with the given flags afterwards,
+/// returning the new pattern and the byte offset at which the cursor should be placed,
+/// or a message explaining why the range couldn't be wrapped
+///
+/// The cursor is placed in the empty alternative of an alternation scaffold, or after the new group otherwise
+pub fn wrap_range(
+    pattern: &str,
+    ast: &Ast,
+    range: Range<usize>,
+    kind: &WrapKind,
+    flags: RegexFlags,
+) -> Result<(String, usize), String> {
+    check_wrap_range(ast, &range)?;
+
+    let (prefix, suffix) = kind.delimiters();
+    let wrapped = format!(
+        "{}{}{}{}{}",
+        &pattern[..range.start],
+        prefix,
+        &pattern[range.clone()],
+        suffix,
+        &pattern[range.end..]
+    );
+
+    // Catches mistakes that can't be detected beforehand, such as an invalid group name
+    compile_regex(&wrapped, flags).map_err(|err| err.to_string())?;
+
+    let group_end = range.end + prefix.len() + suffix.len();
+    let cursor = match kind {
+        WrapKind::Alternation => group_end - ")".len(),
+        _ => group_end,
+    };
+
+    Ok((wrapped, cursor))
+}
+
+/// The ways that an existing capture group can be refactored
+pub enum GroupRefactoring {
+    /// Gives the group the given name, replacing any name that it already has
+    Name(String),
+    MakeNonCapturing,
+    /// Removes the parentheses of the group, keeping what is inside of them
+    Unwrap,
+}
+
+/// Refactors the capture group with the given index in the given pattern, which must still compile with the given flags
+/// afterwards, and rewrites the references in the given replacement strings to match,
+/// returning the new pattern and replacement strings, or a message explaining why the group couldn't be refactored
+///
+/// References to the groups after one that stops capturing are renumbered, so that they still refer to the same groups,
+/// but a group can't stop capturing while a replacement string refers to it
+pub fn refactor_group(
+    pattern: &str,
+    replacements: &[&str],
+    index: usize,
+    refactoring: &GroupRefactoring,
+    flags: RegexFlags,
+) -> Result<(String, Vec<String>), String> {
+    let (ast, regex) = compile_regex(pattern, flags).map_err(|err| err.to_string())?;
+    let (group, parent) =
+        find_capture_group(&ast, None, index).ok_or("The regex doesn't have that group")?;
+
+    // The text before the contents of the group, such as `(` or `(?P<name>`
+    let opening = group.span.start.offset..group.ast.span().start.offset;
+    let (refactored, _) = match refactoring {
+        GroupRefactoring::Name(name) => insert_literal(pattern, opening, &format!("(?P<{}>", name)),
+        GroupRefactoring::MakeNonCapturing => insert_literal(pattern, opening, "(?:"),
+        GroupRefactoring::Unwrap => {
+            check_unwrap(group, parent)?;
+            let contents = &pattern[group.ast.span().range()];
+            insert_literal(pattern, group.span.range(), contents)
+        }
+    };
+
+    // Catches mistakes that can't be detected beforehand, such as an invalid or duplicate group name
+    compile_regex(&refactored, flags).map_err(|err| err.to_string())?;
+
+    let replacements = replacements
+        .iter()
+        .map(|replacement| match refactoring {
+            GroupRefactoring::Name(name) => Ok(rewrite_references(
+                replacement,
+                &regex,
+                |group, reference| {
+                    (group == index && matches!(reference, GroupRef::Name(_))).then(|| name.clone())
+                },
+            )),
+            GroupRefactoring::MakeNonCapturing | GroupRefactoring::Unwrap => {
+                let references = resolve_references(replacement, &regex);
+                if references
+                    .iter()
+                    .any(|reference| reference.group == Some(index))
+                {
+                    return Err(format!(
+                        "The replacement text refers to group {}, so it has to keep capturing",
+                        index
+                    ));
+                }
+
+                Ok(rewrite_references(
+                    replacement,
+                    &regex,
+                    |group, reference| match reference {
+                        GroupRef::Index(_) if group > index => Some((group - 1).to_string()),
+                        _ => None,
+                    },
+                ))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((refactored, replacements))
+}
+
+/// Finds the capture group with the given index in the given AST, along with the node that it is directly inside of,
+/// if it isn't the root of the AST
+fn find_capture_group<'a>(
+    ast: &'a Ast,
+    parent: Option<&'a Ast>,
+    index: usize,
+) -> Option<(&'a Group, Option<&'a Ast>)> {
+    match ast {
+        Ast::Group(group) if group.capture_index() == Some(index as u32) => Some((group, parent)),
+        _ => children(ast)
+            .iter()
+            .find_map(|child| find_capture_group(child, Some(ast), index)),
+    }
+}
+
+/// Checks that removing the parentheses of the given group, which is directly inside of the given node,
+/// doesn't change what the regex matches, returning a message explaining why it would if it does
+fn check_unwrap(group: &Group, parent: Option<&Ast>) -> Result<(), &'static str> {
+    if sets_flags(&group.ast) {
+        return Err("The group limits the flags that are set inside of it to its contents");
+    }
+
+    match (parent, &*group.ast) {
+        (
+            Some(Ast::Repetition(_)),
+            Ast::Literal(_) | Ast::Dot(_) | Ast::Class(_) | Ast::Group(_),
+        ) => Ok(()),
+        (Some(Ast::Repetition(_)), _) => {
+            Err("The group is repeated, so its contents would no longer be repeated as a whole")
+        }
+        (Some(Ast::Concat(_)), Ast::Alternation(_)) => {
+            Err("The alternatives in the group would take in the rest of the sequence around it")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether the given node of an AST sets flags that last until the end of the group that it is in
+fn sets_flags(ast: &Ast) -> bool {
+    match ast {
+        Ast::Flags(_) => true,
+        Ast::Concat(concat) => concat.asts.iter().any(sets_flags),
+        Ast::Alternation(alternation) => alternation.asts.iter().any(sets_flags),
+        _ => false,
+    }
+}
+
+/// Escapes text so that a regex with the given flags matches it literally
+///
+/// Bytes that aren't part of valid UTF-8 are written as `\xNN` escapes with Unicode mode turned off,
+/// which only regexes for matching bytes accept
+pub fn escape_literal(bytes: &[u8], flags: RegexFlags) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(valid) => (valid, &[][..]),
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                // The error has no length if the bytes end part way through a char, in which case the rest are invalid
+                let invalid = &after[..err.error_len().unwrap_or(after.len())];
+                (std::str::from_utf8(valid).unwrap(), invalid)
+            }
+        };
+
+        for c in valid.chars() {
+            if is_meta_character(c) {
+                escaped.push('\\');
+                escaped.push(c);
+            } else if flags.ignore_whitespace && c == ' ' {
+                escaped.push_str("\\ ");
+            } else if flags.ignore_whitespace && c.is_whitespace() {
+                // Other whitespace could be escaped the same way, but would be hard to see,
+                // so it is written as a code point instead. Writing to a string can't fail
+                let _ = write!(escaped, "\\x{{{:X}}}", u32::from(c));
+            } else {
+                escaped.push(c);
+            }
+        }
+
+        if !invalid.is_empty() {
+            escaped.push_str("(?-u:");
+            for byte in invalid {
+                let _ = write!(escaped, "\\x{:02X}", byte);
+            }
+            escaped.push(')');
+        }
+
+        rest = &rest[valid.len() + invalid.len()..];
+    }
+    escaped
+}
+
+/// Replaces the given byte range of the pattern with a literal, such as at the cursor position in the regex editor,
+/// returning the new pattern and the byte offset at which the cursor should be placed after the literal
+pub fn insert_literal(pattern: &str, range: Range<usize>, literal: &str) -> (String, usize) {
+    let inserted = format!(
+        "{}{}{}",
+        &pattern[..range.start],
+        literal,
+        &pattern[range.end..]
+    );
+    (inserted, range.start + literal.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_spaces_are_literal_in_verbose_mode() {
+        let verbose = RegexFlags {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let escaped = escape_literal(b"a b\tc", verbose);
+        assert_eq!(escaped, r"a\ b\x{9}c");
+
+        let (_, regex) = compile_regex(&format!("^{}$", escaped), verbose).unwrap();
+        assert!(regex.is_match("a b\tc"));
+    }
+
+    #[test]
+    fn escaped_literals_match_themselves() {
+        use crate::app::parsing::compile_bytes_regex;
+
+        let verbose = RegexFlags {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let cases: [(&[u8], RegexFlags); 5] = [
+            (b"https://example.com/path?query=a.b&c", Default::default()),
+            (b"C:\\Program Files (x86)\\*.exe", Default::default()),
+            (b"[^$]{1,2}|#~-", Default::default()),
+            (b"a b\t# not a comment\n", verbose),
+            (b"invalid \xFF\xFE utf-8", Default::default()),
+        ];
+        for (literal, flags) in cases {
+            let escaped = escape_literal(literal, flags);
+            let (_, regex) = compile_bytes_regex(&format!("^{}$", escaped), flags).unwrap();
+            assert!(
+                regex.is_match(literal),
+                "{:?} was escaped as {}, which doesn't match it",
+                literal,
+                escaped
+            );
+        }
+
+        assert_eq!(insert_literal("a+c", 1..2, r"\+"), (r"a\+c".to_owned(), 3));
+    }
+
+    /// Refactoring doesn't change what is matched or what the matches are replaced with
+    #[test]
+    fn group_refactorings() {
+        use GroupRefactoring::*;
+        let name = |name: &str| Name(name.into());
+        let cases = [
+            (
+                r"(\d+)-(\d+)",
+                "$2/$1",
+                name("year"),
+                Some((r"(?P<year>\d+)-(\d+)", "$2/$1")),
+            ),
+            (
+                "(?P<a>x)(y)",
+                "${a}$a.$2",
+                name("b"),
+                Some(("(?P<b>x)(y)", "${b}$b.$2")),
+            ),
+            ("(?P<x>a)(?P<y>b)", "", name("y"), None),
+            (
+                "(a)(b)(c)",
+                "$3$2${3}$0$$3",
+                MakeNonCapturing,
+                Some(("(?:a)(b)(c)", "$2$1${2}$0$$3")),
+            ),
+            ("(a)(b)", "$1", MakeNonCapturing, None),
+            ("a(b(c))d", "[$2]", Unwrap, Some(("ab(c)d", "[$1]"))),
+            ("(a)+|(b)", "$2", Unwrap, Some(("a+|(b)", "$1"))),
+            ("x(a|b)y", "", Unwrap, None),
+            ("(a|b)", "", Unwrap, Some(("a|b", ""))),
+            ("(ab)+", "", Unwrap, None),
+            ("((?i)a)b", "", Unwrap, None),
+        ];
+
+        let input = "a-b 12-34 xaby abcd xy abab aaa ABC AB Ab b";
+        for (pattern, replacement, refactoring, expected) in cases {
+            let result =
+                refactor_group(pattern, &[replacement], 1, &refactoring, Default::default());
+            let (refactored, replacements) = match (result, expected) {
+                (Err(_), None) => continue,
+                (Ok(result), Some((expected_pattern, expected_replacement))) => {
+                    assert_eq!(
+                        result,
+                        (
+                            expected_pattern.to_owned(),
+                            vec![expected_replacement.to_owned()]
+                        )
+                    );
+                    result
+                }
+                (result, _) => panic!(
+                    "Refactoring the first group of {:?} with {:?} gave {:?} rather than {:?}",
+                    pattern, replacement, result, expected
+                ),
+            };
+
+            let (_, before) = compile_regex(pattern, Default::default()).unwrap();
+            let (_, after) = compile_regex(&refactored, Default::default()).unwrap();
+            let matches = |regex: &regex::Regex| {
+                regex
+                    .find_iter(input)
+                    .map(|m| m.range())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                matches(&before),
+                matches(&after),
+                "{:?} to {:?}",
+                pattern,
+                refactored
+            );
+            assert_eq!(
+                before.replace_all(input, replacement),
+                after.replace_all(input, &replacements[0]),
+                "{:?} to {:?}",
+                pattern,
+                refactored
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::{
+    state::AppState,
+    text::TextIndex,
+    ui::{create_font_definitions, editor::editor, update_style},
+};
+use egui::{epaint::ClippedShape, text::Galley, Context, Pos2, RawInput, Rect, Shape, Style, Vec2};
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, path::Path};
+
+/// Where the golden geometry is stored, relative to the root of the repository
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens/editor_layout.json");
+
+/// The size of the off-screen window that the editor is rendered into
+const SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// How many frames are rendered before the geometry is recorded, so that deferred layout has time to finish
+const SETTLE_FRAMES: usize = 3;
+
+/// How far recorded positions can be from the golden ones, in points, before they are considered different
+const TOLERANCE: f32 = 0.5;
+
+/// A pattern and input text to render the editor with
+pub struct RenderTestFixture {
+    pub name: &'static str,
+    pub pattern: &'static str,
+    pub input: &'static str,
+}
+
+/// Patterns and input texts covering the layouts that are most prone to regressions
+pub const RENDER_TEST_FIXTURES: &[RenderTestFixture] = &[
+    RenderTestFixture {
+        name: "Capture groups",
+        pattern: r"(\w+)@(\w+)\.com",
+        input: "alice@example.com",
+    },
+    RenderTestFixture {
+        name: "Nested groups",
+        pattern: "((a)(b(c)))",
+        input: "xx abc",
+    },
+    RenderTestFixture {
+        name: "Unicode",
+        pattern: r"(é+)\s(\p{Greek}+)",
+        input: "ééé αβγ",
+    },
+    RenderTestFixture {
+        name: "Multiple lines",
+        pattern: r"(?m)^(\w+): (\d+)$",
+        input: "first line\nsecond: 22\nthird: 333",
+    },
+    RenderTestFixture {
+        name: "Wrapped lines",
+        pattern: r"(fixture)",
+        input: "This line of input text is long enough that it has to be wrapped onto a second row \
+            before it gets to the end, at least if the window is as narrow as the one that the render test uses, \
+            which it is, because that is what this fixture is for",
+    },
+];
+
+/// The geometry of the editor as rendered with a fixture
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+struct RenderedLayout {
+    name: String,
+    /// The byte ranges of the layout sections of the regex text
+    regex_sections: Vec<Range<usize>>,
+    /// The byte ranges of the layout sections of the input text
+    input_sections: Vec<Range<usize>>,
+    /// The rects of the glyphs of the first match in the input text, as `[left, top, right, bottom]`
+    first_match_glyphs: Vec<[f32; 4]>,
+    /// The start and end points of each solid connecting line, as `[start x, start y, end x, end y]`
+    line_endpoints: Vec<[f32; 4]>,
+}
+
+/// Renders the editor with every fixture and compares the resulting geometry against the goldens,
+/// printing the outcome of each fixture along with a summary, and returns whether they all matched
+///
+/// If `update` is true the goldens are instead replaced with the geometry that was rendered
+pub fn run_render_test(update: bool) -> bool {
+    let rendered = RENDER_TEST_FIXTURES
+        .iter()
+        .map(render_fixture)
+        .collect::<Vec<_>>();
+
+    if update {
+        return match write_goldens(&rendered) {
+            Ok(()) => {
+                println!("Updated {} goldens in {}", rendered.len(), GOLDEN_PATH);
+                true
+            }
+            Err(err) => {
+                println!("Failed to update the goldens: {}", err);
+                false
+            }
+        };
+    }
+
+    let goldens = match read_goldens() {
+        Ok(goldens) => goldens,
+        Err(err) => {
+            println!(
+                "Failed to read the goldens, run with `--update-goldens` to create them: {}",
+                err
+            );
+            return false;
+        }
+    };
+
+    let mut failures = 0;
+    for layout in &rendered {
+        let outcome = match goldens.iter().find(|golden| golden.name == layout.name) {
+            Some(golden) => compare_layouts(golden, layout),
+            None => Err("There is no golden for this fixture".into()),
+        };
+
+        match outcome {
+            Ok(()) => println!("ok      {}", layout.name),
+            Err(err) => {
+                println!("FAILED  {}: {}", layout.name, err);
+                failures += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} of {} render test fixtures matched their goldens",
+        rendered.len() - failures,
+        rendered.len()
+    );
+    failures == 0
+}
+
+/// Renders the editor off-screen with the given fixture, and records the geometry of the result
+fn render_fixture(fixture: &RenderTestFixture) -> RenderedLayout {
+    let ctx = Context::default();
+    ctx.set_fonts(create_font_definitions());
+    ctx.set_style(update_style(Style::default()));
+
+    let mut state = AppState::default();
+    state.widgets.regex_text = fixture.pattern.into();
+    state.widgets.input_text = fixture.input.into();
+    state.recompile(&ctx.style());
+
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE)),
+        pixels_per_point: Some(1.0),
+        ..Default::default()
+    };
+
+    let mut shapes = Vec::new();
+    for _ in 0..SETTLE_FRAMES {
+        shapes = ctx.run(input.clone(), |ctx| editor(ctx, &mut state)).shapes;
+    }
+
+    let mut texts = Vec::new();
+    let mut line_endpoints = Vec::new();
+    for ClippedShape(_, shape) in &shapes {
+        collect_geometry(shape, &mut texts, &mut line_endpoints);
+    }
+
+    // The regex is displayed before the input text, and either could also appear elsewhere in the editor
+    let regex = texts
+        .iter()
+        .find(|(_, galley)| galley.job.text == fixture.pattern);
+    let input = texts
+        .iter()
+        .find(|(_, galley)| galley.job.text == fixture.input);
+
+    let first_match = state.logic.as_ref().ok().and_then(|logic| {
+        let range = logic.selector.matches.first()?.first()?.0.clone()?;
+        TextIndex::new(fixture.input).byte_range_to_glyph_range(range)
+    });
+
+    RenderedLayout {
+        name: fixture.name.into(),
+        regex_sections: regex.map_or_else(Vec::new, |(_, galley)| section_ranges(galley)),
+        input_sections: input.map_or_else(Vec::new, |(_, galley)| section_ranges(galley)),
+        first_match_glyphs: match (input, first_match) {
+            (Some((pos, galley)), Some(range)) => glyph_rects(*pos, galley, range),
+            _ => vec![],
+        },
+        line_endpoints,
+    }
+}
+
+/// Finds the laid out text and solid connecting lines in the given shape and any shapes that it contains
+fn collect_geometry<'a>(
+    shape: &'a Shape,
+    texts: &mut Vec<(Pos2, &'a Galley)>,
+    line_endpoints: &mut Vec<[f32; 4]>,
+) {
+    match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                collect_geometry(shape, texts, line_endpoints);
+            }
+        }
+        Shape::Text(text) => texts.push((text.pos, &text.galley)),
+        // Connecting lines are the only bezier curves in the editor; dashed lines are made of many separate
+        // segments, so only solid lines are recorded
+        Shape::CubicBezier(curve) => {
+            let [start, .., end] = curve.points;
+            line_endpoints.push([start.x, start.y, end.x, end.y]);
+        }
+        _ => {}
+    }
+}
+
+fn section_ranges(galley: &Galley) -> Vec<Range<usize>> {
+    galley
+        .job
+        .sections
+        .iter()
+        .map(|section| section.byte_range.clone())
+        .collect()
+}
+
+/// Returns the rects of the glyphs in the given range of the galley, relative to the screen
+fn glyph_rects(pos: Pos2, galley: &Galley, range: Range<usize>) -> Vec<[f32; 4]> {
+    galley
+        .rows
+        .iter()
+        .flat_map(|row| &row.glyphs)
+        .skip(range.start)
+        .take(range.len())
+        .map(|glyph| {
+            let rect = glyph.logical_rect().translate(pos.to_vec2());
+            [rect.left(), rect.top(), rect.right(), rect.bottom()]
+        })
+        .collect()
+}
+
+/// Checks that a rendered layout matches its golden, allowing positions to differ by up to `TOLERANCE`
+fn compare_layouts(golden: &RenderedLayout, rendered: &RenderedLayout) -> Result<(), String> {
+    if rendered.regex_sections != golden.regex_sections {
+        return Err(format!(
+            "Regex sections are {:?}, but should be {:?}",
+            rendered.regex_sections, golden.regex_sections
+        ));
+    }
+
+    if rendered.input_sections != golden.input_sections {
+        return Err(format!(
+            "Input sections are {:?}, but should be {:?}",
+            rendered.input_sections, golden.input_sections
+        ));
+    }
+
+    compare_positions(
+        "First match glyph",
+        &golden.first_match_glyphs,
+        &rendered.first_match_glyphs,
+    )?;
+    compare_positions(
+        "Connecting line",
+        &golden.line_endpoints,
+        &rendered.line_endpoints,
+    )
+}
+
+fn compare_positions(what: &str, golden: &[[f32; 4]], rendered: &[[f32; 4]]) -> Result<(), String> {
+    if rendered.len() != golden.len() {
+        return Err(format!(
+            "There are {} of {}, but there should be {}",
+            rendered.len(),
+            what.to_lowercase(),
+            golden.len()
+        ));
+    }
+
+    for (index, (golden, rendered)) in golden.iter().zip(rendered).enumerate() {
+        if golden
+            .iter()
+            .zip(rendered)
+            .any(|(golden, rendered)| (golden - rendered).abs() > TOLERANCE)
+        {
+            return Err(format!(
+                "{} {} is at {:?}, but should be at {:?}",
+                what, index, rendered, golden
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_goldens() -> Result<Vec<RenderedLayout>, String> {
+    let json = std::fs::read_to_string(GOLDEN_PATH).map_err(|err| err.to_string())?;
+    serde_json::from_str(&json).map_err(|err| err.to_string())
+}
+
+fn write_goldens(rendered: &[RenderedLayout]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(rendered).map_err(|err| err.to_string())?;
+    if let Some(dir) = Path::new(GOLDEN_PATH).parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(GOLDEN_PATH, json + "\n").map_err(|err| err.to_string())
+}
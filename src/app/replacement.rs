@@ -0,0 +1,360 @@
+use crate::app::parsing::{
+    tokenize_replacement, tokenize_replacement_spans, GroupRef, ReplacementToken,
+};
+use regex::Regex;
+use std::{fmt::Write, ops::Range};
+
+/// Ways of using the whole match (`$0`) in a replacement string that are commonly confusing
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WholeMatchUsage {
+    /// The replacement is only the whole match, so replacing leaves the text unchanged
+    Unchanged,
+    /// The replacement combines the whole match with literal text,
+    /// which is inserted around each match while the text between matches is kept
+    AlongsideLiterals,
+}
+
+/// Detects whether the given replacement string uses the whole match in a commonly confusing way
+pub fn whole_match_usage(replacement: &str) -> Option<WholeMatchUsage> {
+    let tokens = tokenize_replacement(replacement);
+    let whole_match = ReplacementToken::Group(GroupRef::Index(0));
+
+    if tokens == [whole_match] {
+        Some(WholeMatchUsage::Unchanged)
+    } else if tokens.contains(&whole_match)
+        && tokens
+            .iter()
+            .any(|token| matches!(token, ReplacementToken::Literal(_)))
+    {
+        Some(WholeMatchUsage::AlongsideLiterals)
+    } else {
+        None
+    }
+}
+
+/// An unbraced reference in a replacement string to a group that doesn't exist, but which starts with a reference to
+/// a group that does, such as `$1_copy`, which refers to a group named `1_copy` rather than to group 1 followed by `_copy`
+pub struct AmbiguousReference {
+    /// The byte range of the whole reference in the replacement string, including the `$`
+    pub span: Range<usize>,
+    /// The reference as written, such as `$1_copy`
+    pub written: String,
+    /// The braced form of the reference that was most likely intended, such as `${1}_copy`
+    pub intended: String,
+}
+
+/// Finds every reference in the given replacement string that refers to a group that doesn't exist in the given regex,
+/// but would refer to one that does if the start of the reference was braced
+pub fn ambiguous_references(replacement: &str, regex: &Regex) -> Vec<AmbiguousReference> {
+    let exists = |name: &str| match name.parse::<u32>() {
+        Ok(index) => (index as usize) < regex.captures_len(),
+        Err(_) => regex.capture_names().flatten().any(|group| group == name),
+    };
+
+    tokenize_replacement_spans(replacement)
+        .into_iter()
+        .filter_map(|(span, token)| {
+            let name = match token {
+                ReplacementToken::Group(GroupRef::Name(name)) => name,
+                _ => return None,
+            };
+
+            // Braced references are never ambiguous, and neither are references to groups that do exist
+            let written = &replacement[span.clone()];
+            if written.starts_with("${") || exists(name) {
+                return None;
+            }
+
+            // Unbraced names only contain ASCII characters, so every byte offset is a char boundary
+            let end = (1..name.len()).rev().find(|&end| exists(&name[..end]))?;
+            Some(AmbiguousReference {
+                intended: format!("${{{}}}{}", &name[..end], &name[end..]),
+                written: written.into(),
+                span,
+            })
+        })
+        .collect()
+}
+
+/// Replaces each of the given ambiguous references in the given replacement string with its intended braced form
+pub fn brace_ambiguous_references(replacement: &str, references: &[AmbiguousReference]) -> String {
+    let mut fixed = String::with_capacity(replacement.len() + references.len() * 2);
+    let mut end = 0;
+    for reference in references {
+        fixed.push_str(&replacement[end..reference.span.start]);
+        fixed.push_str(&reference.intended);
+        end = reference.span.end;
+    }
+    fixed.push_str(&replacement[end..]);
+    fixed
+}
+
+/// Finds the index of each capture group of the given regex that the given replacement string never refers to
+///
+/// A replacement string that doesn't refer to any groups besides the whole match isn't meant to use them,
+/// so none of them are reported for it
+pub fn unreferenced_groups(replacement: &str, regex: &Regex) -> Vec<usize> {
+    let referenced = resolve_references(replacement, regex)
+        .into_iter()
+        .filter_map(|reference| reference.group)
+        .filter(|&group| group > 0)
+        .collect::<Vec<_>>();
+    if referenced.is_empty() {
+        return Vec::new();
+    }
+
+    (1..regex.captures_len())
+        .filter(|group| !referenced.contains(group))
+        .collect()
+}
+
+/// Finds the byte range of every reference in the given replacement string to a group that the given regex doesn't have,
+/// besides those found by `ambiguous_references`
+pub fn missing_references(replacement: &str, regex: &Regex) -> Vec<Range<usize>> {
+    let ambiguous = ambiguous_references(replacement, regex);
+    resolve_references(replacement, regex)
+        .into_iter()
+        .filter(|reference| reference.group.is_none())
+        .map(|reference| reference.span)
+        .filter(|span| !ambiguous.iter().any(|reference| reference.span == *span))
+        .collect()
+}
+
+/// A reference to a capture group in a replacement string, along with the group of a regex that it refers to
+pub struct ResolvedReference {
+    /// The byte range of the reference in the replacement string, including the `$` and any braces
+    pub span: Range<usize>,
+    /// The index of the group that the reference refers to, or `None` if the regex doesn't have that group,
+    /// in which case the reference is replaced with nothing
+    pub group: Option<usize>,
+}
+
+/// Finds every reference to a capture group in the given replacement string, along with the group of the given regex
+/// that each one refers to
+pub fn resolve_references(replacement: &str, regex: &Regex) -> Vec<ResolvedReference> {
+    tokenize_replacement_spans(replacement)
+        .into_iter()
+        .filter_map(|(span, token)| match token {
+            ReplacementToken::Group(group) => Some(ResolvedReference {
+                span,
+                group: group_index(group, regex),
+            }),
+            ReplacementToken::Literal(_) => None,
+        })
+        .collect()
+}
+
+/// Rewrites each reference in the given replacement string that refers to a group of the given regex,
+/// given a function that takes the index of the group and the reference as written,
+/// and returns what the group should be referred to as instead, if anything different
+///
+/// Rewritten references keep their braces, or lack of them
+pub fn rewrite_references(
+    replacement: &str,
+    regex: &Regex,
+    mut rewrite: impl FnMut(usize, GroupRef<'_>) -> Option<String>,
+) -> String {
+    let mut rewritten = String::with_capacity(replacement.len());
+    let mut end = 0;
+    for (span, token) in tokenize_replacement_spans(replacement) {
+        let name = match token {
+            ReplacementToken::Group(group) => {
+                group_index(group, regex).and_then(|index| rewrite(index, group))
+            }
+            ReplacementToken::Literal(_) => None,
+        };
+        let name = match name {
+            Some(name) => name,
+            None => continue,
+        };
+
+        rewritten.push_str(&replacement[end..span.start]);
+        if replacement[span.clone()].starts_with("${") {
+            let _ = write!(rewritten, "${{{}}}", name);
+        } else {
+            let _ = write!(rewritten, "${}", name);
+        }
+        end = span.end;
+    }
+    rewritten.push_str(&replacement[end..]);
+    rewritten
+}
+
+/// Returns the index of the group of the given regex that the given reference refers to, if it has that group
+fn group_index(group: GroupRef<'_>, regex: &Regex) -> Option<usize> {
+    match group {
+        GroupRef::Index(index) => (index < regex.captures_len()).then_some(index),
+        GroupRef::Name(name) => regex.capture_names().position(|group| group == Some(name)),
+    }
+}
+
+/// A part of what a replacement string expands to for a single match
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExpansionPart<'a> {
+    /// Text that is inserted as-is, or that was matched by a group that the replacement string refers to
+    Text(&'a str),
+    /// A reference to a group that didn't participate in the match, or that the regex doesn't have,
+    /// which expands to nothing, as it is written in the replacement string
+    Empty(&'a str),
+}
+
+/// Expands the given replacement string for a single match, given the range of the given text that each group of the
+/// given regex matched, in the same way as `Captures::expand`
+pub fn expand_replacement<'a>(
+    replacement: &'a str,
+    regex: &Regex,
+    groups: &[Option<Range<usize>>],
+    text: &'a str,
+) -> Vec<ExpansionPart<'a>> {
+    tokenize_replacement_spans(replacement)
+        .into_iter()
+        .map(|(span, token)| match token {
+            ReplacementToken::Literal(literal) => ExpansionPart::Text(literal),
+            ReplacementToken::Group(group) => {
+                match group_index(group, regex).and_then(|index| groups.get(index)?.clone()) {
+                    Some(range) => ExpansionPart::Text(&text[range]),
+                    None => ExpansionPart::Empty(&replacement[span]),
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::text::{check_sections, layout_replacement};
+    use egui::Style;
+
+    #[test]
+    fn expansion_matches_captures_expand() {
+        let regex = Regex::new("(?P<name>a)(b)?(c)").unwrap();
+        let text = "ac";
+        let captures = regex.captures(text).unwrap();
+        let groups = captures
+            .iter()
+            .map(|group| group.map(|group| group.range()))
+            .collect::<Vec<_>>();
+
+        let replacements = [
+            // Escaped dollars
+            "$$",
+            "$$1",
+            "a$$$1",
+            // Names take the longest run of name characters, unless they are braced
+            "$1a",
+            "${1}a",
+            "$name",
+            "${name}",
+            "$name-",
+            "$namex",
+            // A `$` that doesn't start a reference is inserted as-is
+            "trailing $",
+            "$-",
+            "$ 1",
+            "${",
+            "${name",
+            "${}",
+            // Groups that the regex doesn't have, or that didn't participate in the match
+            "$2",
+            "$4",
+            "${99}",
+            "$99999999999",
+            "$missing",
+        ];
+        for replacement in replacements {
+            let mut expected = String::new();
+            captures.expand(replacement, &mut expected);
+
+            let expanded = expand_replacement(replacement, &regex, &groups, text)
+                .into_iter()
+                .filter_map(|part| match part {
+                    ExpansionPart::Text(text) => Some(text),
+                    ExpansionPart::Empty(_) => None,
+                })
+                .collect::<String>();
+            assert_eq!(
+                expanded, expected,
+                "{:?} was expanded differently",
+                replacement
+            );
+        }
+    }
+
+    /// References are resolved to the groups that `Regex::replace` would use
+    #[test]
+    fn references_are_resolved_like_regex_replace() {
+        let regex = Regex::new("(?P<name>a)(b)").unwrap();
+        let cases: &[(&str, &[_])] = &[
+            (
+                "$0 $1 $2",
+                &[(0..2, Some(0)), (3..5, Some(1)), (6..8, Some(2))],
+            ),
+            ("$3", &[(0..2, None)]),
+            ("$$1", &[]),
+            ("$$$1", &[(2..4, Some(1))]),
+            ("$12", &[(0..3, None)]),
+            ("$1a", &[(0..3, None)]),
+            ("${1}2 ${name}", &[(0..4, Some(1)), (6..13, Some(1))]),
+            ("${name $ ${}", &[(0..12, None)]),
+            ("${name", &[]),
+        ];
+
+        for &(replacement, expected) in cases {
+            let references = resolve_references(replacement, &regex);
+            let layout = layout_replacement(replacement, &Style::default(), &references, &[], &[]);
+            check_sections(&layout).unwrap();
+
+            let resolved = references
+                .into_iter()
+                .map(|reference| (reference.span, reference.group))
+                .collect::<Vec<_>>();
+            assert_eq!(resolved, expected, "{}", replacement);
+        }
+    }
+
+    #[test]
+    fn groups_that_did_not_participate_expand_to_empty_parts() {
+        let regex = Regex::new("(a)|(?P<b>b)").unwrap();
+        let text = "xb";
+        let groups = regex
+            .captures(text)
+            .unwrap()
+            .iter()
+            .map(|group| group.map(|group| group.range()))
+            .collect::<Vec<_>>();
+
+        let parts = expand_replacement("$1-${b}-$$", &regex, &groups, text);
+        assert_eq!(parts.first(), Some(&ExpansionPart::Empty("$1")));
+    }
+
+    #[test]
+    fn unreferenced_groups_and_missing_references() {
+        let cases: &[(&str, &str, &[usize], &[&str])] = &[
+            // Replacements that only use the whole match aren't meant to use the groups
+            ("(a)(b)", "$0", &[], &[]),
+            ("(a)(b)", "[$$1]", &[], &[]),
+            ("(a)(b)(c)", "$0$2", &[1, 3], &[]),
+            ("(a)(?P<x>b)", "${x}$1", &[], &[]),
+            ("(a)(b)", "$1$3${y}", &[2], &["$3", "${y}"]),
+            // An ambiguous reference is already warned about separately
+            ("(a)(b)", "$2$1_copy", &[1], &[]),
+        ];
+        for &(pattern, replacement, unreferenced, missing) in cases {
+            let regex = Regex::new(pattern).unwrap();
+            assert_eq!(
+                unreferenced_groups(replacement, &regex),
+                unreferenced,
+                "{:?} in {:?}",
+                replacement,
+                pattern
+            );
+
+            let references = missing_references(replacement, &regex)
+                .into_iter()
+                .map(|span| &replacement[span])
+                .collect::<Vec<_>>();
+            assert_eq!(references, missing, "{:?} in {:?}", replacement, pattern);
+        }
+    }
+}
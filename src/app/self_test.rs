@@ -0,0 +1,534 @@
+use super::{
+    ast_tree::AstNode,
+    descriptions::GroupDescriptions,
+    escaped_bytes::{escape_bytes, EscapedBytes},
+    export::{fit_cell, to_aligned_table, to_csv, to_json, ExportedMatch, TableOptions},
+    journal::JournalEntry,
+    parsing::{ast_find_tokens, compile_regex},
+    state::{HighlightMode, LogicOptions, LogicState},
+    text::{
+        check_sections, find_captures, highlight_span, layout_matched_text, layout_regex,
+        layout_regex_err, CaptureSearch, CaptureTable, TextIndex,
+    },
+};
+use egui::{Color32, Style};
+use std::ops::Range;
+
+/// A pattern and input text to run through the processing pipeline
+pub struct SelfTestCase {
+    pub name: &'static str,
+    pub pattern: &'static str,
+    pub input: &'static str,
+}
+
+/// Representative patterns and input texts, covering both valid and malformed patterns
+pub const SELF_TEST_CASES: &[SelfTestCase] = &[
+    SelfTestCase {
+        name: "Literal",
+        pattern: "abc",
+        input: "xxabcxxabc",
+    },
+    SelfTestCase {
+        name: "Capture groups",
+        pattern: r"(\w+)@(\w+)\.com",
+        input: "a@b.com, c@d.com",
+    },
+    SelfTestCase {
+        name: "Named groups",
+        pattern: r"(?P<year>\d{4})-(?P<month>\d{2})",
+        input: "2022-09 and 2023-10",
+    },
+    SelfTestCase {
+        name: "Non-participating groups",
+        pattern: "(a)|(b)",
+        input: "ab",
+    },
+    SelfTestCase {
+        name: "Nested groups",
+        pattern: "((a)(b(c)))",
+        input: "abc abc",
+    },
+    SelfTestCase {
+        name: "Unicode",
+        pattern: r"(é+)\s(\p{Greek}+)",
+        input: "éé αβγ",
+    },
+    SelfTestCase {
+        name: "Multi-byte literals",
+        pattern: "(é+)(日本)",
+        input: "ééé日本 日本 é日本",
+    },
+    SelfTestCase {
+        name: "Emoji",
+        pattern: r"(🦀+)\s?(\w*)",
+        input: "🦀🦀 crab🦀",
+    },
+    SelfTestCase {
+        name: "Multiline",
+        pattern: r"(?m)^(\w+)$",
+        input: "one\ntwo\nthree",
+    },
+    SelfTestCase {
+        name: "Empty matches",
+        pattern: "x*",
+        input: "abc",
+    },
+    SelfTestCase {
+        name: "No matches",
+        pattern: "(z)",
+        input: "abc",
+    },
+    SelfTestCase {
+        name: "Empty pattern",
+        pattern: "",
+        input: "abc",
+    },
+    SelfTestCase {
+        name: "Flags only",
+        pattern: "(?i)",
+        input: "abc",
+    },
+    SelfTestCase {
+        name: "Empty input",
+        pattern: "(a)",
+        input: "",
+    },
+    SelfTestCase {
+        name: "Unclosed group",
+        pattern: "(abc",
+        input: "abc",
+    },
+    SelfTestCase {
+        name: "Duplicate group name",
+        pattern: "(?P<a>x)(?P<a>y)",
+        input: "xy",
+    },
+    SelfTestCase {
+        name: "Invalid repetition",
+        pattern: "a{2,1}",
+        input: "aa",
+    },
+];
+
+/// Runs every self-test case, printing the outcome of each one along with a summary,
+/// and returns whether they all succeeded
+pub fn run_self_test() -> bool {
+    let mut failures = 0;
+    for case in SELF_TEST_CASES {
+        match run_self_test_case(case) {
+            Ok(()) => println!("ok      {}", case.name),
+            Err(err) => {
+                println!("FAILED  {}: {}", case.name, err);
+                failures += 1;
+            }
+        }
+    }
+
+    let total = SELF_TEST_CASES.len();
+    println!("{} of {} self-test cases passed", total - failures, total);
+    failures == 0
+}
+
+/// Runs a pattern and input text through the processing pipeline, checking that the results are consistent
+pub fn run_self_test_case(case: &SelfTestCase) -> Result<(), String> {
+    let style = Style::default();
+
+    let (ast, regex) = match compile_regex(case.pattern, Default::default()) {
+        Ok(compiled) => compiled,
+        Err(err) => {
+            let layout = layout_regex_err(case.pattern.into(), &style, &err, None);
+            return check_sections(&layout.job).map_err(|err| format!("Error layout: {}", err));
+        }
+    };
+
+    let logic = LogicState::new(
+        case.pattern,
+        &style,
+        case.pattern,
+        case.input,
+        LogicOptions {
+            highlight_mode: HighlightMode::Captures,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| format!("Compiled once but not twice: {}", err))?;
+
+    check_sections(&logic.regex_layout.job).map_err(|err| format!("Regex layout: {}", err))?;
+    check_sections(
+        &logic
+            .input_layout
+            .formatting
+            .clone()
+            .convert_to_layout_job(),
+    )
+    .map_err(|err| format!("Input layout: {}", err))?;
+
+    let mut tokens = ast_find_tokens(&ast);
+    tokens.sort_unstable_by_key(|(_, range)| range.start);
+    if let Some((kind, range)) = tokens.iter().find(|(_, range)| {
+        !case.pattern.is_char_boundary(range.start) || !case.pattern.is_char_boundary(range.end)
+    }) {
+        return Err(format!(
+            "{:?} token {:?} is not on char boundaries",
+            kind, range
+        ));
+    }
+
+    if tokens
+        .windows(2)
+        .any(|pair| pair[0].1.end > pair[1].1.start)
+    {
+        return Err("Tokens overlap each other".into());
+    }
+
+    let tree = AstNode::from_ast(&ast, case.pattern);
+    check_ast_node(&tree, 0..case.pattern.len())?;
+
+    // Highlighting a node of the tree splits the sections of the regex layout around its span
+    let mut leaf = &tree;
+    while let Some(child) = leaf.children.last() {
+        leaf = child;
+    }
+    let mut highlighted = logic.regex_layout.job.clone();
+    highlight_span(&mut highlighted, leaf.span.clone(), Color32::WHITE);
+    check_sections(&highlighted).map_err(|err| format!("Highlighted regex layout: {}", err))?;
+
+    if logic.regex_layout.group_nesting.len() != logic.regex_layout.capture_group_chars.len() {
+        return Err("Regex layout does not know how deeply every capture group is nested".into());
+    }
+
+    let degraded = layout_regex(
+        case.pattern.into(),
+        &ast,
+        &style,
+        Some(0),
+        &Default::default(),
+        None,
+    );
+    check_sections(&degraded.job).map_err(|err| format!("Degraded regex layout: {}", err))?;
+
+    check_clamped_glyph_ranges(case.pattern).map_err(|err| format!("Pattern: {}", err))?;
+    check_clamped_glyph_ranges(case.input).map_err(|err| format!("Input text: {}", err))?;
+
+    let expected_matches = if logic.empty_pattern {
+        0
+    } else {
+        regex.find_iter(case.input).count()
+    };
+    if logic.selector.matches.len() != expected_matches {
+        return Err(format!(
+            "Selector has {} matches, but there are {}",
+            logic.selector.matches.len(),
+            expected_matches
+        ));
+    }
+
+    // Searching one match at a time exercises resuming the search after every match
+    let mut search = CaptureSearch::default();
+    while !search.advance(&regex, case.input, 1) {}
+    if search.captures != find_captures(&regex, case.input) {
+        return Err("Resuming the capture search after each match finds different captures".into());
+    }
+
+    if logic.is_match {
+        let captures = find_captures(&regex, case.input);
+        let layout = layout_matched_text(
+            case.input.into(),
+            &captures,
+            &style,
+            &logic.regex_layout.capture_group_colors,
+        );
+        check_sections(&layout.formatting.convert_to_layout_job())
+            .map_err(|err| format!("Matched text layout: {}", err))?;
+
+        if layout.capture_group_chars.len() != expected_matches {
+            return Err(format!(
+                "Matched text layout has {} matches, but there are {}",
+                layout.capture_group_chars.len(),
+                expected_matches
+            ));
+        }
+
+        let group_count = logic.regex_layout.capture_group_chars.len();
+        if let Some(groups) = layout
+            .capture_group_chars
+            .iter()
+            .find(|groups| groups.len() != group_count)
+        {
+            return Err(format!(
+                "Match has {} capture groups, but the regex has {}",
+                groups.len(),
+                group_count
+            ));
+        }
+    }
+
+    check_byte_mode(case, expected_matches)?;
+
+    let captures = if logic.empty_pattern {
+        Vec::new()
+    } else {
+        find_captures(&regex, case.input)
+    };
+    check_json_export(&logic, &captures)?;
+    check_csv_export(&logic, &captures)?;
+    check_table_export(&logic, &captures)?;
+
+    let entry = JournalEntry {
+        timestamp: 0,
+        pattern: case.pattern.into(),
+        match_count: logic.match_count(),
+    };
+    let exported = serde_json::to_string(&entry).map_err(|err| err.to_string())?;
+    let imported = serde_json::from_str::<JournalEntry>(&exported)
+        .map_err(|err| format!("Exported journal entry does not parse: {}", err))?;
+    if imported.pattern != entry.pattern || imported.match_count != entry.match_count {
+        return Err("Exported journal entry does not round trip".into());
+    }
+
+    Ok(())
+}
+
+/// Checks that the span of the given node of an AST tree, and those of all of its children, are within the given range
+fn check_ast_node(node: &AstNode, parent: Range<usize>) -> Result<(), String> {
+    if node.span.start < parent.start || node.span.end > parent.end {
+        return Err(format!(
+            "AST tree node \"{}\" spans {:?}, which is outside of {:?}",
+            node.label, node.span, parent
+        ));
+    }
+
+    node.children
+        .iter()
+        .try_for_each(|child| check_ast_node(child, node.span.clone()))
+}
+
+/// Checks that the input text survives being escaped as bytes, and that matching those bytes finds as many matches as
+/// matching the text does
+fn check_byte_mode(case: &SelfTestCase, expected_matches: usize) -> Result<(), String> {
+    let escaped = escape_bytes(case.input.as_bytes());
+    if EscapedBytes::unescape(&escaped).bytes != case.input.as_bytes() {
+        return Err("Escaped input text does not round trip".into());
+    }
+
+    let logic = LogicState::new(
+        case.pattern,
+        &Style::default(),
+        case.pattern,
+        &escaped,
+        LogicOptions {
+            highlight_mode: HighlightMode::Captures,
+            byte_mode: true,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| format!("Compiled as text but not as bytes: {}", err))?;
+
+    check_sections(
+        &logic
+            .input_layout
+            .formatting
+            .clone()
+            .convert_to_layout_job(),
+    )
+    .map_err(|err| format!("Byte mode input layout: {}", err))?;
+
+    if logic.selector.matches.len() != expected_matches {
+        return Err(format!(
+            "Byte mode selector has {} matches, but there are {}",
+            logic.selector.matches.len(),
+            expected_matches
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the matches exported as JSON parse back into the given captures
+fn check_json_export(logic: &LogicState, captures: &CaptureTable) -> Result<(), String> {
+    let json = to_json(logic, &GroupDescriptions::default());
+    let exported = serde_json::from_str::<Vec<ExportedMatch>>(&json)
+        .map_err(|err| format!("Exported JSON does not parse: {}", err))?;
+    if exported.len() != captures.len() {
+        return Err(format!(
+            "Exported JSON has {} matches, but there are {}",
+            exported.len(),
+            captures.len()
+        ));
+    }
+
+    for (index, (exported, groups)) in exported.iter().zip(captures).enumerate() {
+        let mut ranges = vec![Some(exported.range.clone())];
+        ranges.extend(exported.groups.iter().map(|group| group.range.clone()));
+        if exported.index != index || &ranges != groups {
+            return Err(format!(
+                "Exported JSON match {} has the ranges {:?}, but they are {:?}",
+                index, ranges, groups
+            ));
+        }
+
+        let text = &logic.selector.text;
+        if exported.text != text[exported.range.clone()]
+            || exported
+                .groups
+                .iter()
+                .any(|group| group.text.as_deref() != group.range.clone().map(|r| &text[r]))
+        {
+            return Err(format!(
+                "Exported JSON match {} has text that isn't in its ranges",
+                index
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the matches exported as CSV parse back into the given captures,
+/// with a header and then a row of the index, start, end and the text of every group of each match
+fn check_csv_export(logic: &LogicState, captures: &CaptureTable) -> Result<(), String> {
+    let rows = parse_csv(&to_csv(logic))?;
+    if rows.len() != captures.len() + 1 {
+        return Err(format!(
+            "Exported CSV has {} rows, but there are {} matches",
+            rows.len().saturating_sub(1),
+            captures.len()
+        ));
+    }
+
+    let text = &logic.selector.text;
+    let columns = logic.regex.captures_len() + 3;
+    for (index, (row, groups)) in rows.iter().skip(1).zip(captures).enumerate() {
+        let whole = groups[0].clone().unwrap_or_default();
+        let mut expected = vec![
+            index.to_string(),
+            whole.start.to_string(),
+            whole.end.to_string(),
+        ];
+        expected.extend(
+            groups
+                .iter()
+                .map(|range| range.clone().map_or("", |range| &text[range]).to_owned()),
+        );
+        if rows[0].len() != columns || row != &expected {
+            return Err(format!(
+                "Exported CSV row {} is {:?}, but it should be {:?}",
+                index, row, expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Splits CSV into the fields of each row, undoing the quoting of `escape_csv_field`
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => field.push('"'),
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {
+                if chars.next() != Some('\n') {
+                    return Err("Exported CSV has a line that doesn't end with CRLF".into());
+                }
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if quoted || !field.is_empty() || !row.is_empty() {
+        return Err("Exported CSV doesn't end with a complete row".into());
+    }
+    Ok(rows)
+}
+
+/// Checks that the matches exported as a table have a row for each of the given captures,
+/// with a cell for the text of every group
+fn check_table_export(logic: &LogicState, captures: &CaptureTable) -> Result<(), String> {
+    let options = TableOptions {
+        max_width: usize::MAX,
+        ..Default::default()
+    };
+    let table = to_aligned_table(logic, options, &GroupDescriptions::default());
+    let rows = table
+        .lines()
+        .filter_map(|line| line.strip_prefix('│')?.strip_suffix('│'))
+        .map(|line| line.split('│').map(str::trim).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if rows.len() != captures.len() + 1 {
+        return Err(format!(
+            "Exported table has {} rows, but there are {} matches",
+            rows.len().saturating_sub(1),
+            captures.len()
+        ));
+    }
+
+    let text = &logic.selector.text;
+    for (index, (row, groups)) in rows.iter().skip(1).zip(captures).enumerate() {
+        let mut expected = vec![(index + 1).to_string()];
+        expected.extend(groups.iter().map(|range| {
+            let cell = range.clone().map_or("", |range| &text[range]);
+            fit_cell(cell, options.max_width).trim().to_owned()
+        }));
+        if rows[0].len() != expected.len() || row != &expected {
+            return Err(format!(
+                "Exported table row {} is {:?}, but it should be {:?}",
+                index, row, expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that converting every range of byte offsets in the given text into glyph offsets stays in bounds,
+/// and agrees with the exact conversion whenever both ends of the range are on char boundaries
+fn check_clamped_glyph_ranges(text: &str) -> Result<(), String> {
+    let index = TextIndex::new(text);
+    let glyph_count = text.chars().filter(|&c| c != '\n').count();
+    for start in 0..=text.len() {
+        for end in start..=text.len() + 1 {
+            let range = start..end;
+            let clamped = index.byte_range_to_glyph_range_clamped(range.clone());
+            if clamped.start > clamped.end || clamped.end > glyph_count {
+                return Err(format!(
+                    "Bytes {:?} were clamped to glyphs {:?}, which are out of bounds",
+                    range, clamped
+                ));
+            }
+
+            if let Some(exact) = index.byte_range_to_glyph_range(range.clone()) {
+                if clamped != exact {
+                    return Err(format!(
+                        "Bytes {:?} were clamped to glyphs {:?}, but they are glyphs {:?}",
+                        range, clamped, exact
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        let failures = SELF_TEST_CASES
+            .iter()
+            .filter_map(|case| {
+                let err = run_self_test_case(case).err()?;
+                Some(format!("{}: {}", case.name, err))
+            })
+            .collect::<Vec<_>>();
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
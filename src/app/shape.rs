@@ -1,24 +1,184 @@
 use eframe::epaint::CubicBezierShape;
-use egui::{Color32, Pos2, Stroke, Vec2};
+use egui::{Color32, Pos2, Rect, Shape, Stroke, Vec2};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
-pub enum Orientation {
-    #[allow(dead_code)]
-    Horizontal,
-    Vertical,
+/// The curvature that gives a smooth curve when the connected points are far enough apart
+pub const DEFAULT_CURVATURE: f32 = 0.5;
+
+/// The distance between connected points below which curves are made more curved, to keep them away from the text
+const CURVE_THRESHOLD: f32 = 48.0;
+
+/// The distance between connected points below which elbows are used instead of curves,
+/// as even very curved lines would overlap the text
+const ELBOW_THRESHOLD: f32 = 12.0;
+
+/// The maximum distance between a curve and the line segments it is approximated by when it is dashed
+const FLATTEN_TOLERANCE: f32 = 0.5;
+
+/// The length of each dash of a dashed line, and the length of the gaps between them
+const DASH_LENGTH: f32 = 6.0;
+const GAP_LENGTH: f32 = 4.0;
+
+/// The number of straight segments that curves are approximated by when measuring the distance to them
+const HIT_TEST_SEGMENTS: usize = 16;
+
+/// The style of the lines that connect capture groups in the regex to their matches in the input text
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LineStyle {
+    #[default]
+    Curve,
+    Elbow,
+}
+
+impl LineStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Curve => "Curves",
+            Self::Elbow => "Elbows",
+        }
+    }
+}
+
+/// A line connecting two points, along with its midpoint
+pub struct ConnectingLine {
+    pub shape: Shape,
+    /// The point halfway along the line
+    pub midpoint: Pos2,
+    /// The points that define the path of the line, for finding how close other points are to it
+    pub outline: LineOutline,
+}
+
+impl ConnectingLine {
+    /// Returns the distance from the given point to this line, if it is within `tolerance` of the line
+    pub fn hit_distance(&self, pos: Pos2, tolerance: f32) -> Option<f32> {
+        // Measuring the distance to a line is relatively expensive, so lines that are clearly too far away are skipped
+        if !self.outline.bounds().expand(tolerance).contains(pos) {
+            return None;
+        }
+
+        let distance = self.outline.distance_to(pos);
+        (distance <= tolerance).then_some(distance)
+    }
+}
+
+/// The points that define the path of a connecting line
+pub enum LineOutline {
+    /// The start, control, and end points of a cubic bezier curve
+    Curve([Pos2; 4]),
+    /// The vertices of a line made of straight segments
+    Polyline(Vec<Pos2>),
 }
 
-/// Returns a bezier curve that connects the given points
+impl LineOutline {
+    /// Returns a rect that contains the whole line
+    pub fn bounds(&self) -> Rect {
+        // A bezier curve is always contained by its control points
+        match self {
+            Self::Curve(points) => Rect::from_points(points),
+            Self::Polyline(points) => Rect::from_points(points),
+        }
+    }
+
+    /// Returns the distance from the given point to the closest point on the line,
+    /// approximating curves by straight segments
+    pub fn distance_to(&self, pos: Pos2) -> f32 {
+        match self {
+            Self::Curve(points) => {
+                let path = (0..=HIT_TEST_SEGMENTS)
+                    .map(|i| bezier_point(points, i as f32 / HIT_TEST_SEGMENTS as f32))
+                    .collect::<Vec<_>>();
+                distance_to_path(&path, pos)
+            }
+            Self::Polyline(points) => distance_to_path(points, pos),
+        }
+    }
+}
+
+/// Returns the distance from the given point to the closest point on the given path of straight segments
+pub fn distance_to_path(path: &[Pos2], pos: Pos2) -> f32 {
+    path.windows(2)
+        .map(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            let along = end - start;
+            let t = if along.length_sq() > 0.0 {
+                ((pos - start).dot(along) / along.length_sq()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (start + along * t).distance(pos)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Returns a line in the given style that connects the given points, which are above and below each other,
+/// optionally dashed
+///
+/// Curves are made more curved when the points are close together, and switch to elbows when the points are
+/// very close together, so that the line doesn't run along the text it connects
+pub fn line_between(
+    from: Pos2,
+    to: Pos2,
+    stroke: impl Into<Stroke>,
+    style: LineStyle,
+    dashed: bool,
+) -> ConnectingLine {
+    let stroke = stroke.into();
+    let distance = (to.y - from.y).abs();
+    match style {
+        LineStyle::Curve if distance >= ELBOW_THRESHOLD => {
+            let curvature = DEFAULT_CURVATURE.max(DEFAULT_CURVATURE * CURVE_THRESHOLD / distance);
+            let curve = curve_between(from, to, stroke, curvature);
+            ConnectingLine {
+                midpoint: bezier_point(&curve.points, 0.5),
+                outline: LineOutline::Curve(curve.points),
+                shape: if dashed {
+                    dashed_path(&curve.flatten(Some(FLATTEN_TOLERANCE)), stroke)
+                } else {
+                    curve.into()
+                },
+            }
+        }
+        _ => {
+            let points = elbow_points(from, to);
+            ConnectingLine {
+                // The middle segment of an elbow is centered between the end points
+                midpoint: Pos2::new((from.x + to.x) / 2.0, (from.y + to.y) / 2.0),
+                shape: if dashed {
+                    dashed_path(&points, stroke)
+                } else {
+                    elbow_between(from, to, stroke)
+                },
+                outline: LineOutline::Polyline(points),
+            }
+        }
+    }
+}
+
+/// Returns a dashed line along the given path
+fn dashed_path(path: &[Pos2], stroke: Stroke) -> Shape {
+    Shape::Vec(Shape::dashed_line(path, stroke, DASH_LENGTH, GAP_LENGTH))
+}
+
+/// Returns the point at the given parameter `t` along the cubic bezier curve with the given points,
+/// where a `t` of `0.0` is the start of the curve and a `t` of `1.0` is the end of the curve
+pub fn bezier_point(points: &[Pos2; 4], t: f32) -> Pos2 {
+    let u = 1.0 - t;
+    let [p0, p1, p2, p3] = points.map(Pos2::to_vec2);
+    (p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t))
+        .to_pos2()
+}
+
+/// Returns a bezier curve that connects the given points, which are above and below each other
+///
+/// The control points are offset vertically from the end points by `curvature` times the vertical distance
+/// between the end points, so a curvature of `0.5` places them halfway across
 pub fn curve_between(
     from: Pos2,
     to: Pos2,
     stroke: impl Into<Stroke>,
-    orientation: Orientation,
+    curvature: f32,
 ) -> CubicBezierShape {
-    let control_offset = match orientation {
-        Orientation::Horizontal => Vec2::X * ((to.x - from.x) / 2.0),
-        Orientation::Vertical => Vec2::Y * ((to.y - from.y) / 2.0),
-    };
+    let control_offset = Vec2::Y * ((to.y - from.y) * curvature);
 
     let from_control = from + control_offset;
     let to_control = to - control_offset;
@@ -30,3 +190,114 @@ pub fn curve_between(
         stroke,
     )
 }
+
+/// Returns an orthogonal line that connects the given points, going halfway down (or up),
+/// then across to line up with the end point, then the rest of the way down
+pub fn elbow_between(from: Pos2, to: Pos2, stroke: impl Into<Stroke>) -> Shape {
+    Shape::line(elbow_points(from, to), stroke)
+}
+
+/// Returns the vertices of an orthogonal line that connects the given points, as described in `elbow_between`
+fn elbow_points(from: Pos2, to: Pos2) -> Vec<Pos2> {
+    let middle = (from.y + to.y) / 2.0;
+    vec![from, Pos2::new(from.x, middle), Pos2::new(to.x, middle), to]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_are_more_curved_when_the_points_are_close() {
+        let far = curve_between(Pos2::ZERO, Pos2::new(20.0, 100.0), Stroke::none(), 0.5);
+        assert_eq!(
+            far.points,
+            [
+                Pos2::ZERO,
+                Pos2::new(0.0, 50.0),
+                Pos2::new(20.0, 50.0),
+                Pos2::new(20.0, 100.0)
+            ]
+        );
+
+        // Half of `CURVE_THRESHOLD` apart doubles the curvature
+        let close = line_between(
+            Pos2::ZERO,
+            Pos2::new(0.0, 24.0),
+            Stroke::none(),
+            LineStyle::Curve,
+            false,
+        );
+        match close.outline {
+            LineOutline::Curve(points) => {
+                assert_eq!(points[1], Pos2::new(0.0, 24.0));
+                assert_eq!(points[2], Pos2::ZERO);
+            }
+            LineOutline::Polyline(_) => panic!("Points 24.0 apart should be connected by a curve"),
+        }
+    }
+
+    #[test]
+    fn elbows_turn_halfway_between_the_points() {
+        let (from, to) = (Pos2::new(0.0, 0.0), Pos2::new(10.0, 40.0));
+        let expected = vec![from, Pos2::new(0.0, 20.0), Pos2::new(10.0, 20.0), to];
+        assert_eq!(elbow_points(from, to), expected);
+
+        let elbow = line_between(from, to, Stroke::none(), LineStyle::Elbow, false);
+        assert_eq!(elbow.midpoint, Pos2::new(5.0, 20.0));
+        assert!(matches!(elbow.outline, LineOutline::Polyline(points) if points == expected));
+
+        // Curves between points that are too close together become elbows
+        let close = line_between(
+            from,
+            Pos2::new(10.0, 8.0),
+            Stroke::none(),
+            LineStyle::Curve,
+            false,
+        );
+        assert!(matches!(close.outline, LineOutline::Polyline(_)));
+    }
+
+    #[test]
+    fn distances_are_measured_to_the_closest_segment() {
+        let path = [Pos2::ZERO, Pos2::new(0.0, 10.0), Pos2::new(10.0, 10.0)];
+        assert_eq!(distance_to_path(&path, Pos2::new(3.0, 5.0)), 3.0);
+        assert_eq!(distance_to_path(&path, Pos2::new(5.0, 12.0)), 2.0);
+        // Past the end of the path, the distance is to its last point
+        assert_eq!(distance_to_path(&path, Pos2::new(13.0, 14.0)), 5.0);
+        // Segments of no length are measured as points
+        assert_eq!(
+            distance_to_path(&[Pos2::ZERO, Pos2::ZERO], Pos2::new(3.0, 4.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn lines_are_hit_within_the_tolerance() {
+        let (from, to) = (Pos2::ZERO, Pos2::new(40.0, 100.0));
+        let curve = line_between(from, to, Stroke::none(), LineStyle::Curve, false);
+        let on_curve = bezier_point(
+            match &curve.outline {
+                LineOutline::Curve(points) => points,
+                LineOutline::Polyline(_) => {
+                    panic!("Points 100.0 apart should be connected by a curve")
+                }
+            },
+            0.25,
+        );
+        assert!(curve.hit_distance(on_curve, 1.0).unwrap() < 0.5);
+        assert!(curve
+            .hit_distance(on_curve + Vec2::new(3.0, 0.0), 4.0)
+            .is_some());
+        assert_eq!(
+            curve.hit_distance(on_curve + Vec2::new(3.0, 0.0), 1.0),
+            None
+        );
+        assert_eq!(curve.hit_distance(Pos2::new(200.0, 50.0), 4.0), None);
+
+        let elbow = line_between(from, to, Stroke::none(), LineStyle::Elbow, false);
+        assert_eq!(elbow.hit_distance(Pos2::new(20.0, 52.0), 4.0), Some(2.0));
+        // The corner of the bounds is far from the path of the elbow
+        assert_eq!(elbow.hit_distance(Pos2::new(38.0, 2.0), 4.0), None);
+    }
+}
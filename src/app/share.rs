@@ -0,0 +1,221 @@
+use super::{descriptions::GroupDescriptions, state::WidgetState};
+use serde::{Deserialize, Serialize};
+
+/// The prefix of a URL fragment that encodes a shared session, which distinguishes it from any other fragment
+const FRAGMENT_PREFIX: &str = "session=";
+
+/// The size in bytes of the serialized session above which it is compressed, to keep links short
+const COMPRESSION_THRESHOLD: usize = 512;
+
+/// The largest size in bytes that a compressed session is decompressed to, so that a malicious link can't exhaust memory
+const MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Marks the payload of a fragment as uncompressed JSON
+const UNCOMPRESSED_TAG: u8 = b'j';
+
+/// Marks the payload of a fragment as JSON that has been compressed with DEFLATE
+const COMPRESSED_TAG: u8 = b'z';
+
+/// The parts of a session that are encoded in a shareable link
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct SharedSession {
+    pub regex_text: String,
+    pub input_text: String,
+    pub replace_text: String,
+    /// Links from before descriptions were shared don't have any
+    #[serde(default)]
+    pub descriptions: GroupDescriptions,
+}
+
+impl SharedSession {
+    /// Takes the parts of the given widget state that are shared
+    pub fn from_widgets(widgets: &WidgetState) -> Self {
+        Self {
+            regex_text: widgets.regex_text.clone(),
+            input_text: widgets.input_text.clone(),
+            replace_text: widgets.replace_text().to_owned(),
+            descriptions: widgets.group_descriptions.clone(),
+        }
+    }
+
+    /// Replaces the parts of the given widget state that are shared with those of this session
+    pub fn apply(self, widgets: &mut WidgetState) {
+        widgets.regex_text = self.regex_text;
+        widgets.input_text = self.input_text;
+        widgets.set_replace_text(self.replace_text);
+        widgets.group_descriptions = self.descriptions;
+    }
+
+    /// Encodes this session as a URL fragment, without the leading `#`
+    ///
+    /// The session is serialized as JSON, which is compressed if it is large, then encoded as URL-safe base64,
+    /// with a leading tag byte recording whether it was compressed
+    pub fn to_fragment(&self) -> String {
+        // Only strings, numbers and lists of them are serialized, which can't fail
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        let mut payload = Vec::with_capacity(json.len() + 1);
+        if json.len() > COMPRESSION_THRESHOLD {
+            payload.push(COMPRESSED_TAG);
+            payload.extend(miniz_oxide::deflate::compress_to_vec(&json, 9));
+        } else {
+            payload.push(UNCOMPRESSED_TAG);
+            payload.extend(json);
+        }
+
+        format!(
+            "{}{}",
+            FRAGMENT_PREFIX,
+            base64::encode_config(payload, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    /// Decodes a session from a URL fragment, with or without the leading `#`,
+    /// returning `None` if the fragment doesn't encode a session or is malformed
+    pub fn from_fragment(fragment: &str) -> Option<Self> {
+        let encoded = fragment
+            .strip_prefix('#')
+            .unwrap_or(fragment)
+            .strip_prefix(FRAGMENT_PREFIX)?;
+        let payload = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+
+        let json = match payload.split_first()? {
+            (&UNCOMPRESSED_TAG, json) => json.to_vec(),
+            (&COMPRESSED_TAG, compressed) => miniz_oxide::inflate::decompress_to_vec_with_limit(
+                compressed,
+                MAX_DECOMPRESSED_SIZE,
+            )
+            .ok()?,
+            _ => return None,
+        };
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Reads the session encoded in the fragment of the page's URL, if there is one,
+/// then removes the fragment so that reloading the page doesn't discard any changes made since
+#[cfg(target_arch = "wasm32")]
+pub fn take_session_from_location() -> Option<SharedSession> {
+    let window = web_sys::window()?;
+    let location = window.location();
+    let session = SharedSession::from_fragment(&location.hash().ok()?)?;
+
+    if let (Ok(history), Ok(pathname), Ok(search)) =
+        (window.history(), location.pathname(), location.search())
+    {
+        let _ = history.replace_state_with_url(
+            &wasm_bindgen::JsValue::NULL,
+            "",
+            Some(&format!("{}{}", pathname, search)),
+        );
+    }
+
+    Some(session)
+}
+
+/// Builds a link to the page that reproduces the given session when it is opened
+#[cfg(target_arch = "wasm32")]
+pub fn session_link(session: &SharedSession) -> Option<String> {
+    let href = web_sys::window()?.location().href().ok()?;
+    let base = href.split('#').next().unwrap_or_default();
+    Some(format!("{}#{}", base, session.to_fragment()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions_are_shared() {
+        let mut widgets = WidgetState {
+            regex_text: "(?P<year>\\d{4})-(\\d\\d)".into(),
+            ..Default::default()
+        };
+        let names = [None, Some("year"), None];
+        widgets.group_descriptions.update_groups(names.into_iter());
+        *widgets.group_descriptions.get_mut(1, Some("year")) = "Four-digit year".into();
+        *widgets.group_descriptions.get_mut(2, None) = "Month".into();
+
+        let session = SharedSession::from_widgets(&widgets);
+        let restored = SharedSession::from_fragment(&session.to_fragment()).unwrap();
+        assert_eq!(restored, session);
+
+        let mut restored_widgets = WidgetState::default();
+        restored.apply(&mut restored_widgets);
+        let descriptions = &restored_widgets.group_descriptions;
+        assert_eq!(descriptions.get(1, Some("year")), Some("Four-digit year"));
+        assert_eq!(descriptions.get(2, None), Some("Month"));
+
+        // The groups are unchanged, so nothing is discarded once the regex is compiled
+        restored_widgets
+            .group_descriptions
+            .update_groups(names.into_iter());
+        assert_eq!(restored_widgets.group_descriptions.notice, None);
+    }
+
+    /// Sessions are reproduced exactly from their links, whether or not they are compressed
+    #[test]
+    fn sessions_round_trip_through_links() {
+        let long_input = "Ünïcödé 日本語 🦀\r\n\ttabs and \"quotes\"\n".repeat(100);
+        let sessions = [
+            ("", "", ""),
+            (
+                "(\\w+)\\s(?P<name>é+)",
+                "first line\nsecond line\r\n日本 🦀",
+                "${name}\n$1",
+            ),
+            ("(.*)", long_input.as_str(), "$1"),
+        ];
+
+        for (regex_text, input_text, replace_text) in sessions {
+            let mut widgets = WidgetState {
+                regex_text: regex_text.into(),
+                input_text: input_text.into(),
+                ..Default::default()
+            };
+            widgets.set_replace_text(replace_text.into());
+
+            let session = SharedSession::from_widgets(&widgets);
+            let fragment = session.to_fragment();
+            assert!(
+                fragment
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"=-_".contains(&byte)),
+                "{:?} isn't safe to put in a URL",
+                fragment
+            );
+
+            let mut restored = WidgetState::default();
+            SharedSession::from_fragment(&format!("#{}", fragment))
+                .unwrap()
+                .apply(&mut restored);
+            assert_eq!(SharedSession::from_widgets(&restored), session);
+        }
+
+        let compressed = SharedSession::from_widgets(&WidgetState {
+            input_text: long_input.clone(),
+            ..Default::default()
+        })
+        .to_fragment();
+        assert!(compressed.len() < long_input.len());
+    }
+
+    #[test]
+    fn malformed_links_are_ignored() {
+        for fragment in [
+            "",
+            "#",
+            "#session=",
+            "#session=!!!",
+            "#session=eg",
+            "#other=ajE",
+            "#session=ent9",
+        ] {
+            assert_eq!(
+                SharedSession::from_fragment(fragment),
+                None,
+                "{:?}",
+                fragment
+            );
+        }
+    }
+}
@@ -0,0 +1,110 @@
+/// A commonly needed sub-pattern that can be inserted into the regex
+pub struct Snippet {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The name of the capture group that the pattern is wrapped in when it is inserted
+    pub group_name: &'static str,
+    pub pattern: &'static str,
+    /// Text that the pattern matches, which can be added to the input text to show it matching
+    pub sample: &'static str,
+}
+
+/// The snippets that are offered by the regex editor, in the order that they are listed in
+///
+/// The patterns avoid whitespace and `#` so that they mean the same in verbose mode,
+/// and only match ASCII so that they mean the same in byte mode
+pub const SNIPPETS: &[Snippet] = &[
+    Snippet {
+        name: "Email Address",
+        description: "A simple email address, without quoted or IP address parts",
+        group_name: "email",
+        pattern: r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+        sample: "someone@example.com",
+    },
+    Snippet {
+        name: "URL",
+        description: "An HTTP or HTTPS URL, with an optional port and path",
+        group_name: "url",
+        pattern: r"https?://[A-Za-z0-9.-]+(?::[0-9]{1,5})?(?:/[!-~]*)?",
+        sample: "https://example.com:8080/path?query=1",
+    },
+    Snippet {
+        name: "IPv4 Address",
+        description: "Four numbers from 0 to 255 separated by dots, without leading zeros",
+        group_name: "ipv4",
+        pattern: r"(?:(?:25[0-5]|2[0-4][0-9]|1[0-9]{2}|[1-9]?[0-9])\.){3}(?:25[0-5]|2[0-4][0-9]|1[0-9]{2}|[1-9]?[0-9])",
+        sample: "192.168.0.1",
+    },
+    Snippet {
+        name: "ISO 8601 Date",
+        description: "A date such as 2024-02-29, allowing up to 31 days in any month",
+        group_name: "date",
+        pattern: r"[0-9]{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12][0-9]|3[01])",
+        sample: "2024-02-29",
+    },
+    Snippet {
+        name: "ISO 8601 Time",
+        description: "A 24-hour time such as 23:59, with optional seconds",
+        group_name: "time",
+        pattern: r"(?:[01][0-9]|2[0-3]):[0-5][0-9](?::[0-5][0-9])?",
+        sample: "23:59:30",
+    },
+    Snippet {
+        name: "UUID",
+        description: "Five groups of hexadecimal digits separated by hyphens, in either case",
+        group_name: "uuid",
+        pattern: r"[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}",
+        sample: "123e4567-e89b-12d3-a456-426614174000",
+    },
+];
+
+impl Snippet {
+    /// Returns the pattern wrapped in a capture group named after the snippet,
+    /// numbering the name if it is one of the given names that are already taken
+    pub fn grouped(&self, taken: &[&str]) -> String {
+        let mut name = self.group_name.to_string();
+        let mut number = 1;
+        while taken.contains(&name.as_str()) {
+            number += 1;
+            name = format!("{}_{}", self.group_name, number);
+        }
+        format!("(?P<{}>{})", name, self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::{compile_bytes_regex, compile_regex, RegexFlags};
+
+    #[test]
+    fn snippets_match_their_samples() {
+        let verbose = RegexFlags {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        for snippet in SNIPPETS {
+            let pattern = format!("^{}$", snippet.grouped(&[]));
+            for flags in [Default::default(), verbose] {
+                let (_, regex) = compile_regex(&pattern, flags).unwrap();
+                let (_, bytes_regex) = compile_bytes_regex(&pattern, flags).unwrap();
+                let group = regex
+                    .captures(snippet.sample)
+                    .and_then(|captures| captures.name(snippet.group_name));
+                assert_eq!(
+                    group.map(|group| group.as_str()),
+                    Some(snippet.sample),
+                    "The {} snippet",
+                    snippet.name
+                );
+                assert!(bytes_regex.is_match(snippet.sample.as_bytes()));
+            }
+        }
+    }
+
+    #[test]
+    fn snippets_avoid_taken_group_names() {
+        let grouped = SNIPPETS[0].grouped(&["email", "email_2"]);
+        assert!(grouped.starts_with("(?P<email_3>"), "{}", grouped);
+    }
+}
@@ -1,18 +1,77 @@
-use super::text::{layout_matched_text, layout_regex, MatchedTextLayout, RegexLayout};
+#[cfg(not(target_arch = "wasm32"))]
+use super::capture_worker::CaptureWorker;
+use super::clipboard::ClipboardRead;
+use super::color::CustomGroupColors;
+use super::descriptions::GroupDescriptions;
+use super::engine_info::EngineInfo;
+use super::export::{format_matches, parse_template, TableOptions, TemplateError};
+use super::hir_tree::HirNode;
+use super::journal::Journal;
+use super::library::{LibraryEntry, PatternLibrary};
+use super::lint::{lint_ast, Lint};
+use super::replacement::{expand_replacement, ExpansionPart};
+use super::shape::LineStyle;
+use super::syntax_docs::SyntaxSpans;
+use super::text::{
+    find_captures, find_captures_per_line, find_per_line, format_plain_text, layout_matched_text,
+    layout_matched_text_matches_only, layout_regex, line_ranges, match_lines, CaptureSearch,
+    CaptureTable, MatchedTextLayout, RegexLayout, TextIndex,
+};
+use super::timings::{timed, Timings};
+use super::tutorial::{TutorialState, TUTORIAL_STEPS};
 use super::{
+    ast_tree::{AstNode, AstSpans},
+    escaped_bytes::{escape_bytes, EscapedBytes},
+    explain::ExplainLine,
     loop_vec::LoopVec,
-    parsing::{compile_regex, RegexError},
+    parsing::{
+        ast_contains_newline, ast_is_empty, capture_group_skeleton, compile_bytes_regex,
+        compile_regex, translate_regex, Diagnosis, RegexError, RegexFlags, Suggestion,
+    },
+    verbose::compact_verbose,
+};
+#[cfg(feature = "backend-fancy")]
+use super::{
+    parsing::{capture_group_ast, compile_fancy_regex},
+    text::find_fancy_captures,
 };
 use egui::Style;
 use lazy_static::lazy_static;
-use regex::Regex;
-use regex_syntax::ast::Ast;
-use std::ops::Range;
+use regex::{bytes, Regex};
+use regex_syntax::{ast::Ast, hir::Hir};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    collections::{HashMap, VecDeque},
+    mem::discriminant,
+    ops::Range,
+};
 
 /// State for the application as a whole
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct AppState {
     pub widgets: WidgetState,
+    #[serde(skip)]
     pub logic: LogicResult,
+    #[serde(skip)]
+    pub journal: Journal,
+    /// Snapshots of the input text from before it was replaced, which aren't persisted between sessions
+    #[serde(skip)]
+    pub input_history: InputHistory,
+    /// Edits to the regex or input text that haven't been applied to the logic state yet
+    #[serde(skip)]
+    pub pending_edit: Option<PendingEdit>,
+    /// The value of `WidgetState::match_selection_generation` when the input editor was last scrolled to the selected
+    /// match, so that it is scrolled again once the selection changes, even if it was changed from another panel
+    #[serde(skip)]
+    pub scrolled_selection_generation: u64,
+    /// A byte range of the input text that the input editor should be scrolled to, such as a line clicked in another panel
+    #[serde(skip)]
+    pub input_scroll_target: Option<Range<usize>>,
+    /// The layout of the last regex that compiled, while the current one doesn't, so that capture groups keep their
+    /// colors when the regex is edited by way of patterns that don't compile, such as while a new group is typed out
+    #[serde(skip)]
+    pub last_regex_layout: Option<RegexLayout>,
 }
 
 impl Default for AppState {
@@ -20,16 +79,389 @@ impl Default for AppState {
         Self {
             widgets: Default::default(),
             logic: Ok(Default::default()),
+            journal: Default::default(),
+            input_history: Default::default(),
+            pending_edit: None,
+            scrolled_selection_generation: 0,
+            input_scroll_target: None,
+            last_regex_layout: None,
+        }
+    }
+}
+
+/// The size in bytes of input text above which edits are debounced,
+/// as matching the regex against text that large is too slow to do on every keystroke
+const DEBOUNCE_THRESHOLD: usize = 16 * 1024;
+
+/// How many seconds after the most recent edit that debounced edits are applied
+const DEBOUNCE_DELAY: f64 = 0.15;
+
+/// Edits that are waiting for typing to pause before they are applied to the logic state,
+/// during which the previous matches are kept and the edited text is displayed without highlighting
+#[derive(Clone, Copy)]
+pub struct PendingEdit {
+    /// When the edits should be applied, in seconds as measured by egui
+    pub due: f64,
+    /// Whether the regex text was edited, in which case it has to be recompiled
+    pub regex: bool,
+    /// Whether the input text was edited, in which case it has to be matched again
+    pub input: bool,
+}
+
+/// Returns whether edits should be debounced rather than applied immediately, when matching against the given input text
+pub fn should_debounce(input_text: &str) -> bool {
+    input_text.len() > DEBOUNCE_THRESHOLD
+}
+
+impl PendingEdit {
+    /// Delays applying an edit to the regex or input text until `DEBOUNCE_DELAY` after the given time,
+    /// along with any other edits that are already pending
+    pub fn add(pending: &mut Option<Self>, now: f64, regex: bool, input: bool) {
+        let pending = pending.get_or_insert(Self {
+            due: now,
+            regex: false,
+            input: false,
+        });
+        pending.due = now + DEBOUNCE_DELAY;
+        pending.regex |= regex;
+        pending.input |= input;
+    }
+}
+
+impl AppState {
+    /// Applies the pending edits if they are due at the given time,
+    /// otherwise returns how many seconds are left until they are
+    pub fn apply_pending_edit(&mut self, now: f64, style: &Style) -> Option<f64> {
+        let pending = self.pending_edit?;
+        if pending.due > now {
+            return Some(pending.due - now);
+        }
+
+        if pending.regex {
+            let logic = LogicState::new(
+                &self.widgets.regex_text,
+                style,
+                &self.widgets.regex_text,
+                &self.widgets.input_text,
+                self.widgets.logic_options(LogicState::previous_layout(
+                    &self.logic,
+                    &self.last_regex_layout,
+                )),
+            );
+            LogicState::replace(&mut self.logic, &mut self.last_regex_layout, logic);
+
+            if let Ok(logic) = &self.logic {
+                self.widgets
+                    .group_descriptions
+                    .update_groups(logic.regex.capture_names());
+                if self.widgets.journal_enabled {
+                    self.journal
+                        .record(&self.widgets.regex_text, logic.match_count());
+                }
+                self.widgets.record_pattern();
+            }
+            self.widgets.invalidate_results();
+        } else if pending.input {
+            if let Ok(logic) = &mut self.logic {
+                logic.set_input_text(self.widgets.input_text.clone(), style);
+            }
+            self.widgets.invalidate_results();
+        }
+
+        self.pending_edit = None;
+        None
+    }
+
+    /// Compiles the regex text and lays out the input text from scratch, such as after the widget state is restored
+    /// or the theme is changed, keeping the colors of the capture groups that were in the previous regex
+    pub fn recompile(&mut self, style: &Style) {
+        self.pending_edit = None;
+        let logic = LogicState::new(
+            &self.widgets.regex_text,
+            style,
+            &self.widgets.regex_text,
+            &self.widgets.input_text,
+            self.widgets.logic_options(LogicState::previous_layout(
+                &self.logic,
+                &self.last_regex_layout,
+            )),
+        );
+        LogicState::replace(&mut self.logic, &mut self.last_regex_layout, logic);
+
+        if let Ok(logic) = &self.logic {
+            self.widgets
+                .group_descriptions
+                .update_groups(logic.regex.capture_names());
+        }
+
+        self.widgets.invalidate_results();
+    }
+
+    /// Restores the input text from before it was last replaced by `load_input_text`, if there is a snapshot of it
+    pub fn undo_input_change(&mut self, style: &Style) {
+        if let Some(text) = self.input_history.pop() {
+            self.widgets.input_truncation = None;
+            self.set_input_text(text, style);
+        }
+    }
+
+    /// Replaces the input text, updating all of the state that depends on it
+    pub fn set_input_text(&mut self, text: String, style: &Style) {
+        if let Some(pending) = &mut self.pending_edit {
+            pending.input = false;
+        }
+        if let Ok(logic) = &mut self.logic {
+            logic.set_input_text(text.clone(), style);
+        }
+        self.widgets.input_text = text;
+        self.widgets.invalidate_results();
+    }
+
+    /// Adds the given text to the input text on a line of its own, such that it can be undone
+    pub fn append_input_line(&mut self, line: &str, style: &Style) {
+        let mut text = self.widgets.input_text.clone();
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(line);
+        self.load_input_text(text, InputSource::Snippet, style);
+    }
+
+    /// Changes whether each line of the input text is matched separately, and lays out the input text again accordingly
+    pub fn set_line_mode(&mut self, line_mode: bool, style: &Style) {
+        self.widgets.line_mode = line_mode;
+        if let Ok(logic) = &mut self.logic {
+            logic.line_mode = line_mode;
+            logic.set_input_text(self.widgets.input_text.clone(), style);
+        }
+    }
+
+    /// Switches between matching the input text as UTF-8 text and as raw bytes, converting the input text so that
+    /// it represents the same bytes, and compiles the regex again for the new kind of input
+    pub fn set_byte_mode(&mut self, byte_mode: bool, style: &Style) {
+        if byte_mode == self.widgets.byte_mode {
+            return;
+        }
+
+        self.widgets.byte_mode = byte_mode;
+        self.widgets.clipboard_test = ClipboardTestState::Closed;
+        let text = &self.widgets.input_text;
+        self.widgets.input_text = if byte_mode {
+            escape_bytes(text.as_bytes())
+        } else {
+            String::from_utf8_lossy(&EscapedBytes::unescape(text).bytes).into_owned()
+        };
+        self.recompile(style);
+    }
+
+    /// Sets the flags that apply to the whole regex, and compiles it again with them
+    pub fn set_regex_flags(&mut self, flags: RegexFlags, style: &Style) {
+        self.widgets.regex_flags = flags;
+        self.recompile(style);
+    }
+
+    /// Removes the whitespace and comments that verbose mode ignores from the regex, turns verbose mode off,
+    /// and compiles the regex again
+    pub fn compact_regex(&mut self, style: &Style) {
+        let flags = &mut self.widgets.regex_flags;
+        self.widgets.regex_text =
+            compact_verbose(&self.widgets.regex_text, flags.ignore_whitespace);
+        flags.ignore_whitespace = false;
+        self.recompile(style);
+    }
+
+    /// Switches the regex engine that the pattern is compiled with, and compiles it again
+    #[cfg(feature = "backend-fancy")]
+    pub fn set_regex_engine(&mut self, engine: RegexEngine, style: &Style) {
+        self.widgets.regex_engine = engine;
+        self.widgets.clipboard_test = ClipboardTestState::Closed;
+        self.recompile(style);
+    }
+
+    /// Explains the unsupported syntax that the regex failed to compile because of, if it did,
+    /// leaving out suggestions that can't be applied in the current mode
+    pub fn diagnosis(&self) -> Option<Diagnosis> {
+        let diagnosis = self
+            .logic
+            .as_ref()
+            .err()?
+            .diagnose(&self.widgets.regex_text)?;
+
+        // Bytes can only be matched with the standard engine
+        #[cfg(feature = "backend-fancy")]
+        let diagnosis = Diagnosis {
+            suggestion: diagnosis.suggestion.filter(|suggestion| {
+                !self.widgets.byte_mode || *suggestion != Suggestion::UseFancyEngine
+            }),
+            ..diagnosis
+        };
+        Some(diagnosis)
+    }
+
+    /// Applies a suggested fix for unsupported syntax in the regex, and compiles it again
+    pub fn apply_suggestion(&mut self, suggestion: Suggestion, style: &Style) {
+        match suggestion {
+            Suggestion::Edit {
+                range, replacement, ..
+            } => {
+                self.widgets.regex_text.replace_range(range, replacement);
+                self.recompile(style);
+            }
+            #[cfg(feature = "backend-fancy")]
+            Suggestion::UseFancyEngine => self.set_regex_engine(RegexEngine::Fancy, style),
+        }
+    }
+
+    /// Swaps the regex text with the stashed alternative pattern, and compiles it
+    pub fn swap_stash(&mut self, style: &Style) {
+        let previous_count = self.logic.as_ref().ok().and_then(LogicState::match_count);
+
+        let widgets = &mut self.widgets;
+        std::mem::swap(&mut widgets.regex_text, &mut widgets.stashed_regex);
+        self.recompile(style);
+
+        self.widgets.stash_swap_count = Some(previous_count);
+    }
+
+    /// Selects the next whole match, or the previous one, wrapping around at either end
+    pub fn select_adjacent_match(&mut self, forwards: bool) {
+        let matches = match &mut self.logic {
+            Ok(logic) if !logic.selector.matches.is_empty() => &mut logic.selector.matches,
+            _ => return,
+        };
+
+        if forwards {
+            matches.inc();
+        } else {
+            matches.dec();
+        }
+        self.widgets.match_selection_generation += 1;
+    }
+
+    /// The text of the selected whole match, if there is one
+    pub fn selected_match_text(&self) -> Option<&str> {
+        let selector = &self.logic.as_ref().ok()?.selector;
+        let range = selector.matches.get_current()?.first()?.0.clone()?;
+        selector.text.get(range)
+    }
+
+    /// Replaces the regex text with a pattern from the pattern history, and recompiles it
+    pub fn restore_pattern(&mut self, pattern: String, style: &Style) {
+        self.widgets.regex_text = pattern;
+        self.widgets.stash_swap_count = None;
+        self.recompile(style);
+    }
+
+    /// Saves the regex text, replacement text and input text to the pattern library under the given name,
+    /// replacing any entry that was already saved under it
+    pub fn save_to_library(&mut self, name: String) {
+        let entry = LibraryEntry {
+            pattern: self.widgets.regex_text.clone(),
+            replace_text: self.widgets.replace_text().to_owned(),
+            input: self.widgets.input_text.clone(),
+        };
+        self.widgets.pattern_library.insert(name, entry);
+    }
+
+    /// Replaces the regex text, replacement text and input text with those of the library entry with the given name,
+    /// if there is one, and recompiles the regex
+    pub fn load_from_library(&mut self, name: &str, style: &Style) {
+        let entry = match self.widgets.pattern_library.get(name) {
+            Some(entry) => entry.clone(),
+            None => return,
+        };
+
+        self.widgets.set_replace_text(entry.replace_text);
+        self.widgets.regex_text = entry.pattern;
+        self.widgets.stash_swap_count = None;
+        self.recompile(style);
+        if self.widgets.input_text != entry.input {
+            self.load_input_text(entry.input, InputSource::Library, style);
+        }
+    }
+
+    /// Replaces the input text with the contents of a file from the given source,
+    /// returning whether any of it had to be replaced because it wasn't valid UTF-8
+    ///
+    /// In byte mode invalid bytes are escaped instead, so that they can still be matched
+    pub fn load_input_bytes(&mut self, bytes: Vec<u8>, source: InputSource, style: &Style) -> bool {
+        let (text, lossy) = if self.widgets.byte_mode {
+            (escape_bytes(&bytes), false)
+        } else {
+            match String::from_utf8(bytes) {
+                Ok(text) => (text, false),
+                Err(err) => (String::from_utf8_lossy(err.as_bytes()).into_owned(), true),
+            }
+        };
+        self.load_input_text(text, source, style);
+        lossy
+    }
+
+    /// Replaces the input text with text from the given source,
+    /// truncating it if it is larger than the input size limit
+    pub fn load_input_text(&mut self, mut text: String, source: InputSource, style: &Style) {
+        // Text that was edited in the input editor can already be undone there
+        if !matches!(source, InputSource::Edited | InputSource::Pasted) {
+            self.input_history.push(self.widgets.input_text.clone());
+        }
+
+        let limit = self.widgets.input_size_limit;
+        if text.len() <= limit {
+            self.widgets.input_truncation = None;
+            self.set_input_text(text, style);
+            return;
+        }
+
+        let mut end = limit;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let full_text = text.clone();
+        text.truncate(end);
+        self.widgets.input_truncation = Some(InputTruncation {
+            source,
+            original_bytes: full_text.len(),
+            full_text: Some(full_text),
+            full_match_count: None,
+        });
+        self.set_input_text(text, style);
+    }
+
+    /// Shows the tutorial at the given step, loading the input text that the step works with
+    pub fn begin_tutorial_step(&mut self, step: usize, style: &Style) {
+        let tutorial = &mut self.widgets.tutorial;
+        tutorial.active = true;
+        tutorial.step = step.min(TUTORIAL_STEPS.len() - 1);
+        let input = TUTORIAL_STEPS[tutorial.step].input;
+
+        // The steps are all about matching text
+        self.set_byte_mode(false, style);
+
+        if self.widgets.input_text != input {
+            self.load_input_text(input.into(), InputSource::Tutorial, style);
+        }
+    }
+
+    /// Changes how matches in the input text are highlighted, and lays out the input text again accordingly
+    pub fn set_highlight_mode(&mut self, mode: HighlightMode, style: &Style) {
+        self.widgets.highlight_mode = mode;
+        if let Ok(logic) = &mut self.logic {
+            logic.highlight_mode = mode;
+            logic.set_input_text(self.widgets.input_text.clone(), style);
         }
     }
 }
 
-#[derive(Default, Eq, PartialEq, Copy, Clone)]
+#[derive(Default, Eq, PartialEq, Copy, Clone, Deserialize, Serialize)]
 pub enum TabBarState {
     #[default]
     Collapsed,
     SyntaxGuide,
     Information,
+    Library,
+    Lines,
+    Explain,
 }
 
 impl TabBarState {
@@ -42,125 +474,2863 @@ impl TabBarState {
     }
 }
 
-/// State for egui widgets
-pub struct WidgetState {
-    pub regex_text: String,
-    pub input_text: String,
-    pub replace_text: String,
-    pub result_text: String,
-    pub tab_bar_state: TabBarState,
-    #[cfg(not(target_arch = "wasm32"))]
-    pub about_visible: bool,
+/// Which tree describing the structure of the regex is shown in the information tab
+#[derive(Default, Eq, PartialEq, Copy, Clone, Deserialize, Serialize)]
+pub enum SyntaxTree {
+    /// The AST, which follows the pattern as it is written
+    #[default]
+    Ast,
+    /// The HIR, which is what the regex engine compiles, after classes and flags have been expanded
+    Hir,
 }
 
-impl Default for WidgetState {
+impl SyntaxTree {
+    /// The name of this tree, as displayed in the tree selector
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ast => "AST",
+            Self::Hir => "HIR",
+        }
+    }
+}
+
+/// State for testing the regex against text pasted from the clipboard, without changing the input text
+#[derive(Default)]
+pub enum ClipboardTestState {
+    #[default]
+    Closed,
+    /// Waiting for the clipboard to be read
+    Reading(ClipboardRead),
+    /// Waiting for text to be pasted, as the clipboard couldn't be read for the given reason
+    AwaitingPaste(String),
+    /// The pasted text and the matches that were found in it
+    Tested(MatchesSelector),
+}
+
+/// The modes that the result area can be in, each of which owns its own state,
+/// so that switching to a different mode and back again restores it exactly
+#[derive(Clone, Deserialize, Serialize)]
+pub enum ResultModeState {
+    /// Replace each match in the input text with the replacement text
+    Replace {
+        replace_text: String,
+        /// How many of the matches are replaced
+        #[serde(default)]
+        limit: ReplaceLimit,
+        #[serde(skip)]
+        result: Option<String>,
+        /// The byte range of `result` that each replaced match was replaced with, in order
+        #[serde(skip)]
+        replaced_spans: Vec<Range<usize>>,
+    },
+    /// Split the input text into pieces delimited by each match
+    Split {
+        /// The maximum number of pieces to split the input text into, or 0 for no limit
+        limit: usize,
+        #[serde(skip)]
+        result: Option<Vec<SplitPiece>>,
+        /// The index of the piece that is highlighted in the input text, if any
+        #[serde(skip)]
+        selected: Option<usize>,
+    },
+    /// Format each match according to a template
+    Format {
+        template: String,
+        #[serde(skip)]
+        result: Option<Result<String, TemplateError>>,
+    },
+    /// Step through the matches one at a time, choosing whether to replace each one, like an editor's query-replace
+    StepReplace {
+        replace_text: String,
+        /// The progress through the matches, which starts over whenever it is invalidated
+        #[serde(skip)]
+        steps: Option<ReplaceSteps>,
+    },
+}
+
+impl Default for ResultModeState {
     fn default() -> Self {
-        Self {
-            regex_text: Default::default(),
-            input_text: Default::default(),
+        Self::Replace {
             replace_text: "$0".into(),
-            result_text: Default::default(),
-            tab_bar_state: Default::default(),
-            #[cfg(not(target_arch = "wasm32"))]
-            about_visible: Default::default(),
+            limit: Default::default(),
+            result: None,
+            replaced_spans: vec![],
         }
     }
 }
 
-pub type LogicResult = Result<LogicState, RegexError>;
+impl ResultModeState {
+    /// Returns the default state of each of the available modes, in the order they should be displayed
+    pub fn defaults() -> [Self; 4] {
+        [
+            Self::default(),
+            Self::Split {
+                limit: 0,
+                result: None,
+                selected: None,
+            },
+            Self::Format {
+                template: "{match.index}: {match}\\n".into(),
+                result: None,
+            },
+            Self::StepReplace {
+                replace_text: "$0".into(),
+                steps: None,
+            },
+        ]
+    }
 
-#[derive(Default)]
-pub struct MatchesSelector {
+    /// The name of this mode, as displayed in the mode selector
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Replace { .. } => "Replace",
+            Self::Split { .. } => "Split",
+            Self::Format { .. } => "Format",
+            Self::StepReplace { .. } => "Step Replace",
+        }
+    }
+
+    /// Returns whether this and the given state are of the same mode, regardless of their contents
+    pub fn is_same_mode(&self, other: &Self) -> bool {
+        discriminant(self) == discriminant(other)
+    }
+
+    /// Returns the output of this mode as text, or `None` if it hasn't been computed or failed,
+    /// with the pieces of a split on separate lines
+    pub fn output_text(&self) -> Option<String> {
+        match self {
+            Self::Replace { result, .. } => result.clone(),
+            Self::Split { result, .. } => result.as_ref().map(|pieces| {
+                pieces
+                    .iter()
+                    .map(|piece| piece.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            Self::Format { result, .. } => result.clone().and_then(Result::ok),
+            Self::StepReplace { steps, .. } => steps.as_ref().map(|steps| steps.text.clone()),
+        }
+    }
+
+    /// Discards the output of this mode, so that it will be recomputed when it is next updated
+    pub fn invalidate(&mut self) {
+        match self {
+            Self::Replace { result, .. } => *result = None,
+            Self::Split {
+                result, selected, ..
+            } => {
+                *result = None;
+                *selected = None;
+            }
+            Self::Format { result, .. } => *result = None,
+            Self::StepReplace { steps, .. } => *steps = None,
+        }
+    }
+
+    /// Recomputes the output of this mode from the given logic state and input text, if it has been invalidated
+    ///
+    /// If the pattern is empty, no matching is performed, so the output just mirrors the input text,
+    /// or is empty if the output is made of formatted matches
+    pub fn update(&mut self, logic: &LogicState, input_text: &str) {
+        let regex = &logic.regex;
+        match self {
+            Self::Replace {
+                result: result @ None,
+                replaced_spans,
+                ..
+            } if logic.empty_pattern => {
+                *result = Some(input_text.to_owned());
+                replaced_spans.clear();
+            }
+            Self::Split {
+                result: result @ None,
+                ..
+            } if logic.empty_pattern => {
+                *result = Some(vec![SplitPiece::new(input_text, 0..input_text.len())]);
+            }
+            // The matches in the selector are formatted, so that has to wait until they have all been found
+            Self::Format {
+                template,
+                result: result @ None,
+            } if logic.empty_pattern || logic.match_count().is_some() => {
+                // Errors in the template are reported even if there aren't any matches to format
+                *result = Some(parse_template(template).map(|tokens| {
+                    if logic.empty_pattern {
+                        String::new()
+                    } else {
+                        format_matches(&tokens, logic)
+                    }
+                }));
+            }
+            Self::Replace {
+                replace_text,
+                limit,
+                result: result @ None,
+                replaced_spans,
+            } => {
+                let (output, spans) = replace_matches(
+                    &logic.input_captures(input_text),
+                    regex,
+                    input_text,
+                    replace_text,
+                    limit.max_replacements(),
+                );
+                *result = Some(output);
+                *replaced_spans = spans;
+            }
+            Self::Split {
+                limit,
+                result: result @ None,
+                ..
+            } => {
+                *result = Some(split_pieces(
+                    &logic.input_captures(input_text),
+                    input_text,
+                    *limit,
+                ));
+            }
+            Self::StepReplace {
+                steps: steps @ None,
+                ..
+            } => *steps = Some(ReplaceSteps::new(input_text)),
+            _ => {}
+        }
+    }
+}
+
+/// A piece of the input text produced by the split mode
+#[derive(Clone)]
+pub struct SplitPiece {
+    /// The byte range of the input text that the piece was taken from
+    pub range: Range<usize>,
     pub text: String,
-    pub matches: LoopVec<LoopVec<(Range<usize>, Option<String>)>>,
 }
 
-impl MatchesSelector {
-    pub fn create_from_regex(regex: &Regex, text: String) -> Self {
-        let matches = regex
-            .captures_iter(&text)
-            .map(|captures| {
-                captures
-                    .iter()
-                    .zip(regex.capture_names())
-                    .filter_map(|(r#match, name)| {
-                        r#match.map(|r#match| (r#match.range(), name.map(|name| name.into())))
-                    })
-                    .collect()
-            })
-            .collect();
+impl SplitPiece {
+    /// Creates a piece from the given range of the given input text
+    fn new(input_text: &str, range: Range<usize>) -> Self {
+        Self {
+            text: input_text[range.clone()].to_owned(),
+            range,
+        }
+    }
+}
 
-        Self { text, matches }
+/// Splits the given input text into the pieces between the given matches, like `Regex::splitn`,
+/// into at most `limit` pieces, or without a limit if it is 0
+fn split_pieces(captures: &CaptureTable, input_text: &str, limit: usize) -> Vec<SplitPiece> {
+    let max_matches = match limit {
+        0 => usize::MAX,
+        limit => limit - 1,
+    };
+
+    let mut pieces = Vec::new();
+    let mut end = 0;
+    for whole in captures
+        .iter()
+        .filter_map(|groups| groups.first().cloned().flatten())
+        .take(max_matches)
+    {
+        pieces.push(SplitPiece::new(input_text, end..whole.start));
+        end = whole.end;
     }
+    pieces.push(SplitPiece::new(input_text, end..input_text.len()));
+    pieces
+}
 
-    pub fn current_str(&self) -> Option<&str> {
-        self.text.get(self.current_range()?.clone())
+/// How many of the matches in the input text are replaced by the replace mode
+#[derive(Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ReplaceLimit {
+    /// Replace every match
+    #[default]
+    All,
+    /// Only replace the first match
+    First,
+    /// Only replace the first this many matches
+    FirstN(usize),
+}
+
+impl ReplaceLimit {
+    /// The name of this limit, as displayed in the limit selector
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "All",
+            Self::First => "First",
+            Self::FirstN(_) => "First N",
+        }
     }
 
-    pub fn current_range(&self) -> Option<&Range<usize>> {
-        Some(&self.matches.get_current()?.get_current()?.0)
+    /// The maximum number of matches to replace
+    pub fn max_replacements(self) -> usize {
+        match self {
+            Self::All => usize::MAX,
+            Self::First => 1,
+            Self::FirstN(n) => n,
+        }
     }
 }
 
-/// State for application logic
-pub struct LogicState {
-    pub ast: Ast,
-    pub regex: Regex,
-    pub selector: MatchesSelector,
-    pub regex_layout: RegexLayout,
-    pub input_layout: MatchedTextLayout,
+/// The progress of stepping through the matches in the input text, replacing the ones that are accepted
+/// in a working copy of the input text
+#[derive(Clone, Default)]
+pub struct ReplaceSteps {
+    /// The input text, with each accepted match replaced so far
+    pub text: String,
+    /// The index of the match to decide on next
+    pub index: usize,
+    pub accepted: usize,
+    pub skipped: usize,
+    /// The byte range of `text` that each accepted match was replaced with, in order
+    pub replaced_spans: Vec<Range<usize>>,
+    /// How much longer `text` is than the input text, up to the end of the last match that was decided on
+    delta: isize,
 }
 
-impl Default for LogicState {
-    fn default() -> Self {
-        lazy_static! {
-            static ref EMPTY_REGEX: (Ast, Regex) = compile_regex("").unwrap();
-        };
+impl ReplaceSteps {
+    /// Starts stepping through the matches in the given input text from the first one
+    pub fn new(input_text: &str) -> Self {
         Self {
-            ast: EMPTY_REGEX.0.clone(),
-            regex: EMPTY_REGEX.1.clone(),
-            selector: Default::default(),
-            regex_layout: Default::default(),
-            input_layout: Default::default(),
+            text: input_text.to_owned(),
+            ..Default::default()
         }
     }
+
+    /// Converts an offset into the input text that is after every match decided on so far
+    /// into the corresponding offset into `text`
+    pub fn to_text_offset(&self, offset: usize) -> usize {
+        (offset as isize + self.delta) as usize
+    }
+
+    /// Replaces the match to decide on, which spans the given range of the input text, with the given expansion,
+    /// and moves on to the next match
+    ///
+    /// The matches must be decided on in order, as each replacement shifts the text after it by the difference in length
+    pub fn accept(&mut self, range: Range<usize>, expansion: &str) {
+        let start = self.to_text_offset(range.start);
+        let end = self.to_text_offset(range.end);
+        self.text.replace_range(start..end, expansion);
+        self.replaced_spans.push(start..start + expansion.len());
+        self.delta += expansion.len() as isize - range.len() as isize;
+        self.accepted += 1;
+        self.index += 1;
+    }
+
+    /// Leaves the match to decide on as it is, and moves on to the next match
+    pub fn skip(&mut self) {
+        self.skipped += 1;
+        self.index += 1;
+    }
 }
 
-impl LogicState {
-    /// Compiles the given regular expression pattern and lays out the given text accordingly
-    pub fn new(
-        pattern: &str,
-        style: &Style,
-        regex_text: impl ToString,
-        input_text: impl ToString,
-        previous_state: Option<&Self>,
-    ) -> LogicResult {
-        compile_regex(pattern).map(|(ast, regex)| {
-            let input_text = input_text.to_string();
+/// Replaces at most `max` of the given matches of the given regex in the given text with the given replacement string,
+/// like `Regex::replacen`, but also returns the byte range of the output that each match was replaced with
+fn replace_matches(
+    captures: &CaptureTable,
+    regex: &Regex,
+    text: &str,
+    replacement: &str,
+    max: usize,
+) -> (String, Vec<Range<usize>>) {
+    let mut output = String::with_capacity(text.len());
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for groups in captures.iter().take(max) {
+        // The first group is always the whole match
+        let whole = match groups.first().cloned().flatten() {
+            Some(whole) => whole,
+            None => continue,
+        };
 
-            let selector = MatchesSelector::create_from_regex(&regex, input_text.clone());
+        output.push_str(&text[end..whole.start]);
+        let start = output.len();
+        for part in expand_replacement(replacement, regex, groups, text) {
+            if let ExpansionPart::Text(text) = part {
+                output.push_str(text);
+            }
+        }
+        spans.push(start..output.len());
+        end = whole.end;
+    }
+    output.push_str(&text[end..]);
+    (output, spans)
+}
 
-            let regex_layout = layout_regex(
-                regex_text.to_string(),
-                &ast,
-                style,
-                previous_state.map(|state| &state.regex_layout),
-            );
+/// State for the pattern journal viewer window
+#[derive(Default)]
+pub struct JournalViewerState {
+    pub visible: bool,
+    /// Text to filter the listed entries by
+    pub search: String,
+    /// Whether clearing the journal is awaiting confirmation
+    pub confirm_clear: bool,
+}
 
-            let input_layout = layout_matched_text(
-                input_text,
-                &regex,
-                style,
-                &regex_layout.capture_group_colors,
-            );
+/// State for the pattern library tab
+#[derive(Default)]
+pub struct LibraryTabState {
+    /// The name to save the current pattern under
+    pub name: String,
+    /// Whether saving over an existing entry with the same name is awaiting confirmation
+    pub confirm_overwrite: bool,
+}
 
-            Self {
-                ast,
-                regex,
-                selector,
-                regex_layout,
-                input_layout,
+/// State for the matching lines tab
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LinesTabState {
+    /// Whether the lines that don't match are listed instead of those that do, like `grep -v`
+    pub invert: bool,
+    /// The outcome of the most recent attempt to save the listed lines
+    #[serde(skip)]
+    pub save_status: Option<String>,
+}
+
+/// The maximum number of patterns that are kept in the pattern history
+const MAX_PATTERN_HISTORY: usize = 50;
+
+/// The maximum number of snapshots of the input text that are kept
+const MAX_INPUT_SNAPSHOTS: usize = 20;
+
+/// The maximum combined size of the snapshots of the input text that are kept, in bytes
+const MAX_INPUT_SNAPSHOT_BYTES: usize = 32_000_000;
+
+/// Snapshots of the input text from before it was replaced programmatically, such as by loading a file,
+/// so that those replacements can be undone; ordinary edits can already be undone by the input editor itself
+#[derive(Default)]
+pub struct InputHistory {
+    snapshots: VecDeque<String>,
+    /// Whether a snapshot has been taken since the last time the offer to undo it was shown
+    pub unannounced: bool,
+}
+
+impl InputHistory {
+    /// Takes a snapshot of the given input text, discarding the oldest snapshots if there are too many
+    ///
+    /// Text that is too large to be kept is discarded, along with all earlier snapshots,
+    /// as undoing past it would skip over a change
+    pub fn push(&mut self, text: String) {
+        if text.len() > MAX_INPUT_SNAPSHOT_BYTES {
+            self.snapshots.clear();
+            return;
+        }
+
+        self.snapshots.push_back(text);
+        let mut total_bytes = self.snapshots.iter().map(String::len).sum::<usize>();
+        while self.snapshots.len() > MAX_INPUT_SNAPSHOTS || total_bytes > MAX_INPUT_SNAPSHOT_BYTES {
+            if let Some(oldest) = self.snapshots.pop_front() {
+                total_bytes -= oldest.len();
             }
-        })
+        }
+
+        self.unannounced = true;
+    }
+
+    /// Removes and returns the most recent snapshot, if there is one
+    pub fn pop(&mut self) -> Option<String> {
+        self.unannounced = false;
+        self.snapshots.pop_back()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.snapshots.is_empty()
+    }
+}
+
+/// Where the input text came from
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InputSource {
+    Edited,
+    Pasted,
+    ClipboardTest,
+    DroppedFile,
+    OpenedFile,
+    Tutorial,
+    Library,
+    Snippet,
+}
+
+impl InputSource {
+    /// A description of this source, as displayed in the truncation notice
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Edited => "edited",
+            Self::Pasted => "pasted",
+            Self::ClipboardTest => "loaded from the clipboard test",
+            Self::DroppedFile => "loaded from a dropped file",
+            Self::OpenedFile => "loaded from a file",
+            Self::Tutorial => "loaded by the tutorial",
+            Self::Library => "loaded from the pattern library",
+            Self::Snippet => "added with a snippet",
+        }
+    }
+}
+
+/// Bookkeeping for input text that was cut short because it was larger than the input size limit
+#[derive(Deserialize, Serialize)]
+pub struct InputTruncation {
+    pub source: InputSource,
+    /// The size of the text before it was truncated, in bytes
+    pub original_bytes: usize,
+    /// The text before it was truncated, which isn't persisted as it may be very large
+    #[serde(skip)]
+    pub full_text: Option<String>,
+    /// The number of matches in the text before it was truncated, if they have been counted
+    #[serde(skip)]
+    pub full_match_count: Option<usize>,
+}
+
+/// The smallest zoom factor that the UI can be scaled by
+pub const MIN_ZOOM: f32 = 0.5;
+
+/// The largest zoom factor that the UI can be scaled by
+pub const MAX_ZOOM: f32 = 3.0;
+
+/// How much the zoom factor changes by with each press of a zoom shortcut
+pub const ZOOM_STEP: f32 = 0.1;
+
+/// Preferences for how the UI looks, which are restored before the first frame is shown
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiPrefs {
+    /// Whether the dark theme was last chosen over the light theme,
+    /// or `None` if the theme hasn't been toggled, in which case the system theme is used
+    pub dark_mode: Option<bool>,
+    /// The factor that the UI is scaled by, on top of the scale of the display
+    zoom: f32,
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            dark_mode: None,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl UiPrefs {
+    /// The factor that the UI is scaled by, which is always within `MIN_ZOOM..=MAX_ZOOM`,
+    /// even if a bad value was restored from the last session
+    pub fn zoom(&self) -> f32 {
+        if self.zoom.is_finite() {
+            self.zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+        } else {
+            1.0
+        }
+    }
+
+    /// Sets the factor that the UI is scaled by, clamping it to `MIN_ZOOM..=MAX_ZOOM`
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+        self.zoom = self.zoom();
+    }
+}
+
+/// State for the inspector panel
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct InspectorState {
+    pub visible: bool,
+    /// The width of the inspector panel, restored when it is shown again
+    pub width: f32,
+    /// Whether each section of the inspector is expanded
+    pub regex_open: bool,
+    pub lints_open: bool,
+    pub matches_open: bool,
+    pub match_list_open: bool,
+    pub statistics_open: bool,
+    pub unique_values_open: bool,
+    pub descriptions_open: bool,
+    /// The capture group whose unique values are listed, where 0 is the whole match
+    pub unique_values_group: usize,
+    pub unique_values_order: ValueOrder,
+    /// The outcome of the most recent attempt to save the unique values to a file
+    #[serde(skip)]
+    pub unique_values_status: Option<String>,
+}
+
+impl Default for InspectorState {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            width: 200.0,
+            regex_open: true,
+            lints_open: false,
+            matches_open: true,
+            match_list_open: false,
+            statistics_open: false,
+            unique_values_open: false,
+            descriptions_open: false,
+            unique_values_group: 0,
+            unique_values_order: Default::default(),
+            unique_values_status: None,
+        }
+    }
+}
+
+/// The order that the unique values of a capture group are listed in
+#[derive(Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ValueOrder {
+    /// The most frequent values first
+    #[default]
+    Count,
+    Alphabetical,
+}
+
+impl ValueOrder {
+    /// The name of this order, as displayed in the order selector
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Count => "By Count",
+            Self::Alphabetical => "Alphabetical",
+        }
+    }
+}
+
+/// The distinct values that a capture group matched, each with how many matches it had that value in
+pub struct UniqueValues {
+    /// The index of the capture group, where 0 is the whole match
+    pub group: usize,
+    pub values: Vec<(String, usize)>,
+    /// How many matches the group didn't participate in
+    pub missing: usize,
+    order: ValueOrder,
+}
+
+impl UniqueValues {
+    /// Counts the given values of the given capture group, one for each match, in the given order
+    pub fn count<'a>(
+        group: usize,
+        values: impl IntoIterator<Item = Option<&'a str>>,
+        order: ValueOrder,
+    ) -> Self {
+        let mut counts = HashMap::<String, usize>::new();
+        let mut missing = 0;
+        for value in values {
+            match value {
+                Some(value) => *counts.entry(value.to_owned()).or_default() += 1,
+                None => missing += 1,
+            }
+        }
+
+        let mut values = Self {
+            group,
+            values: counts.into_iter().collect(),
+            missing,
+            order,
+        };
+        values.sort_values();
+        values
+    }
+
+    /// Lists the values in the given order, if they aren't already
+    pub fn sort(&mut self, order: ValueOrder) {
+        if self.order != order {
+            self.order = order;
+            self.sort_values();
+        }
+    }
+
+    fn sort_values(&mut self) {
+        match self.order {
+            ValueOrder::Count => self
+                .values
+                .sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b))),
+            ValueOrder::Alphabetical => self.values.sort_unstable_by(|(a, _), (b, _)| a.cmp(b)),
+        }
+    }
+}
+
+/// The size in bytes of input text above which it isn't persisted,
+/// as crash snapshots of the app state are saved every few seconds, and copying text that large each time is slow
+const MAX_PERSISTED_INPUT_BYTES: usize = 1024 * 1024;
+
+/// Serializes the input text, or an empty string in its place if it is larger than `MAX_PERSISTED_INPUT_BYTES`
+fn serialize_input_text<S: Serializer>(text: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if text.len() > MAX_PERSISTED_INPUT_BYTES {
+        serializer.serialize_str("")
+    } else {
+        serializer.serialize_str(text)
+    }
+}
+
+/// State for egui widgets
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct WidgetState {
+    pub regex_text: String,
+    /// An alternative pattern that can be swapped with the regex text, for comparing the two
+    pub stashed_regex: String,
+    /// The number of matches there were before the regex text was last swapped with the stashed pattern,
+    /// if it hasn't been edited since then
+    #[serde(skip)]
+    pub stash_swap_count: Option<Option<usize>>,
+    #[serde(serialize_with = "serialize_input_text")]
+    pub input_text: String,
+    /// The size in bytes above which input text is truncated when it is loaded
+    pub input_size_limit: usize,
+    /// Information about how the input text was truncated, if it was
+    pub input_truncation: Option<InputTruncation>,
+    /// The state of the currently active result mode
+    pub result_mode: ResultModeState,
+    /// The states of the result modes that have previously been active, to be restored when switching back to them
+    pub inactive_result_modes: Vec<ResultModeState>,
+    pub highlight_mode: HighlightMode,
+    /// Whether each line of the input text is matched separately
+    pub line_mode: bool,
+    /// Whether the input text is matched as raw bytes, with bytes that aren't valid UTF-8 written as `\xNN`
+    pub byte_mode: bool,
+    /// Flags that apply to the whole regex, without being written into the pattern
+    pub regex_flags: RegexFlags,
+    pub regex_engine: RegexEngine,
+    pub line_style: LineStyle,
+    /// Whether connecting lines are labelled with the name or index of their capture group
+    pub line_labels_visible: bool,
+    /// Whether the capture groups in the input text are shown as stacked underlines, one row per level of nesting,
+    /// instead of being filled in with their colors
+    pub layered_highlighting: bool,
+    pub group_descriptions: GroupDescriptions,
+    pub tab_bar_state: TabBarState,
+    /// Which tree describing the structure of the regex is shown in the information tab
+    pub syntax_tree: SyntaxTree,
+    /// The sections shown in the editor, in the order they are shown in; sections not in this list are hidden
+    pub sections: Vec<SectionId>,
+    /// The width of the tab bar contents panel when it is fully open
+    pub tab_bar_width: f32,
+    pub inspector: InspectorState,
+    pub ui_prefs: UiPrefs,
+    #[serde(skip)]
+    pub clipboard_test: ClipboardTestState,
+    /// Incremented whenever the match selected in the inspector changes, to trigger the emphasis animation
+    #[serde(skip)]
+    pub match_selection_generation: u64,
+    /// Whether the window with the details of the error in the regex is open
+    #[serde(skip)]
+    pub error_details_visible: bool,
+    /// Whether the window with the regex laid out in verbose mode is open
+    #[serde(skip)]
+    pub verbose_format_visible: bool,
+    /// The name to give to a named group when wrapping part of the regex in one
+    #[serde(skip)]
+    pub wrap_group_name: String,
+    /// The name to give to an existing capture group when naming it
+    #[serde(skip)]
+    pub group_name: String,
+    /// Whether the note explaining how the whole match is used in the replacement has been dismissed
+    #[serde(skip)]
+    pub whole_match_note_dismissed: bool,
+    /// Whether the replacement string syntax should be revealed the next time the syntax guide is shown
+    #[serde(skip)]
+    pub reveal_replacement_syntax: bool,
+    /// How matches are formatted when they are copied as a table
+    pub table_options: TableOptions,
+    /// The outcome of the most recent attempt to save the formatted matches to a file
+    #[serde(skip)]
+    pub format_save_status: Option<String>,
+    /// The outcome of the most recent attempt to export the matches to a file
+    #[serde(skip)]
+    pub export_status: Option<String>,
+    /// The number of capture groups above which the regex is visualised in a degraded form
+    pub degraded_group_threshold: usize,
+    /// Whether the regex is visualised in full even if it has more capture groups than `degraded_group_threshold`
+    #[serde(skip)]
+    pub force_full_rendering: bool,
+    /// The number of matches in the input text above which only the first this many are highlighted
+    pub highlight_match_limit: usize,
+    /// Whether every successfully compiled pattern is recorded in the pattern journal
+    pub journal_enabled: bool,
+    #[serde(skip)]
+    pub journal_viewer: JournalViewerState,
+    /// The colors chosen for capture groups in place of the generated ones
+    pub custom_colors: CustomGroupColors,
+    #[serde(skip)]
+    pub settings_visible: bool,
+    /// Whether the next text pasted into the regex editor is escaped, so that it is matched literally
+    #[serde(skip)]
+    pub escape_next_paste: bool,
+    /// Whether the regex editor spans multiple lines and wraps long lines, rather than being a single line that scrolls
+    pub multiline_regex: bool,
+    /// Whether inserting a snippet into the regex also adds its sample to the input text
+    pub append_snippet_sample: bool,
+    /// The most recent patterns that compiled successfully, oldest first, which can be switched back to
+    pub pattern_history: Vec<String>,
+    pub pattern_library: PatternLibrary,
+    #[serde(skip)]
+    pub library_tab: LibraryTabState,
+    pub lines_tab: LinesTabState,
+    /// Progress through the tutorial, which is persisted so that it can be picked up again later
+    pub tutorial: TutorialState,
+    /// Whether animations should be replaced with static alternatives
+    pub reduce_motion: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub about_visible: bool,
+    /// A file that was chosen to be opened as the input text, along with its size in bytes,
+    /// which is awaiting confirmation because it is large enough that highlighting it may be slow
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub pending_input_file: Option<(std::path::PathBuf, u64)>,
+    /// The outcome of the most recent attempt to open or save a file from the file menu
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub file_status: Option<String>,
+}
+
+impl Default for WidgetState {
+    fn default() -> Self {
+        Self {
+            regex_text: Default::default(),
+            stashed_regex: Default::default(),
+            stash_swap_count: Default::default(),
+            input_text: Default::default(),
+            input_size_limit: 10_000_000,
+            input_truncation: Default::default(),
+            result_mode: Default::default(),
+            inactive_result_modes: Default::default(),
+            highlight_mode: Default::default(),
+            line_mode: Default::default(),
+            byte_mode: Default::default(),
+            regex_flags: Default::default(),
+            regex_engine: Default::default(),
+            line_style: Default::default(),
+            line_labels_visible: true,
+            layered_highlighting: Default::default(),
+            group_descriptions: Default::default(),
+            tab_bar_state: Default::default(),
+            syntax_tree: Default::default(),
+            sections: SectionId::ALL.to_vec(),
+            tab_bar_width: 280.0,
+            inspector: Default::default(),
+            ui_prefs: Default::default(),
+            clipboard_test: Default::default(),
+            match_selection_generation: Default::default(),
+            error_details_visible: Default::default(),
+            verbose_format_visible: Default::default(),
+            wrap_group_name: Default::default(),
+            group_name: Default::default(),
+            whole_match_note_dismissed: Default::default(),
+            table_options: Default::default(),
+            format_save_status: Default::default(),
+            export_status: Default::default(),
+            reveal_replacement_syntax: Default::default(),
+            degraded_group_threshold: 32,
+            force_full_rendering: Default::default(),
+            highlight_match_limit: 2_000,
+            journal_enabled: Default::default(),
+            journal_viewer: Default::default(),
+            custom_colors: Default::default(),
+            settings_visible: Default::default(),
+            escape_next_paste: Default::default(),
+            multiline_regex: Default::default(),
+            append_snippet_sample: true,
+            pattern_history: Default::default(),
+            pattern_library: Default::default(),
+            library_tab: Default::default(),
+            lines_tab: Default::default(),
+            tutorial: Default::default(),
+            reduce_motion: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            about_visible: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_input_file: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            file_status: Default::default(),
+        }
+    }
+}
+
+impl WidgetState {
+    /// Adds the regex text to the pattern history, which should only be done if it compiled successfully,
+    /// unless it is the same as the most recent pattern, discarding the oldest patterns beyond `MAX_PATTERN_HISTORY`
+    pub fn record_pattern(&mut self) {
+        if self.pattern_history.last() == Some(&self.regex_text) {
+            return;
+        }
+
+        self.pattern_history.push(self.regex_text.clone());
+        if self.pattern_history.len() > MAX_PATTERN_HISTORY {
+            self.pattern_history
+                .drain(..self.pattern_history.len() - MAX_PATTERN_HISTORY);
+        }
+    }
+
+    /// Returns the number of capture groups above which the regex should be visualised in a degraded form,
+    /// or `None` if it should always be visualised in full
+    pub fn group_threshold(&self) -> Option<usize> {
+        (!self.force_full_rendering).then_some(self.degraded_group_threshold)
+    }
+
+    /// Returns the options for compiling the regex and matching the input text of this state,
+    /// keeping the colors of the capture groups in the given layout of the previous regex
+    pub fn logic_options<'a>(&self, previous_layout: Option<&'a RegexLayout>) -> LogicOptions<'a> {
+        LogicOptions {
+            highlight_mode: self.highlight_mode,
+            line_mode: self.line_mode,
+            byte_mode: self.byte_mode,
+            engine: self.regex_engine,
+            flags: self.regex_flags,
+            group_threshold: self.group_threshold(),
+            match_limit: Some(self.highlight_match_limit),
+            custom_colors: self.custom_colors.clone(),
+            previous_layout,
+        }
+    }
+
+    /// Returns why the result modes can't be used, if they can't,
+    /// which is whenever the input text isn't matched with the standard engine
+    pub fn results_unavailable(&self) -> Option<&'static str> {
+        if self.byte_mode {
+            Some("Results aren't available in byte mode")
+        } else if cfg!(feature = "backend-fancy") && self.regex_engine == RegexEngine::Fancy {
+            Some("Results aren't available with the fancy-regex engine")
+        } else {
+            None
+        }
+    }
+
+    /// Switches to the same mode as the given state,
+    /// restoring the previous state of that mode if there is one, or using the given state otherwise
+    pub fn switch_result_mode(&mut self, mode: ResultModeState) {
+        if self.result_mode.is_same_mode(&mode) {
+            return;
+        }
+
+        let new_mode = match self
+            .inactive_result_modes
+            .iter()
+            .position(|inactive| inactive.is_same_mode(&mode))
+        {
+            Some(index) => self.inactive_result_modes.swap_remove(index),
+            None => mode,
+        };
+
+        let old_mode = std::mem::replace(&mut self.result_mode, new_mode);
+        self.inactive_result_modes.push(old_mode);
+    }
+
+    /// Returns the replacement text of the replace mode or the step replace mode, whether or not it is the active mode,
+    /// preferring the active mode if it is either of them
+    pub fn replace_text(&self) -> &str {
+        self.replace_texts().next().unwrap_or("$0")
+    }
+
+    /// Returns the replacement text of each result mode that has one, whether or not it is the active mode,
+    /// starting with the active mode
+    pub fn replace_texts(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&self.result_mode)
+            .chain(&self.inactive_result_modes)
+            .filter_map(|mode| match mode {
+                ResultModeState::Replace { replace_text, .. }
+                | ResultModeState::StepReplace { replace_text, .. } => Some(replace_text.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Like `replace_texts`, but returns mutable references
+    pub fn replace_texts_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        std::iter::once(&mut self.result_mode)
+            .chain(&mut self.inactive_result_modes)
+            .filter_map(|mode| match mode {
+                ResultModeState::Replace { replace_text, .. }
+                | ResultModeState::StepReplace { replace_text, .. } => Some(replace_text),
+                _ => None,
+            })
+    }
+
+    /// Switches to the replace mode and replaces its replacement text
+    pub fn set_replace_text(&mut self, text: String) {
+        self.switch_result_mode(ResultModeState::default());
+        if let ResultModeState::Replace { replace_text, .. } = &mut self.result_mode {
+            *replace_text = text;
+        }
+        self.result_mode.invalidate();
+    }
+
+    /// Discards the output of every result mode, such as when the regex or input text changes
+    pub fn invalidate_results(&mut self) {
+        self.result_mode.invalidate();
+        for mode in &mut self.inactive_result_modes {
+            mode.invalidate();
+        }
+    }
+}
+
+pub type LogicResult = Result<LogicState, RegexError>;
+
+/// Input text and capture group combinations larger than this (In bytes times capture groups)
+/// are highlighted as whole matches only when using `HighlightMode::Auto`
+const AUTO_MATCHES_ONLY_THRESHOLD: usize = 4 * 1024 * 1024;
+
+/// The sections of the editor, which can be reordered and hidden
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum SectionId {
+    Regex,
+    Input,
+    ResultMode,
+    Result,
+}
+
+impl SectionId {
+    /// Every section, in the order they are shown in by default
+    pub const ALL: [Self; 4] = [Self::Regex, Self::Input, Self::ResultMode, Self::Result];
+
+    /// The name of this section, as displayed in the layout menu
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Regex => "Regular Expression",
+            Self::Input => "Input Text",
+            Self::ResultMode => "Replace / Split",
+            Self::Result => "Result Text",
+        }
+    }
+
+    /// Returns whether this section expands to fill the vertical space available to it
+    pub fn expands(self) -> bool {
+        matches!(self, Self::Input | Self::Result)
+    }
+}
+
+/// How matches in the input text should be highlighted
+#[derive(Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum HighlightMode {
+    /// Only highlight whole matches if the input text is large enough that extracting capture groups would be slow
+    #[default]
+    Auto,
+    /// Highlight every capture group in every match
+    Captures,
+    /// Only highlight whole matches, and only extract capture groups for the currently selected match
+    MatchesOnly,
+}
+
+impl HighlightMode {
+    /// The name of this mode, as displayed in the mode selector
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Captures => "Capture Groups",
+            Self::MatchesOnly => "Matches Only",
+        }
+    }
+
+    /// Returns whether only whole matches should be highlighted when using the given regex on the given text
+    pub fn matches_only(self, regex: &Regex, text: &str) -> bool {
+        match self {
+            Self::Auto => {
+                regex.captures_len() > 1
+                    && text.len().saturating_mul(regex.captures_len()) > AUTO_MATCHES_ONLY_THRESHOLD
+            }
+            Self::Captures => false,
+            Self::MatchesOnly => true,
+        }
+    }
+}
+
+/// The regex engine that the pattern is compiled with, unless the input text is matched as bytes
+#[derive(Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RegexEngine {
+    /// The `regex` crate, which always matches in linear time
+    #[default]
+    Standard,
+    /// fancy-regex, which supports lookaround and backreferences by backtracking,
+    /// and which is only available with the `backend-fancy` feature
+    Fancy,
+}
+
+impl RegexEngine {
+    /// The name of this engine, as displayed in the engine selector
+    #[cfg(feature = "backend-fancy")]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "regex",
+            Self::Fancy => "fancy-regex",
+        }
+    }
+}
+
+/// Every capture group of a match, in the same order as in the regex, each with the range of the text that it matched
+/// (Or `None` if it didn't participate in the match) and its name if it has one
+pub type MatchGroups = LoopVec<(Option<Range<usize>>, Option<String>)>;
+
+#[derive(Default)]
+pub struct MatchesSelector {
+    pub text: String,
+    pub matches: LoopVec<MatchGroups>,
+    /// If capture groups are extracted lazily, whether they have been extracted yet for each match
+    filled_captures: Option<Vec<bool>>,
+}
+
+impl MatchesSelector {
+    /// Creates a selector over the matches in the given capture table, which must have been found in the given text
+    pub fn create_from_captures(captures: &CaptureTable, regex: &Regex, text: String) -> Self {
+        let matches = captures
+            .iter()
+            .map(|groups| named_groups(groups, regex))
+            .collect();
+
+        Self {
+            text,
+            matches,
+            filled_captures: None,
+        }
+    }
+
+    /// Adds the matches in the given rows of a capture table, which must have been found in the text of this selector
+    /// after every match that is already in it, without changing which match is selected
+    pub fn extend_from_captures(&mut self, captures: &[Vec<Option<Range<usize>>>], regex: &Regex) {
+        self.matches
+            .extend(captures.iter().map(|groups| named_groups(groups, regex)));
+    }
+
+    /// Creates a selector from the ranges of only the whole matches in the given text, deferring the extraction of
+    /// capture groups until `fill_current_captures` is called for each match
+    pub fn create_matches_only(match_ranges: &[Range<usize>], text: String) -> Self {
+        let matches = match_ranges
+            .iter()
+            .map(|range| std::iter::once((Some(range.clone()), None)).collect())
+            .collect::<LoopVec<_>>();
+
+        Self {
+            filled_captures: Some(vec![false; matches.len()]),
+            text,
+            matches,
+        }
+    }
+
+    /// Extracts the capture groups of the current match, if that was deferred and hasn't been done yet
+    pub fn fill_current_captures(&mut self, regex: &Regex) {
+        let index = self.matches.index();
+        let filled = match &mut self.filled_captures {
+            Some(filled) if filled.get(index) == Some(&false) => filled,
+            _ => return,
+        };
+        filled[index] = true;
+
+        let start = match self.matches.get_current().and_then(|groups| groups.first()) {
+            Some((Some(range), _)) => range.start,
+            _ => return,
+        };
+
+        // Searching from the start of the match will find the same match again, as it is the leftmost-first match there
+        let mut locations = regex.capture_locations();
+        if regex
+            .captures_read_at(&mut locations, &self.text, start)
+            .is_some()
+        {
+            let groups = (0..locations.len())
+                .map(|index| locations.get(index).map(|(start, end)| start..end))
+                .collect::<Vec<_>>();
+
+            *self.matches.get_current_mut().unwrap() = named_groups(&groups, regex);
+        }
+    }
+
+    /// Returns the text matched by the selected capture group of the selected match,
+    /// or `None` if that group didn't participate in the match
+    pub fn current_str(&self) -> Option<&str> {
+        self.text.get(self.current_range()?.clone())
+    }
+
+    /// Returns the range of the text matched by the selected capture group of the selected match,
+    /// or `None` if that group didn't participate in the match
+    pub fn current_range(&self) -> Option<&Range<usize>> {
+        self.matches.get_current()?.get_current()?.0.as_ref()
+    }
+}
+
+/// Pairs each capture group in a row of a capture table with its name, including the groups that didn't participate,
+/// so that the position of each group is the same as its index in the regex
+fn named_groups(groups: &[Option<Range<usize>>], regex: &Regex) -> MatchGroups {
+    groups
+        .iter()
+        .zip(regex.capture_names())
+        .map(|(range, name)| (range.clone(), name.map(|name| name.into())))
+        .collect()
+}
+
+/// Statistics about the matches in the input text, which are gathered as the selector is built
+/// rather than by searching the input text again
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchStats {
+    pub match_count: usize,
+    /// The total length of every match
+    pub total_length: usize,
+    /// The length of the shortest match, or 0 if there are no matches
+    pub min_length: usize,
+    pub max_length: usize,
+    /// How many matches each capture group participated in, excluding the implicit group of the whole match,
+    /// or `None` if capture groups weren't extracted because only whole matches are highlighted
+    pub group_participation: Option<Vec<usize>>,
+    /// Whether lengths are measured in bytes rather than chars, as they are when the input text is matched as bytes
+    pub in_bytes: bool,
+}
+
+impl MatchStats {
+    /// Creates statistics for no matches of a regex with the given number of capture groups,
+    /// including the implicit group of the whole match
+    pub fn new(captures_len: usize, in_bytes: bool) -> Self {
+        Self {
+            group_participation: Some(vec![0; captures_len.saturating_sub(1)]),
+            in_bytes,
+            ..Default::default()
+        }
+    }
+
+    /// Creates statistics for the given ranges of whole matches in the given text, without any capture groups
+    pub fn from_match_ranges(match_ranges: &[Range<usize>], text: &str) -> Self {
+        let mut stats = Self::default();
+        for range in match_ranges {
+            stats.add_match(text, range);
+        }
+        stats
+    }
+
+    /// Adds the matches in the given rows of a capture table, which must have been found in the given text
+    pub fn add_captures(&mut self, captures: &[Vec<Option<Range<usize>>>], text: &str) {
+        for groups in captures {
+            if let Some(Some(range)) = groups.first() {
+                self.add_match(text, range);
+            }
+
+            if let Some(participation) = &mut self.group_participation {
+                for (count, range) in participation.iter_mut().zip(groups.iter().skip(1)) {
+                    *count += usize::from(range.is_some());
+                }
+            }
+        }
+    }
+
+    fn add_match(&mut self, text: &str, range: &Range<usize>) {
+        let matched = &text[range.clone()];
+        let length = if self.in_bytes {
+            EscapedBytes::unescape(matched).bytes.len()
+        } else {
+            matched.chars().count()
+        };
+
+        self.min_length = if self.match_count == 0 {
+            length
+        } else {
+            self.min_length.min(length)
+        };
+        self.max_length = self.max_length.max(length);
+        self.total_length += length;
+        self.match_count += 1;
+    }
+
+    /// Returns the mean length of the matches, or `None` if there are no matches
+    pub fn mean_length(&self) -> Option<f64> {
+        (self.match_count > 0).then(|| self.total_length as f64 / self.match_count as f64)
+    }
+}
+
+/// How many matches in the input text are searched for captures each frame
+const CAPTURES_PER_FRAME: usize = 1000;
+
+/// Input text longer than this many bytes is not laid out until the frame after it changes,
+/// so that whether the regex matched it at all can be shown before the expensive layout is done
+const DEFERRED_LAYOUT_THRESHOLD: usize = 64 * 1024;
+
+/// State for application logic
+pub struct LogicState {
+    pub ast: Ast,
+    /// The high-level intermediate representation of the regex, translated from `ast`,
+    /// or `None` if the AST isn't of the whole pattern, as with fancy-regex patterns that `regex-syntax` can't parse
+    pub hir: Option<Hir>,
+    pub regex: Regex,
+    pub selector: MatchesSelector,
+    pub regex_layout: RegexLayout,
+    pub input_layout: MatchedTextLayout,
+    /// Statistics about the matches in `selector`
+    pub match_stats: MatchStats,
+    /// A tree describing the structure of the regex, which is only built once it is first needed
+    pub ast_tree: Option<AstNode>,
+    /// A tree describing the HIR of the regex, which is only built once it is first needed
+    pub hir_tree: Option<HirNode>,
+    /// Facts about how the regex engine sees the regex, which are only found once they are first needed
+    pub engine_info: Option<EngineInfo>,
+    /// A plain-English explanation of the regex, which is only written once it is first needed
+    pub explanation: Option<Vec<ExplainLine>>,
+    /// The byte range of each line of the input text along with whether the regex matches it,
+    /// which are only found once they are first needed
+    pub line_matches: Option<Vec<(Range<usize>, bool)>>,
+    /// The distinct values of a capture group, which are only counted once they are first needed
+    pub unique_values: Option<UniqueValues>,
+    /// The span of the pattern that is selected in the AST tree, if any
+    pub selected_ast_span: Option<Range<usize>>,
+    /// The span of the pattern that the pointer is over in the AST tree this frame, if any
+    pub hovered_ast_span: Option<Range<usize>>,
+    pub syntax_spans: SyntaxSpans,
+    /// The span and description of each node of the AST, for explaining the part of the pattern under the pointer
+    pub ast_spans: AstSpans,
+    /// The parts of the pattern that could make matching slow
+    pub lints: Vec<Lint>,
+    /// How long compiling the regex, and matching and laying out the input text, took
+    pub timings: Timings,
+    /// How matches in the input text should be highlighted
+    pub highlight_mode: HighlightMode,
+    /// Whether only whole matches in the input text are highlighted, as decided by `highlight_mode`
+    pub matches_only: bool,
+    /// Whether each line of the input text is matched separately
+    pub line_mode: bool,
+    /// Whether the pattern contains a newline, which can never be matched when each line is matched separately
+    pub newline_in_pattern: bool,
+    /// Whether the pattern is empty (or only sets flags), in which case no matching is performed
+    pub empty_pattern: bool,
+    /// Whether the regex matches the input text at all
+    pub is_match: bool,
+    /// Whether the regex matches the input text, but the matches have not been laid out yet
+    pub layout_pending: bool,
+    /// The number of matches in the input text above which only the first this many are found and highlighted,
+    /// or `None` if every match should be
+    pub match_limit: Option<usize>,
+    /// The colors chosen for capture groups in place of the generated ones
+    pub custom_colors: CustomGroupColors,
+    /// The total number of matches in the input text, if there are more than `match_limit`
+    pub truncated_match_count: Option<usize>,
+    /// The search for captures in the input text, if it is spread over several frames and hasn't finished yet
+    pub capture_search: Option<CaptureSearch>,
+    /// The thread that continues the search for captures after its first step, so that it doesn't stall the UI (Native)
+    #[cfg(not(target_arch = "wasm32"))]
+    capture_worker: Option<CaptureWorker>,
+    /// The regex compiled for matching bytes and the bytes of the input text, if the input text is matched as bytes,
+    /// in which case `regex` only has the same capture groups as the pattern, and can't be used for matching
+    pub byte_matching: Option<ByteMatching>,
+    /// The regex compiled with fancy-regex, if the pattern is compiled with that engine,
+    /// in which case `regex` only has the same capture groups as the pattern, and can't be used for matching
+    #[cfg(feature = "backend-fancy")]
+    pub fancy_matching: Option<FancyMatching>,
+}
+
+/// How `LogicState::new` compiles the regex and matches the input text, which all default to how a new session does
+#[derive(Default)]
+pub struct LogicOptions<'a> {
+    pub highlight_mode: HighlightMode,
+    /// Whether each line of the input text is matched separately
+    pub line_mode: bool,
+    /// Whether the input text is matched as the bytes that it is written with
+    pub byte_mode: bool,
+    pub engine: RegexEngine,
+    pub flags: RegexFlags,
+    /// The number of capture groups above which the regex is visualised in a degraded form,
+    /// or `None` if it should always be visualised in full
+    pub group_threshold: Option<usize>,
+    /// The number of matches above which only the first this many are found and highlighted, if any
+    pub match_limit: Option<usize>,
+    /// The colors chosen for capture groups in place of the generated ones
+    pub custom_colors: CustomGroupColors,
+    /// The layout of the previous regex, whose capture groups keep their colors if they are still in the new one
+    pub previous_layout: Option<&'a RegexLayout>,
+}
+
+/// The state needed to match the input text as raw bytes
+pub struct ByteMatching {
+    pub regex: bytes::Regex,
+    /// The bytes that are written in the input text
+    pub input: EscapedBytes,
+}
+
+/// The state needed to match the input text with fancy-regex
+#[cfg(feature = "backend-fancy")]
+pub struct FancyMatching {
+    pub regex: fancy_regex::Regex,
+    /// Whether `regex-syntax` couldn't parse the pattern, such as because it has lookaround,
+    /// in which case the AST only has the pattern's groups, so the rest of it isn't highlighted
+    pub partial_ast: bool,
+}
+
+impl Default for LogicState {
+    fn default() -> Self {
+        lazy_static! {
+            static ref EMPTY_REGEX: (Ast, Regex) = compile_regex("", Default::default()).unwrap();
+        };
+        Self {
+            ast: EMPTY_REGEX.0.clone(),
+            hir: Some(Hir::empty()),
+            regex: EMPTY_REGEX.1.clone(),
+            selector: Default::default(),
+            regex_layout: Default::default(),
+            input_layout: Default::default(),
+            match_stats: Default::default(),
+            ast_tree: None,
+            hir_tree: None,
+            engine_info: None,
+            explanation: None,
+            line_matches: None,
+            unique_values: None,
+            selected_ast_span: None,
+            hovered_ast_span: None,
+            syntax_spans: Default::default(),
+            ast_spans: Default::default(),
+            lints: Vec::new(),
+            timings: Default::default(),
+            highlight_mode: Default::default(),
+            matches_only: false,
+            line_mode: false,
+            newline_in_pattern: false,
+            empty_pattern: true,
+            is_match: true,
+            layout_pending: false,
+            match_limit: None,
+            custom_colors: Default::default(),
+            truncated_match_count: None,
+            capture_search: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            capture_worker: None,
+            byte_matching: None,
+            #[cfg(feature = "backend-fancy")]
+            fancy_matching: None,
+        }
+    }
+}
+
+impl LogicState {
+    /// Returns the layout of the regex of the given logic state if it compiled,
+    /// otherwise that of the last regex that did
+    pub fn previous_layout<'a>(
+        logic: &'a LogicResult,
+        last_regex_layout: &'a Option<RegexLayout>,
+    ) -> Option<&'a RegexLayout> {
+        match logic {
+            Ok(logic) => Some(&logic.regex_layout),
+            Err(_) => last_regex_layout.as_ref(),
+        }
+    }
+
+    /// Replaces the given logic state with that of a newly compiled regex,
+    /// keeping the layout of the old regex in `last_regex_layout` if the new one didn't compile
+    pub fn replace(
+        logic: &mut LogicResult,
+        last_regex_layout: &mut Option<RegexLayout>,
+        new: LogicResult,
+    ) {
+        let compiled = new.is_ok();
+        match std::mem::replace(logic, new) {
+            _ if compiled => *last_regex_layout = None,
+            Ok(old) => *last_regex_layout = Some(old.regex_layout),
+            Err(_) => {}
+        }
+    }
+
+    /// Compiles the given regular expression pattern and lays out the given text accordingly
+    pub fn new(
+        pattern: &str,
+        style: &Style,
+        regex_text: impl ToString,
+        input_text: impl ToString,
+        options: LogicOptions<'_>,
+    ) -> LogicResult {
+        let LogicOptions {
+            highlight_mode,
+            line_mode,
+            byte_mode,
+            engine,
+            flags,
+            group_threshold,
+            match_limit,
+            custom_colors,
+            previous_layout,
+        } = options;
+
+        // Bytes can only be matched with the standard engine
+        #[cfg(feature = "backend-fancy")]
+        let mut fancy_matching = None;
+        // The lints are found as part of compiling the regex, as they only depend on the pattern
+        let (compiled, compile_time) = timed(|| {
+            let compiled = match engine {
+                _ if byte_mode => compile_bytes_regex(pattern, flags).map(|(ast, regex)| {
+                    let skeleton = capture_group_skeleton(regex.capture_names());
+                    let byte_matching = ByteMatching {
+                        regex,
+                        input: Default::default(),
+                    };
+                    let hir = translate_regex(pattern, &ast, flags, true).ok();
+                    (ast, hir, skeleton, Some(byte_matching))
+                }),
+                #[cfg(feature = "backend-fancy")]
+                RegexEngine::Fancy => compile_fancy_regex(pattern, flags).map(|(ast, regex)| {
+                    let skeleton = capture_group_skeleton(regex.capture_names());
+                    // fancy-regex is always Unicode-aware
+                    let flags = RegexFlags {
+                        unicode: true,
+                        ..flags
+                    };
+                    let hir = ast
+                        .as_ref()
+                        .and_then(|ast| translate_regex(pattern, ast, flags, false).ok());
+                    fancy_matching = Some(FancyMatching {
+                        regex,
+                        partial_ast: ast.is_none(),
+                    });
+                    (
+                        ast.unwrap_or_else(|| capture_group_ast(pattern)),
+                        hir,
+                        skeleton,
+                        None,
+                    )
+                }),
+                _ => compile_regex(pattern, flags).map(|(ast, regex)| {
+                    let hir = translate_regex(pattern, &ast, flags, false).ok();
+                    (ast, hir, regex, None)
+                }),
+            };
+            compiled.map(|(ast, hir, regex, byte_matching)| {
+                let lints = lint_ast(&ast, pattern, byte_mode);
+                (ast, hir, regex, byte_matching, lints)
+            })
+        });
+
+        compiled.map(|(ast, hir, regex, byte_matching, lints)| {
+            let regex_layout = layout_regex(
+                regex_text.to_string(),
+                &ast,
+                style,
+                group_threshold,
+                &custom_colors,
+                previous_layout,
+            );
+
+            let syntax_spans = SyntaxSpans::from_ast(&ast);
+            let ast_spans = AstSpans::from_ast(&ast, pattern);
+            let empty_pattern = ast_is_empty(&ast);
+            // An AST of only the capture groups leaves out the rest of the pattern, which `regex-syntax` couldn't parse,
+            // so there must be more to it than flags
+            #[cfg(feature = "backend-fancy")]
+            let empty_pattern =
+                empty_pattern && !matches!(&fancy_matching, Some(fancy) if fancy.partial_ast);
+            let newline_in_pattern = ast_contains_newline(&ast);
+
+            let mut state = Self {
+                ast,
+                hir,
+                regex,
+                selector: Default::default(),
+                regex_layout,
+                input_layout: Default::default(),
+                match_stats: Default::default(),
+                ast_tree: None,
+                hir_tree: None,
+                engine_info: None,
+                explanation: None,
+                line_matches: None,
+                unique_values: None,
+                selected_ast_span: None,
+                hovered_ast_span: None,
+                syntax_spans,
+                ast_spans,
+                lints,
+                timings: Timings {
+                    compile: compile_time,
+                    ..Default::default()
+                },
+                highlight_mode,
+                matches_only: false,
+                // Bytes are always matched as a whole
+                line_mode: line_mode && !byte_mode,
+                newline_in_pattern,
+                empty_pattern,
+                is_match: false,
+                layout_pending: false,
+                match_limit,
+                custom_colors,
+                truncated_match_count: None,
+                capture_search: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                capture_worker: None,
+                byte_matching,
+                #[cfg(feature = "backend-fancy")]
+                fancy_matching,
+            };
+
+            state.set_input_text(input_text.to_string(), style);
+            state
+        })
+    }
+
+    /// Determines whether the regex matches the given input text, and lays out the text accordingly
+    ///
+    /// If the regex doesn't match the text at all (Or the pattern is empty), searching it for captures is skipped
+    /// entirely, and if the text is large, laying it out is deferred until `finish_layout` is called
+    ///
+    /// Any search for captures in the previous input text is abandoned, along with whatever it had found so far
+    pub fn set_input_text(&mut self, text: String, style: &Style) {
+        self.timings = Timings {
+            compile: self.timings.compile,
+            ..Default::default()
+        };
+        self.truncated_match_count = None;
+        self.line_matches = None;
+        self.unique_values = None;
+        self.capture_search = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.capture_worker = None;
+        }
+        if let Some(byte_matching) = &mut self.byte_matching {
+            byte_matching.input = EscapedBytes::unescape(&text);
+        }
+
+        let (is_match, matching_time) = timed(|| {
+            !self.empty_pattern
+                && match &self.byte_matching {
+                    Some(byte_matching) => byte_matching.regex.is_match(&byte_matching.input.bytes),
+                    None if self.line_mode => {
+                        line_ranges(&text).any(|line| self.text_is_match(&text[line]))
+                    }
+                    None => self.text_is_match(&text),
+                }
+        });
+        self.is_match = is_match;
+        self.timings.matching += matching_time;
+        self.layout_pending = self.is_match && text.len() > DEFERRED_LAYOUT_THRESHOLD;
+
+        if self.is_match && !self.layout_pending {
+            self.layout_input_text(text, style);
+        } else {
+            self.selector = Default::default();
+            self.match_stats = Default::default();
+            let (formatting, layout_time) = timed(|| format_plain_text(text, style));
+            self.timings.layout += layout_time;
+            self.input_layout = MatchedTextLayout {
+                formatting,
+                capture_group_chars: vec![],
+                text_index: Default::default(),
+                coverage: Default::default(),
+            };
+        }
+    }
+
+    /// Returns whether the regex matches the given text, with whichever engine the pattern was compiled with
+    ///
+    /// Text is only matched as bytes by the regex compiled for matching bytes, so this mustn't be used in byte mode
+    fn text_is_match(&self, text: &str) -> bool {
+        #[cfg(feature = "backend-fancy")]
+        if let Some(fancy_matching) = &self.fancy_matching {
+            // Exceeding the backtracking limit is treated as not matching
+            return fancy_matching.regex.is_match(text).unwrap_or(false);
+        }
+        self.regex.is_match(text)
+    }
+
+    /// Returns whether the input text is matched with `regex`, rather than with a regex compiled for matching bytes
+    /// or with fancy-regex, in which case `regex` only has the same capture groups as the pattern
+    pub fn matches_with_regex(&self) -> bool {
+        #[cfg(feature = "backend-fancy")]
+        if self.fancy_matching.is_some() {
+            return false;
+        }
+        self.byte_matching.is_none()
+    }
+
+    /// Finds every match in the given text along with the ranges matched by each of its capture groups,
+    /// with whichever engine the pattern was compiled with, treating the text as a whole even in line mode
+    ///
+    /// In byte mode the text is that which the bytes are written in, as with the input text
+    pub fn captures_in(&self, text: &str) -> CaptureTable {
+        if let Some(byte_matching) = &self.byte_matching {
+            return EscapedBytes::unescape(text).find_captures(&byte_matching.regex);
+        }
+        #[cfg(feature = "backend-fancy")]
+        if let Some(fancy_matching) = &self.fancy_matching {
+            return find_fancy_captures(&fancy_matching.regex, text);
+        }
+        find_captures(&self.regex, text)
+    }
+
+    /// Counts the matches in the given input text without finding their captures, with whichever engine the pattern
+    /// was compiled with, matching each line separately in line mode
+    ///
+    /// In byte mode the text is that which the bytes are written in, as with the input text
+    pub fn count_matches_in(&self, text: &str) -> usize {
+        if let Some(byte_matching) = &self.byte_matching {
+            let bytes = EscapedBytes::unescape(text).bytes;
+            return byte_matching.regex.find_iter(&bytes).count();
+        }
+
+        let count = |text: &str| {
+            #[cfg(feature = "backend-fancy")]
+            if let Some(fancy_matching) = &self.fancy_matching {
+                // Exceeding the backtracking limit ends the search, as it does when finding captures
+                return fancy_matching
+                    .regex
+                    .find_iter(text)
+                    .map_while(Result::ok)
+                    .count();
+            }
+            self.regex.find_iter(text).count()
+        };
+        if self.line_mode {
+            line_ranges(text).map(|line| count(&text[line])).sum()
+        } else {
+            count(text)
+        }
+    }
+
+    /// Finds every match in the given input text along with the ranges matched by each of its capture groups,
+    /// in the same way as the matches that are highlighted, matching each line separately in line mode
+    ///
+    /// Unlike the selector, this has every match, rather than only the first `match_limit` of them
+    pub fn input_captures(&self, text: &str) -> CaptureTable {
+        if self.line_mode {
+            find_per_line(text, |line| self.captures_in(line))
+        } else {
+            self.captures_in(text)
+        }
+    }
+
+    /// Returns the byte range of each line of the given input text along with whether the regex matches it,
+    /// finding them if they haven't been found since the input text last changed
+    pub fn line_matches(&mut self, input_text: &str) -> &[(Range<usize>, bool)] {
+        if self.line_matches.is_none() {
+            let line_matches = match &self.byte_matching {
+                Some(byte_matching) => match_lines(input_text, |line| {
+                    byte_matching
+                        .regex
+                        .is_match(&EscapedBytes::unescape(line).bytes)
+                }),
+                None => match_lines(input_text, |line| self.text_is_match(line)),
+            };
+            self.line_matches = Some(line_matches);
+        }
+        self.line_matches.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the distinct values of the given capture group in the given order, counting them if they haven't been
+    /// counted for that group since the input text last changed
+    ///
+    /// This should only be called once every match has been found, as matches found afterwards aren't counted
+    pub fn unique_values(&mut self, group: usize, order: ValueOrder) -> &mut UniqueValues {
+        if !matches!(&self.unique_values, Some(values) if values.group == group) {
+            // Capture groups other than the whole match have to be searched for again if they weren't extracted
+            let ranges = (0..self.selector.matches.len())
+                .map(|index| match self.selector.matches[index].get(group) {
+                    _ if self.matches_only && group > 0 => {
+                        self.match_groups(index).get(group).cloned().flatten()
+                    }
+                    Some((range, _)) => range.clone(),
+                    None => None,
+                })
+                .collect::<Vec<_>>();
+
+            let text = &self.selector.text;
+            self.unique_values = Some(UniqueValues::count(
+                group,
+                ranges.into_iter().map(|range| text.get(range?)),
+                order,
+            ));
+        }
+
+        let values = self.unique_values.as_mut().unwrap();
+        values.sort(order);
+        values
+    }
+
+    /// Returns the number of matches in the input text, or `None` if they haven't all been found yet
+    ///
+    /// This includes any matches beyond `match_limit`, which aren't in the selector
+    pub fn match_count(&self) -> Option<usize> {
+        (!self.layout_pending && self.capture_search.is_none()).then(|| {
+            self.truncated_match_count
+                .unwrap_or_else(|| self.selector.matches.len())
+        })
+    }
+
+    /// Returns the index of the match that the glyph at the given index of the input text is in, along with the index of
+    /// the innermost capture group in that match that matched it, which is 0 if only the whole match did
+    pub fn match_at_glyph(&self, glyph: usize) -> Option<(usize, usize)> {
+        self.input_layout.match_at_glyph(glyph).or_else(|| {
+            // Whole matches aren't in the layout's capture groups, nor is anything if only whole matches are highlighted
+            let offset = TextIndex::new(&self.selector.text).glyph_to_byte(glyph)?;
+            self.selector
+                .matches
+                .iter()
+                .position(|groups| matches!(groups.first(), Some((Some(range), _)) if range.contains(&offset)))
+                .map(|index| (index, 0))
+        })
+    }
+
+    /// Finds the ranges of the selector's text that each capture group of the match with the given index matched,
+    /// by searching for the match again from where it starts
+    pub fn match_groups(&self, index: usize) -> Vec<Option<Range<usize>>> {
+        let start = match self
+            .selector
+            .matches
+            .get(index)
+            .and_then(|groups| groups.first())
+        {
+            Some((Some(range), _)) => range.start,
+            _ => return vec![],
+        };
+
+        if let Some(byte_matching) = &self.byte_matching {
+            return byte_matching
+                .input
+                .captures_at(&byte_matching.regex, start)
+                .unwrap_or_default();
+        }
+
+        // Each line is matched separately in line mode, so the match is searched for again in its own line
+        let text = &self.selector.text;
+        let line = if self.line_mode {
+            line_ranges(text)
+                .find(|line| line.start <= start && start <= line.end)
+                .unwrap_or(0..text.len())
+        } else {
+            0..text.len()
+        };
+
+        #[cfg(feature = "backend-fancy")]
+        if let Some(fancy_matching) = &self.fancy_matching {
+            let captures = fancy_matching
+                .regex
+                .captures_from_pos(&text[line.clone()], start - line.start);
+            return match captures {
+                Ok(Some(captures)) => captures
+                    .iter()
+                    .map(|group| {
+                        group.map(|group| line.start + group.start()..line.start + group.end())
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+        }
+
+        let mut locations = self.regex.capture_locations();
+        if self
+            .regex
+            .captures_read_at(&mut locations, &text[line.clone()], start - line.start)
+            .is_none()
+        {
+            return vec![];
+        }
+
+        (0..locations.len())
+            .map(|group| {
+                locations
+                    .get(group)
+                    .map(|(start, end)| line.start + start..line.start + end)
+            })
+            .collect()
+    }
+
+    /// Lays out the given input text if doing so was deferred by `set_input_text`,
+    /// or continues searching it for captures if that is spread over several frames
+    pub fn finish_layout(&mut self, text: &str, style: &Style) {
+        if self.layout_pending {
+            self.layout_input_text(text.to_owned(), style);
+        } else {
+            self.advance_capture_search(text, style);
+        }
+    }
+
+    /// Finds the captures of the next few matches in the given input text, if the search for them hasn't finished yet,
+    /// and lays out the text again to highlight every match that has been found so far
+    fn advance_capture_search(&mut self, text: &str, style: &Style) {
+        // The search is put back afterwards unless it has finished
+        let mut search = match self.capture_search.take() {
+            Some(search) => search,
+            None => return,
+        };
+
+        let found = search.captures.len();
+
+        // Natively, only the first step of the search is performed on the UI thread, with the rest continued on
+        // a worker thread, and the text is laid out again whenever the worker has found more matches
+        #[cfg(not(target_arch = "wasm32"))]
+        let finished = match &mut self.capture_worker {
+            Some(worker) => {
+                let finished = worker.poll(&mut search);
+                if !finished && search.captures.len() == found {
+                    self.capture_search = Some(search);
+                    return;
+                }
+                finished
+            }
+            None => {
+                let finished = search.advance(&self.regex, text, CAPTURES_PER_FRAME);
+                if !finished {
+                    self.capture_worker = Some(CaptureWorker::spawn(
+                        search.clone(),
+                        self.regex.clone(),
+                        text.to_owned(),
+                    ));
+                }
+                finished
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let finished = search.advance(&self.regex, text, CAPTURES_PER_FRAME);
+
+        // The search stops early once it has found `match_limit` matches, in which case the rest are only counted,
+        // which is much cheaper than finding their captures
+        if finished && search.is_full() && !search.is_finished(text) {
+            let (total, counting_time) = timed(|| self.count_matches_in(text));
+            self.timings.matching += counting_time;
+            self.truncated_match_count = (total > search.captures.len()).then_some(total);
+        }
+
+        self.selector
+            .extend_from_captures(&search.captures[found..], &self.regex);
+        self.match_stats
+            .add_captures(&search.captures[found..], text);
+        let (input_layout, layout_time) = timed(|| {
+            layout_matched_text(
+                text.to_owned(),
+                &search.captures,
+                style,
+                &self.regex_layout.capture_group_colors,
+            )
+        });
+        self.timings.layout += layout_time;
+        self.input_layout = input_layout;
+
+        if finished {
+            // The search keeps track of how long it took, even on the worker thread
+            self.timings.matching += search.elapsed;
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.capture_worker = None;
+            }
+        } else {
+            self.capture_search = Some(search);
+        }
+    }
+
+    /// Lays out the given input text with the given captures highlighted, all at once
+    fn layout_captures(&mut self, text: String, captures: &CaptureTable, style: &Style) {
+        let (input_layout, layout_time) = timed(|| {
+            layout_matched_text(
+                text,
+                captures,
+                style,
+                &self.regex_layout.capture_group_colors,
+            )
+        });
+        self.timings.layout += layout_time;
+        self.input_layout = input_layout;
+    }
+
+    fn layout_input_text(&mut self, text: String, style: &Style) {
+        // Bytes, and text matched with fancy-regex, have their captures found all at once
+        if !self.matches_with_regex() {
+            let (mut captures, matching_time) = timed(|| match &self.byte_matching {
+                Some(byte_matching) => byte_matching.input.find_captures(&byte_matching.regex),
+                None if self.line_mode => find_per_line(&text, |line| self.captures_in(line)),
+                None => self.captures_in(&text),
+            });
+            self.timings.matching += matching_time;
+            self.truncated_match_count = truncate_matches(&mut captures, self.match_limit);
+            self.matches_only = false;
+            self.selector =
+                MatchesSelector::create_from_captures(&captures, &self.regex, text.clone());
+            self.match_stats =
+                MatchStats::new(self.regex.captures_len(), self.byte_matching.is_some());
+            self.match_stats.add_captures(&captures, &text);
+            self.layout_captures(text, &captures, style);
+            self.layout_pending = false;
+            return;
+        }
+
+        // Lines are matched separately by finding their captures, so whole matches can't be highlighted on their own
+        self.matches_only = !self.line_mode && self.highlight_mode.matches_only(&self.regex, &text);
+        if self.matches_only {
+            // The matches are found once, and shared between the selector and the layout
+            let (mut match_ranges, matching_time) = timed(|| {
+                self.regex
+                    .find_iter(&text)
+                    .map(|r#match| r#match.range())
+                    .collect::<Vec<_>>()
+            });
+            self.timings.matching += matching_time;
+            self.truncated_match_count = truncate_matches(&mut match_ranges, self.match_limit);
+            self.selector = MatchesSelector::create_matches_only(&match_ranges, text.clone());
+            self.match_stats = MatchStats::from_match_ranges(&match_ranges, &text);
+            let (input_layout, layout_time) = timed(|| {
+                layout_matched_text_matches_only(text, match_ranges, style, &self.custom_colors)
+            });
+            self.timings.layout += layout_time;
+            self.input_layout = input_layout;
+        } else {
+            if self.line_mode {
+                let (mut captures, matching_time) =
+                    timed(|| find_captures_per_line(&self.regex, &text));
+                self.timings.matching += matching_time;
+                self.truncated_match_count = truncate_matches(&mut captures, self.match_limit);
+                self.selector =
+                    MatchesSelector::create_from_captures(&captures, &self.regex, text.clone());
+                self.match_stats = MatchStats::new(self.regex.captures_len(), false);
+                self.match_stats.add_captures(&captures, &text);
+                self.layout_captures(text, &captures, style);
+            } else {
+                // Searching for captures can be slow, so only a few matches are found at a time,
+                // with the rest being found over the following frames
+                self.selector = MatchesSelector::create_from_captures(
+                    &CaptureTable::new(),
+                    &self.regex,
+                    text.clone(),
+                );
+                self.match_stats = MatchStats::new(self.regex.captures_len(), false);
+                self.capture_search = Some(CaptureSearch::new(self.match_limit));
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.capture_worker = None;
+                }
+                self.advance_capture_search(&text, style);
+            }
+        }
+        self.layout_pending = false;
+    }
+}
+
+/// Discards the matches beyond the given limit, if there are more than that,
+/// returning how many matches there were in total if any were discarded
+fn truncate_matches<T>(matches: &mut Vec<T>, limit: Option<usize>) -> Option<usize> {
+    let total = matches.len();
+    match limit {
+        Some(limit) if total > limit => {
+            matches.truncate(limit);
+            Some(total)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_alternations_compile_and_lay_out() {
+        use crate::app::ast_tree::AstNode;
+
+        let pattern = (0..10_000)
+            .map(|index| format!("word{}", index))
+            .collect::<Vec<_>>()
+            .join("|");
+        let input = "word9999 word42 other";
+
+        let logic = LogicState::new(
+            &pattern,
+            &Style::default(),
+            &pattern,
+            input,
+            Default::default(),
+        )
+        .unwrap();
+
+        // Each literal and each `|` is a section of its own, so the layout only grows with the number of alternatives
+        let sections = logic.regex_layout.job.sections.len();
+        assert!(sections < 2 * 10_000, "{} sections", sections);
+        assert_eq!(logic.match_count(), Some(2));
+
+        let tree = AstNode::from_ast(&logic.ast, &pattern);
+        assert_eq!(tree.children.len(), 10_000);
+    }
+
+    #[test]
+    fn result_modes_survive_a_serde_round_trip() {
+        let round_trip = |mode: &ResultModeState| {
+            let json = serde_json::to_string(mode).unwrap();
+            serde_json::from_str::<ResultModeState>(&json).unwrap()
+        };
+
+        let replace = ResultModeState::Replace {
+            replace_text: "<$1>".into(),
+            limit: ReplaceLimit::FirstN(3),
+            result: Some("skipped".into()),
+            replaced_spans: vec![0..1, 2..3],
+        };
+        match round_trip(&replace) {
+            ResultModeState::Replace {
+                replace_text,
+                limit,
+                result,
+                replaced_spans,
+            } => {
+                assert_eq!(replace_text, "<$1>");
+                assert!(limit == ReplaceLimit::FirstN(3));
+                assert_eq!(result, None);
+                assert!(replaced_spans.is_empty());
+            }
+            mode => panic!("Replace was deserialized as {}", mode.name()),
+        }
+
+        let split = ResultModeState::Split {
+            limit: 7,
+            result: None,
+            selected: Some(2),
+        };
+        match round_trip(&split) {
+            ResultModeState::Split {
+                limit, selected, ..
+            } => {
+                assert_eq!(limit, 7);
+                assert_eq!(selected, None);
+            }
+            mode => panic!("Split was deserialized as {}", mode.name()),
+        }
+
+        let format = ResultModeState::Format {
+            template: "{group.year}\\t{match}".into(),
+            result: None,
+        };
+        match round_trip(&format) {
+            ResultModeState::Format { template, .. } => {
+                assert_eq!(template, "{group.year}\\t{match}")
+            }
+            mode => panic!("Format was deserialized as {}", mode.name()),
+        }
+
+        let step_replace = ResultModeState::StepReplace {
+            replace_text: "${name}!".into(),
+            steps: None,
+        };
+        match round_trip(&step_replace) {
+            ResultModeState::StepReplace { replace_text, .. } => {
+                assert_eq!(replace_text, "${name}!")
+            }
+            mode => panic!("Step Replace was deserialized as {}", mode.name()),
+        }
+    }
+
+    #[test]
+    fn large_input_text_is_not_persisted() {
+        let round_trip = |input_text: String| {
+            let widgets = WidgetState {
+                input_text,
+                ..Default::default()
+            };
+            let json = serde_json::to_string(&widgets).unwrap();
+            serde_json::from_str::<WidgetState>(&json)
+                .unwrap()
+                .input_text
+        };
+
+        let small = "a".repeat(MAX_PERSISTED_INPUT_BYTES);
+        assert_eq!(round_trip(small.clone()), small);
+        assert_eq!(round_trip(small + "a"), "");
+    }
+
+    #[test]
+    fn matches_are_counted_with_the_engine_that_matches_them() {
+        let count = |pattern, input, options| {
+            LogicState::new(pattern, &Style::default(), pattern, input, options)
+                .unwrap()
+                .count_matches_in(input)
+        };
+        let line_mode = LogicOptions {
+            line_mode: true,
+            ..Default::default()
+        };
+        assert_eq!(count("^a", "ab\nab", line_mode), 2);
+
+        let byte_mode = LogicOptions {
+            byte_mode: true,
+            ..Default::default()
+        };
+        assert_eq!(count(r"(?-u)\xFF", r"\xFFa\xFF", byte_mode), 2);
+
+        #[cfg(feature = "backend-fancy")]
+        {
+            let fancy = LogicOptions {
+                engine: RegexEngine::Fancy,
+                ..Default::default()
+            };
+            assert_eq!(count("a(?=b)", "ab ac ab", fancy), 2);
+        }
+    }
+
+    #[test]
+    fn result_modes_match_each_line_in_line_mode() {
+        let input = "ab\nab";
+        for (line_mode, replaced, pieces) in [
+            (false, "xb\nax", vec![0..0, 1..4, 5..5]),
+            (true, "xx\nxx", vec![0..0, 1..1, 2..3, 4..4, 5..5]),
+        ] {
+            let logic = LogicState::new(
+                "^a|b$",
+                &Style::default(),
+                "^a|b$",
+                input,
+                LogicOptions {
+                    line_mode,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let mut replace = ResultModeState::Replace {
+                replace_text: "x".into(),
+                limit: ReplaceLimit::All,
+                result: None,
+                replaced_spans: vec![],
+            };
+            replace.update(&logic, input);
+            assert_eq!(replace.output_text().as_deref(), Some(replaced));
+
+            let mut split = ResultModeState::defaults()[1].clone();
+            split.update(&logic, input);
+            match split {
+                ResultModeState::Split {
+                    result: Some(result),
+                    ..
+                } => {
+                    let ranges = result.into_iter().map(|piece| piece.range);
+                    assert_eq!(ranges.collect::<Vec<_>>(), pieces);
+                }
+                _ => panic!("Splitting didn't produce any pieces"),
+            }
+        }
+    }
+
+    /// Compiles the given pattern with every capture group highlighted, however long the input text is
+    fn captures_logic(pattern: &str, input: &str) -> LogicState {
+        LogicState::new(
+            pattern,
+            &Style::default(),
+            pattern,
+            input,
+            LogicOptions {
+                highlight_mode: HighlightMode::Captures,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    /// The history skips repeats of the most recent pattern, and only keeps the newest patterns
+    #[test]
+    fn pattern_history() {
+        let mut widgets = WidgetState::default();
+        for pattern in ["a", "a", "b", "a"] {
+            widgets.regex_text = pattern.into();
+            widgets.record_pattern();
+        }
+        assert_eq!(widgets.pattern_history, ["a", "b", "a"]);
+
+        for index in 0..100 {
+            widgets.regex_text = index.to_string();
+            widgets.record_pattern();
+        }
+        assert_eq!(widgets.pattern_history.len(), 50);
+        assert_eq!(widgets.pattern_history.first().unwrap(), "50");
+        assert_eq!(widgets.pattern_history.last().unwrap(), "99");
+    }
+
+    /// Loading reports whether any of the bytes weren't valid UTF-8, and in byte mode they are escaped rather than replaced
+    #[test]
+    fn input_bytes_are_loaded() {
+        let cases = [
+            (false, &b"caf\xC3\xA9"[..], "café", false),
+            (false, b"a\xFFb", "a\u{FFFD}b", true),
+            (true, b"a\xFFb", "a\\xFFb", false),
+        ];
+
+        for (byte_mode, bytes, expected, expected_lossy) in cases {
+            let mut state = AppState::default();
+            state.widgets.byte_mode = byte_mode;
+            let lossy =
+                state.load_input_bytes(bytes.to_vec(), InputSource::OpenedFile, &Style::default());
+            assert_eq!(
+                (state.widgets.input_text.as_str(), lossy),
+                (expected, expected_lossy),
+                "{:?}",
+                bytes
+            );
+        }
+    }
+
+    /// Debounced edits keep the previous matches until they are due, and are then all applied together
+    #[test]
+    fn debounced_edits() {
+        let style = Style::default();
+        let mut state = AppState::default();
+        state.widgets.regex_text = "a".into();
+        state.widgets.input_text = "ab".repeat(10_000);
+        state.recompile(&style);
+
+        state.widgets.regex_text = "(b)".into();
+        PendingEdit::add(&mut state.pending_edit, 1.0, true, false);
+        state.widgets.input_text.push('b');
+        PendingEdit::add(&mut state.pending_edit, 1.1, false, true);
+
+        assert!(state.apply_pending_edit(1.2, &style).is_some());
+        assert_eq!(state.logic.as_ref().unwrap().regex.as_str(), "a");
+
+        assert!(state.apply_pending_edit(2.0, &style).is_none());
+        assert!(state.pending_edit.is_none());
+        let logic = state.logic.as_ref().unwrap();
+        assert_eq!(logic.regex.as_str(), "(b)");
+        assert_eq!(logic.selector.text, state.widgets.input_text);
+    }
+
+    #[test]
+    fn capture_searches_finish() {
+        let style = Style::default();
+        let input = "ab".repeat(5_000);
+        let mut logic = captures_logic("(a)", &input);
+
+        // Natively the rest of the search happens on another thread, so give it a while to finish
+        for _ in 0..1000 {
+            // Every match found so far is highlighted, even before the search finishes
+            assert_eq!(
+                logic.input_layout.capture_group_chars.len(),
+                logic.selector.matches.len()
+            );
+            if logic.match_count().is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            logic.finish_layout(&input, &style);
+        }
+
+        assert_eq!(logic.match_count(), Some(5_000));
+        assert_eq!(logic.input_layout.capture_group_chars.len(), 5_000);
+    }
+
+    #[test]
+    fn only_the_first_matches_are_highlighted() {
+        let style = Style::default();
+        let input = "ab\n".repeat(50);
+        for line_mode in [false, true] {
+            let mut logic = LogicState::new(
+                "(a)",
+                &style,
+                "(a)",
+                &input,
+                LogicOptions {
+                    highlight_mode: HighlightMode::Captures,
+                    line_mode,
+                    match_limit: Some(10),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            logic.finish_layout(&input, &style);
+
+            assert_eq!(logic.match_count(), Some(50), "Line mode: {}", line_mode);
+            assert_eq!(logic.selector.matches.len(), 10, "Line mode: {}", line_mode);
+            assert_eq!(
+                logic.input_layout.capture_group_chars.len(),
+                10,
+                "Line mode: {}",
+                line_mode
+            );
+        }
+    }
+
+    #[test]
+    fn only_the_first_whole_matches_are_highlighted() {
+        let style = Style::default();
+        let input = "ab\n".repeat(50);
+        let mut logic = LogicState::new(
+            "(a)",
+            &style,
+            "(a)",
+            &input,
+            LogicOptions {
+                highlight_mode: HighlightMode::MatchesOnly,
+                match_limit: Some(10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        logic.finish_layout(&input, &style);
+
+        assert!(logic.matches_only);
+        assert_eq!(logic.match_count(), Some(50));
+        assert_eq!(logic.truncated_match_count, Some(50));
+        assert_eq!(logic.selector.matches.len(), 10);
+        assert_eq!(logic.input_layout.coverage.matched_bytes, 10);
+    }
+
+    #[test]
+    fn replace_limit() {
+        let input = "a a a a";
+        let logic = captures_logic("a", input);
+
+        for (limit, expected, expected_replaced) in [
+            (ReplaceLimit::All, "b b b b", 4),
+            (ReplaceLimit::First, "b a a a", 1),
+            (ReplaceLimit::FirstN(3), "b b b a", 3),
+            (ReplaceLimit::FirstN(9), "b b b b", 4),
+        ] {
+            let mut mode = ResultModeState::Replace {
+                replace_text: "b".into(),
+                limit,
+                result: None,
+                replaced_spans: vec![],
+            };
+            mode.update(&logic, input);
+
+            match mode {
+                ResultModeState::Replace {
+                    result,
+                    replaced_spans,
+                    ..
+                } => {
+                    assert_eq!(result.as_deref(), Some(expected), "{}", limit.name());
+                    assert_eq!(replaced_spans.len(), expected_replaced, "{}", limit.name());
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn split_pieces() {
+        let input = "a,,b,";
+        let logic = captures_logic(",", input);
+
+        let mut mode = ResultModeState::defaults()[1].clone();
+        mode.update(&logic, input);
+        match mode {
+            ResultModeState::Split {
+                result: Some(pieces),
+                ..
+            } => {
+                let ranges = pieces
+                    .iter()
+                    .map(|piece| piece.range.clone())
+                    .collect::<Vec<_>>();
+                assert_eq!(ranges, [0..1, 2..2, 3..4, 5..5]);
+            }
+            _ => panic!("Splitting didn't produce any pieces"),
+        }
+    }
+
+    /// Adjacent matches, replacements containing newlines, and matches replaced with nothing
+    #[test]
+    fn replaced_spans() {
+        use crate::app::text::{check_sections, layout_result_text};
+
+        let input = "aab\na";
+        let logic = captures_logic("a", input);
+
+        for (replace_text, expected) in [
+            ("<$0\n>", vec![0..4, 4..8, 10..14]),
+            ("", vec![0..0, 0..0, 2..2]),
+        ] {
+            let mut mode = ResultModeState::Replace {
+                replace_text: replace_text.into(),
+                limit: ReplaceLimit::All,
+                result: None,
+                replaced_spans: vec![],
+            };
+            mode.update(&logic, input);
+
+            match mode {
+                ResultModeState::Replace {
+                    result: Some(result),
+                    replaced_spans,
+                    ..
+                } => {
+                    assert_eq!(result, logic.regex.replace_all(input, replace_text));
+                    assert_eq!(replaced_spans, expected, "{:?}", replace_text);
+                    check_sections(&layout_result_text(
+                        &result,
+                        &Style::default(),
+                        &replaced_spans,
+                        &Default::default(),
+                    ))
+                    .unwrap();
+                }
+                _ => panic!("Replacing with {:?} didn't produce a result", replace_text),
+            }
+        }
+    }
+
+    /// Groups that don't participate are still kept, so that the position of each group is its index
+    #[test]
+    fn group_indexes() {
+        let regex = Regex::new("(a)|(?P<b>b)").unwrap();
+        let selector =
+            MatchesSelector::create_from_captures(&find_captures(&regex, "b"), &regex, "b".into());
+
+        let groups = selector
+            .matches
+            .get_current()
+            .unwrap()
+            .iter()
+            .map(|(range, name)| (range.clone(), name.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [(Some(0..1), None), (None, None), (Some(0..1), Some("b"))]
+        );
+    }
+
+    /// Glyphs that are before, between and after the matches aren't in any of them
+    #[test]
+    fn hovered_matches() {
+        let input = "xab a";
+        for (highlight_mode, expected) in [
+            (
+                HighlightMode::Captures,
+                [None, Some((0, 1)), Some((0, 0)), None, Some((1, 1)), None],
+            ),
+            // Capture groups aren't laid out when only whole matches are highlighted
+            (
+                HighlightMode::MatchesOnly,
+                [None, Some((0, 0)), Some((0, 0)), None, Some((1, 0)), None],
+            ),
+        ] {
+            let logic = LogicState::new(
+                "(a)b?",
+                &Style::default(),
+                "(a)b?",
+                input,
+                LogicOptions {
+                    highlight_mode,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let found = (0..expected.len())
+                .map(|glyph| logic.match_at_glyph(glyph))
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+        }
+    }
+
+    /// Each edit lists which of the previous groups each of the edited groups is, if any
+    #[test]
+    fn group_colors_are_stable_across_edits() {
+        let style = Style::default();
+        let compile = |pattern: &str, previous: Option<&LogicState>| {
+            LogicState::new(
+                pattern,
+                &style,
+                pattern,
+                "",
+                LogicOptions {
+                    highlight_mode: HighlightMode::Captures,
+                    previous_layout: previous.map(|previous| &previous.regex_layout),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        for (before, after, survivors) in [
+            ("(a)(b)", "(x)(a)(b)", &[None, Some(0), Some(1)][..]),
+            ("(a)(b)(c)", "(a)(c)", &[Some(0), Some(2)]),
+            ("(a)", "(a)(b)", &[Some(0), None]),
+            (
+                r"(\d+)-(\d+)",
+                r"(\d+)-(x)-(\d+)",
+                &[Some(0), None, Some(1)],
+            ),
+            ("(a)(?P<name>b)", "(?P<name>bc)(a)", &[Some(1), Some(0)]),
+            ("(a(b))", "(a(b)c)", &[Some(0), Some(1)]),
+        ] {
+            let old = compile(before, None);
+            let new = compile(after, Some(&old));
+            let old_colors = &old.regex_layout.capture_group_colors[1..];
+            let new_colors = &new.regex_layout.capture_group_colors[1..];
+
+            for (group, survivor) in survivors.iter().enumerate() {
+                let color = new_colors[group];
+                let kept = match survivor {
+                    Some(old_group) => color == old_colors[*old_group],
+                    // New groups get a color that no surviving group has, while there are colors to spare
+                    None => !survivors
+                        .iter()
+                        .flatten()
+                        .any(|&old_group| old_colors[old_group] == color),
+                };
+                assert!(
+                    kept,
+                    "Group {} of {} has the wrong color after editing {}",
+                    group + 1,
+                    after,
+                    before
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matching_lines() {
+        let style = Style::default();
+        let mut state = AppState::default();
+        state.widgets.regex_text = "^error".into();
+        state.widgets.input_text = "error: a\r\nok\nerror: b".into();
+        state.recompile(&style);
+
+        let logic = state.logic.as_mut().unwrap();
+        assert_eq!(
+            logic.line_matches(&state.widgets.input_text),
+            [(0..8, true), (10..12, false), (13..21, true)]
+        );
+
+        // The lines are found again once the input text changes
+        state.set_input_text("ok\nerror".into(), &style);
+        let logic = state.logic.as_mut().unwrap();
+        assert_eq!(
+            logic.line_matches(&state.widgets.input_text),
+            [(0..2, false), (3..8, true)]
+        );
+
+        // In byte mode, each line is matched as the bytes that it is written with
+        state.widgets.byte_mode = true;
+        state.widgets.regex_text = r"(?-u)^\xFF".into();
+        state.widgets.input_text = "\\xFF\nff".into();
+        state.recompile(&style);
+        let logic = state.logic.as_mut().unwrap();
+        assert!(matches!(
+            logic.line_matches(&state.widgets.input_text),
+            [(_, true), (_, false)]
+        ));
+    }
+
+    #[test]
+    fn match_stats() {
+        let mut state = AppState::default();
+        state.widgets.regex_text = r"(\d)(x)?é*".into();
+        state.widgets.input_text = "1x 2éé 3".into();
+        state.recompile(&Style::default());
+
+        // Lengths are measured in chars rather than bytes
+        let stats = &state.logic.as_ref().unwrap().match_stats;
+        let expected = MatchStats {
+            match_count: 3,
+            total_length: 6,
+            min_length: 1,
+            max_length: 3,
+            group_participation: Some(vec![3, 1]),
+            in_bytes: false,
+        };
+        assert_eq!(*stats, expected);
+        assert_eq!(stats.mean_length(), Some(2.0));
+
+        // Capture groups aren't counted when only whole matches are highlighted
+        let stats = MatchStats::from_match_ranges(&[0..2, 3..3], "1x 2");
+        assert_eq!(stats.group_participation, None);
+        assert_eq!((stats.min_length, stats.max_length), (0, 2));
+    }
+
+    #[test]
+    fn unique_values() {
+        for highlight_mode in [HighlightMode::Captures, HighlightMode::MatchesOnly] {
+            let mut logic = LogicState::new(
+                r"(\w)(?:=(\d))?",
+                &Style::default(),
+                r"(\w)(?:=(\d))?",
+                "a=1 b=2 a=3 c a=4",
+                LogicOptions {
+                    highlight_mode,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // The most frequent values are listed first, with ties listed alphabetically
+            let values = logic.unique_values(1, ValueOrder::Count);
+            let expected = [
+                ("a".to_owned(), 3),
+                ("b".to_owned(), 1),
+                ("c".to_owned(), 1),
+            ];
+            assert_eq!(values.values, expected);
+            assert_eq!(values.missing, 0);
+
+            // A group that didn't participate in a match isn't counted as a value
+            let values = logic.unique_values(2, ValueOrder::Alphabetical);
+            assert_eq!(values.values.len(), 4);
+            assert_eq!(values.values[0].0, "1");
+            assert_eq!(values.missing, 1);
+        }
+    }
+
+    #[test]
+    fn replace_steps() {
+        let input = "a bb ccc dddd";
+        let logic = captures_logic(r"\w+", input);
+        let ranges = logic
+            .regex
+            .find_iter(input)
+            .map(|r#match| r#match.range())
+            .collect::<Vec<_>>();
+
+        // Replacements both longer and shorter than their matches shift the matches after them
+        let mut steps = ReplaceSteps::new(input);
+        steps.accept(ranges[0].clone(), "<a>");
+        steps.skip();
+        steps.accept(ranges[2].clone(), "");
+        steps.accept(ranges[3].clone(), "d");
+        assert_eq!(steps.text, "<a> bb  d");
+        assert_eq!(steps.replaced_spans, [0..3, 7..7, 8..9]);
+        assert_eq!((steps.index, steps.accepted, steps.skipped), (4, 3, 1));
+
+        // Switching to the mode starts stepping through the current input text
+        let mut mode = ResultModeState::defaults()[3].clone();
+        mode.update(&logic, input);
+        assert_eq!(mode.output_text().as_deref(), Some(input));
+    }
+
+    /// Highlighted text should be dark text on a pale background, rather than a dark island in a light editor
+    #[test]
+    fn light_theme_highlights_are_pale() {
+        use crate::app::{color, parsing::compile_regex, text::layout_regex_err};
+        use egui::{Color32, Visuals};
+
+        let style = Style {
+            visuals: Visuals::light(),
+            ..Default::default()
+        };
+        let logic = LogicState::new(
+            "(a)(b)",
+            &style,
+            "(a)(b)",
+            "ab",
+            LogicOptions {
+                highlight_mode: HighlightMode::Captures,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let err = compile_regex("(a", Default::default()).unwrap_err();
+        let err_layout = layout_regex_err("(a".into(), &style, &err, None);
+        let input_layout = logic
+            .input_layout
+            .formatting
+            .clone()
+            .convert_to_layout_job();
+        for (name, job) in [
+            ("Regex", &logic.regex_layout.job),
+            ("Error", &err_layout.job),
+            ("Input", &input_layout),
+        ] {
+            let highlighted = job
+                .sections
+                .iter()
+                .filter(|section| section.format.background != Color32::TRANSPARENT)
+                .collect::<Vec<_>>();
+            assert!(
+                !highlighted.is_empty(),
+                "{} layout has nothing highlighted",
+                name
+            );
+
+            for section in highlighted {
+                assert!(
+                    !color::is_dark(section.format.background)
+                        && color::is_dark(section.format.color),
+                    "{} layout has {:?} on {:?} in the light theme",
+                    name,
+                    section.format.color,
+                    section.format.background
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn custom_group_colors() {
+        use crate::app::color;
+        use egui::Color32;
+
+        let mut custom_colors = CustomGroupColors::default();
+        custom_colors.choose(1, Some(Color32::RED));
+
+        let logic = LogicState::new(
+            "(a)(b)",
+            &Style::default(),
+            "(a)(b)",
+            "ab",
+            LogicOptions {
+                highlight_mode: HighlightMode::Captures,
+                custom_colors: custom_colors.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let colors = &logic.regex_layout.capture_group_colors;
+        assert_eq!(colors[1], color::group_color(0, true));
+        assert_eq!(colors[2].background, Color32::RED);
+
+        // The input text is highlighted with the chosen color too
+        let input_layout = logic
+            .input_layout
+            .formatting
+            .clone()
+            .convert_to_layout_job();
+        assert!(input_layout.sections.iter().any(
+            |section| section.byte_range == (1..2) && section.format.background == Color32::RED
+        ));
+
+        let json = serde_json::to_string(&custom_colors).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CustomGroupColors>(&json).unwrap(),
+            custom_colors
+        );
+
+        // Going back to the generated color forgets the choice entirely
+        custom_colors.choose(1, None);
+        assert!(custom_colors.is_empty());
+    }
+
+    /// Lookaround and backreferences are only supported by the fancy-regex engine
+    #[cfg(feature = "backend-fancy")]
+    #[test]
+    fn fancy_engine_matches_unsupported_syntax() {
+        let new_logic = |pattern: &str, input: &str, engine| {
+            LogicState::new(
+                pattern,
+                &Style::default(),
+                pattern,
+                input,
+                LogicOptions {
+                    highlight_mode: HighlightMode::Captures,
+                    engine,
+                    ..Default::default()
+                },
+            )
+        };
+
+        for (pattern, input, expected) in [
+            (
+                r"(\w+)(?= world)",
+                "hello world",
+                vec![Some(0..5), Some(0..5)],
+            ),
+            (r"(a)\1", "baa", vec![Some(1..3), Some(1..2)]),
+            (r"(?<!x)(?P<n>b)", "xbb", vec![Some(2..3), Some(2..3)]),
+        ] {
+            assert!(
+                new_logic(pattern, input, RegexEngine::Standard).is_err(),
+                "The standard engine compiled {:?}",
+                pattern
+            );
+            let logic = new_logic(pattern, input, RegexEngine::Fancy).unwrap();
+            assert!(logic.fancy_matching.as_ref().unwrap().partial_ast);
+            assert!(!logic.matches_with_regex());
+            // The capture group skeleton has the same groups as the pattern
+            assert_eq!(logic.regex.captures_len(), expected.len(), "{:?}", pattern);
+            assert_eq!(logic.captures_in(input), [expected], "{:?}", pattern);
+        }
+
+        // Patterns that the standard engine supports are still fully parsed
+        let logic = new_logic(r"a+(b)", "aab", RegexEngine::Fancy).unwrap();
+        assert_eq!(
+            logic.fancy_matching.map(|fancy| fancy.partial_ast),
+            Some(false)
+        );
+    }
+
+    /// The sample of a snippet is added on a line of its own, which can be undone
+    #[test]
+    fn appended_input_lines_can_be_undone() {
+        let style = Style::default();
+        let mut state = AppState::default();
+        state.widgets.input_text = "text".into();
+        state.append_input_line("sample", &style);
+        assert_eq!(state.widgets.input_text, "text\nsample");
+        state.undo_input_change(&style);
+        assert_eq!(state.widgets.input_text, "text");
     }
 }
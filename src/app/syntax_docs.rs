@@ -0,0 +1,706 @@
+use super::text::GetRangeExt;
+use egui::{text::LayoutJob, FontId, TextFormat};
+use regex_syntax::ast::{
+    AssertionKind, Ast, Class, ClassAsciiKind, ClassBracketed, ClassPerlKind, ClassSet,
+    ClassSetBinaryOpKind, ClassSetItem, ClassUnicode, ClassUnicodeKind, GroupKind, HexLiteralKind,
+    Literal, LiteralKind, RepetitionKind, RepetitionRange, SpecialLiteralKind,
+};
+use std::ops::Range;
+
+/// A part of the description of a piece of syntax
+pub enum Fragment {
+    /// Plain text
+    Text(&'static str),
+    /// Text that should be rendered in a monospace font, such as an example of syntax
+    Code(&'static str),
+}
+
+use Fragment::{Code, Text};
+
+/// Documentation of a single piece of regex syntax
+pub struct SyntaxEntry {
+    /// An example of the syntax
+    pub syntax: &'static str,
+    /// A description of what the syntax does
+    pub description: &'static [Fragment],
+}
+
+impl SyntaxEntry {
+    /// Lays out the description of this syntax, using the given font for any fragments of code
+    pub fn layout_description(&self, monospace: FontId) -> LayoutJob {
+        let mut job = LayoutJob::default();
+        for fragment in self.description {
+            match fragment {
+                Text(text) => job.append(text, 0.0, Default::default()),
+                Code(text) => job.append(
+                    text,
+                    0.0,
+                    TextFormat {
+                        font_id: monospace.clone(),
+                        ..Default::default()
+                    },
+                ),
+            }
+        }
+        job
+    }
+}
+
+const fn entry(syntax: &'static str, description: &'static [Fragment]) -> SyntaxEntry {
+    SyntaxEntry {
+        syntax,
+        description,
+    }
+}
+
+pub const MATCHING_ONE_CHARACTER: &[SyntaxEntry] = &[
+    entry(
+        ".",
+        &[
+            Text("Any character except new line (Includes new line with "),
+            Code("s"),
+            Text(" flag)"),
+        ],
+    ),
+    entry(
+        r"\d",
+        &[Text("Digit (Equivalent to "), Code(r"\p{Nd}"), Text(")")],
+    ),
+    entry(r"\D", &[Text("Not digit")]),
+    entry(r"\pN", &[Text("One-letter name Unicode character class")]),
+    entry(
+        r"\p{Greek}",
+        &[Text("Unicode character class (General category or script)")],
+    ),
+    entry(
+        r"\PN",
+        &[Text("Negated one-letter name Unicode character class")],
+    ),
+    entry(
+        r"\P{Greek}",
+        &[Text(
+            "Negated Unicode character class (General category or script)",
+        )],
+    ),
+];
+
+pub const CHARACTER_CLASSES: &[SyntaxEntry] = &[
+    entry(
+        "[xyz]",
+        &[Text("A character class matching either x, y or z (Union)")],
+    ),
+    entry(
+        "[^xyz]",
+        &[Text(
+            "A character class matching any character except x, y and z",
+        )],
+    ),
+    entry(
+        "[a-z]",
+        &[Text(
+            "A character class matching any character in the range a-z",
+        )],
+    ),
+    entry(
+        "[[:alpha:]]",
+        &[
+            Text("ASCII character class (Equivalent to "),
+            Code("[A-Za-z]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:^alpha:]]",
+        &[
+            Text("Negated ASCII character class (Equivalent to "),
+            Code("[^A-Za-z]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[x[^xyz]]",
+        &[Text(
+            "Nested/grouping character class (Matching any character except y and z)",
+        )],
+    ),
+    entry("[a-x&&xyz]", &[Text("Intersection (Matching x or y)")]),
+    entry(
+        "[0-9&&[^4]]",
+        &[Text(
+            "Subtraction using intersection and negative (Matching 0-9 except 4)",
+        )],
+    ),
+    entry(
+        "[0-9--4]",
+        &[Text("Direct subtraction (Matching 0-9 except 4)")],
+    ),
+    entry(
+        "[a-g~~b-h]",
+        &[Text("Symmetric difference (Matching a and h only)")],
+    ),
+    entry(
+        r"[\[\]]",
+        &[Text("Escaping in character classes (Matching [ or ])")],
+    ),
+];
+
+pub const COMPOSITES: &[SyntaxEntry] = &[
+    entry("xy", &[Text("Concatenation (x followed by y)")]),
+    entry("x|y", &[Text("Alternation (x or y, prefer x)")]),
+];
+
+pub const REPETITIONS: &[SyntaxEntry] = &[
+    entry("x*", &[Text("Zero or more of x (Greedy)")]),
+    entry("x+", &[Text("One or more of x (Greedy)")]),
+    entry("x?", &[Text("Zero or one of x (Greedy)")]),
+    entry("x*?", &[Text("Zero or more of x (Ungreedy/lazy)")]),
+    entry("x+?", &[Text("One or more of x (Ungreedy/lazy)")]),
+    entry("x??", &[Text("Zero or one of x (Ungreedy/lazy)")]),
+    entry(
+        "x{n,m}",
+        &[Text("At least n of x and at most m of x (Greedy)")],
+    ),
+    entry("x{n,}", &[Text("At least n of x (Greedy)")]),
+    entry("x{n}", &[Text("Exactly n of x")]),
+    entry(
+        "x{n,m}?",
+        &[Text("At least n of x and at most m of x (Ungreedy/lazy)")],
+    ),
+    entry("x{n,}?", &[Text("At least n of x (Ungreedy/lazy)")]),
+    entry("x{n}?", &[Text("Exactly n of x")]),
+];
+
+pub const EMPTY_MATCHES: &[SyntaxEntry] = &[
+    entry(
+        "^",
+        &[Text(
+            "The beginning of the text (Or the start of a line with multi-line mode enabled)",
+        )],
+    ),
+    entry(
+        "$",
+        &[Text(
+            "The end of the text (Or the end of a line with multi-line mode enabled)",
+        )],
+    ),
+    entry(
+        r"\A",
+        &[Text(
+            "Only the beginning of the text (Even with multi-line mode enabled)",
+        )],
+    ),
+    entry(
+        r"\z",
+        &[Text(
+            "Only the end of the text (Even with multi-line mode enabled)",
+        )],
+    ),
+    entry(
+        r"\b",
+        &[
+            Text("A Unicode word boundary ("),
+            Code(r"\w"),
+            Text(" on one side and "),
+            Code(r"\W"),
+            Text(", "),
+            Code(r"\A"),
+            Text(" or "),
+            Code(r"\a"),
+            Text(" on the other)"),
+        ],
+    ),
+    entry(r"\B", &[Text("Not a Unicode word boundary")]),
+];
+
+pub const GROUPING_AND_FLAGS: &[SyntaxEntry] = &[
+    entry(
+        "(exp)",
+        &[Text(
+            "Numbered capture group (Indexed by opening parenthesis)",
+        )],
+    ),
+    entry(
+        "(?P<name>exp)",
+        &[
+            Text("Named (Also numbered) capture group (Characters allowed for name: "),
+            Code(r"[_0-9a-zA-Z.\[\]]"),
+            Text(")"),
+        ],
+    ),
+    entry("(?:exp)", &[Text("Non-capturing group")]),
+    entry("(?flags)", &[Text("Set flags within current group")]),
+    entry("(?flags:exp)", &[Text("Set flags for exp (Non-capturing)")]),
+];
+
+pub const FLAGS: &[SyntaxEntry] = &[
+    entry(
+        "i",
+        &[Text(
+            "Case-insensitive: Letters match both upper and lower case",
+        )],
+    ),
+    entry(
+        "m",
+        &[
+            Text("Multi-line mode: "),
+            Code("^"),
+            Text(" and "),
+            Code("$"),
+            Text(" match the beginnings and ends of lines"),
+        ],
+    ),
+    entry(
+        "s",
+        &[Text("Allow "), Code("."), Text(" to match "), Code(r"\n")],
+    ),
+    entry(
+        "U",
+        &[
+            Text("Swap the meaning of "),
+            Code("x*"),
+            Text(" and "),
+            Code("x*?"),
+        ],
+    ),
+    entry("u", &[Text("Unicode support (Enabled by default)")]),
+    entry(
+        "x",
+        &[
+            Text("Ignore whitespace and allow line comments (Comments start with "),
+            Code("#"),
+            Text(")"),
+        ],
+    ),
+];
+
+pub const ESCAPE_SEQUENCES: &[SyntaxEntry] = &[
+    entry(
+        r"\*",
+        &[Text(
+            r"Literal *, works for any punctuation character: \.+*?()|[]{}^$",
+        )],
+    ),
+    entry(r"\a", &[Text(r"Bell (\x07)")]),
+    entry(r"\f", &[Text(r"Form feed (\x0C)")]),
+    entry(r"\t", &[Text("Horizontal tab")]),
+    entry(r"\n", &[Text("New line")]),
+    entry(r"\r", &[Text("Carriage return")]),
+    entry(r"\v", &[Text(r"Vertical tab (\x0B)")]),
+    entry(
+        r"\123",
+        &[Text(
+            "Octal character code (Up to three digits) (When enabled)",
+        )],
+    ),
+    entry(r"\x7F", &[Text("Hex character code (Exactly two digits)")]),
+    entry(
+        r"\x{10FFFF}",
+        &[Text(
+            "Any hex character code corresponding to a Unicode code point",
+        )],
+    ),
+    entry(
+        r"\u007F",
+        &[Text("Hex character code (Exactly four digits)")],
+    ),
+    entry(
+        r"\u{7F}",
+        &[Text(
+            "Any hex character code corresponding to a Unicode code point",
+        )],
+    ),
+    entry(
+        r"\U0000007F",
+        &[Text("Hex character code (Exactly eight digits)")],
+    ),
+    entry(
+        r"\U{7F}",
+        &[Text(
+            "Any hex character code corresponding to a Unicode code point",
+        )],
+    ),
+];
+
+pub const PERL_CHARACTER_CLASSES: &[SyntaxEntry] = &[
+    entry(r"\d", &[Text("Digit ("), Code(r"\p{Nd}"), Text(")")]),
+    entry(r"\D", &[Text("Not digit")]),
+    entry(
+        r"\s",
+        &[Text("Whitespace ("), Code(r"\p{White_Space}"), Text(")")],
+    ),
+    entry(r"\S", &[Text("Not whitespace")]),
+    entry(
+        r"\w",
+        &[
+            Text("Word character ("),
+            Code(r"\p{Alphabetic}"),
+            Text(" + "),
+            Code(r"\p{M}"),
+            Text(" + "),
+            Code(r"\d"),
+            Text(" + "),
+            Code(r"\p{Pc}"),
+            Text(" + "),
+            Code(r"\p{Join_Control}"),
+            Text(")"),
+        ],
+    ),
+    entry(r"\W", &[Text("Not word character")]),
+];
+
+pub const ASCII_CHARACTER_CLASSES: &[SyntaxEntry] = &[
+    entry(
+        "[[:alnum:]]",
+        &[
+            Text("Alphanumeric (Equivalent to "),
+            Code("[0-9A-Za-z]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:alpha:]]",
+        &[
+            Text("Alphabetic (Equivalent to "),
+            Code("[A-Za-z]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:ascii:]]",
+        &[
+            Text("ASCII (Equivalent to "),
+            Code(r"[\x00-\x7F]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:blank:]]",
+        &[Text("Blank (Equivalent to "), Code(r"[\t ]"), Text(")")],
+    ),
+    entry(
+        "[[:cntrl:]]",
+        &[
+            Text("Control (Equivalent to "),
+            Code(r"[\x00-\x1F\x7F]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:digit:]]",
+        &[Text("Digits (Equivalent to "), Code("[0-9]"), Text(")")],
+    ),
+    entry(
+        "[[:graph:]]",
+        &[Text("Graphical (Equivalent to "), Code("[!-~]"), Text(")")],
+    ),
+    entry(
+        "[[:lower:]]",
+        &[Text("Lower case (Equivalent to "), Code("[a-z]"), Text(")")],
+    ),
+    entry(
+        "[[:print:]]",
+        &[Text("Printable (Equivalent to "), Code("[ -~]"), Text(")")],
+    ),
+    entry(
+        "[[:punct:]]",
+        &[
+            Text("Punctuation (Equivalent to "),
+            Code(r"[!-/:-@\[-`{-~]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:space:]]",
+        &[
+            Text("Whitespace (Equivalent to "),
+            Code(r"[\t\n\v\f\r ]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:upper:]]",
+        &[Text("Upper case (Equivalent to "), Code("[A-Z]"), Text(")")],
+    ),
+    entry(
+        "[[:word:]]",
+        &[
+            Text("Word characters (Equivalent to "),
+            Code("[0-9A-Za-z_]"),
+            Text(")"),
+        ],
+    ),
+    entry(
+        "[[:xdigit:]]",
+        &[
+            Text("Hex digit (Equivalent to "),
+            Code("[0-9A-Fa-f]"),
+            Text(")"),
+        ],
+    ),
+];
+
+#[cfg(feature = "backend-fancy")]
+pub const FANCY_REGEX_ONLY: &[SyntaxEntry] = &[
+    entry(
+        "(?=exp)",
+        &[Text("Positive lookahead (exp must match next)")],
+    ),
+    entry(
+        "(?!exp)",
+        &[Text("Negative lookahead (exp must not match next)")],
+    ),
+    entry(
+        "(?<=exp)",
+        &[Text(
+            "Positive lookbehind (exp must match just before, and must have a fixed length)",
+        )],
+    ),
+    entry(
+        "(?<!exp)",
+        &[Text(
+            "Negative lookbehind (exp must not match just before, and must have a fixed length)",
+        )],
+    ),
+    entry(
+        r"\1",
+        &[Text("Backreference to the text matched by capture group 1")],
+    ),
+    entry(
+        r"\k<name>",
+        &[Text(
+            "Backreference to the text matched by the named capture group",
+        )],
+    ),
+    entry(
+        "(?>exp)",
+        &[Text(
+            "Atomic group (Never backtracks into exp once it has matched)",
+        )],
+    ),
+    entry(
+        "x*+",
+        &[
+            Text("Possessive repetition (Equivalent to "),
+            Code("(?>x*)"),
+            Text(")"),
+        ],
+    ),
+];
+
+/// Finds the entry in the given table that documents the given syntax
+///
+/// Panics if there is no such entry, as that means the tables and the code that looks them up are out of sync
+fn find(table: &'static [SyntaxEntry], syntax: &str) -> &'static SyntaxEntry {
+    table
+        .iter()
+        .find(|entry| entry.syntax == syntax)
+        .unwrap_or_else(|| panic!("No syntax guide entry for `{}`", syntax))
+}
+
+/// A mapping from the spans of tokens in a regex to the syntax guide entries that document them
+#[derive(Default)]
+pub struct SyntaxSpans {
+    spans: Vec<(Range<usize>, &'static SyntaxEntry)>,
+}
+
+impl SyntaxSpans {
+    /// Finds the tokens in the given AST that are documented in the syntax guide
+    pub fn from_ast(ast: &Ast) -> Self {
+        let mut spans = Self::default();
+        spans.visit_ast(ast);
+        spans
+    }
+
+    /// Returns the byte range and documentation of the innermost token that contains the given byte offset, if any do
+    pub fn entry_at(&self, offset: usize) -> Option<(&Range<usize>, &'static SyntaxEntry)> {
+        self.spans
+            .iter()
+            .filter(|(range, _)| range.contains(&offset))
+            .min_by_key(|(range, _)| range.len())
+            .map(|(range, entry)| (range, *entry))
+    }
+
+    fn push(&mut self, range: Range<usize>, entry: &'static SyntaxEntry) {
+        self.spans.push((range, entry));
+    }
+
+    fn visit_ast(&mut self, ast: &Ast) {
+        match ast {
+            Ast::Empty(_) => {}
+            Ast::Flags(flags) => {
+                self.push(flags.span.range(), find(GROUPING_AND_FLAGS, "(?flags)"))
+            }
+            Ast::Literal(literal) => self.visit_literal(literal),
+            Ast::Dot(span) => self.push(span.range(), find(MATCHING_ONE_CHARACTER, ".")),
+            Ast::Assertion(assertion) => {
+                let syntax = match assertion.kind {
+                    AssertionKind::StartLine => "^",
+                    AssertionKind::EndLine => "$",
+                    AssertionKind::StartText => r"\A",
+                    AssertionKind::EndText => r"\z",
+                    AssertionKind::WordBoundary => r"\b",
+                    AssertionKind::NotWordBoundary => r"\B",
+                };
+                self.push(assertion.span.range(), find(EMPTY_MATCHES, syntax));
+            }
+            Ast::Class(Class::Unicode(class)) => self.visit_unicode_class(class),
+            Ast::Class(Class::Perl(class)) => self.push(
+                class.span.range(),
+                perl_class_entry(&class.kind, class.negated),
+            ),
+            Ast::Class(Class::Bracketed(class)) => self.visit_bracketed_class(class, false),
+            Ast::Repetition(repetition) => {
+                let syntax = match (&repetition.op.kind, repetition.greedy) {
+                    (RepetitionKind::ZeroOrOne, true) => "x?",
+                    (RepetitionKind::ZeroOrOne, false) => "x??",
+                    (RepetitionKind::ZeroOrMore, true) => "x*",
+                    (RepetitionKind::ZeroOrMore, false) => "x*?",
+                    (RepetitionKind::OneOrMore, true) => "x+",
+                    (RepetitionKind::OneOrMore, false) => "x+?",
+                    (RepetitionKind::Range(RepetitionRange::Exactly(_)), true) => "x{n}",
+                    (RepetitionKind::Range(RepetitionRange::Exactly(_)), false) => "x{n}?",
+                    (RepetitionKind::Range(RepetitionRange::AtLeast(_)), true) => "x{n,}",
+                    (RepetitionKind::Range(RepetitionRange::AtLeast(_)), false) => "x{n,}?",
+                    (RepetitionKind::Range(RepetitionRange::Bounded(..)), true) => "x{n,m}",
+                    (RepetitionKind::Range(RepetitionRange::Bounded(..)), false) => "x{n,m}?",
+                };
+                self.push(repetition.op.span.range(), find(REPETITIONS, syntax));
+                self.visit_ast(&repetition.ast);
+            }
+            Ast::Group(group) => {
+                let syntax = match &group.kind {
+                    GroupKind::CaptureIndex(_) => "(exp)",
+                    GroupKind::CaptureName(_) => "(?P<name>exp)",
+                    GroupKind::NonCapturing(flags) if flags.items.is_empty() => "(?:exp)",
+                    GroupKind::NonCapturing(_) => "(?flags:exp)",
+                };
+                self.push(group.span.range(), find(GROUPING_AND_FLAGS, syntax));
+                self.visit_ast(&group.ast);
+            }
+            Ast::Alternation(alternation) => {
+                self.push(alternation.span.range(), find(COMPOSITES, "x|y"));
+                alternation.asts.iter().for_each(|ast| self.visit_ast(ast));
+            }
+            Ast::Concat(concat) => concat.asts.iter().for_each(|ast| self.visit_ast(ast)),
+        }
+    }
+
+    fn visit_literal(&mut self, literal: &Literal) {
+        let syntax = match &literal.kind {
+            LiteralKind::Verbatim | LiteralKind::Special(SpecialLiteralKind::Space) => return,
+            LiteralKind::Punctuation => r"\*",
+            LiteralKind::Octal => r"\123",
+            LiteralKind::HexFixed(HexLiteralKind::X) => r"\x7F",
+            LiteralKind::HexFixed(HexLiteralKind::UnicodeShort) => r"\u007F",
+            LiteralKind::HexFixed(HexLiteralKind::UnicodeLong) => r"\U0000007F",
+            LiteralKind::HexBrace(HexLiteralKind::X) => r"\x{10FFFF}",
+            LiteralKind::HexBrace(HexLiteralKind::UnicodeShort) => r"\u{7F}",
+            LiteralKind::HexBrace(HexLiteralKind::UnicodeLong) => r"\U{7F}",
+            LiteralKind::Special(SpecialLiteralKind::Bell) => r"\a",
+            LiteralKind::Special(SpecialLiteralKind::FormFeed) => r"\f",
+            LiteralKind::Special(SpecialLiteralKind::Tab) => r"\t",
+            LiteralKind::Special(SpecialLiteralKind::LineFeed) => r"\n",
+            LiteralKind::Special(SpecialLiteralKind::CarriageReturn) => r"\r",
+            LiteralKind::Special(SpecialLiteralKind::VerticalTab) => r"\v",
+        };
+        self.push(literal.span.range(), find(ESCAPE_SEQUENCES, syntax));
+    }
+
+    fn visit_unicode_class(&mut self, class: &ClassUnicode) {
+        let syntax = match (&class.kind, class.negated) {
+            (ClassUnicodeKind::OneLetter(_), false) => r"\pN",
+            (ClassUnicodeKind::OneLetter(_), true) => r"\PN",
+            (_, false) => r"\p{Greek}",
+            (_, true) => r"\P{Greek}",
+        };
+        self.push(class.span.range(), find(MATCHING_ONE_CHARACTER, syntax));
+    }
+
+    fn visit_bracketed_class(&mut self, class: &ClassBracketed, nested: bool) {
+        let syntax = match (nested, class.negated) {
+            (true, _) => "[x[^xyz]]",
+            (false, false) => "[xyz]",
+            (false, true) => "[^xyz]",
+        };
+        self.push(class.span.range(), find(CHARACTER_CLASSES, syntax));
+        self.visit_class_set(&class.kind);
+    }
+
+    fn visit_class_set(&mut self, set: &ClassSet) {
+        match set {
+            ClassSet::Item(item) => self.visit_class_set_item(item),
+            ClassSet::BinaryOp(op) => {
+                let syntax = match op.kind {
+                    ClassSetBinaryOpKind::Intersection => "[a-x&&xyz]",
+                    ClassSetBinaryOpKind::Difference => "[0-9--4]",
+                    ClassSetBinaryOpKind::SymmetricDifference => "[a-g~~b-h]",
+                };
+                self.push(op.span.range(), find(CHARACTER_CLASSES, syntax));
+                self.visit_class_set(&op.lhs);
+                self.visit_class_set(&op.rhs);
+            }
+        }
+    }
+
+    fn visit_class_set_item(&mut self, item: &ClassSetItem) {
+        match item {
+            ClassSetItem::Empty(_) => {}
+            ClassSetItem::Literal(literal) => self.visit_literal(literal),
+            ClassSetItem::Range(range) => {
+                self.push(range.span.range(), find(CHARACTER_CLASSES, "[a-z]"));
+                self.visit_literal(&range.start);
+                self.visit_literal(&range.end);
+            }
+            ClassSetItem::Ascii(class) => {
+                let entry = if class.negated {
+                    find(CHARACTER_CLASSES, "[[:^alpha:]]")
+                } else {
+                    ascii_class_entry(&class.kind)
+                };
+                self.push(class.span.range(), entry);
+            }
+            ClassSetItem::Unicode(class) => self.visit_unicode_class(class),
+            ClassSetItem::Perl(class) => self.push(
+                class.span.range(),
+                perl_class_entry(&class.kind, class.negated),
+            ),
+            ClassSetItem::Bracketed(class) => self.visit_bracketed_class(class, true),
+            ClassSetItem::Union(union) => union
+                .items
+                .iter()
+                .for_each(|item| self.visit_class_set_item(item)),
+        }
+    }
+}
+
+fn perl_class_entry(kind: &ClassPerlKind, negated: bool) -> &'static SyntaxEntry {
+    let syntax = match (kind, negated) {
+        (ClassPerlKind::Digit, false) => r"\d",
+        (ClassPerlKind::Digit, true) => r"\D",
+        (ClassPerlKind::Space, false) => r"\s",
+        (ClassPerlKind::Space, true) => r"\S",
+        (ClassPerlKind::Word, false) => r"\w",
+        (ClassPerlKind::Word, true) => r"\W",
+    };
+    find(PERL_CHARACTER_CLASSES, syntax)
+}
+
+fn ascii_class_entry(kind: &ClassAsciiKind) -> &'static SyntaxEntry {
+    let syntax = match kind {
+        ClassAsciiKind::Alnum => "[[:alnum:]]",
+        ClassAsciiKind::Alpha => "[[:alpha:]]",
+        ClassAsciiKind::Ascii => "[[:ascii:]]",
+        ClassAsciiKind::Blank => "[[:blank:]]",
+        ClassAsciiKind::Cntrl => "[[:cntrl:]]",
+        ClassAsciiKind::Digit => "[[:digit:]]",
+        ClassAsciiKind::Graph => "[[:graph:]]",
+        ClassAsciiKind::Lower => "[[:lower:]]",
+        ClassAsciiKind::Print => "[[:print:]]",
+        ClassAsciiKind::Punct => "[[:punct:]]",
+        ClassAsciiKind::Space => "[[:space:]]",
+        ClassAsciiKind::Upper => "[[:upper:]]",
+        ClassAsciiKind::Word => "[[:word:]]",
+        ClassAsciiKind::Xdigit => "[[:xdigit:]]",
+    };
+    find(ASCII_CHARACTER_CLASSES, syntax)
+}
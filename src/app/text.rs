@@ -1,16 +1,21 @@
 use super::{
     color,
-    color::FromBackgroundExt,
-    parsing::{ast_find_capture_groups, RegexError},
+    color::{CustomGroupColors, FromBackgroundExt, GroupColor},
+    parsing::{
+        ast_find_capture_groups, ast_find_capture_names, ast_find_tokens, RegexError, TokenKind,
+    },
+    replacement::ResolvedReference,
+    timings::timed,
 };
 use eframe::epaint::text::Row;
 use egui::{
     text::{LayoutJob, LayoutSection},
-    Color32, FontId, Rect, Style, TextFormat, TextStyle,
+    Color32, FontId, Pos2, Rect, Stroke, Style, TextFormat, TextStyle,
 };
 use regex::Regex;
 use regex_syntax::ast::{Ast, Span};
-use std::ops::{ControlFlow, Range};
+use std::{cmp::Reverse, ops::Range, time::Duration};
+use unicode_width::UnicodeWidthStr;
 
 pub trait GetRangeExt {
     fn range(&self) -> Range<usize>;
@@ -22,18 +27,121 @@ impl GetRangeExt for Span {
     }
 }
 
-fn convert_byte_range_to_char_range(range: Range<usize>, text: &str) -> Option<Range<usize>> {
-    let head = text.get(0..range.start)?;
-    let tail = text.get(range)?;
-    let head_offset = str_glyph_count(head);
-    let tail_offset = head_offset + str_glyph_count(tail);
-    Some(head_offset..tail_offset)
+/// An index over a string for converting between byte offsets, char offsets, line and column positions,
+/// and glyph offsets, built once per string so that each conversion doesn't need to rescan it from the start
+///
+/// Glyph offsets are char offsets that don't count newlines (`\n`), as egui excludes those when laying out text into glyphs
+#[derive(Clone)]
+pub struct TextIndex {
+    /// The byte offset of each char in the string, followed by the length of the string
+    char_starts: Vec<usize>,
+    /// The char offset at which each line in the string starts
+    line_starts: Vec<usize>,
+    /// The glyph offset at which each line in the string starts
+    line_glyph_starts: Vec<usize>,
 }
 
-/// Counts the number of chars in the given string, excluding newlines (`\n`),
-/// as egui excludes those when laying out text into glyphs
-fn str_glyph_count(text: &str) -> usize {
-    text.chars().count() - text.matches('\n').count()
+impl Default for TextIndex {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextIndex {
+    pub fn new(text: &str) -> Self {
+        let mut char_starts = Vec::with_capacity(text.len() + 1);
+        let mut line_starts = vec![0];
+        for (index, (byte, c)) in text.char_indices().enumerate() {
+            char_starts.push(byte);
+            if c == '\n' {
+                line_starts.push(index + 1);
+            }
+        }
+        char_starts.push(text.len());
+
+        // Every line before each line ends with exactly one newline, which isn't a glyph
+        let line_glyph_starts = line_starts
+            .iter()
+            .enumerate()
+            .map(|(line, start)| start - line)
+            .collect();
+
+        Self {
+            char_starts,
+            line_starts,
+            line_glyph_starts,
+        }
+    }
+
+    /// Converts a byte offset into a char offset, returning `None` if it isn't on a char boundary
+    pub fn byte_to_char(&self, byte: usize) -> Option<usize> {
+        self.char_starts.binary_search(&byte).ok()
+    }
+
+    /// Converts a char offset into a byte offset, returning `None` if it is past the end of the string
+    pub fn char_to_byte(&self, char: usize) -> Option<usize> {
+        self.char_starts.get(char).copied()
+    }
+
+    /// Converts a byte offset into a zero-based line number and a column measured in chars,
+    /// returning `None` if it isn't on a char boundary
+    pub fn byte_to_line_col(&self, byte: usize) -> Option<(usize, usize)> {
+        let char = self.byte_to_char(byte)?;
+        let line = self.line_of_char(char);
+        Some((line, char - self.line_starts[line]))
+    }
+
+    /// Converts a byte offset into a glyph offset, returning `None` if it isn't on a char boundary
+    pub fn glyph_offset(&self, byte: usize) -> Option<usize> {
+        let char = self.byte_to_char(byte)?;
+        // Every line before the one containing the char ends with exactly one newline, which isn't a glyph
+        Some(char - self.line_of_char(char))
+    }
+
+    /// Converts a glyph offset into the byte offset of the corresponding char,
+    /// returning `None` if it is past the end of the string
+    pub fn glyph_to_byte(&self, glyph: usize) -> Option<usize> {
+        let line = self
+            .line_glyph_starts
+            .partition_point(|&start| start <= glyph)
+            - 1;
+
+        // The end of the string is not a glyph
+        let char = glyph + line;
+        if char + 1 >= self.char_starts.len() {
+            return None;
+        }
+        self.char_to_byte(char)
+    }
+
+    /// Converts a range of byte offsets into a range of glyph offsets,
+    /// returning `None` if either end isn't on a char boundary
+    pub fn byte_range_to_glyph_range(&self, range: Range<usize>) -> Option<Range<usize>> {
+        Some(self.glyph_offset(range.start)?..self.glyph_offset(range.end)?)
+    }
+
+    /// Converts a range of byte offsets into a range of glyph offsets, widening it to the nearest char boundaries if
+    /// either end isn't on one, and truncating it to the end of the string if it goes past it
+    pub fn byte_range_to_glyph_range_clamped(&self, range: Range<usize>) -> Range<usize> {
+        let last_char = self.char_starts.len() - 1;
+        let start = (self
+            .char_starts
+            .partition_point(|&start| start <= range.start)
+            - 1)
+        .min(last_char);
+        let end = self
+            .char_starts
+            .partition_point(|&start| start < range.end)
+            .clamp(start, last_char);
+
+        // Every line before the one containing each char ends with exactly one newline, which isn't a glyph
+        start - self.line_of_char(start)..end - self.line_of_char(end)
+    }
+
+    /// Returns the index of the line containing the char at the given char offset
+    fn line_of_char(&self, char: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= char) - 1
+    }
 }
 
 /// A mapping of bytes in a string to corresponding TextFormats, not yet converted to a full LayoutJob
@@ -88,6 +196,29 @@ impl TextFormatMapping {
         }
     }
 
+    /// Replaces the format of each byte in the given range with one derived from its current format by the given function,
+    /// without changing the formats of any bytes outside of the range, even if they are in the same section
+    pub fn override_range_format(
+        &mut self,
+        range: Range<usize>,
+        derive: impl Fn(&TextFormat) -> TextFormat,
+    ) {
+        let mapping = match self.mapping.get_mut(range) {
+            Some(mapping) => mapping,
+            None => return,
+        };
+
+        // Each format only needs to be derived once, however many bytes in the range use it
+        let mut derived = vec![None; self.formats.len()];
+        for index in mapping {
+            let original = *index;
+            *index = *derived[original].get_or_insert_with(|| {
+                self.formats.push(derive(&self.formats[original]));
+                self.formats.len() - 1
+            });
+        }
+    }
+
     pub fn convert_to_layout_job(self) -> LayoutJob {
         let sections = self.build_layout_sections();
         LayoutJob {
@@ -144,14 +275,52 @@ pub struct RegexLayout {
     /// correspond to those capture groups, as well as the depth of the capture group in the regex ast
     pub capture_group_chars: Vec<(usize, Range<usize>)>,
     /// The colors used to highlight each capture group in the regex
-    pub capture_group_colors: Vec<Color32>,
+    pub capture_group_colors: Vec<GroupColor>,
+    /// The index in the palette of group colors of the color of each capture group, indexed like `capture_group_chars`
+    pub capture_group_palette: Vec<usize>,
+    /// The name of each capture group, if it has one, indexed like `capture_group_chars`
+    pub capture_group_names: Vec<Option<String>>,
+    /// How many other capture groups each capture group is nested inside of, indexed like `capture_group_chars`
+    pub group_nesting: Vec<usize>,
+    /// Whether the regex has so many capture groups that highlighting each of them would be meaningless,
+    /// so only the boundaries of capture groups are highlighted, and they all share the same color
+    pub degraded: bool,
+}
+
+impl RegexLayout {
+    /// Returns whether the capture group at the given index, indexed like `capture_group_chars`,
+    /// has any other capture groups nested inside of it
+    pub fn encloses_groups(&self, index: usize) -> bool {
+        // Groups are ordered by where they start, so if a group encloses any others, the one after it is one of them
+        matches!(
+            (self.group_nesting.get(index), self.group_nesting.get(index + 1)),
+            (Some(outer), Some(inner)) if inner > outer
+        )
+    }
+
+    /// Returns the index of the innermost capture group that contains the glyph at the given index, if any do
+    pub fn group_at_glyph(&self, glyph: usize) -> Option<usize> {
+        innermost_group(
+            self.capture_group_chars
+                .iter()
+                .enumerate()
+                .map(|(index, (_, range))| (index + 1, range)),
+            glyph,
+        )
+    }
 }
 
+/// Returns information about how a regular expression should be rendered
+///
+/// If the regex has more capture groups than `group_threshold`, it is laid out in a degraded form,
+/// see `RegexLayout::degraded`
 pub fn layout_regex(
     regex: String,
     ast: &Ast,
     style: &Style,
-    _previous_layout: Option<&RegexLayout>,
+    group_threshold: Option<usize>,
+    custom_colors: &CustomGroupColors,
+    previous_layout: Option<&RegexLayout>,
 ) -> RegexLayout {
     if regex.is_empty() {
         return Default::default();
@@ -159,37 +328,100 @@ pub fn layout_regex(
 
     // Find the spans of each of the capture groups in the regular expression
     let (depths, ranges) = ast_find_capture_groups(ast);
+    let capture_group_names = ast_find_capture_names(ast);
+    let degraded = matches!(group_threshold, Some(threshold) if ranges.len() > threshold);
+
+    // Convert the byte ranges into char ranges, to later be used to index into the glyphs of the layed out galley
+    let index = TextIndex::new(&regex);
+    let glyph_ranges = ranges
+        .iter()
+        // Spans should always be on char boundaries, but a panic would take the whole app down with it
+        .map(|range| index.byte_range_to_glyph_range_clamped(range.clone()))
+        .collect::<Vec<_>>();
 
     // Calculate the color that each capture group will have
+    let capture_group_palette = if degraded {
+        vec![0; ranges.len()]
+    } else {
+        assign_group_colors(
+            &regex,
+            &glyph_ranges,
+            &capture_group_names,
+            previous_layout.filter(|previous| !previous.degraded),
+        )
+    };
+
     // Capture groups are 1-indexed, so prepend a placeholder color for the 0th index
-    let capture_group_colors = std::iter::once(Color32::TRANSPARENT)
+    let capture_group_colors = std::iter::once(GroupColor::NONE)
         .chain(
-            color::BACKGROUND_COLORS
-                .into_iter()
-                .cycle()
-                .take(ranges.len()),
+            capture_group_palette
+                .iter()
+                .map(|&index| custom_colors.get(index, style.visuals.dark_mode)),
         )
         .collect::<Vec<_>>();
 
-    let sections = build_layout_sections(
-        &mut vec![0; regex.len()],
-        ranges.iter().cloned().enumerate(),
-        TextStyle::Monospace.resolve(style),
-        &capture_group_colors,
-    );
+    let font_id = TextStyle::Monospace.resolve(style);
+    let sections = if degraded {
+        // Only highlight the parentheses at the boundaries of each capture group
+        let mut mapping = vec![0; regex.len()];
+        for range in &ranges {
+            mapping[range.start] = 1;
+            mapping[range.end - 1] = 1;
+        }
 
-    let max_depth = *depths.iter().max().unwrap_or(&0);
+        let formats = vec![
+            TextFormat::simple(font_id.clone(), style.visuals.text_color()),
+            TextFormat::simple(
+                font_id,
+                custom_colors.get(0, style.visuals.dark_mode).foreground,
+            ),
+        ];
+        TextFormatMapping::new(regex.clone(), mapping, formats)
+            .convert_to_layout_job()
+            .sections
+    } else {
+        // Mark each byte of the pattern with the kind of token that it is part of
+        let mut token_colors = vec![None; regex.len()];
+        for (kind, range) in ast_find_tokens(ast) {
+            token_colors[range].fill(Some(kind));
+        }
 
-    // Convert the byte ranges into char ranges, to later be used to index into the glyphs of the layed out galley
+        build_layout_sections(
+            &mut vec![0; regex.len()],
+            ranges.iter().cloned().enumerate(),
+            &token_colors,
+            style.visuals.dark_mode,
+            font_id,
+            &capture_group_colors,
+        )
+    };
+
+    // Capture groups are ordered by where they start, so every group that contains a group comes before it
+    let mut enclosing: Vec<&Range<usize>> = Vec::new();
+    let group_nesting = ranges
+        .iter()
+        .map(|range| {
+            while enclosing
+                .last()
+                .map_or(false, |outer| outer.end <= range.start)
+            {
+                enclosing.pop();
+            }
+            enclosing.push(range);
+            enclosing.len() - 1
+        })
+        .collect();
+
+    let max_depth = *depths.iter().max().unwrap_or(&0);
     let capture_group_chars = depths
         .into_iter()
-        .zip(ranges)
+        .zip(glyph_ranges)
         .map(|(depth, range)| {
             (
                 // Invert the depth value, as it will eventually be used as the thickness of the connecting line,
                 // so shallower lines should be thicker than deeper lines that may be rendered ontop of them
                 (0..=max_depth).nth_back(depth).unwrap(),
-                convert_byte_range_to_char_range(range, &regex).unwrap(),
+                range,
             )
         })
         .collect();
@@ -202,57 +434,221 @@ pub fn layout_regex(
         },
         capture_group_chars,
         capture_group_colors,
+        capture_group_palette,
+        capture_group_names,
+        group_nesting,
+        degraded,
     }
 }
 
-/// Returns information about how a malformed regular expression string should be rendered
-pub fn layout_regex_err(regex: String, style: &Style, err: &RegexError) -> RegexLayout {
-    let (span, aux) = match err {
-        RegexError::Parse(e) => (Some(e.span()), e.auxiliary_span()),
-        RegexError::Compile(_) => (None, None),
-    };
+/// Chooses the index in the palette of group colors of each capture group of a regex, given the char ranges and names
+/// of the groups, keeping the colors that the same groups had in the previous layout of the regex, if there is one,
+/// so that editing one part of the regex doesn't change the colors of the groups in the rest of it
+///
+/// Groups are matched up with those in the previous layout by name if they have one, then by how much their spans
+/// overlap once the edit is accounted for; only the groups that can't be matched up are given fresh colors
+fn assign_group_colors(
+    regex: &str,
+    ranges: &[Range<usize>],
+    names: &[Option<String>],
+    previous: Option<&RegexLayout>,
+) -> Vec<usize> {
+    let mut colors = vec![None; ranges.len()];
 
-    fn plaintext(byte_range: Range<usize>, font_id: FontId) -> LayoutSection {
-        LayoutSection {
-            leading_space: 0.0,
-            byte_range,
-            format: TextFormat::simple(font_id, color::FG_RED),
+    if let Some(previous) = previous {
+        let old_chars = previous.job.text.chars().collect::<Vec<_>>();
+        let new_chars = regex.chars().collect::<Vec<_>>();
+        let old_groups = &previous.capture_group_chars;
+        let mut taken = vec![false; old_groups.len()];
+
+        // Groups with the same name are the same group, however much they were edited
+        for (new, name) in names.iter().enumerate() {
+            let old = previous
+                .capture_group_names
+                .iter()
+                .position(|old| old.is_some() && old == name);
+            if let Some(old) = old.filter(|&old| old < old_groups.len()) {
+                colors[new] = previous.capture_group_palette.get(old).copied();
+                taken[old] = true;
+            }
+        }
+
+        // Treat the edit as replacing a single run of chars, between a prefix and suffix that weren't changed
+        let prefix = old_chars
+            .iter()
+            .zip(&new_chars)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = old_chars[prefix..]
+            .iter()
+            .rev()
+            .zip(new_chars[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let old_edit_end = old_chars.len() - suffix;
+        let new_edit_end = new_chars.len() - suffix;
+
+        // Positions before the edit stay where they are, those after it move with the suffix,
+        // and those within it are moved to whichever end of the edit keeps the group the largest
+        let move_start = |start: usize| match start {
+            start if start >= old_edit_end => start - old_edit_end + new_edit_end,
+            start => start.min(prefix),
+        };
+        let move_end = |end: usize| match end {
+            end if end <= prefix => end,
+            end => end.max(old_edit_end) - old_edit_end + new_edit_end,
+        };
+
+        let mut candidates = Vec::new();
+        for (old, (_, old_range)) in old_groups.iter().enumerate() {
+            let moved = move_start(old_range.start)..move_end(old_range.end);
+            for (new, new_range) in ranges.iter().enumerate() {
+                let overlap =
+                    moved.end.min(new_range.end) as f32 - moved.start.max(new_range.start) as f32;
+                if overlap <= 0.0 || colors[new].is_some() || taken[old] {
+                    continue;
+                }
+
+                let union = moved.end.max(new_range.end) - moved.start.min(new_range.start);
+                // Groups of the same text are more likely to be the same group if the edit is ambiguous,
+                // such as whether `(a)` becomes `(a)(b)` by appending `(b)` or by inserting `)(b` after `(a`
+                let same_text =
+                    old_chars.get(old_range.clone()) == new_chars.get(new_range.clone());
+                candidates.push((overlap / union as f32, same_text, old, new));
+            }
+        }
+
+        // Match up the groups that overlap the most first
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)));
+        for (_, _, old, new) in candidates {
+            if colors[new].is_none() && !taken[old] {
+                colors[new] = previous.capture_group_palette.get(old).copied();
+                taken[old] = true;
+            }
+        }
+    }
+
+    // Give each new group a color that no other group has,
+    // preferring the color it would have had if the colors were simply taken from the palette in order
+    for new in 0..colors.len() {
+        if colors[new].is_none() {
+            colors[new] = std::iter::once(new)
+                .chain(0..)
+                .find(|&index| !colors.contains(&Some(index)));
+        }
+    }
+
+    colors.into_iter().flatten().collect()
+}
+
+/// Highlights the given span of a laid out text with the given background color, splitting the sections that the span
+/// starts or ends part way through
+pub fn highlight_span(job: &mut LayoutJob, span: Range<usize>, background: Color32) {
+    let mut sections = Vec::with_capacity(job.sections.len() + 2);
+    for section in job.sections.drain(..) {
+        let range = section.byte_range.clone();
+        let inside = span.start.max(range.start)..span.end.min(range.end);
+        if inside.start >= inside.end {
+            sections.push(section);
+            continue;
+        }
+
+        let parts = [
+            (range.start..inside.start, false),
+            (inside.clone(), true),
+            (inside.end..range.end, false),
+        ];
+        for (byte_range, highlighted) in parts {
+            if byte_range.is_empty() {
+                continue;
+            }
+
+            let mut format = section.format.clone();
+            if highlighted {
+                format.background = background;
+            }
+            sections.push(LayoutSection {
+                leading_space: 0.0,
+                byte_range,
+                format,
+            });
         }
     }
+    job.sections = sections;
+}
+
+/// Returns information about how a malformed regular expression string should be rendered
+///
+/// The parts of the regex that are unchanged from `previous_layout`, the layout of the last regex that compiled,
+/// keep the highlighting they had in it, so that only the edited part of the regex and the error itself are shown in red
+pub fn layout_regex_err(
+    regex: String,
+    style: &Style,
+    err: &RegexError,
+    previous_layout: Option<&RegexLayout>,
+) -> RegexLayout {
+    let (span, aux) = (err.span(), err.auxiliary_span());
 
-    fn highlight(byte_range: Range<usize>, font_id: FontId) -> LayoutSection {
-        LayoutSection {
+    let dark_mode = style.visuals.dark_mode;
+    let unchanged = previous_layout
+        .map(|previous| unchanged_regions(&previous.job.text, &regex))
+        .unwrap_or_default();
+    let plaintext = |byte_range: Range<usize>, font_id: FontId| {
+        let error = |byte_range| LayoutSection {
             leading_space: 0.0,
             byte_range,
-            format: TextFormat {
-                font_id,
-                color: Color32::WHITE,
-                background: color::BG_RED,
-                ..Default::default()
-            },
+            format: TextFormat::simple(font_id.clone(), color::error_color(dark_mode)),
+        };
+        match previous_layout {
+            Some(previous) => {
+                retain_sections(byte_range, &unchanged, &previous.job.sections, error)
+            }
+            None => vec![error(byte_range)],
         }
-    }
+    };
+    let highlight = |byte_range: Range<usize>, font_id: FontId| {
+        vec![LayoutSection {
+            leading_space: 0.0,
+            byte_range,
+            format: color::error_highlight(font_id, dark_mode),
+        }]
+    };
 
     let font_id = TextStyle::Monospace.resolve(style);
 
     let sections = match (span, aux) {
-        (None, _) => vec![highlight(0..regex.len(), font_id)],
-        (Some(span), None) => vec![
+        (None, _) => highlight(0..regex.len(), font_id),
+        (Some(span), None) => [
             plaintext(0..span.start.offset, font_id.clone()),
             highlight(span.range(), font_id.clone()),
             plaintext(span.end.offset..regex.len(), font_id),
-        ],
+        ]
+        .concat(),
         (Some(span), Some(aux)) => {
             let min = span.min(aux);
             let max = span.max(aux);
 
-            vec![
+            // The auxiliary span is only underlined, so that it isn't mistaken for where the error is
+            let format = |range: Range<usize>, font_id: FontId| {
+                if range == aux.range() && range != span.range() {
+                    vec![LayoutSection {
+                        leading_space: 0.0,
+                        byte_range: range,
+                        format: color::error_aux_highlight(font_id, dark_mode),
+                    }]
+                } else {
+                    highlight(range, font_id)
+                }
+            };
+
+            [
                 plaintext(0..min.start.offset, font_id.clone()),
-                highlight(min.range(), font_id.clone()),
+                format(min.range(), font_id.clone()),
                 plaintext(min.end.offset..max.start.offset, font_id.clone()),
-                highlight(max.range(), font_id.clone()),
+                format(max.range(), font_id.clone()),
                 plaintext(max.end.offset..regex.len(), font_id),
             ]
+            .concat()
         }
     };
 
@@ -264,7 +660,216 @@ pub fn layout_regex_err(regex: String, style: &Style, err: &RegexError) -> Regex
         },
         capture_group_chars: vec![],
         capture_group_colors: vec![],
+        capture_group_palette: vec![],
+        capture_group_names: vec![],
+        group_nesting: vec![],
+        degraded: false,
+    }
+}
+
+/// Finds the common prefix and suffix of an old and a new version of some text,
+/// returning the byte range of each in the new text along with how far it was moved from where it was in the old text
+pub fn unchanged_regions(old: &str, new: &str) -> Vec<(Range<usize>, isize)> {
+    let prefix = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(old, new)| old == new)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+
+    // The suffix can't overlap the prefix in either text
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .chars()
+        .rev()
+        .zip(new.chars().rev())
+        .take_while(|(old, new)| old == new)
+        .scan(0, |len, (c, _)| {
+            *len += c.len_utf8();
+            (*len <= max_suffix).then_some(*len)
+        })
+        .last()
+        .unwrap_or_default();
+
+    let moved_by = new.len() as isize - old.len() as isize;
+    vec![(0..prefix, 0), (new.len() - suffix..new.len(), moved_by)]
+}
+
+/// Returns sections covering the given byte range of some new text, which are moved from the given sections of
+/// the old text where the range overlaps the given regions that are unchanged from the old text,
+/// and are created with `fallback` elsewhere
+fn retain_sections(
+    range: Range<usize>,
+    unchanged: &[(Range<usize>, isize)],
+    old_sections: &[LayoutSection],
+    fallback: impl Fn(Range<usize>) -> LayoutSection,
+) -> Vec<LayoutSection> {
+    let mut sections = Vec::new();
+    let mut start = range.start;
+    for (region, moved_by) in unchanged {
+        let overlap = start.max(region.start)..range.end.min(region.end);
+        if overlap.is_empty() {
+            continue;
+        }
+
+        if start < overlap.start {
+            sections.push(fallback(start..overlap.start));
+        }
+        for old in old_sections {
+            let moved_to = |offset: usize| (offset as isize + moved_by).max(0) as usize;
+            let moved = moved_to(old.byte_range.start)..moved_to(old.byte_range.end);
+            let byte_range = moved.start.max(overlap.start)..moved.end.min(overlap.end);
+            if !byte_range.is_empty() {
+                sections.push(LayoutSection {
+                    leading_space: 0.0,
+                    byte_range,
+                    format: old.format.clone(),
+                });
+            }
+        }
+        start = overlap.end;
+    }
+
+    if start < range.end || sections.is_empty() {
+        sections.push(fallback(start..range.end));
+    }
+    sections
+}
+
+/// Returns a layout job that points out the spans of an error in the given regex,
+/// with one line per span that marks it with carets (Or dashes for the auxiliary span) followed by its label,
+/// which lines up with the regex when both are laid out in the monospace font
+///
+/// Returns `None` if the spans of the error aren't known
+pub fn layout_error_annotation(regex: &str, style: &Style, err: &RegexError) -> Option<LayoutJob> {
+    let font_id = TextStyle::Monospace.resolve(style);
+    let dark_mode = style.visuals.dark_mode;
+    let mut job = LayoutJob::default();
+    for (index, (span, label)) in err.labeled_spans().into_iter().enumerate() {
+        // Only the line of the regex that the span starts on is lined up with
+        let range = span.range();
+        let before = regex.get(..range.start).unwrap_or_default();
+        let indent = before.rsplit('\n').next().unwrap_or_default().width();
+        let spanned = regex.get(range).unwrap_or_default();
+        let width = spanned
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .width()
+            .max(1);
+        let (marker, color) = match index {
+            0 => ('^', color::error_color(dark_mode)),
+            _ => ('-', style.visuals.weak_text_color()),
+        };
+
+        let line_break = if index == 0 { "" } else { "\n" };
+        let text = format!(
+            "{}{}{} {}",
+            line_break,
+            " ".repeat(indent),
+            marker.to_string().repeat(width),
+            label
+        );
+        job.append(&text, 0.0, TextFormat::simple(font_id.clone(), color));
+    }
+    (!job.text.is_empty()).then_some(job)
+}
+
+/// Returns a layout job for an output template, highlighting the part of it that is malformed
+pub fn layout_template_err(template: &str, style: &Style, span: Range<usize>) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(style);
+    let dark_mode = style.visuals.dark_mode;
+    let mut job = LayoutJob::default();
+    job.append(
+        &template[..span.start],
+        0.0,
+        TextFormat::simple(font_id.clone(), color::error_color(dark_mode)),
+    );
+    job.append(
+        &template[span.clone()],
+        0.0,
+        color::error_highlight(font_id.clone(), dark_mode),
+    );
+    job.append(
+        &template[span.end..],
+        0.0,
+        TextFormat::simple(font_id, color::error_color(dark_mode)),
+    );
+    job
+}
+
+/// Lays out the result of replacing matches, highlighting each of the given spans that a match was replaced with,
+/// taking the colors of capture groups in turn so that the replacements of adjacent matches can be told apart
+///
+/// Matches that were replaced with nothing leave an empty span, which there is nothing to highlight in
+pub fn layout_result_text(
+    text: &str,
+    style: &Style,
+    replaced_spans: &[Range<usize>],
+    custom_colors: &CustomGroupColors,
+) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(style);
+    let plain = TextFormat::simple(font_id.clone(), style.visuals.text_color());
+
+    let mut job = LayoutJob::default();
+    let mut end = 0;
+    for (index, span) in replaced_spans.iter().enumerate() {
+        // The spans are of the text that the result was laid out for last time, which may have changed since
+        if span.start < end || text.get(span.clone()).is_none() {
+            break;
+        }
+
+        job.append(&text[end..span.start], 0.0, plain.clone());
+        if !span.is_empty() {
+            let color = custom_colors.get(index, style.visuals.dark_mode).background;
+            job.append(
+                &text[span.clone()],
+                0.0,
+                TextFormat::background(font_id.clone(), color),
+            );
+        }
+        end = span.end;
     }
+    job.append(&text[end..], 0.0, plain);
+    job
+}
+
+/// Lays out a replacement string, highlighting each reference to a capture group with the color of that group at the
+/// same index in `colors`, and each reference to a group that doesn't exist in red, unless it is underlined in yellow
+/// as one of the given ambiguous references
+pub fn layout_replacement(
+    text: &str,
+    style: &Style,
+    references: &[ResolvedReference],
+    ambiguous: &[Range<usize>],
+    colors: &[GroupColor],
+) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(style);
+    let plain = TextFormat::simple(font_id.clone(), style.visuals.text_color());
+
+    let mut job = LayoutJob::default();
+    let mut end = 0;
+    for reference in references {
+        let format = match reference.group {
+            _ if ambiguous.contains(&reference.span) => TextFormat {
+                underline: Stroke::new(2.0, Color32::YELLOW),
+                ..plain.clone()
+            },
+            Some(index) => match colors.get(index) {
+                Some(color) if color.background != Color32::TRANSPARENT => {
+                    TextFormat::background(font_id.clone(), color.background)
+                }
+                _ => plain.clone(),
+            },
+            None => color::error_highlight(font_id.clone(), style.visuals.dark_mode),
+        };
+
+        job.append(&text[end..reference.span.start], 0.0, plain.clone());
+        job.append(&text[reference.span.clone()], 0.0, format);
+        end = reference.span.end;
+    }
+    job.append(&text[end..], 0.0, plain);
+    job
 }
 
 /// Information about how text that was matched against a regex should be rendered
@@ -275,48 +880,290 @@ pub struct MatchedTextLayout {
     /// A vec of mappings from the indexes of capture groups in the regex to the parts of the text that were
     /// matched by that capture group, with one mapping for each overall match in the text
     pub capture_group_chars: Vec<Vec<Option<Range<usize>>>>,
+    /// An index over the matched text, if one was needed to lay it out
+    pub text_index: TextIndex,
+    /// How much of the text was matched
+    pub coverage: MatchCoverage,
+}
+
+/// How many bytes of a text were matched by a regex, overall and by each of its capture groups
+#[derive(Default)]
+pub struct MatchCoverage {
+    /// The length of the text in bytes
+    pub total_bytes: usize,
+    /// The number of bytes that are inside of any whole match
+    pub matched_bytes: usize,
+    /// The number of bytes that are inside of any match of each capture group, indexed by capture group index
+    /// (Index 0 is the implicit capture group corresponding to the whole match); empty if captures weren't extracted
+    pub group_bytes: Vec<usize>,
+}
+
+impl MatchCoverage {
+    /// Returns the fraction of the text that is covered by the given number of bytes, as a percentage
+    pub fn percentage(&self, bytes: usize) -> f32 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            bytes as f32 / self.total_bytes as f32 * 100.0
+        }
+    }
+}
+
+/// Returns the number of positions that are inside of any of the given ranges, counting overlapping parts only once
+pub fn union_length(mut ranges: Vec<Range<usize>>) -> usize {
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut length = 0;
+    let mut covered_until = 0;
+    for range in ranges {
+        let start = range.start.max(covered_until);
+        if range.end > start {
+            length += range.end - start;
+            covered_until = range.end;
+        }
+    }
+    length
+}
+
+impl MatchedTextLayout {
+    /// Returns the index of the match that the glyph at the given index is in, along with the index of the innermost
+    /// capture group in that match that matched it, if any capture group did
+    pub fn match_at_glyph(&self, glyph: usize) -> Option<(usize, usize)> {
+        innermost_group(
+            self.capture_group_chars
+                .iter()
+                .enumerate()
+                .flat_map(|(match_index, ranges)| {
+                    ranges.iter().enumerate().filter_map(move |(index, range)| {
+                        Some(((match_index, index + 1), range.as_ref()?))
+                    })
+                }),
+            glyph,
+        )
+    }
+}
+
+/// Returns the index of the group with the smallest range that contains the given glyph,
+/// preferring later groups when ranges are equal, as nested groups always come after the groups that contain them
+fn innermost_group<'a, T: Ord + Copy>(
+    ranges: impl Iterator<Item = (T, &'a Range<usize>)>,
+    glyph: usize,
+) -> Option<T> {
+    ranges
+        .filter(|(_, range)| range.contains(&glyph))
+        .min_by_key(|(index, range)| (range.len(), Reverse(*index)))
+        .map(|(index, _)| index)
+}
+
+/// The byte ranges matched by each capture group (Including the implicit capture group corresponding to the whole match,
+/// at index 0) in each match of a regex, with `None` for capture groups that didn't participate in a match
+///
+/// This is the single representation of matches that the input layout, the matches selector and the connecting lines
+/// are all derived from, so that they always agree on which capture groups matched where
+pub type CaptureTable = Vec<Vec<Option<Range<usize>>>>;
+
+/// Finds every match of the given regex in the given text, along with the ranges matched by each capture group
+pub fn find_captures(regex: &Regex, text: &str) -> CaptureTable {
+    regex
+        .captures_iter(text)
+        .map(|captures| {
+            captures
+                .iter()
+                .map(|r#match| r#match.map(|r#match| r#match.range()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Finds every match of the given fancy-regex regex in the given text, along with the ranges matched by each capture group
+///
+/// The search stops at the first match that exceeds the backtracking limit, so only the matches before it are found
+#[cfg(feature = "backend-fancy")]
+pub fn find_fancy_captures(regex: &fancy_regex::Regex, text: &str) -> CaptureTable {
+    regex
+        .captures_iter(text)
+        .map_while(Result::ok)
+        .map(|captures| {
+            captures
+                .iter()
+                .map(|r#match| r#match.map(|r#match| r#match.range()))
+                .collect()
+        })
+        .collect()
+}
+
+/// A search for the captures of every match of a regex in a text, which can be split into several steps
+/// so that a slow search doesn't stall everything else until it finishes
+#[derive(Clone, Default)]
+pub struct CaptureSearch {
+    /// The captures of each match found so far
+    pub captures: CaptureTable,
+    /// The byte offset in the text that the search resumes from, which is past the end of the text once it has finished
+    pub resume_offset: usize,
+    /// The end of the most recent match, which an empty match can't be found at
+    last_match_end: Option<usize>,
+    /// The number of matches after which the search stops, even if there are more, or `None` if it finds every match
+    max_matches: Option<usize>,
+    /// How long the search has spent finding matches, over every step so far
+    pub elapsed: Duration,
+}
+
+impl CaptureSearch {
+    /// Creates a search that stops once it has found `max_matches` matches, if given
+    pub fn new(max_matches: Option<usize>) -> Self {
+        Self {
+            max_matches,
+            ..Default::default()
+        }
+    }
+
+    /// Finds the captures of at most `limit` more matches, returning whether the search has finished,
+    /// either because every match has been found, or because `max_matches` have been
+    ///
+    /// The same regex and text must be given every time, in which case the same matches as `find_captures` are found
+    pub fn advance(&mut self, regex: &Regex, text: &str, limit: usize) -> bool {
+        let (finished, elapsed) = timed(|| self.advance_untimed(regex, text, limit));
+        self.elapsed += elapsed;
+        finished
+    }
+
+    fn advance_untimed(&mut self, regex: &Regex, text: &str, limit: usize) -> bool {
+        let mut locations = regex.capture_locations();
+        for _ in 0..limit {
+            if self.is_finished(text) || self.is_full() {
+                break;
+            }
+
+            let (start, end) =
+                match regex.captures_read_at(&mut locations, text, self.resume_offset) {
+                    Some(found) => (found.start(), found.end()),
+                    None => {
+                        self.resume_offset = text.len() + 1;
+                        break;
+                    }
+                };
+
+            // This mirrors `Regex::captures_iter`, which steps over the next char after an empty match so as to not
+            // find it again, and skips empty matches that immediately follow the previous match
+            if start == end {
+                self.resume_offset = end + text[end..].chars().next().map_or(1, char::len_utf8);
+                if self.last_match_end == Some(end) {
+                    continue;
+                }
+            } else {
+                self.resume_offset = end;
+            }
+
+            self.last_match_end = Some(end);
+            self.captures.push(
+                (0..locations.len())
+                    .map(|group| locations.get(group).map(|(start, end)| start..end))
+                    .collect(),
+            );
+        }
+
+        self.is_finished(text) || self.is_full()
+    }
+
+    /// Whether every match in the given text has been found
+    pub fn is_finished(&self, text: &str) -> bool {
+        self.resume_offset > text.len()
+    }
+
+    /// Whether the search has found as many matches as it stops after
+    pub fn is_full(&self) -> bool {
+        matches!(self.max_matches, Some(max) if self.captures.len() >= max)
+    }
+}
+
+/// Returns the byte range of each line in the given text, excluding the line endings (Either `\n` or `\r\n`)
+pub fn line_ranges(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = 0;
+    text.split('\n').map(move |line| {
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        let range = start..start + content.len();
+        start += line.len() + 1;
+        range
+    })
+}
+
+/// Returns the byte range of each line in the given text, along with whether it matches, like `grep`
+pub fn match_lines(text: &str, is_match: impl Fn(&str) -> bool) -> Vec<(Range<usize>, bool)> {
+    line_ranges(text)
+        .map(|line| {
+            let matched = is_match(&text[line.clone()]);
+            (line, matched)
+        })
+        .collect()
+}
+
+/// Finds every match of the given regex in each line of the given text separately, like `grep`,
+/// so that matches never span multiple lines, and `^` and `$` always match at the start and end of each line
+///
+/// The ranges are relative to the whole text, as with `find_captures`
+pub fn find_captures_per_line(regex: &Regex, text: &str) -> CaptureTable {
+    find_per_line(text, |line| find_captures(regex, line))
+}
+
+/// Finds every match in each line of the given text separately with the given function,
+/// which finds the captures of every match in the text that it is given
+///
+/// The ranges are relative to the whole text, as with `find_captures`
+pub fn find_per_line(text: &str, find: impl Fn(&str) -> CaptureTable) -> CaptureTable {
+    line_ranges(text)
+        .flat_map(|line| {
+            let offset = line.start;
+            find(&text[line]).into_iter().map(move |groups| {
+                groups
+                    .into_iter()
+                    .map(|range| range.map(|range| range.start + offset..range.end + offset))
+                    .collect()
+            })
+        })
+        .collect()
 }
 
 pub fn layout_matched_text(
     text: String,
-    regex: &Regex,
+    captures: &CaptureTable,
     style: &Style,
-    capture_group_colors: &[Color32],
+    capture_group_colors: &[GroupColor],
 ) -> MatchedTextLayout {
     if text.is_empty() {
         return Default::default();
     }
 
-    if regex.as_str().is_empty() {
-        return MatchedTextLayout {
-            formatting: format_plain_text(text, style),
-            capture_group_chars: vec![],
-        };
-    }
-
     let mut capture_group_chars = Vec::new();
     let mut ranges = Vec::new();
+    let mut group_ranges = vec![Vec::new(); captures.first().map_or(0, Vec::len)];
+
+    let index = TextIndex::new(&text);
+    for groups in captures {
+        for (group, range) in groups.iter().enumerate() {
+            if let Some(range) = range {
+                group_ranges[group].push(range.clone());
+            }
+        }
 
-    for captures in regex.captures_iter(&text) {
         // Convert the byte ranges into char ranges, to later be used to index into the glyphs of the layed out galley
-        let char_ranges = captures
+        let char_ranges = groups
             .iter()
             .skip(1) // The first (0th) capture group always corresponds to the entire match, not any 'real' capture groups
-            .map(|r#match| {
-                r#match.map(|r#match| {
-                    convert_byte_range_to_char_range(r#match.range(), &text).unwrap()
-                })
+            .map(|range| {
+                range
+                    .clone()
+                    .and_then(|range| index.byte_range_to_glyph_range(range))
             })
             .collect();
 
         capture_group_chars.push(char_ranges);
 
         // Get the spans of the matched text from each capture group
-        let iter = captures
+        let iter = groups
             .iter()
             .enumerate()
             .skip(1) // The first (0th) capture group always corresponds to the entire match, not any 'real' capture groups
-            .filter_map(|(index, r#match)| r#match.map(|r#match| (index, r#match.range())));
+            .filter_map(|(index, range)| Some((index, range.clone()?)));
 
         ranges.extend(iter);
     }
@@ -326,6 +1173,16 @@ pub fn layout_matched_text(
         section_indexes[range].fill(index);
     }
 
+    let group_bytes = group_ranges
+        .into_iter()
+        .map(union_length)
+        .collect::<Vec<_>>();
+    let coverage = MatchCoverage {
+        total_bytes: text.len(),
+        matched_bytes: group_bytes.first().copied().unwrap_or_default(),
+        group_bytes,
+    };
+
     let font_id = TextStyle::Monospace.resolve(style);
 
     MatchedTextLayout {
@@ -334,10 +1191,61 @@ pub fn layout_matched_text(
             section_indexes,
             capture_group_colors
                 .iter()
-                .map(|&color| TextFormat::background(font_id.clone(), color))
+                .map(|color| TextFormat::background(font_id.clone(), color.background))
                 .collect(),
         ),
         capture_group_chars,
+        text_index: index,
+        coverage,
+    }
+}
+
+/// Returns information about how text should be rendered given the ranges of the matches of a regex in it,
+/// highlighting whole matches with a single color instead of highlighting each capture group
+pub fn layout_matched_text_matches_only(
+    text: String,
+    match_ranges: Vec<Range<usize>>,
+    style: &Style,
+    custom_colors: &CustomGroupColors,
+) -> MatchedTextLayout {
+    if text.is_empty() || match_ranges.is_empty() {
+        return MatchedTextLayout {
+            formatting: format_plain_text(text, style),
+            capture_group_chars: vec![],
+            text_index: Default::default(),
+            coverage: Default::default(),
+        };
+    }
+
+    let mut section_indexes = vec![0; text.len()];
+    for range in &match_ranges {
+        section_indexes[range.clone()].fill(1);
+    }
+
+    let coverage = MatchCoverage {
+        total_bytes: text.len(),
+        matched_bytes: union_length(match_ranges),
+        group_bytes: vec![],
+    };
+
+    let font_id = TextStyle::Monospace.resolve(style);
+
+    MatchedTextLayout {
+        formatting: TextFormatMapping::new(
+            text,
+            section_indexes,
+            vec![
+                TextFormat::background(font_id.clone(), Color32::TRANSPARENT),
+                TextFormat::background(
+                    font_id,
+                    custom_colors.get(0, style.visuals.dark_mode).background,
+                ),
+            ],
+        ),
+        // Capture groups aren't extracted, so there is nothing to draw connecting lines to
+        capture_group_chars: vec![],
+        text_index: Default::default(),
+        coverage,
     }
 }
 
@@ -365,65 +1273,118 @@ pub fn layout_plain_text(text: String, style: &Style) -> LayoutJob {
     )
 }
 
-/// Returns a bounding rect equal to the union of the bounding rects of all of the glyphs in the given rows that
-/// are delimited by the given range
+/// Returns a bounding rect for each of the given rows that the given range of glyphs touches, equal to the union of
+/// the bounding rects of the glyphs in that row that are delimited by the range, in the order of the rows
 ///
-/// Returns None if the range is entirely out of the bounds of the rows - if the range is only partially out of bounds,
-/// it will be truncated to the part that is in bounds
-pub fn glyph_bounds(rows: &[Row], range: &Range<usize>) -> Option<Rect> {
-    let mut iter = rows.iter();
-    let (mut offset, first_row) = match iter.try_fold(0, |offset, row| {
-        // Skip to the row that the range starts in
-        let new_offset = offset + row.glyphs.len();
-        if range.start >= new_offset {
-            ControlFlow::Continue(new_offset)
-        } else {
-            ControlFlow::Break((offset, row))
+/// Returns an empty vec if the range is entirely out of the bounds of the rows - if the range is only partially out of
+/// bounds, it will be truncated to the part that is in bounds
+pub fn glyph_bounds(rows: &[Row], range: &Range<usize>) -> Vec<Rect> {
+    let mut bounds = Vec::new();
+    let mut offset = 0;
+    for row in rows {
+        if offset >= range.end {
+            // Stop iterating once the entire range has been exhausted
+            break;
         }
-    }) {
-        // If `try_fold` returns `ControlFlow::Continue` that means the entire iterator was exhausted,
-        // or in other words the range is out of the bounds of all of the rows
-        ControlFlow::Continue(_) => return None,
-        ControlFlow::Break(result) => result,
-    };
 
-    let mut tail_start = range.start;
-
-    // Manually prepend the first row, as `map_while` would otherwise not see it,
-    // because `try_fold` consumes (from the iterator) every element it visits,
-    // including the one on which `ControlFlow::Break` is returned, which `first_row` is
-    std::iter::once(first_row)
-        .chain(iter)
-        .map_while(|row| {
-            if tail_start >= range.end {
-                // Stop iterating once the entire range has been exhausted
-                return None;
-            }
+        let row_start = offset;
+        offset += row.glyphs.len();
 
-            let row_end = offset + row.glyphs.len();
+        // The part of the range that corresponds to this row, relative to the start of the row
+        let head = range.start.max(row_start) - row_start..range.end.min(offset) - row_start;
+        if head.start < head.end {
+            bounds.push(row.glyphs[head].iter().fold(Rect::NOTHING, |rect, glyph| {
+                rect.union(glyph.logical_rect())
+            }));
+        }
+    }
+    bounds
+}
 
-            // Split off the part of the range that corresponds to this row,
-            // leaving the rest of the range that corresponds to the following rows
-            let head = tail_start - offset..range.end.min(row_end) - offset;
-            tail_start = row_end;
+/// Returns the index of the glyph in the given rows that is at the given position (Relative to the galley),
+/// or None if there is no glyph there
+///
+/// This is the inverse of `glyph_bounds`
+pub fn glyph_at(rows: &[Row], pos: Pos2) -> Option<usize> {
+    let mut offset = 0;
+    for row in rows {
+        if row.rect.y_range().contains(&pos.y) {
+            return row
+                .glyphs
+                .iter()
+                .position(|glyph| glyph.logical_rect().x_range().contains(&pos.x))
+                .map(|index| offset + index);
+        }
+        offset += row.glyphs.len();
+    }
+    None
+}
 
-            // Update the offset for the next row
-            offset = row_end;
+/// Returns the top and bottom of a caret at the given line and column (Measured in chars) of the text laid out in the
+/// given rows (Relative to the galley), or None if there is no such position
+///
+/// Unlike glyph offsets, lines and columns tell apart the end of a line, an empty line and the start of the next line
+pub fn caret_at(rows: &[Row], line: usize, column: usize) -> Option<[Pos2; 2]> {
+    let mut current_line = 0;
+    // The number of glyphs in the rows of the line before the current row, if the line is wrapped onto several rows
+    let mut line_offset = 0;
+    for (index, row) in rows.iter().enumerate() {
+        if current_line == line {
+            let row_column = column.checked_sub(line_offset)?;
+            let last_row = row.ends_with_newline || index + 1 == rows.len();
+            if row_column < row.glyphs.len() || (row_column == row.glyphs.len() && last_row) {
+                let x = row
+                    .glyphs
+                    .get(row_column)
+                    .map_or(row.rect.right(), |glyph| glyph.logical_rect().left());
+                return Some([
+                    Pos2::new(x, row.rect.top()),
+                    Pos2::new(x, row.rect.bottom()),
+                ]);
+            }
+            line_offset += row.glyphs.len();
+        }
 
-            Some(row.glyphs[head].iter().fold(Rect::NOTHING, |rect, glyph| {
-                rect.union(glyph.logical_rect())
-            }))
-        })
-        // Choose the widest rect out of those that this range produced
-        .max_by(|x, y| x.width().partial_cmp(&y.width()).unwrap())
+        if row.ends_with_newline {
+            current_line += 1;
+            line_offset = 0;
+        }
+    }
+    None
 }
 
-/// Builds a vec of layout sections from the given iterator of ranges
+/// Sorts the given positions into the given number of evenly sized buckets, where each position is a fraction of the
+/// way along the whole, returning each bucket that is used along with the index of the first position in it
+///
+/// The positions are expected to be in ascending order, as only consecutive positions are merged
+pub fn marker_buckets(
+    fractions: impl IntoIterator<Item = f32>,
+    bucket_count: usize,
+) -> Vec<(usize, usize)> {
+    let mut buckets: Vec<(usize, usize)> = Vec::new();
+    if bucket_count == 0 {
+        return buckets;
+    }
+
+    for (index, fraction) in fractions.into_iter().enumerate() {
+        let bucket =
+            ((fraction.clamp(0.0, 1.0) * bucket_count as f32) as usize).min(bucket_count - 1);
+        if buckets.last().map(|&(last, _)| last) != Some(bucket) {
+            buckets.push((bucket, index));
+        }
+    }
+    buckets
+}
+
+/// Builds a vec of layout sections from the given iterator of ranges, highlighting each range with the background
+/// color at its index in `colors`, and each byte with the color of the kind of token at its index in `tokens`, if any
 fn build_layout_sections(
     section_indexes: &mut [usize],
     ranges: impl ExactSizeIterator<Item = (usize, Range<usize>)>,
+    tokens: &[Option<TokenKind>],
+    dark_mode: bool,
     font_id: FontId,
-    colors: &[Color32],
+    colors: &[GroupColor],
 ) -> Vec<LayoutSection> {
     // This is a lower bound for how many sections there will be, as each range will have at least 1 section,
     // but gaps between ranges or ranges that overlap will result in multiple additional sections
@@ -436,30 +1397,637 @@ fn build_layout_sections(
         section_indexes[range].fill(index + 1);
     }
 
+    let format = |index: usize, token: Option<TokenKind>| {
+        let mut format = TextFormat::background(font_id.clone(), colors[index].background);
+        if let Some(kind) = token {
+            // Tokens in capture groups have to stand out against the group's background rather than the editor's
+            let dark_background = if format.background == Color32::TRANSPARENT {
+                dark_mode
+            } else {
+                color::is_dark(format.background)
+            };
+            format.color = color::token_color(kind, dark_background);
+        }
+        format
+    };
+
     // Derived from the `Slice::group_by` method;
-    // Find consecutive runs of bytes with equal marked indexes, and create a layout section for each run
+    // Find consecutive runs of bytes with equal marked indexes and tokens, and create a layout section for each run
+    let token_at = |byte: usize| tokens.get(byte).copied().flatten();
     let mut head = 0;
-    let mut len = 1;
-    let mut iter = section_indexes.windows(2);
-    while let Some(&[left, right]) = iter.next() {
-        if left != right {
+    for byte in 1..section_indexes.len() {
+        if section_indexes[byte] != section_indexes[head] || token_at(byte) != token_at(head) {
             sections.push(LayoutSection {
                 leading_space: 0.0,
-                byte_range: head..len,
-                format: TextFormat::background(font_id.clone(), colors[left]),
+                byte_range: head..byte,
+                format: format(section_indexes[head], token_at(head)),
             });
 
-            head = len;
+            head = byte;
         }
-        len += 1;
     }
 
-    let i = section_indexes[head];
     sections.push(LayoutSection {
         leading_space: 0.0,
-        byte_range: head..len,
-        format: TextFormat::background(font_id, colors[i]),
+        byte_range: head..section_indexes.len(),
+        format: format(section_indexes[head], token_at(head)),
     });
 
     sections
 }
+
+/// Checks that the sections of the given layout job are in bounds and cover the text contiguously
+#[cfg(any(test, feature = "self-test"))]
+pub fn check_sections(job: &LayoutJob) -> Result<(), String> {
+    let mut end = 0;
+    for section in &job.sections {
+        let range = &section.byte_range;
+        if range.start != end || range.end < range.start {
+            return Err(format!(
+                "Section {:?} does not follow on from {}",
+                range, end
+            ));
+        }
+
+        if !job.text.is_char_boundary(range.start) || !job.text.is_char_boundary(range.end) {
+            return Err(format!("Section {:?} is not on char boundaries", range));
+        }
+
+        end = range.end;
+    }
+
+    if end != job.text.len() {
+        return Err(format!(
+            "Sections end at {}, but the text is {} bytes long",
+            end,
+            job.text.len()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::{compile_regex, parse_regex};
+    use eframe::epaint::text::Glyph;
+    use egui::Vec2;
+
+    #[test]
+    fn text_index_of_ascii_text() {
+        let text = "abc\ndef";
+        let index = TextIndex::new(text);
+
+        assert_eq!(index.byte_to_char(5), Some(5));
+        assert_eq!(index.byte_to_char(text.len()), Some(7));
+        assert_eq!(index.byte_to_char(8), None);
+        assert_eq!(index.char_to_byte(7), Some(text.len()));
+        assert_eq!(index.char_to_byte(8), None);
+
+        assert_eq!(index.byte_to_line_col(3), Some((0, 3)));
+        assert_eq!(index.byte_to_line_col(4), Some((1, 0)));
+        assert_eq!(index.byte_to_line_col(text.len()), Some((1, 3)));
+        assert_eq!(index.byte_to_line_col(8), None);
+
+        // The newline isn't a glyph, so it has the same glyph offset as the char after it
+        assert_eq!(index.glyph_offset(3), Some(3));
+        assert_eq!(index.glyph_offset(4), Some(3));
+        assert_eq!(index.glyph_offset(text.len()), Some(6));
+        assert_eq!(index.glyph_offset(8), None);
+        assert_eq!(
+            (0..7)
+                .map(|glyph| index.glyph_to_byte(glyph))
+                .collect::<Vec<_>>(),
+            [Some(0), Some(1), Some(2), Some(4), Some(5), Some(6), None]
+        );
+
+        assert_eq!(index.byte_range_to_glyph_range(2..5), Some(2..4));
+        assert_eq!(index.byte_range_to_glyph_range(2..8), None);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(5..100), 4..6);
+
+        // The empty text only has its end
+        let empty = TextIndex::default();
+        assert_eq!(empty.byte_to_char(0), Some(0));
+        assert_eq!(empty.byte_to_char(1), None);
+        assert_eq!(empty.byte_to_line_col(0), Some((0, 0)));
+        assert_eq!(empty.glyph_offset(0), Some(0));
+        assert_eq!(empty.glyph_to_byte(0), None);
+    }
+
+    #[test]
+    fn text_index_of_crlf_lines() {
+        // Carriage returns are glyphs at the ends of their lines, but the newlines after them aren't
+        let text = "ab\r\ncd\r\n\r\ne";
+        let index = TextIndex::new(text);
+
+        assert_eq!(index.byte_to_char(text.len()), Some(11));
+        assert_eq!(index.byte_to_char(12), None);
+        assert_eq!(index.char_to_byte(11), Some(text.len()));
+        assert_eq!(index.char_to_byte(12), None);
+
+        assert_eq!(index.byte_to_line_col(2), Some((0, 2)));
+        assert_eq!(index.byte_to_line_col(3), Some((0, 3)));
+        assert_eq!(index.byte_to_line_col(4), Some((1, 0)));
+        assert_eq!(index.byte_to_line_col(8), Some((2, 0)));
+        assert_eq!(index.byte_to_line_col(text.len()), Some((3, 1)));
+        assert_eq!(index.byte_to_line_col(12), None);
+
+        assert_eq!(index.glyph_offset(2), Some(2));
+        assert_eq!(index.glyph_offset(3), Some(3));
+        assert_eq!(index.glyph_offset(4), Some(3));
+        assert_eq!(index.glyph_offset(8), Some(6));
+        assert_eq!(index.glyph_offset(text.len()), Some(8));
+        assert_eq!(index.glyph_offset(12), None);
+        assert_eq!(
+            (0..9)
+                .map(|glyph| index.glyph_to_byte(glyph))
+                .collect::<Vec<_>>(),
+            [
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(4),
+                Some(5),
+                Some(6),
+                Some(8),
+                Some(10),
+                None
+            ]
+        );
+
+        assert_eq!(index.byte_range_to_glyph_range(0..4), Some(0..3));
+        assert_eq!(index.byte_range_to_glyph_range(6..8), Some(5..6));
+        assert_eq!(index.byte_range_to_glyph_range(6..12), None);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(2..100), 2..8);
+    }
+
+    #[test]
+    fn text_index_of_multi_byte_chars() {
+        let text = "aé\nñb";
+        let index = TextIndex::new(text);
+
+        assert_eq!(index.byte_to_char(1), Some(1));
+        assert_eq!(index.byte_to_char(2), None);
+        assert_eq!(index.byte_to_char(3), Some(2));
+        assert_eq!(index.byte_to_char(text.len()), Some(5));
+        assert_eq!(index.byte_to_char(8), None);
+        assert_eq!(index.char_to_byte(3), Some(4));
+        assert_eq!(index.char_to_byte(5), Some(text.len()));
+        assert_eq!(index.char_to_byte(6), None);
+
+        assert_eq!(index.byte_to_line_col(4), Some((1, 0)));
+        assert_eq!(index.byte_to_line_col(6), Some((1, 1)));
+        assert_eq!(index.byte_to_line_col(text.len()), Some((1, 2)));
+        assert_eq!(index.byte_to_line_col(5), None);
+        assert_eq!(index.byte_to_line_col(8), None);
+
+        assert_eq!(index.glyph_offset(1), Some(1));
+        assert_eq!(index.glyph_offset(4), Some(2));
+        assert_eq!(index.glyph_offset(text.len()), Some(4));
+        assert_eq!(index.glyph_offset(2), None);
+        assert_eq!(index.glyph_offset(8), None);
+        assert_eq!(
+            (0..5)
+                .map(|glyph| index.glyph_to_byte(glyph))
+                .collect::<Vec<_>>(),
+            [Some(0), Some(1), Some(4), Some(6), None]
+        );
+
+        assert_eq!(index.byte_range_to_glyph_range(1..6), Some(1..3));
+        assert_eq!(index.byte_range_to_glyph_range(2..6), None);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(2..5), 1..3);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(6..100), 3..4);
+    }
+
+    #[test]
+    fn text_index_of_emoji() {
+        // A skin tone modifier is a char of its own, so it is also a glyph of its own
+        let text = "😀x\n👍🏽";
+        let index = TextIndex::new(text);
+
+        assert_eq!(index.byte_to_char(4), Some(1));
+        assert_eq!(index.byte_to_char(2), None);
+        assert_eq!(index.byte_to_char(10), Some(4));
+        assert_eq!(index.byte_to_char(text.len()), Some(5));
+        assert_eq!(index.byte_to_char(15), None);
+        assert_eq!(index.char_to_byte(4), Some(10));
+        assert_eq!(index.char_to_byte(5), Some(text.len()));
+        assert_eq!(index.char_to_byte(6), None);
+
+        assert_eq!(index.byte_to_line_col(10), Some((1, 1)));
+        assert_eq!(index.byte_to_line_col(text.len()), Some((1, 2)));
+        assert_eq!(index.byte_to_line_col(12), None);
+        assert_eq!(index.byte_to_line_col(15), None);
+
+        assert_eq!(index.glyph_offset(6), Some(2));
+        assert_eq!(index.glyph_offset(10), Some(3));
+        assert_eq!(index.glyph_offset(text.len()), Some(4));
+        assert_eq!(index.glyph_offset(1), None);
+        assert_eq!(index.glyph_offset(15), None);
+        assert_eq!(
+            (0..5)
+                .map(|glyph| index.glyph_to_byte(glyph))
+                .collect::<Vec<_>>(),
+            [Some(0), Some(4), Some(6), Some(10), None]
+        );
+
+        assert_eq!(index.byte_range_to_glyph_range(0..5), Some(0..2));
+        assert_eq!(index.byte_range_to_glyph_range(6..8), None);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(1..7), 0..3);
+        assert_eq!(index.byte_range_to_glyph_range_clamped(12..100), 3..4);
+    }
+
+    #[test]
+    fn union_length_counts_overlaps_once() {
+        assert_eq!(union_length(vec![]), 0);
+        assert_eq!(union_length(vec![4..6, 0..2, 1..3, 5..5]), 5);
+        // Ranges inside of others add nothing
+        assert_eq!(union_length(vec![0..10, 2..4, 8..12]), 12);
+    }
+
+    #[test]
+    fn crlf_lines_are_searched_without_their_line_endings() {
+        let text = "ab\r\ncab\r\n";
+        assert_eq!(line_ranges(text).collect::<Vec<_>>(), [0..2, 4..7, 9..9]);
+
+        // `$` matches before each `\r`, and the ranges are offset by the start of their line
+        let captures = find_captures_per_line(&Regex::new("(a)(b)$").unwrap(), text);
+        assert_eq!(
+            captures,
+            [
+                [Some(0..2), Some(0..1), Some(1..2)],
+                [Some(5..7), Some(5..6), Some(6..7)],
+            ]
+        );
+        for groups in &captures {
+            let matched = groups[0].clone().unwrap();
+            assert!(!text[matched].contains('\r'));
+        }
+
+        assert_eq!(
+            match_lines(text, |line| line.ends_with('b')),
+            [(0..2, true), (4..7, true), (9..9, false)]
+        );
+    }
+
+    #[test]
+    fn match_coverage() {
+        let text = "ab a xab";
+        let captures = find_captures(&Regex::new("(a)(b)?").unwrap(), text);
+        let colors = (0..3)
+            .map(|index| color::group_color(index, false))
+            .collect::<Vec<_>>();
+        let coverage =
+            layout_matched_text(text.into(), &captures, &Style::default(), &colors).coverage;
+        assert_eq!(coverage.total_bytes, 8);
+        assert_eq!(coverage.matched_bytes, 5);
+        assert_eq!(coverage.group_bytes, [5, 3, 2]);
+        assert_eq!(coverage.percentage(2), 25.0);
+
+        let coverage = layout_matched_text_matches_only(
+            text.into(),
+            vec![0..3, 2..4],
+            &Style::default(),
+            &CustomGroupColors::default(),
+        )
+        .coverage;
+        assert_eq!(coverage.matched_bytes, 4);
+        assert!(coverage.group_bytes.is_empty());
+
+        assert_eq!(MatchCoverage::default().percentage(0), 0.0);
+    }
+
+    /// Uses rows of `GLYPHS_PER_ROW` glyphs that are each 10 points wide and 20 points tall,
+    /// with each row directly below the one before it
+    #[test]
+    fn glyph_bounds_are_split_across_rows() {
+        const GLYPHS_PER_ROW: usize = 4;
+        let rows = (0..3)
+            .map(|row| Row {
+                glyphs: (0..GLYPHS_PER_ROW)
+                    .map(|column| Glyph {
+                        chr: 'x',
+                        pos: Pos2::new(column as f32 * 10.0, row as f32 * 20.0),
+                        size: Vec2::new(10.0, 20.0),
+                        uv_rect: Default::default(),
+                        section_index: 0,
+                    })
+                    .collect(),
+                rect: Rect::from_min_size(Pos2::new(0.0, row as f32 * 20.0), Vec2::new(40.0, 20.0)),
+                visuals: Default::default(),
+                ends_with_newline: false,
+            })
+            .collect::<Vec<_>>();
+
+        let rect = |left: f32, top: f32, right: f32| {
+            Rect::from_min_max(Pos2::new(left, top), Pos2::new(right, top + 20.0))
+        };
+        let cases = [
+            ("Starting mid-row", 1..3, vec![rect(10.0, 0.0, 30.0)]),
+            (
+                "Spanning three rows",
+                2..10,
+                vec![
+                    rect(20.0, 0.0, 40.0),
+                    rect(0.0, 20.0, 40.0),
+                    rect(0.0, 40.0, 20.0),
+                ],
+            ),
+            (
+                "Ending on a row boundary",
+                2..8,
+                vec![rect(20.0, 0.0, 40.0), rect(0.0, 20.0, 40.0)],
+            ),
+            (
+                "Partially out of bounds",
+                10..20,
+                vec![rect(20.0, 40.0, 40.0)],
+            ),
+            ("Out of bounds", 12..14, vec![]),
+            ("Empty", 5..5, vec![]),
+        ];
+
+        for (name, range, expected) in cases {
+            assert_eq!(glyph_bounds(&rows, &range), expected, "{}", name);
+        }
+    }
+
+    /// The newline isn't a glyph, so the `a` on the second line is the fourth glyph
+    #[test]
+    fn match_at_glyph() {
+        let text = "xab\na";
+        let captures = find_captures(&Regex::new("(a)(b)?").unwrap(), text);
+        let colors = (0..3)
+            .map(|index| color::group_color(index, true))
+            .collect::<Vec<_>>();
+        let layout = layout_matched_text(text.into(), &captures, &Style::default(), &colors);
+
+        let found = (0..4)
+            .map(|glyph| layout.match_at_glyph(glyph))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [None, Some((0, 1)), Some((0, 2)), Some((1, 1))]);
+    }
+
+    /// The range starts and ends part way through sections, which have to be split rather than changed as a whole
+    #[test]
+    fn overriding_part_of_the_formatting() {
+        let plain = TextFormat::default();
+        let highlighted = TextFormat::background(FontId::monospace(12.0), Color32::RED);
+        let mut formatting = TextFormatMapping::new(
+            "aabbcc".into(),
+            vec![0, 0, 1, 1, 0, 0],
+            vec![plain.clone(), highlighted.clone()],
+        );
+
+        formatting.override_range_format(1..5, |format| color::emphasised_format(format, true));
+        let job = formatting.convert_to_layout_job();
+        check_sections(&job).unwrap();
+
+        let expected = [
+            (0..1, plain.clone()),
+            (1..2, color::emphasised_format(&plain, true)),
+            (2..4, color::emphasised_format(&highlighted, true)),
+            (4..5, color::emphasised_format(&plain, true)),
+            (5..6, plain),
+        ];
+        for (range, format) in expected {
+            assert!(
+                job.sections
+                    .iter()
+                    .filter(|section| range.contains(&section.byte_range.start))
+                    .all(|section| section.byte_range.end <= range.end && section.format == format),
+                "Bytes {:?} don't have the expected format",
+                range
+            );
+        }
+    }
+
+    #[test]
+    fn enclosing_groups() {
+        for (pattern, expected) in [
+            (r"((\d+)-(\d+))(x)", &[true, false, false, false][..]),
+            ("(a(b(c)))", &[true, true, false]),
+            ("(a)(b)", &[false, false]),
+        ] {
+            let ast = parse_regex(pattern, Default::default()).unwrap();
+            let layout = layout_regex(
+                pattern.into(),
+                &ast,
+                &Style::default(),
+                None,
+                &Default::default(),
+                None,
+            );
+            let encloses = (0..expected.len())
+                .map(|index| layout.encloses_groups(index))
+                .collect::<Vec<_>>();
+            assert_eq!(encloses, expected, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn caret_positions() {
+        // The lines "ab", "" and "cdef", with the last one wrapped onto two rows
+        let rows = [(2, true), (0, true), (2, false), (2, false)]
+            .into_iter()
+            .enumerate()
+            .map(|(row, (length, ends_with_newline))| Row {
+                glyphs: (0..length)
+                    .map(|column| Glyph {
+                        chr: 'x',
+                        pos: Pos2::new(column as f32 * 10.0, row as f32 * 20.0),
+                        size: Vec2::new(10.0, 20.0),
+                        uv_rect: Default::default(),
+                        section_index: 0,
+                    })
+                    .collect(),
+                rect: Rect::from_min_size(
+                    Pos2::new(0.0, row as f32 * 20.0),
+                    Vec2::new(length as f32 * 10.0, 20.0),
+                ),
+                visuals: Default::default(),
+                ends_with_newline,
+            })
+            .collect::<Vec<_>>();
+
+        let cases = [
+            ("The end of a line", (0, 2), Some(Pos2::new(20.0, 0.0))),
+            ("An empty line", (1, 0), Some(Pos2::new(0.0, 20.0))),
+            ("The start of a line", (2, 0), Some(Pos2::new(0.0, 40.0))),
+            ("A wrapped row boundary", (2, 2), Some(Pos2::new(0.0, 60.0))),
+            ("The end of the text", (2, 4), Some(Pos2::new(20.0, 60.0))),
+            ("Past the end of a line", (2, 5), None),
+            ("Past the last line", (3, 0), None),
+        ];
+        for (name, (line, column), expected) in cases {
+            let top = caret_at(&rows, line, column).map(|[top, _]| top);
+            assert_eq!(top, expected, "{}", name);
+        }
+    }
+
+    /// Matches that are close together share a marker, which selects the first of them
+    #[test]
+    fn scroll_markers() {
+        assert_eq!(
+            marker_buckets([0.0, 0.001, 0.5, 0.999, 1.0], 10),
+            [(0, 0), (5, 2), (9, 3)]
+        );
+        assert!(marker_buckets([0.5], 0).is_empty());
+    }
+
+    #[test]
+    fn error_annotations() {
+        let style = Style::default();
+        for (pattern, expected) in [
+            ("(a", "^ error here"),
+            ("a{2,1}", " ^^^^^ error here"),
+            (
+                "(?P<n>a)(?P<n>b)",
+                "            ^ error here\n    - related to here",
+            ),
+            (
+                "Ω(?P<n>a)(?P<n>b)",
+                "             ^ error here\n     - related to here",
+            ),
+        ] {
+            let err = compile_regex(pattern, Default::default()).unwrap_err();
+            let annotation = layout_error_annotation(pattern, &style, &err).unwrap();
+            assert_eq!(annotation.text, expected);
+
+            // Only the auxiliary span is underlined, rather than filled in
+            let layout = layout_regex_err(pattern.into(), &style, &err, None);
+            check_sections(&layout.job).unwrap();
+            let underlined = layout
+                .job
+                .sections
+                .iter()
+                .filter(|section| section.format.underline.width > 0.0)
+                .map(|section| section.byte_range.clone())
+                .collect::<Vec<_>>();
+            let aux = err.auxiliary_span().map(|aux| aux.range());
+            assert_eq!(
+                underlined,
+                aux.into_iter().collect::<Vec<_>>(),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    /// Each edit is given with the length of the prefix and suffix that it leaves unchanged
+    #[test]
+    fn error_layouts_keep_the_unchanged_highlighting() {
+        let style = Style::default();
+        let format_at = |job: &LayoutJob, offset: usize| {
+            job.sections
+                .iter()
+                .find(|section| section.byte_range.contains(&offset))
+                .map(|section| section.format.clone())
+        };
+
+        for (old, new, prefix, suffix) in [
+            // Insertions
+            (r"(\d+)-(\w)", r"(\d+)-(\w)(", 10, 0),
+            (r"(\d+)-(\w)", r"((\d+)-(\w)", 1, 9),
+            // Deletions
+            (r"(\d+)-(\w)", r"(\d+-(\w)", 4, 5),
+            (r"(a)(?P<b>é)", r"(a)(?P<b>é", 11, 0),
+            // Edits in the middle
+            (r"(\d+)-(\w)", r"(\d+)-(?z)", 7, 1),
+            (r"(a)b(c)", r"(a)[(c)", 3, 3),
+        ] {
+            let expected = vec![
+                (0..prefix, 0),
+                (
+                    new.len() - suffix..new.len(),
+                    new.len() as isize - old.len() as isize,
+                ),
+            ];
+            assert_eq!(
+                unchanged_regions(old, new),
+                expected,
+                "{:?} to {:?}",
+                old,
+                new
+            );
+
+            let (ast, _) = compile_regex(old, Default::default()).unwrap();
+            let previous = layout_regex(old.into(), &ast, &style, None, &Default::default(), None);
+            let err = compile_regex(new, Default::default()).unwrap_err();
+            let layout = layout_regex_err(new.into(), &style, &err, Some(&previous));
+            check_sections(&layout.job).unwrap();
+
+            // The unchanged parts keep their highlighting, and everything else but the error is shown as an error
+            let error_spans = err.labeled_spans();
+            for (offset, _) in new.char_indices() {
+                if error_spans
+                    .iter()
+                    .any(|(span, _)| span.range().contains(&offset))
+                {
+                    continue;
+                }
+                let expected = if offset < prefix {
+                    format_at(&previous.job, offset)
+                } else if offset >= new.len() - suffix {
+                    format_at(&previous.job, offset + old.len() - new.len())
+                } else {
+                    Some(TextFormat::simple(
+                        TextStyle::Monospace.resolve(&style),
+                        color::error_color(style.visuals.dark_mode),
+                    ))
+                };
+                assert_eq!(
+                    format_at(&layout.job, offset),
+                    expected,
+                    "{:?}, edited from {:?}, is highlighted wrongly at byte {}",
+                    new,
+                    old,
+                    offset
+                );
+            }
+        }
+    }
+
+    /// Glyph ranges skip the newlines, which aren't laid out as glyphs
+    #[test]
+    fn multiline_regex_layout() {
+        let pattern = "(?x)\n(?P<year>\\d{4})  # The year\n-\n(\n    \\w\n)";
+        let ast = parse_regex(pattern, Default::default()).unwrap();
+        let layout = layout_regex(
+            pattern.into(),
+            &ast,
+            &Style::default(),
+            None,
+            &Default::default(),
+            None,
+        );
+
+        let mut galley = None;
+        let _ = egui::Context::default().run(Default::default(), |ctx| {
+            galley = Some(ctx.fonts().layout_job(layout.job.clone()));
+        });
+        let rows = &galley.unwrap().rows;
+        let glyphs = rows
+            .iter()
+            .flat_map(|row| &row.glyphs)
+            .map(|glyph| glyph.chr)
+            .collect::<String>();
+
+        let expected = [(r"(?P<year>\d{4})", 1), ("(\n    \\w\n)", 3)];
+        let groups = &layout.capture_group_chars;
+        assert_eq!(groups.len(), expected.len());
+        for ((_, range), (text, row)) in groups.iter().zip(expected) {
+            let chars = glyphs.chars().skip(range.start).take(range.len());
+            assert!(
+                chars.eq(text.chars().filter(|&c| c != '\n')),
+                "The glyphs at {:?} aren't those of {:?}",
+                range,
+                text
+            );
+
+            let bounds = glyph_bounds(rows, range);
+            assert_eq!(bounds[0].top(), rows[row].rect.top(), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+/// `std::time::Instant` panics on the web, where the `instant` crate measures time with `performance.now()` instead
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub use instant::Instant;
+
+/// How long the work of showing a regex matching the input text took, to give a feel for how expensive the regex is
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Timings {
+    /// Parsing and compiling the regex
+    pub compile: Duration,
+    /// Finding the matches and their captures in the input text, including on other threads
+    pub matching: Duration,
+    /// Laying out the input text with its matches highlighted
+    pub layout: Duration,
+}
+
+/// Runs the given function, returning its result along with how long it took
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
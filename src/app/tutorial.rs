@@ -0,0 +1,192 @@
+use super::state::SectionId;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The current state of the editor, as checked against the goal of a tutorial step
+pub struct TutorialAttempt<'a> {
+    pub regex: &'a Regex,
+    pub input: &'a str,
+    /// The replacement text, if the result area is in replace mode
+    pub replace_text: Option<&'a str>,
+}
+
+/// Checks whether an attempt meets the goal of a tutorial step, returning a hint about what is missing if it doesn't
+pub type TutorialChecker = fn(&TutorialAttempt<'_>) -> Result<(), String>;
+
+/// A part of the UI that a tutorial step draws attention to
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TutorialTarget {
+    Section(SectionId),
+    Inspector,
+}
+
+/// A single step of the tutorial
+pub struct TutorialStep {
+    pub title: &'static str,
+    pub instruction: &'static str,
+    /// The input text that is loaded when the step begins
+    pub input: &'static str,
+    pub target: Option<TutorialTarget>,
+    pub check: TutorialChecker,
+    /// A pattern that completes the step
+    pub solution: &'static str,
+    /// The replacement text that completes the step along with `solution`, if one is needed
+    pub solution_replace_text: Option<&'static str>,
+}
+
+/// The steps of the tutorial, in order
+pub const TUTORIAL_STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        title: "Literals",
+        instruction: "Most characters in a regex match themselves. Type a pattern that matches every \"cat\" in the input text.",
+        input: "The cat sat on the mat, but the other cat sat on the hat.",
+        target: Some(TutorialTarget::Section(SectionId::Regex)),
+        check: check_literals,
+        solution: "cat",
+        solution_replace_text: None,
+    },
+    TutorialStep {
+        title: "Character Classes",
+        instruction: "A character class such as [0-9] or \\d matches any one of a set of characters. \
+            Match every digit in the input text, one digit at a time.",
+        input: "Order 66 shipped in 3 boxes on day 12.",
+        target: Some(TutorialTarget::Section(SectionId::Regex)),
+        check: check_classes,
+        solution: r"\d",
+        solution_replace_text: None,
+    },
+    TutorialStep {
+        title: "Repetition",
+        instruction: "Quantifiers such as + repeat whatever comes before them. \
+            Change the pattern so that each whole number is a single match.",
+        input: "Order 66 shipped in 3 boxes on day 12.",
+        target: Some(TutorialTarget::Section(SectionId::Input)),
+        check: check_repetition,
+        solution: r"\d+",
+        solution_replace_text: None,
+    },
+    TutorialStep {
+        title: "Groups",
+        instruction: "Parentheses capture part of a match, and (?P<name>...) gives the capture a name. \
+            Capture the year of each date in a group named \"year\", then look for it in the inspector.",
+        input: "Released on 2019-04-12, updated on 2022-09-30.",
+        target: Some(TutorialTarget::Inspector),
+        check: check_groups,
+        solution: r"(?P<year>\d{4})-",
+        solution_replace_text: None,
+    },
+    TutorialStep {
+        title: "Replacement",
+        instruction: "Switch to Replace mode, where $name or ${name} inserts what a group captured. \
+            Swap each surname and forename, so that the result reads \"John Smith\" and \"Jane Doe\".",
+        input: "Smith, John\nDoe, Jane",
+        target: Some(TutorialTarget::Section(SectionId::ResultMode)),
+        check: check_replacement,
+        solution: r"(?P<surname>\w+), (?P<forename>\w+)",
+        solution_replace_text: Some("$forename $surname"),
+    },
+];
+
+fn check_literals(attempt: &TutorialAttempt<'_>) -> Result<(), String> {
+    expect_matches(attempt, &[4..7, 38..41])
+}
+
+fn check_classes(attempt: &TutorialAttempt<'_>) -> Result<(), String> {
+    expect_matches(attempt, &[6..7, 7..8, 20..21, 35..36, 36..37])
+}
+
+fn check_repetition(attempt: &TutorialAttempt<'_>) -> Result<(), String> {
+    expect_matches(attempt, &[6..8, 20..21, 35..37])
+}
+
+fn check_groups(attempt: &TutorialAttempt<'_>) -> Result<(), String> {
+    if !attempt
+        .regex
+        .capture_names()
+        .flatten()
+        .any(|name| name == "year")
+    {
+        return Err("There isn't a group named \"year\" yet".into());
+    }
+
+    let captured = attempt
+        .regex
+        .captures_iter(attempt.input)
+        .filter_map(|captures| captures.name("year"))
+        .map(|group| group.range());
+    expect_ranges(captured, &[12..16, 35..39], "captures of \"year\"")
+}
+
+fn check_replacement(attempt: &TutorialAttempt<'_>) -> Result<(), String> {
+    let replace_text = match attempt.replace_text {
+        Some(replace_text) => replace_text,
+        None => return Err("The result area isn't in Replace mode yet".into()),
+    };
+
+    if attempt.regex.replace_all(attempt.input, replace_text) == "John Smith\nJane Doe" {
+        Ok(())
+    } else {
+        Err("The result doesn't read \"John Smith\" and \"Jane Doe\" yet".into())
+    }
+}
+
+/// Checks that the matches of the attempt cover exactly the given ranges of the input text
+fn expect_matches(attempt: &TutorialAttempt<'_>, expected: &[Range<usize>]) -> Result<(), String> {
+    let matches = attempt
+        .regex
+        .find_iter(attempt.input)
+        .map(|found| found.range());
+    expect_ranges(matches, expected, "matches")
+}
+
+fn expect_ranges(
+    actual: impl Iterator<Item = Range<usize>>,
+    expected: &[Range<usize>],
+    what: &str,
+) -> Result<(), String> {
+    let actual: Vec<_> = actual.collect();
+    if actual == expected {
+        Ok(())
+    } else if actual.len() != expected.len() {
+        Err(format!(
+            "There are {} {}, but there should be {}",
+            actual.len(),
+            what,
+            expected.len()
+        ))
+    } else {
+        Err(format!("The {} don't cover the right text yet", what))
+    }
+}
+
+/// Progress through the tutorial
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TutorialState {
+    /// Whether the tutorial window is shown
+    pub active: bool,
+    /// The index of the current step
+    pub step: usize,
+    /// How many steps have been unlocked by completing the ones before them
+    pub unlocked: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parsing::compile_regex;
+
+    #[test]
+    fn tutorial_solutions_pass() {
+        for step in TUTORIAL_STEPS {
+            let (_, regex) = compile_regex(step.solution, Default::default()).unwrap();
+            let attempt = TutorialAttempt {
+                regex: &regex,
+                input: step.input,
+                replace_text: step.solution_replace_text,
+            };
+            assert_eq!((step.check)(&attempt), Ok(()), "{}", step.title);
+        }
+    }
+}
@@ -1,6 +1,15 @@
-mod editor;
+mod clipboard_test;
+pub mod editor;
+mod file_drop;
 mod inspector;
+mod journal;
+mod settings;
 mod tab_bar;
+mod tutorial;
+mod undo_toast;
+
+pub mod recovery;
+pub mod shortcuts;
 
 /// Functions for displaying UI specific to a native build of the app
 #[cfg(not(target_arch = "wasm32"))]
@@ -10,14 +19,121 @@ pub mod native;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
 
-use egui::{FontData, FontDefinitions, FontFamily, Style, Vec2, Visuals};
+use crate::app::state::{AppState, UiPrefs, ZOOM_STEP};
+use egui::{Context, Event, FontData, FontDefinitions, FontFamily, Key, Style, Vec2, Visuals};
+use std::time::Duration;
+
+/// The name of the command modifier, as displayed in the tooltips of buttons that have keyboard shortcuts
+pub const COMMAND_NAME: &str = if cfg!(target_os = "macos") {
+    "Cmd"
+} else {
+    "Ctrl"
+};
+
+/// Toggles between light and dark theme, remembering the choice for the next session,
+/// and lays out the regex and input text again as the highlighting colors depend on the theme
+pub fn toggle_theme(ctx: &Context, state: &mut AppState) {
+    let dark_mode = !ctx.style().visuals.dark_mode;
+    state.widgets.ui_prefs.dark_mode = Some(dark_mode);
+    apply_ui_prefs(ctx, &state.widgets.ui_prefs);
+    state.recompile(&ctx.style());
+}
+
+/// Applies the given preferences to the UI, such as when restoring them from the last session
+pub fn apply_ui_prefs(ctx: &Context, prefs: &UiPrefs) {
+    match prefs.dark_mode {
+        Some(true) => ctx.set_visuals(Visuals::dark()),
+        Some(false) => ctx.set_visuals(Visuals::light()),
+        None => {}
+    }
+}
+
+/// Handles the keyboard shortcuts for zooming the UI in and out, and for resetting the zoom,
+/// consuming their input so that it isn't also typed into a focused editor
+pub fn zoom_shortcuts(ctx: &Context, prefs: &mut UiPrefs) {
+    let mut steps = 0.0;
+    let mut reset = false;
+
+    let mut input = ctx.input_mut();
+    let command = input.modifiers.command;
+    input.events.retain(|event| {
+        match event {
+            // The zoom keys aren't physical keys on every keyboard layout, so they are recognised by the text they type
+            Event::Text(text) if command => match text.as_str() {
+                "=" | "+" => steps += 1.0,
+                "-" => steps -= 1.0,
+                "0" => reset = true,
+                _ => return true,
+            },
+            Event::Key {
+                key: Key::Num0,
+                pressed: true,
+                modifiers,
+            } if modifiers.command => reset = true,
+            _ => return true,
+        }
+        false
+    });
+    drop(input);
+
+    if reset {
+        prefs.set_zoom(1.0);
+    } else if steps != 0.0 {
+        // Snap to a multiple of the step, so that repeated steps don't accumulate rounding errors
+        prefs.set_zoom(((prefs.zoom() / ZOOM_STEP).round() + steps) * ZOOM_STEP);
+    }
+}
+
+/// Scales the UI by the zoom factor of the given preferences, on top of the native scale of the display
+///
+/// The new scale takes effect from the next frame, and isn't applied while the pointer is in use,
+/// so that dragging the zoom slider doesn't move it out from under the pointer
+pub fn apply_zoom(ctx: &Context, prefs: &UiPrefs, native_pixels_per_point: Option<f32>) {
+    let pixels_per_point = prefs.zoom() * native_pixels_per_point.unwrap_or(1.0);
+    if (ctx.pixels_per_point() - pixels_per_point).abs() > f32::EPSILON && !ctx.is_using_pointer() {
+        ctx.set_pixels_per_point(pixels_per_point);
+    }
+}
+
+/// Formats a number of bytes into a human readable size, such as "3.2 KB"
+pub fn format_byte_count(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
 
-/// Toggles between light and dark theme
-pub fn toggle_theme(visuals: &Visuals) -> Visuals {
-    if visuals.dark_mode {
-        Visuals::light()
+/// Formats a count with its digits grouped in threes, such as "1,204"
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    // The digits are all ASCII, so splitting them by byte can't split a char
+    digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Formats a duration into a human readable time, such as "0.4 ms", with more precision for shorter times
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis < 10.0 {
+        format!("{:.1} ms", millis)
+    } else if millis < 1000.0 {
+        format!("{:.0} ms", millis)
     } else {
-        Visuals::dark()
+        format!("{:.1} s", millis / 1000.0)
     }
 }
 
@@ -52,3 +168,89 @@ pub fn create_font_definitions() -> FontDefinitions {
 
     fonts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_preference_is_remembered() {
+        use crate::app::state::WidgetState;
+
+        let ctx = Context::default();
+        let mut state = AppState::default();
+        let dark_mode = ctx.style().visuals.dark_mode;
+        toggle_theme(&ctx, &mut state);
+        assert_ne!(ctx.style().visuals.dark_mode, dark_mode);
+        assert_eq!(state.widgets.ui_prefs.dark_mode, Some(!dark_mode));
+
+        // The next session starts with whichever theme was chosen last
+        let json = serde_json::to_string(&state.widgets).unwrap();
+        let widgets = serde_json::from_str::<WidgetState>(&json).unwrap();
+        let next_session = Context::default();
+        apply_ui_prefs(&next_session, &widgets.ui_prefs);
+        assert_ne!(next_session.style().visuals.dark_mode, dark_mode);
+    }
+
+    #[test]
+    fn zoom_shortcuts_are_clamped() {
+        use crate::app::state::MAX_ZOOM;
+        use egui::{Modifiers, RawInput};
+
+        // Runs a frame with the given text typed while the command modifier is held,
+        // returning the zoom afterwards and the text that wasn't consumed by the shortcuts
+        fn type_with_command(prefs: &mut UiPrefs, typed: &[&str]) -> (f32, Vec<String>) {
+            let input = RawInput {
+                modifiers: Modifiers::COMMAND,
+                events: typed
+                    .iter()
+                    .map(|text| Event::Text(text.to_string()))
+                    .collect(),
+                ..Default::default()
+            };
+            let mut unconsumed = Vec::new();
+            let _ = Context::default().run(input, |ctx| {
+                zoom_shortcuts(ctx, prefs);
+                unconsumed.extend(ctx.input().events.iter().filter_map(|event| match event {
+                    Event::Text(text) => Some(text.clone()),
+                    _ => None,
+                }));
+            });
+            (prefs.zoom(), unconsumed)
+        }
+
+        let mut prefs = UiPrefs::default();
+        let (zoom, unconsumed) = type_with_command(&mut prefs, &["=", "=", "-", "a"]);
+        assert!((zoom - 1.1).abs() < 1e-4, "Zoomed to {}", zoom);
+        assert_eq!(unconsumed, ["a"]);
+
+        assert_eq!(type_with_command(&mut prefs, &["+"; 100]).0, MAX_ZOOM);
+        assert_eq!(type_with_command(&mut prefs, &["0"]).0, 1.0);
+
+        // Preferences from before the zoom was remembered default to the normal scale
+        let prefs = serde_json::from_str::<UiPrefs>(r#"{"dark_mode":true}"#).unwrap();
+        assert_eq!(prefs.zoom(), 1.0);
+    }
+
+    #[test]
+    fn timing_formats() {
+        for (count, expected) in [
+            (0, "0"),
+            (999, "999"),
+            (1204, "1,204"),
+            (100000, "100,000"),
+            (1234567, "1,234,567"),
+        ] {
+            assert_eq!(format_count(count), expected);
+        }
+
+        for (duration, expected) in [
+            (Duration::from_micros(400), "0.4 ms"),
+            (Duration::from_micros(9_940), "9.9 ms"),
+            (Duration::from_millis(12), "12 ms"),
+            (Duration::from_millis(1500), "1.5 s"),
+        ] {
+            assert_eq!(format_duration(duration), expected, "{:?}", duration);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::app::clipboard::ClipboardRead;
+use crate::app::state::{AppState, ClipboardTestState, InputSource, MatchesSelector};
+use egui::{Context, Event, Grid, Ui, Window};
+
+/// The maximum number of matches to list in the window
+const MAX_LISTED_MATCHES: usize = 16;
+
+/// The actions that can be taken from the clipboard test window
+enum ClipboardTestAction {
+    /// Replace the input text with the tested text
+    LoadAsInput,
+    /// Read the clipboard again, to test whatever is on it now
+    ReadAgain,
+}
+
+/// Adds a window that displays the matches found by running the regex against the text on the clipboard
+pub fn clipboard_test(ctx: &Context, state: &mut AppState) {
+    if matches!(state.widgets.clipboard_test, ClipboardTestState::Closed) {
+        return;
+    }
+
+    // The clipboard is tested once it has been read, which waits until the regex is valid to test it with
+    if let (ClipboardTestState::Reading(read), Ok(logic)) =
+        (&state.widgets.clipboard_test, &state.logic)
+    {
+        match read.poll() {
+            Some(Ok(text)) => {
+                let captures = logic.captures_in(&text);
+                state.widgets.clipboard_test = ClipboardTestState::Tested(
+                    MatchesSelector::create_from_captures(&captures, &logic.regex, text),
+                );
+            }
+            Some(Err(err)) => state.widgets.clipboard_test = ClipboardTestState::AwaitingPaste(err),
+            None => {}
+        }
+    }
+
+    // Text can also be pasted, such as when the clipboard can't be read directly,
+    // as long as it isn't being pasted into a text edit
+    if ctx.memory().focus().is_none() {
+        let pasted = ctx
+            .input()
+            .events
+            .iter()
+            .rev()
+            .find_map(|event| match event {
+                Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+
+        if let (Some(text), Ok(logic)) = (pasted, &state.logic) {
+            let captures = logic.captures_in(&text);
+            state.widgets.clipboard_test = ClipboardTestState::Tested(
+                MatchesSelector::create_from_captures(&captures, &logic.regex, text),
+            );
+        }
+    }
+
+    let mut open = true;
+    let mut action = None;
+    Window::new("Test Clipboard")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            action = clipboard_test_ui(ui, state);
+        });
+
+    let test = std::mem::take(&mut state.widgets.clipboard_test);
+    match (test, action) {
+        (_, Some(ClipboardTestAction::ReadAgain)) => {
+            state.widgets.clipboard_test = ClipboardTestState::Reading(ClipboardRead::start(ctx));
+        }
+        (ClipboardTestState::Tested(selector), Some(ClipboardTestAction::LoadAsInput)) => {
+            state.load_input_text(selector.text, InputSource::ClipboardTest, &ctx.style());
+        }
+        (test, _) if open => state.widgets.clipboard_test = test,
+        _ => {}
+    }
+}
+
+/// Displays the matches found in the text on the clipboard
+///
+/// Returns the action that was taken, if any
+fn clipboard_test_ui(ui: &mut Ui, state: &AppState) -> Option<ClipboardTestAction> {
+    match &state.logic {
+        Err(_) => {
+            ui.label("The regular expression is malformed. Click the red ⊗ to view the error.");
+            return None;
+        }
+        Ok(logic) if logic.empty_pattern => {
+            ui.label("Enter a pattern to begin.");
+            return None;
+        }
+        Ok(_) => {}
+    }
+
+    let selector = match &state.widgets.clipboard_test {
+        ClipboardTestState::Tested(selector) => selector,
+        ClipboardTestState::AwaitingPaste(reason) => {
+            ui.label(format!("The clipboard couldn't be read: {}", reason));
+            ui.label(
+                "Press Ctrl+V to test the regex against the contents of the clipboard instead.",
+            );
+            ui.weak("The input text will not be changed.");
+            return None;
+        }
+        _ => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Reading the clipboard…");
+            });
+            ui.weak("The browser may ask for permission to read it.");
+            return None;
+        }
+    };
+
+    ui.label(format!(
+        "{} matches in {} bytes of pasted text",
+        selector.matches.len(),
+        selector.text.len()
+    ));
+
+    Grid::new("clipboard_test_matches")
+        .num_columns(2)
+        .show(ui, |ui| {
+            // The first capture group of each match is always the whole match
+            for (index, range) in selector
+                .matches
+                .iter()
+                .filter_map(|groups| groups.first()?.0.as_ref())
+                .enumerate()
+                .take(MAX_LISTED_MATCHES)
+            {
+                ui.label((index + 1).to_string());
+                ui.monospace(selector.text[range.clone()].replace('\n', "\\n"));
+                ui.end_row();
+            }
+        });
+
+    if selector.matches.len() > MAX_LISTED_MATCHES {
+        ui.weak(format!(
+            "…and {} more",
+            selector.matches.len() - MAX_LISTED_MATCHES
+        ));
+    }
+
+    ui.separator();
+    let mut action = None;
+    ui.horizontal(|ui| {
+        if ui
+            .button("📋 Read Again")
+            .on_hover_text("Test whatever is on the clipboard now, or press Ctrl+V to paste it")
+            .clicked()
+        {
+            action = Some(ClipboardTestAction::ReadAgain);
+        }
+        if ui
+            .button("Load As Input")
+            .on_hover_text("Replace the input text with the tested text")
+            .clicked()
+        {
+            action = Some(ClipboardTestAction::LoadAsInput);
+        }
+    });
+    action
+}
@@ -1,13 +1,46 @@
-use crate::app::state::{AppState, LogicState};
-use crate::app::text::{glyph_bounds, layout_matched_text, layout_plain_text, layout_regex_err};
-use crate::app::{
-    shape::{curve_between, Orientation},
-    state::MatchesSelector,
+use crate::app::clipboard::ClipboardRead;
+use crate::app::color;
+use crate::app::completion::completion_at;
+use crate::app::escaped_bytes::EscapedBytes;
+use crate::app::export::{parse_template, regex_as_code, CodeLanguage};
+use crate::app::parsing::{ast_find_capture_groups, compile_regex};
+use crate::app::refactor::{
+    check_wrap_range, escape_literal, insert_literal, refactor_group, wrap_range, GroupRefactoring,
+    WrapKind,
 };
+use crate::app::replacement::{
+    ambiguous_references, brace_ambiguous_references, expand_replacement, missing_references,
+    resolve_references, unreferenced_groups, whole_match_usage, ExpansionPart, WholeMatchUsage,
+};
+use crate::app::shape::{line_between, ConnectingLine, LineStyle};
+use crate::app::snippets::SNIPPETS;
+#[cfg(feature = "backend-fancy")]
+use crate::app::state::RegexEngine;
+use crate::app::state::{
+    should_debounce, AppState, ClipboardTestState, HighlightMode, InputSource, LogicOptions,
+    LogicState, PendingEdit, ReplaceLimit, ReplaceSteps, ResultModeState, SectionId, TabBarState,
+};
+use crate::app::text::{
+    caret_at, glyph_at, glyph_bounds, highlight_span, layout_plain_text, layout_regex_err,
+    layout_replacement, layout_result_text, layout_template_err, marker_buckets, TextIndex,
+};
+use crate::app::tutorial::TutorialTarget;
+use crate::app::ui::{
+    format_byte_count, format_count, format_duration, tutorial::record_target, COMMAND_NAME,
+};
+use crate::app::verbose::format_verbose;
 use egui::{
-    layers::ShapeIdx, text_edit::TextEditOutput, Align, CentralPanel, Color32, Context, Frame,
-    Layout, Response, RichText, ScrollArea, Shape, Stroke, TextEdit, Ui, Vec2,
+    layers::ShapeIdx,
+    show_tooltip_at_pointer,
+    text::{CCursor, CCursorRange},
+    text_edit::TextEditOutput,
+    Align, Area, Button, CentralPanel, Checkbox, Color32, ComboBox, Context, CursorIcon, DragValue,
+    Event, FontId, Frame, Grid, Id, Key, Label, Layout, Modifiers, Order, Pos2, ProgressBar, Rect,
+    Response, RichText, ScrollArea, Sense, Shape, Spinner, Stroke, TextEdit, TextStyle, Ui, Vec2,
+    Window,
 };
+use regex::Regex;
+use std::{mem::discriminant, ops::Range, time::Duration};
 
 /// Adds a container that displays the main interactive parts of the UI
 pub fn editor(ctx: &Context, state: &mut AppState) {
@@ -16,53 +49,712 @@ pub fn editor(ctx: &Context, state: &mut AppState) {
 
 /// Displays the main interactive parts of the UI
 pub fn editor_ui(ui: &mut Ui, state: &mut AppState) {
+    // Apply edits that were debounced once typing pauses, making sure that happens even if nothing else causes a repaint
+    let now = ui.input().time;
+    if let Some(remaining) = state.apply_pending_edit(now, ui.style()) {
+        ui.ctx()
+            .request_repaint_after(Duration::from_secs_f64(remaining));
+    }
+
+    // Lay out the input text if that was deferred when it last changed, or continue searching it for captures
+    if let Ok(logic) = &mut state.logic {
+        logic.finish_layout(&state.widgets.input_text, ui.style());
+
+        // Keep searching for captures even if nothing else causes a repaint
+        if logic.capture_search.is_some() {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    if ui
+        .ctx()
+        .input_mut()
+        .consume_key(Modifiers::COMMAND, Key::Tab)
+    {
+        state.swap_stash(ui.style());
+    }
+
     ScrollArea::vertical().show(ui, |ui| {
-        regex_header(ui);
-        let regex_result = regex_editor(ui, state);
+        ui.with_layout(Layout::right_to_left(Align::Min), |ui| {
+            section_layout_menu(ui, state)
+        });
 
-        input_header(ui);
+        let mut regex_result = None;
+        let mut input_result = None;
         let mut connecting_lines_idx = None;
-        let input_result = ui
-            .allocate_ui_with_layout(
-                ui.available_size() - (ui.max_rect().size() * Vec2::Y * 0.5),
-                Layout::centered_and_justified(ui.layout().main_dir()),
-                |ui| input_editor(ui, state, &mut connecting_lines_idx),
-            )
-            .inner;
 
-        replace_header(ui);
-        let replace_result = replace_editor(ui, state);
+        let sections = state.widgets.sections.clone();
+        for (position, section) in sections.iter().enumerate() {
+            // Expanding sections leave room for any expanding sections after them
+            let expanding_after = sections[position + 1..].iter().any(|next| next.expands());
+            let rect = ui
+                .scope(|ui| match section {
+                    SectionId::Regex => regex_result = Some(regex_section(ui, state)),
+                    SectionId::Input => {
+                        input_result = Some(input_section(
+                            ui,
+                            state,
+                            &mut connecting_lines_idx,
+                            expanding_after,
+                        ))
+                    }
+                    SectionId::ResultMode => {
+                        result_mode_selector(ui, state);
+                        result_mode_options(ui, state);
+                        replace_steps(ui, state);
+                    }
+                    SectionId::Result => result_section(ui, state, expanding_after),
+                })
+                .response
+                .rect;
+
+            if state.widgets.tutorial.active {
+                record_target(ui.ctx(), TutorialTarget::Section(*section), rect);
+            }
+        }
+
+        // The matches and capture groups are of the text from before any pending edits,
+        // so nothing is drawn over the edited text until they have been applied
+        if state.pending_edit.is_some() {
+            return;
+        }
+
+        if let (Some(regex_result), Some(input_result), Some(idx)) =
+            (&regex_result, &input_result, connecting_lines_idx)
+        {
+            connecting_lines(ui, state, idx, regex_result, input_result);
+            endpoint_flash(ui, state);
+        }
+
+        if let Some(input_result) = &input_result {
+            layered_underlines(ui, state, input_result);
+            scroll_to_selected_match(ui, state, input_result);
+            scroll_to_target(ui, state, input_result);
+            selected_match_emphasis(ui, state, input_result);
+            empty_match_carets(ui, state, input_result);
+            split_piece_highlight(ui, state, input_result);
+            select_clicked_match(state, input_result);
+            match_tooltip(ui, state, input_result);
+        }
+
+        if let Some(regex_result) = &regex_result {
+            // A group's description is written by the user for that group specifically,
+            // so it takes priority over the generic explanation of the syntax there
+            let described = group_description_tooltip(ui, state, regex_result, |logic, glyph| {
+                logic.regex_layout.group_at_glyph(glyph)
+            });
+            if !described {
+                syntax_tooltip(ui, state, regex_result);
+            }
+        }
+
+        // Everything has been added to the scroll area by now, so its full height is known
+        if let Some(input_result) = &input_result {
+            scroll_markers(ui, state, input_result);
+        }
+    });
+}
+
+/// Displays a menu for reordering and hiding the sections of the editor
+fn section_layout_menu(ui: &mut Ui, state: &mut AppState) {
+    ui.menu_button("⚙ Layout", |ui| {
+        let sections = &mut state.widgets.sections;
+        let count = sections.len();
+        let mut hide = None;
+        let mut swap = None;
+
+        for (position, section) in sections.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let up = ui.add_enabled(position > 0, Button::new("⏶").small());
+                if up.on_hover_text("Move Up").clicked() {
+                    swap = Some(position - 1);
+                }
+
+                let down = ui.add_enabled(position + 1 < count, Button::new("⏷").small());
+                if down.on_hover_text("Move Down").clicked() {
+                    swap = Some(position);
+                }
+
+                let mut visible = true;
+                if ui.checkbox(&mut visible, section.name()).changed() {
+                    hide = Some(position);
+                }
+            });
+        }
+
+        // Hidden sections are listed afterwards, and are shown at the end of the editor when they are made visible again
+        for section in SectionId::ALL {
+            if sections.contains(&section) {
+                continue;
+            }
+
+            let mut visible = false;
+            if ui.checkbox(&mut visible, section.name()).changed() {
+                sections.push(section);
+            }
+        }
+
+        if let Some(position) = swap {
+            sections.swap(position, position + 1);
+        }
+
+        if let Some(position) = hide {
+            sections.remove(position);
+        }
+    });
+}
+
+/// Returns the size available to an expanding section,
+/// leaving half of the height of the editor for any expanding sections after it
+fn expanding_size(ui: &Ui, expanding_after: bool) -> Vec2 {
+    if expanding_after {
+        ui.available_size() - (ui.max_rect().size() * Vec2::Y * 0.5)
+    } else {
+        ui.available_size()
+    }
+}
+
+/// Displays the regex editor and associated widgets
+fn regex_section(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
+    regex_header(ui, state);
+    degraded_banner(ui, state);
+    let completion_key = completion_key(ui);
+    let regex_result = regex_editor(ui, state);
+    completion_popup(ui, state, &regex_result, completion_key);
+    error_details(ui, state);
+    verbose_format(ui, state);
+    wrap_selection_menu(ui, state, &regex_result);
+    if regex_result.response.changed() {
+        state.widgets.stash_swap_count = None;
+        // Debounced edits are recorded once they have been compiled
+        if state.logic.is_ok() && state.pending_edit.is_none() {
+            state.widgets.record_pattern();
+        }
+    }
+    stash(ui, state);
+    invalidate_results_if_changed(ui, state, &regex_result.response);
+    regex_result
+}
 
-        result_header(ui);
-        ui.allocate_ui_with_layout(
-            ui.available_size(),
+/// Displays the input editor and associated widgets
+fn input_section(
+    ui: &mut Ui,
+    state: &mut AppState,
+    connecting_lines_idx: &mut Option<ShapeIdx>,
+    expanding_after: bool,
+) -> TextEditOutput {
+    input_header(ui, state);
+    truncation_notice(ui, state);
+    let input_result = ui
+        .allocate_ui_with_layout(
+            expanding_size(ui, expanding_after),
             Layout::centered_and_justified(ui.layout().main_dir()),
-            |ui| {
-                result_body(
-                    ui,
-                    state,
-                    &regex_result.response,
-                    &input_result.response,
-                    &replace_result.response,
-                )
-            },
+            |ui| input_editor(ui, state, connecting_lines_idx),
+        )
+        .inner;
+    invalidate_results_if_changed(ui, state, &input_result.response);
+    escape_selection_menu(ui, state, &input_result);
+    timing_status(ui, state);
+
+    // Text that is pasted into the editor is subject to the same size limit as text loaded in other ways
+    if state.widgets.input_text.len() > state.widgets.input_size_limit {
+        let source = if ui
+            .input()
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::Paste(_)))
+        {
+            InputSource::Pasted
+        } else {
+            InputSource::Edited
+        };
+
+        let text = std::mem::take(&mut state.widgets.input_text);
+        state.load_input_text(text, source, ui.style());
+    }
+
+    input_result
+}
+
+/// Displays how long compiling the regex, and matching and laying out the input text, took,
+/// so that it's clear how expensive the regex is, and whether any slowness is from matching or from layout
+fn timing_status(ui: &mut Ui, state: &AppState) {
+    let logic = match &state.logic {
+        Ok(logic) if !logic.empty_pattern => logic,
+        _ => return,
+    };
+
+    let timings = &logic.timings;
+    let mut status = format!("Compiled in {}", format_duration(timings.compile));
+    match logic.match_count() {
+        Some(count) => status.push_str(&format!(
+            ", matched {} {} in {}, laid out in {}",
+            format_count(count),
+            if count == 1 { "time" } else { "times" },
+            format_duration(timings.matching),
+            format_duration(timings.layout)
+        )),
+        None => status.push_str(", matching…"),
+    }
+    ui.label(RichText::new(status).small().weak());
+}
+
+/// Displays a notice explaining that the input text was truncated, if it was,
+/// along with actions for dealing with the part of the text that was cut off
+fn truncation_notice(ui: &mut Ui, state: &mut AppState) {
+    let truncation = match &mut state.widgets.input_truncation {
+        Some(truncation) => truncation,
+        None => return,
+    };
+
+    let mut dismissed = false;
+    let mut raise_limit = false;
+    ui.horizontal_wrapped(|ui| {
+        dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+
+        ui.colored_label(
+            Color32::YELLOW,
+            format!(
+                "⚠ Input truncated: showing first {} of {} {}",
+                format_byte_count(state.widgets.input_text.len()),
+                format_byte_count(truncation.original_bytes),
+                truncation.source.description()
+            ),
         );
 
-        connecting_lines(
-            ui,
-            state,
-            connecting_lines_idx.unwrap(),
-            &regex_result,
-            &input_result,
+        // The full text isn't persisted, so it can't be recovered after restarting
+        let full_text = match &truncation.full_text {
+            Some(full_text) => full_text,
+            None => return,
+        };
+
+        raise_limit = ui
+            .small_button("Raise Limit")
+            .on_hover_text("Raise the input size limit to fit the full text, and load all of it")
+            .clicked();
+
+        match (truncation.full_match_count, &state.logic) {
+            (Some(count), _) => {
+                ui.weak(format!("{} matches in the full text", count));
+            }
+            (None, Ok(logic)) => {
+                if ui
+                    .small_button("Count Matches")
+                    .on_hover_text("Count the matches in the full text, without highlighting them")
+                    .clicked()
+                {
+                    truncation.full_match_count = Some(logic.count_matches_in(full_text));
+                }
+            }
+            (None, _) => {}
+        }
+    });
+
+    let source = truncation.source;
+    if raise_limit {
+        if let Some(text) = truncation.full_text.take() {
+            state.widgets.input_size_limit = text.len();
+            state.load_input_text(text, source, ui.style());
+        }
+    } else if dismissed {
+        state.widgets.input_truncation = None;
+    }
+}
+
+/// Displays the result of the current result mode and associated widgets
+fn result_section(ui: &mut Ui, state: &mut AppState, expanding_after: bool) {
+    // Only the active mode is recomputed; inactive modes are recomputed once they are switched back to
+    let unavailable = state.widgets.results_unavailable();
+    match &state.logic {
+        Ok(logic) if unavailable.is_none() => state
+            .widgets
+            .result_mode
+            .update(logic, &state.widgets.input_text),
+        _ => {}
+    }
+
+    result_header(ui, state);
+    if let Some(reason) = unavailable {
+        ui.weak(reason);
+        return;
+    }
+
+    whole_match_note(ui, state);
+    ambiguous_reference_note(ui, state);
+    group_reference_note(ui, state);
+    ui.allocate_ui_with_layout(
+        expanding_size(ui, expanding_after),
+        Layout::centered_and_justified(ui.layout().main_dir()),
+        |ui| result_body(ui, state),
+    );
+}
+
+/// Discards the output of every result mode if one of the shared inputs changed
+fn invalidate_results_if_changed(ui: &Ui, state: &mut AppState, response: &Response) {
+    if response.changed() {
+        state.widgets.invalidate_results();
+
+        // The result section may have already been shown this frame, if it is above the changed input
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Displays the header for the regex editor, along with toggles for the flags that apply to the whole regex
+fn regex_header(ui: &mut Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Regular Expression");
+        regex_flag_toggles(ui, state);
+        #[cfg(feature = "backend-fancy")]
+        regex_engine_selector(ui, state);
+
+        if ui
+            .small_button("Format")
+            .on_hover_text("Show the regex laid out over multiple lines in verbose mode (x)")
+            .clicked()
+        {
+            state.widgets.verbose_format_visible ^= true;
+        }
+        if ui
+            .small_button("Compact")
+            .on_hover_text(
+                "Remove the whitespace and comments that verbose mode ignores from the regex, \
+                and turn verbose mode off",
+            )
+            .clicked()
+        {
+            state.compact_regex(ui.style());
+        }
+        snippet_menu(ui, state);
+    });
+}
+
+/// Displays a dropdown button listing the snippets, where selecting one inserts it into the regex at the cursor,
+/// and optionally adds its sample to the input text
+fn snippet_menu(ui: &mut Ui, state: &mut AppState) {
+    let mut inserted = None;
+    ui.menu_button("Insert Pattern", |ui| {
+        ui.checkbox(
+            &mut state.widgets.append_snippet_sample,
+            "Add a sample to the input text",
         );
+        ui.separator();
+
+        for snippet in SNIPPETS {
+            let response = ui.button(snippet.name).on_hover_ui(|ui| {
+                ui.label(snippet.description);
+                ui.label(RichText::new(snippet.grouped(&[])).monospace());
+                ui.weak(format!("Sample: {}", snippet.sample));
+            });
+            if response.clicked() {
+                inserted = Some(snippet);
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text(
+        "Insert a common pattern in a named group, replacing the selection in the regex",
+    );
+
+    if let Some(snippet) = inserted {
+        let taken = state
+            .logic
+            .as_ref()
+            .map(|logic| logic.regex.capture_names().flatten().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let text = snippet.grouped(&taken);
+        insert_into_regex(ui, state, &text);
+        if state.widgets.append_snippet_sample {
+            state.append_input_line(snippet.sample, ui.style());
+        }
+    }
+}
+
+/// Displays a selector for the regex engine that the pattern is compiled with, recompiling it when another is selected
+#[cfg(feature = "backend-fancy")]
+fn regex_engine_selector(ui: &mut Ui, state: &mut AppState) {
+    let mut engine = state.widgets.regex_engine;
+    ui.add_enabled_ui(!state.widgets.byte_mode, |ui| {
+        ComboBox::from_id_source("regex_engine")
+            .selected_text(engine.name())
+            .show_ui(ui, |ui| {
+                for option in [RegexEngine::Standard, RegexEngine::Fancy] {
+                    ui.selectable_value(&mut engine, option, option.name());
+                }
+            })
+            .response
+            .on_hover_text(
+                "fancy-regex supports lookaround and backreferences, \
+                but can take exponentially long to match some patterns",
+            )
+            .on_disabled_hover_text("Bytes are always matched with the regex engine");
+    });
+
+    if engine != state.widgets.regex_engine {
+        state.set_regex_engine(engine, ui.style());
+    }
+}
+
+/// Displays a checkbox for each of the flags that can be applied to the whole regex,
+/// recompiling it whenever one of them is toggled
+fn regex_flag_toggles(ui: &mut Ui, state: &mut AppState) {
+    let mut flags = state.widgets.regex_flags;
+    let mut changed = false;
+    for (letter, description, enabled) in flags.toggles() {
+        changed |= ui
+            .checkbox(enabled, RichText::new(letter).monospace())
+            .on_hover_text(description)
+            .changed();
+    }
+
+    if changed {
+        state.set_regex_flags(flags, ui.style());
+    }
+}
+
+/// Displays a button to swap the regex with the stashed alternative pattern, and a preview of that pattern
+fn stash(ui: &mut Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        if ui
+            .small_button("⇄ Swap")
+            .on_hover_text(format!(
+                "Swap the regex with the stashed pattern ({}+Tab)",
+                COMMAND_NAME
+            ))
+            .clicked()
+        {
+            state.swap_stash(ui.style());
+        }
+
+        if state.widgets.stashed_regex.is_empty() {
+            ui.weak("Stash is empty");
+        } else {
+            ui.add(
+                Label::new(
+                    RichText::new(&state.widgets.stashed_regex)
+                        .monospace()
+                        .weak(),
+                )
+                .wrap(false),
+            );
+        }
+
+        let previous_count = match state.widgets.stash_swap_count {
+            Some(previous_count) => previous_count,
+            None => return,
+        };
+
+        let current_count = state.logic.as_ref().ok().and_then(LogicState::match_count);
+        let format_count =
+            |count: Option<usize>| count.map_or("?".into(), |count| count.to_string());
+        ui.weak(format!(
+            "Matches: {} → {}",
+            format_count(previous_count),
+            format_count(current_count)
+        ));
+    });
+}
+
+/// Displays a window with the full details of the error in the regex, if there is one and the window is open
+fn error_details(ui: &Ui, state: &mut AppState) {
+    let err = match &state.logic {
+        Err(err) => err,
+        Ok(_) => {
+            state.widgets.error_details_visible = false;
+            return;
+        }
+    };
+
+    let mut details = match err.location(&state.widgets.regex_text) {
+        Some(location) => format!("{}\n\n{}", location, err),
+        None => err.to_string(),
+    };
+    let diagnosis = state.diagnosis();
+    if let Some(diagnosis) = &diagnosis {
+        details = format!("{}\n\n{}", details, diagnosis.explanation);
+    }
+
+    let mut applied = None;
+    Window::new("Regex Error")
+        .open(&mut state.widgets.error_details_visible)
+        .default_width(480.0)
+        .show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("📋 Copy").clicked() {
+                    ui.output().copied_text = details.clone();
+                }
+                if let Some(suggestion) = diagnosis.and_then(|diagnosis| diagnosis.suggestion) {
+                    if ui.button(format!("💡 {}", suggestion.label())).clicked() {
+                        applied = Some(suggestion);
+                    }
+                }
+            });
+
+            ScrollArea::vertical().show(ui, |ui| {
+                TextEdit::multiline(&mut details.as_str())
+                    .font(TextStyle::Monospace)
+                    .text_color(Color32::RED)
+                    .desired_width(f32::INFINITY)
+                    .show(ui);
+            });
+        });
+
+    if let Some(suggestion) = applied {
+        state.apply_suggestion(suggestion, ui.style());
+    }
+}
+
+/// Displays a window with the regex laid out over multiple lines in verbose mode, if the window is open
+fn verbose_format(ui: &Ui, state: &mut AppState) {
+    if !state.widgets.verbose_format_visible {
+        return;
+    }
+
+    let formatted = format_verbose(&state.widgets.regex_text, state.widgets.regex_flags);
+    let mut edited = None;
+    Window::new("Verbose Format")
+        .open(&mut state.widgets.verbose_format_visible)
+        .default_width(480.0)
+        .show(ui.ctx(), |ui| match formatted {
+            Ok(formatted) => {
+                ui.horizontal(|ui| {
+                    if ui.button("📋 Copy").clicked() {
+                        ui.output().copied_text = formatted.clone();
+                    }
+                    if ui
+                        .button("✏ Edit")
+                        .on_hover_text(
+                            "Replace the regex with this, and edit it over multiple lines",
+                        )
+                        .clicked()
+                    {
+                        edited = Some(formatted.clone());
+                    }
+                });
+
+                ScrollArea::vertical().show(ui, |ui| {
+                    TextEdit::multiline(&mut formatted.as_str())
+                        .font(TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .show(ui);
+                });
+            }
+            Err(_) => {
+                ui.label("The regular expression is malformed. Click the red ⊗ to view the error.");
+            }
+        });
+
+    if let Some(formatted) = edited {
+        state.widgets.multiline_regex = true;
+        state.widgets.verbose_format_visible = false;
+        state.restore_pattern(formatted, ui.style());
+    }
+}
+
+/// Displays a banner explaining why the regex is visualised in a degraded form, if it is
+fn degraded_banner(ui: &mut Ui, state: &mut AppState) {
+    let group_count = match &state.logic {
+        Ok(logic) if logic.regex_layout.degraded => logic.regex_layout.capture_group_chars.len(),
+        _ => return,
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        ui.weak(format!(
+            "The regex has {} capture groups, which is too many to color individually, \
+            so only group boundaries are highlighted and only the selected group is connected to its match.",
+            group_count
+        ));
+
+        if ui.small_button("Render Fully").clicked() {
+            state.widgets.force_full_rendering = true;
+            state.recompile(ui.style());
+        }
+    });
+}
+
+/// Displays a dropdown button listing the most recent patterns that compiled successfully, newest first,
+/// where selecting one replaces the regex text with it
+fn pattern_history(ui: &mut Ui, state: &mut AppState) {
+    let mut restored = None;
+    ui.add_enabled_ui(!state.widgets.pattern_history.is_empty(), |ui| {
+        ui.menu_button("🕘", |ui| {
+            ScrollArea::vertical()
+                .max_height(PATTERN_HISTORY_HEIGHT)
+                .show(ui, |ui| {
+                    for pattern in state.widgets.pattern_history.iter().rev() {
+                        let label = RichText::new(pattern).monospace();
+                        if ui.add(Button::new(label).wrap(false)).clicked() {
+                            restored = Some(pattern.clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+            ui.separator();
+            if ui.button("🗑 Clear History").clicked() {
+                state.widgets.pattern_history.clear();
+                ui.close_menu();
+            }
+        })
+        .response
+        .on_hover_text("Recently used patterns");
     });
+
+    if let Some(pattern) = restored {
+        state.restore_pattern(pattern, ui.style());
+    }
 }
 
-/// Displays the header for the regex editor
-fn regex_header(ui: &mut Ui) {
-    ui.label("Regular Expression");
+/// How tall the list of patterns in the pattern history can grow before it scrolls
+const PATTERN_HISTORY_HEIGHT: f32 = 320.0;
+
+/// Displays a button for pasting text into the regex editor as a literal, and escapes any characters that have a
+/// special meaning in a regex in the text that is pasted into it, if the button was clicked or Shift was held
+fn paste_escaped(ui: &mut Ui, state: &mut AppState) {
+    let id = Id::new(REGEX_EDITOR_ID);
+    if ui
+        .selectable_label(
+            state.widgets.escape_next_paste,
+            RichText::new("\\.").monospace(),
+        )
+        .on_hover_text(format!(
+            "Paste text as a literal, escaping any characters that have a special meaning in a regex \
+            (Click then {0}+V, or {0}+Shift+V)",
+            COMMAND_NAME
+        ))
+        .clicked()
+    {
+        state.widgets.escape_next_paste ^= true;
+        ui.memory().request_focus(id);
+    }
+
+    if !ui.memory().has_focus(id) {
+        state.widgets.escape_next_paste = false;
+        return;
+    }
+
+    let mut input = ui.ctx().input_mut();
+    let shift_pasted = input.events.iter().any(|event| match event {
+        Event::Key {
+            key: Key::V,
+            pressed: true,
+            modifiers,
+        } => modifiers.command && modifiers.shift,
+        _ => false,
+    });
+    for event in &mut input.events {
+        if let Event::Paste(text) = event {
+            if state.widgets.escape_next_paste || shift_pasted {
+                *text = escape_literal(text.as_bytes(), state.widgets.regex_flags);
+                state.widgets.escape_next_paste = false;
+            }
+        }
+    }
 }
 
+/// The id of the regex editor, which is used to focus it from a keyboard shortcut
+pub const REGEX_EDITOR_ID: &str = "regex_editor";
+
 /// Handles the regular expression text and associated state
 fn regex_editor(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
     // If the text gets edited the layouter will be ran again; keep track of this to enable caching state
@@ -80,36 +772,138 @@ fn regex_editor(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
                 ui.add_space(3.0);
 
                 let icon = state.logic.is_err().then_some("⊗").unwrap_or_default();
-                let response = ui.label(RichText::new(icon).color(Color32::RED).size(21.0));
+                let response = ui.add(
+                    Label::new(RichText::new(icon).color(Color32::RED).size(21.0))
+                        .sense(Sense::click()),
+                );
                 if let Err(err) = &state.logic {
-                    response.on_hover_text(
-                        RichText::new(err.to_string())
-                            .color(Color32::RED)
-                            .monospace(),
+                    let diagnosis = state.diagnosis();
+                    let explanation = diagnosis
+                        .as_ref()
+                        .map(|diagnosis| format!("\n\n{}", diagnosis.explanation))
+                        .unwrap_or_default();
+                    let spans = err
+                        .labeled_spans()
+                        .into_iter()
+                        .map(|(span, label)| {
+                            format!(
+                                "\n{}: bytes {}..{}",
+                                label, span.start.offset, span.end.offset
+                            )
+                        })
+                        .collect::<String>();
+                    let response = response.on_hover_text(
+                        RichText::new(format!(
+                            "{}{}{}\nClick for details",
+                            err.message(),
+                            spans,
+                            explanation
+                        ))
+                        .color(Color32::RED)
+                        .monospace(),
                     );
+                    if response.clicked() {
+                        state.widgets.error_details_visible ^= true;
+                    }
+
+                    if let Some(suggestion) = diagnosis.and_then(|diagnosis| diagnosis.suggestion) {
+                        if ui
+                            .small_button("💡")
+                            .on_hover_text(suggestion.label())
+                            .clicked()
+                        {
+                            state.apply_suggestion(suggestion, ui.style());
+                        }
+                    }
                 }
 
-                let result = TextEdit::singleline(&mut state.widgets.regex_text)
+                pattern_history(ui, state);
+                paste_escaped(ui, state);
+                ui.toggle_value(&mut state.widgets.multiline_regex, "¶")
+                    .on_hover_text(
+                        "Edit the regex over multiple lines, such as for verbose mode (x), \
+                        and wrap long lines",
+                    );
+
+                let group_threshold = state.widgets.group_threshold();
+                let match_limit = state.widgets.highlight_match_limit;
+                let text_edit = if state.widgets.multiline_regex {
+                    TextEdit::multiline(&mut state.widgets.regex_text).desired_rows(1)
+                } else {
+                    TextEdit::singleline(&mut state.widgets.regex_text)
+                };
+                let result = text_edit
+                    .id(Id::new(REGEX_EDITOR_ID))
                     .desired_width(f32::INFINITY)
                     .frame(false)
                     .margin(Vec2::new(8.0, 4.0))
                     .layouter(&mut |ui, text, wrap_width| {
-                        if regex_changed {
+                        if regex_changed && should_debounce(&state.widgets.input_text) {
+                            PendingEdit::add(&mut state.pending_edit, ui.input().time, true, false);
+                        } else if regex_changed {
                             // Recompute relevant state if the text was edited
-                            state.logic = LogicState::new(
+                            let logic = LogicState::new(
                                 text,
                                 ui.style(),
                                 text,
                                 &state.widgets.input_text,
-                                state.logic.as_ref().ok(),
+                                LogicOptions {
+                                    highlight_mode: state.widgets.highlight_mode,
+                                    line_mode: state.widgets.line_mode,
+                                    byte_mode: state.widgets.byte_mode,
+                                    engine: state.widgets.regex_engine,
+                                    flags: state.widgets.regex_flags,
+                                    group_threshold,
+                                    match_limit: Some(match_limit),
+                                    custom_colors: state.widgets.custom_colors.clone(),
+                                    previous_layout: LogicState::previous_layout(
+                                        &state.logic,
+                                        &state.last_regex_layout,
+                                    ),
+                                },
+                            );
+                            LogicState::replace(
+                                &mut state.logic,
+                                &mut state.last_regex_layout,
+                                logic,
                             );
+
+                            if let Ok(logic) = &state.logic {
+                                state
+                                    .widgets
+                                    .group_descriptions
+                                    .update_groups(logic.regex.capture_names());
+
+                                if state.widgets.journal_enabled {
+                                    state.journal.record(text, logic.match_count());
+                                }
+                            }
                         }
                         regex_changed = true;
 
-                        let mut layout_job = state.logic.as_ref().map_or_else(
-                            |err| layout_regex_err(text.into(), ui.style(), err).job,
-                            |state| state.regex_layout.job.clone(),
-                        );
+                        // The previous layout is of different text if the regex was edited since it was compiled
+                        let pending =
+                            matches!(state.pending_edit, Some(PendingEdit { regex: true, .. }));
+                        let mut layout_job = match &state.logic {
+                            _ if pending => layout_plain_text(text.into(), ui.style()),
+                            Ok(logic) => logic.regex_layout.job.clone(),
+                            Err(err) => {
+                                let previous = state.last_regex_layout.as_ref();
+                                layout_regex_err(text.into(), ui.style(), err, previous).job
+                            }
+                        };
+
+                        // Highlight the node of the AST tree that is hovered over or selected
+                        let span = state.logic.as_ref().ok().and_then(|logic| {
+                            logic
+                                .hovered_ast_span
+                                .clone()
+                                .or_else(|| logic.selected_ast_span.clone())
+                        });
+                        if let Some(span) = span {
+                            highlight_span(&mut layout_job, span, ui.visuals().selection.bg_fill);
+                        }
+
                         layout_job.wrap.max_width = wrap_width;
                         ui.fonts().layout_job(layout_job)
                     })
@@ -121,123 +915,1361 @@ fn regex_editor(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
         .inner
 }
 
-/// Displays the header for the input editor
-fn input_header(ui: &mut Ui) {
-    ui.label("Input Text");
-}
+/// Offers actions for wrapping the selected part of the regex in a group, for refactoring the capture group under the
+/// cursor, and for copying the regex as code, in a context menu on the regex editor
+fn wrap_selection_menu(ui: &Ui, state: &mut AppState, regex_result: &TextEditOutput) {
+    let id = regex_result.response.id.with("wrap_selection");
+    let selection = remembered_selection(ui, regex_result, id);
 
-/// Handles the input text and associated state
-fn input_editor(ui: &mut Ui, state: &mut AppState, idx: &mut Option<ShapeIdx>) -> TextEditOutput {
-    // If the text gets edited the layouter will be ran again; keep track of this to enable caching state
-    let mut input_changed = false;
-    Frame::canvas(ui.style())
-        .show(ui, |ui| {
-            TextEdit::multiline(&mut state.widgets.input_text)
-                .desired_width(f32::INFINITY)
-                .frame(false)
-                .layouter(&mut |ui, text, wrap_width| {
-                    *idx = Some(ui.painter().add(Shape::Noop));
+    let mut wrapped = None;
+    let mut refactored = None;
+    regex_result.response.clone().context_menu(|ui| {
+        wrapped = wrap_selection_menu_ui(ui, state, selection, id.with("error"));
+        if wrapped.is_some() {
+            ui.close_menu();
+        }
 
-                    if input_changed {
-                        if let Ok(logic) = &mut state.logic {
-                            // Re-layout the text if it or the regex were changed
-                            logic.input_layout = layout_matched_text(
-                                text.to_owned(),
-                                &logic.regex,
-                                ui.style(),
-                                &logic.regex_layout.capture_group_colors,
-                            );
+        // Pressing the secondary pointer button moves the cursor to the pointer, so the group is the one clicked on
+        let cursor = regex_result
+            .state
+            .ccursor_range()
+            .map(|range| range.primary.index);
+        refactored = group_refactor_menu_ui(ui, state, cursor, id.with("group_error"));
+        if refactored.is_some() {
+            ui.close_menu();
+        }
 
-                            logic.selector =
-                                MatchesSelector::create_from_regex(&logic.regex, text.to_owned());
-                        }
-                    }
-                    input_changed = true;
+        ui.separator();
+        ui.menu_button("Copy Regex As", |ui| copy_regex_as_code(ui, state));
+    });
 
-                    let mut layout_job = state.logic.as_ref().map_or_else(
-                        |_| layout_plain_text(text.to_owned(), ui.style()),
-                        |state| {
-                            state
-                                .input_layout
-                                .formatting
-                                .clone()
-                                .convert_to_layout_job()
-                        },
-                    );
-                    layout_job.wrap.max_width = wrap_width;
-                    ui.fonts().layout_job(layout_job)
-                })
-                .show(ui)
-        })
-        .inner
-}
+    if let Some((pattern, cursor)) = wrapped {
+        state.widgets.regex_text = pattern;
+        state.recompile(ui.style());
 
-/// Displays the header for the replace editor
-fn replace_header(ui: &mut Ui) {
-    ui.label("Replace With");
+        let mut text_state = regex_result.state.clone();
+        text_state.set_ccursor_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        text_state.store(ui.ctx(), regex_result.response.id);
+        ui.memory().request_focus(regex_result.response.id);
+        ui.data().remove::<Range<usize>>(id);
+    }
+
+    if let Some((pattern, replacements)) = refactored {
+        state.widgets.regex_text = pattern;
+        for (replace_text, replacement) in state.widgets.replace_texts_mut().zip(replacements) {
+            *replace_text = replacement;
+        }
+        state.recompile(ui.style());
+    }
 }
 
-/// Handles the replace text and associated state
-fn replace_editor(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
-    Frame::canvas(ui.style())
-        .show(ui, |ui| {
-            TextEdit::singleline(&mut state.widgets.replace_text)
-                .desired_width(f32::INFINITY)
-                .margin(Vec2::new(8.0, 4.0))
-                .hint_text(RichText::new("<Empty String>").monospace())
-                .show(ui)
+/// Gets the char range of the last non-empty selection of the given text edit, which is remembered under the given id
+///
+/// Pressing the secondary pointer button on a text edit collapses its selection before the context menu opens,
+/// so the last non-empty selection is remembered and used instead
+fn remembered_selection(ui: &Ui, output: &TextEditOutput, id: Id) -> Option<Range<usize>> {
+    let selection = output
+        .state
+        .ccursor_range()
+        .map(|range| {
+            let [min, max] = range.sorted();
+            min.index..max.index
         })
-        .inner
-}
+        .filter(|range| !range.is_empty());
 
-/// Displays the header for the result body
-fn result_header(ui: &mut Ui) {
-    ui.label("Result Text");
+    if let Some(selection) = selection {
+        ui.data().insert_temp(id, selection);
+    } else if output.response.clicked() || output.response.changed() {
+        ui.data().remove::<Range<usize>>(id);
+    }
+
+    ui.data().get_temp::<Range<usize>>(id)
 }
 
-/// Displays the result text from using the regex and replace text to alter the input text
-fn result_body(
-    ui: &mut Ui,
-    state: &mut AppState,
-    regex_response: &Response,
-    input_response: &Response,
-    replace_response: &Response,
-) {
-    // Re-run the regex replacement if any of the inputs changed
-    if regex_response.changed() || input_response.changed() || replace_response.changed() {
-        if let Ok(logic) = &state.logic {
-            state.widgets.result_text = logic
-                .regex
-                .replace_all(&state.widgets.input_text, &state.widgets.replace_text)
-                .into_owned();
-        }
-    }
+/// Offers an action for inserting the selected part of the input text into the regex as a literal,
+/// in a context menu on the input editor
+fn escape_selection_menu(ui: &Ui, state: &mut AppState, input_result: &TextEditOutput) {
+    let id = input_result.response.id.with("escape_selection");
+    let selection = remembered_selection(ui, input_result, id);
+
+    let mut inserted = None;
+    input_result.response.clone().context_menu(|ui| {
+        let widgets = &state.widgets;
+        let index = TextIndex::new(&widgets.input_text);
+        let literal = selection.clone().and_then(|selection| {
+            let range = index.char_to_byte(selection.start)?..index.char_to_byte(selection.end)?;
+            let text = &widgets.input_text[range];
+
+            // In byte mode the input text is written with escape sequences for the bytes that it represents
+            let bytes = if widgets.byte_mode {
+                EscapedBytes::unescape(text).bytes
+            } else {
+                text.as_bytes().to_vec()
+            };
+            Some(escape_literal(&bytes, widgets.regex_flags))
+        });
 
-    Frame::canvas(ui.style()).show(ui, |ui| {
-        TextEdit::multiline(&mut state.widgets.result_text.as_str())
-            .desired_width(f32::INFINITY)
-            .show(ui)
+        let response = ui.add_enabled(
+            literal.is_some(),
+            Button::new("Insert Into Regex as Literal"),
+        );
+        let response = match &literal {
+            Some(literal) => response.on_hover_text(RichText::new(literal).monospace()),
+            None => response.on_disabled_hover_text(
+                "Select part of the input text to insert it into the regex",
+            ),
+        };
+        if response.clicked() {
+            inserted = literal;
+            ui.close_menu();
+        }
     });
+
+    if let Some(literal) = inserted {
+        insert_into_regex(ui, state, &literal);
+        ui.data().remove::<Range<usize>>(id);
+    }
 }
 
+/// Inserts text into the regex in place of its selection, or at the end if it has never had a cursor,
+/// then focuses the regex editor with the cursor placed after the text
+fn insert_into_regex(ui: &Ui, state: &mut AppState, text: &str) {
+    let id = Id::new(REGEX_EDITOR_ID);
+    let mut text_state = TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+
+    let pattern = &state.widgets.regex_text;
+    let index = TextIndex::new(pattern);
+    let range = text_state
+        .ccursor_range()
+        .and_then(|range| {
+            let [min, max] = range.sorted();
+            Some(index.char_to_byte(min.index)?..index.char_to_byte(max.index)?)
+        })
+        .unwrap_or(pattern.len()..pattern.len());
+
+    let (pattern, cursor) = insert_literal(pattern, range, text);
+    let cursor = TextIndex::new(&pattern)
+        .byte_to_char(cursor)
+        .unwrap_or_default();
+    state.widgets.regex_text = pattern;
+    state.recompile(ui.style());
+
+    text_state.set_ccursor_range(Some(CCursorRange::one(CCursor::new(cursor))));
+    text_state.store(ui.ctx(), id);
+    ui.memory().request_focus(id);
+}
+
+/// The state of the completion popup of the regex editor, which is remembered between frames
+#[derive(Clone, Default)]
+struct CompletionPopup {
+    /// Whether the popup was shown last frame, in which case it takes the navigation keys from the regex editor
+    open: bool,
+    candidates: Vec<&'static str>,
+    /// The index of the highlighted candidate
+    selected: usize,
+    /// The pattern and byte offset of the cursor that the popup was dismissed at,
+    /// so that it stays closed until either changes
+    dismissed: Option<(String, usize)>,
+}
+
+/// A key pressed while the completion popup is open
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CompletionKey {
+    Up,
+    Down,
+    Accept,
+    Dismiss,
+}
+
+/// Takes the key that navigates the completion popup of the regex editor, if it is open,
+/// before the regex editor can handle the key itself
+fn completion_key(ui: &Ui) -> Option<CompletionKey> {
+    let id = Id::new(REGEX_EDITOR_ID);
+    let open = ui
+        .data()
+        .get_temp::<CompletionPopup>(id.with("completion"))
+        .map(|popup| popup.open)
+        .unwrap_or_default();
+    if !open {
+        return None;
+    }
+
+    let mut input = ui.input_mut();
+    if input.consume_key(Modifiers::NONE, Key::ArrowUp) {
+        Some(CompletionKey::Up)
+    } else if input.consume_key(Modifiers::NONE, Key::ArrowDown) {
+        Some(CompletionKey::Down)
+    } else if input.consume_key(Modifiers::NONE, Key::Enter) {
+        Some(CompletionKey::Accept)
+    } else if input.key_pressed(Key::Escape) {
+        drop(input);
+        // Pressing escape unfocuses the focused widget before anything else sees it, so the regex editor is refocused
+        ui.memory().request_focus(id);
+        Some(CompletionKey::Dismiss)
+    } else {
+        None
+    }
+}
+
+/// Displays a popup under the cursor of the regex editor that offers names to complete the partial name before it,
+/// such as in `\p{Gre`, then replaces the partial name with the chosen one
+fn completion_popup(
+    ui: &Ui,
+    state: &mut AppState,
+    regex_result: &TextEditOutput,
+    key: Option<CompletionKey>,
+) {
+    let id = regex_result.response.id.with("completion");
+    let mut popup = ui
+        .data()
+        .get_temp::<CompletionPopup>(id)
+        .unwrap_or_default();
+
+    // The editor loses focus when the popup is clicked, before the click is handled
+    let response = &regex_result.response;
+    let focused = response.has_focus() || response.lost_focus();
+    let pattern = &state.widgets.regex_text;
+    let cursor = regex_result
+        .state
+        .ccursor_range()
+        .filter(|range| focused && range.primary.index == range.secondary.index)
+        .and_then(|range| {
+            let offset = TextIndex::new(pattern).char_to_byte(range.primary.index)?;
+            Some((range.primary, offset))
+        });
+
+    let at = cursor.map(|(_, offset)| (pattern.clone(), offset));
+    if popup.dismissed.is_some() && popup.dismissed != at {
+        popup.dismissed = None;
+    }
+    if key == Some(CompletionKey::Dismiss) {
+        popup.dismissed = at;
+    }
+
+    let completion = cursor
+        .filter(|_| popup.dismissed.is_none())
+        .and_then(|(ccursor, offset)| Some((ccursor, completion_at(pattern, offset)?)));
+    let Some((ccursor, completion)) = completion else {
+        popup.open = false;
+        ui.data().insert_temp(id, popup);
+        return;
+    };
+
+    let count = completion.candidates.len();
+    if popup.candidates != completion.candidates {
+        popup.candidates = completion.candidates.clone();
+        popup.selected = 0;
+    }
+    match key {
+        Some(CompletionKey::Up) => popup.selected = (popup.selected + count - 1) % count,
+        Some(CompletionKey::Down) => popup.selected = (popup.selected + 1) % count,
+        _ => {}
+    }
+
+    let mut accepted =
+        (key == Some(CompletionKey::Accept)).then(|| popup.candidates[popup.selected]);
+    let cursor_rect = regex_result
+        .galley
+        .pos_from_cursor(&regex_result.galley.from_ccursor(ccursor));
+    Area::new(id)
+        .order(Order::Foreground)
+        .fixed_pos(regex_result.text_draw_pos + cursor_rect.left_bottom().to_vec2())
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for (index, name) in popup.candidates.iter().enumerate() {
+                        let selected = index == popup.selected;
+                        let response =
+                            ui.selectable_label(selected, RichText::new(*name).monospace());
+                        if selected && key.is_some() {
+                            response.scroll_to_me(None);
+                        }
+                        if response.clicked() {
+                            accepted = Some(*name);
+                        }
+                    }
+                });
+            });
+        });
+
+    popup.open = accepted.is_none();
+    ui.data().insert_temp(id, popup);
+
+    if let Some(name) = accepted {
+        let (pattern, cursor) = completion.apply(&state.widgets.regex_text, name);
+        let cursor = TextIndex::new(&pattern)
+            .byte_to_char(cursor)
+            .unwrap_or_default();
+        state.widgets.regex_text = pattern;
+        state.recompile(ui.style());
+
+        let mut text_state = regex_result.state.clone();
+        text_state.set_ccursor_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        text_state.store(ui.ctx(), response.id);
+        ui.memory().request_focus(response.id);
+    }
+}
+
+/// Displays a button for each language that the regex can be copied as code for, which copies it
+pub fn copy_regex_as_code(ui: &mut Ui, state: &AppState) {
+    let flags = state.widgets.regex_flags;
+    let descriptions = match &state.logic {
+        Ok(logic) => state
+            .widgets
+            .group_descriptions
+            .describe_groups(logic.regex.capture_names()),
+        Err(_) => Vec::new(),
+    };
+    for language in CodeLanguage::ALL {
+        if ui.button(language.name()).clicked() {
+            ui.output().copied_text =
+                regex_as_code(&state.widgets.regex_text, flags, language, &descriptions);
+            ui.close_menu();
+        }
+    }
+}
+
+/// Displays the actions for wrapping the given char range of the regex in a group
+///
+/// Returns the new regex text and the char offset at which the cursor should be placed, if an action was taken
+fn wrap_selection_menu_ui(
+    ui: &mut Ui,
+    state: &mut AppState,
+    selection: Option<Range<usize>>,
+    error_id: Id,
+) -> Option<(String, usize)> {
+    let AppState { widgets, logic, .. } = state;
+    let logic = match logic {
+        Ok(logic) => logic,
+        Err(_) => {
+            ui.label("Fix the regular expression before wrapping part of it in a group");
+            return None;
+        }
+    };
+
+    let index = TextIndex::new(&widgets.regex_text);
+    let range = selection.and_then(|selection| {
+        Some(index.char_to_byte(selection.start)?..index.char_to_byte(selection.end)?)
+    });
+
+    let range = match range {
+        Some(range) => range,
+        None => {
+            ui.label("Select part of the regular expression to wrap it in a group");
+            return None;
+        }
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Wrap");
+        ui.monospace(&widgets.regex_text[range.clone()]);
+    });
+    ui.separator();
+
+    if let Err(message) = check_wrap_range(&logic.ast, &range) {
+        ui.colored_label(Color32::RED, message);
+        return None;
+    }
+
+    let mut kind = None;
+    if ui.button("In Non-Capturing Group").clicked() {
+        kind = Some(WrapKind::NonCapturing);
+    }
+
+    if ui.button("In Capturing Group").clicked() {
+        kind = Some(WrapKind::Capturing);
+    }
+
+    ui.horizontal(|ui| {
+        let name = &mut widgets.wrap_group_name;
+        let valid = compile_regex(&format!("(?P<{}>)", name), Default::default()).is_ok();
+        if ui
+            .add_enabled(valid, Button::new("In Named Group"))
+            .on_disabled_hover_text("Enter a valid group name")
+            .clicked()
+        {
+            kind = Some(WrapKind::Named(name.clone()));
+        }
+
+        TextEdit::singleline(name)
+            .hint_text("Name")
+            .desired_width(96.0)
+            .show(ui);
+    });
+
+    if ui
+        .button("In Alternation")
+        .on_hover_text("Wrap in a group with an empty alternative to fill in")
+        .clicked()
+    {
+        kind = Some(WrapKind::Alternation);
+    }
+
+    let error = ui.data().get_temp::<String>(error_id);
+    if let Some(error) = error {
+        ui.colored_label(Color32::RED, error);
+    }
+
+    match wrap_range(
+        &widgets.regex_text,
+        &logic.ast,
+        range,
+        &kind?,
+        widgets.regex_flags,
+    ) {
+        Ok((pattern, cursor)) => {
+            ui.data().remove::<String>(error_id);
+            let cursor = TextIndex::new(&pattern).byte_to_char(cursor)?;
+            Some((pattern, cursor))
+        }
+        Err(error) => {
+            ui.data().insert_temp(error_id, error);
+            None
+        }
+    }
+}
+
+/// Displays the refactorings of the innermost capture group containing the given char offset of the regex, if any,
+/// returning the refactored pattern and replacement texts if one was chosen and could be applied
+///
+/// If a refactoring couldn't be applied, the reason is remembered under the given id and displayed until one can be
+fn group_refactor_menu_ui(
+    ui: &mut Ui,
+    state: &mut AppState,
+    cursor: Option<usize>,
+    error_id: Id,
+) -> Option<(String, Vec<String>)> {
+    let AppState { widgets, logic, .. } = state;
+    let logic = logic.as_ref().ok()?;
+    let offset = TextIndex::new(&widgets.regex_text).char_to_byte(cursor?)?;
+
+    let (depths, spans) = ast_find_capture_groups(&logic.ast);
+    let index = spans
+        .iter()
+        .zip(depths)
+        .enumerate()
+        .filter(|(_, (span, _))| span.start < offset && offset < span.end)
+        .max_by_key(|(_, (_, depth))| *depth)
+        .map(|(index, _)| index + 1)?;
+
+    ui.separator();
+    ui.label(format!("Capture Group {}", index));
+
+    let mut refactoring = None;
+    ui.horizontal(|ui| {
+        let name = &mut widgets.group_name;
+        let valid = compile_regex(&format!("(?P<{}>)", name), Default::default()).is_ok();
+        if ui
+            .add_enabled(valid, Button::new("Name This Group"))
+            .on_disabled_hover_text("Enter a valid group name")
+            .clicked()
+        {
+            refactoring = Some(GroupRefactoring::Name(name.clone()));
+        }
+
+        TextEdit::singleline(name)
+            .hint_text("Name")
+            .desired_width(96.0)
+            .show(ui);
+    });
+
+    if ui
+        .button("Make Non-Capturing")
+        .on_hover_text("Renumbers the references to later groups in the replacement text")
+        .clicked()
+    {
+        refactoring = Some(GroupRefactoring::MakeNonCapturing);
+    }
+
+    if ui.button("Remove Group, Keeping Contents").clicked() {
+        refactoring = Some(GroupRefactoring::Unwrap);
+    }
+
+    let error = ui.data().get_temp::<String>(error_id);
+    if let Some(error) = error {
+        ui.colored_label(Color32::RED, error);
+    }
+
+    let replacements = widgets.replace_texts().collect::<Vec<_>>();
+    match refactor_group(
+        &widgets.regex_text,
+        &replacements,
+        index,
+        &refactoring?,
+        widgets.regex_flags,
+    ) {
+        Ok(refactored) => {
+            ui.data().remove::<String>(error_id);
+            Some(refactored)
+        }
+        Err(error) => {
+            ui.data().insert_temp(error_id, error);
+            None
+        }
+    }
+}
+
+/// Displays the header for the input editor, along with a badge showing whether the regex matches the input text
+fn input_header(ui: &mut Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Input Text");
+
+        if ui
+            .add_enabled(!state.widgets.byte_mode, Button::new("📋 Test Clipboard").small())
+            .on_hover_text("Test the regex against the contents of the clipboard, without changing the input text")
+            .on_disabled_hover_text("The clipboard can't be tested in byte mode")
+            .clicked()
+        {
+            state.widgets.clipboard_test = ClipboardTestState::Reading(ClipboardRead::start(ui.ctx()));
+        }
+
+        highlight_mode_selector(ui, state);
+
+        let mut line_mode = state.widgets.line_mode;
+        if ui
+            .add_enabled(!state.widgets.byte_mode, Checkbox::new(&mut line_mode, "Line Mode"))
+            .on_hover_text(
+                "Match each line separately, like grep, so that matches never span lines, \
+                and ^ and $ match at the start and end of every line",
+            )
+            .on_disabled_hover_text("Bytes are always matched as a whole")
+            .changed()
+        {
+            state.set_line_mode(line_mode, ui.style());
+        }
+
+        let mut byte_mode = state.widgets.byte_mode;
+        if ui
+            .checkbox(&mut byte_mode, "Byte Mode")
+            .on_hover_text(
+                "Match the input text as raw bytes, so that it doesn't have to be valid UTF-8, \
+                with bytes that aren't valid UTF-8 written as \\xNN and backslashes written as \\\\",
+            )
+            .changed()
+        {
+            state.set_byte_mode(byte_mode, ui.style());
+        }
+
+        line_style_selector(ui, state);
+        ui.checkbox(&mut state.widgets.line_labels_visible, "Line Labels")
+            .on_hover_text("Label each connecting line with the name or index of its capture group");
+        ui.checkbox(&mut state.widgets.layered_highlighting, "Layers")
+            .on_hover_text(
+                "Underline each capture group that other groups are nested inside of, as only the innermost group \
+                is filled in, with more deeply nested groups underlined further down",
+            );
+
+        let logic = match &state.logic {
+            Ok(logic) => logic,
+            Err(_) => return,
+        };
+
+        if logic.empty_pattern {
+            ui.weak("Enter a pattern to begin");
+        } else if !logic.is_match {
+            ui.colored_label(Color32::RED, "✗ No match");
+        } else if logic.layout_pending {
+            ui.colored_label(Color32::GREEN, "✓ Matches");
+            ui.add(Spinner::new());
+            ui.weak("Highlighting matches…");
+        } else if let Some(search) = &logic.capture_search {
+            ui.colored_label(Color32::GREEN, "✓ Matches");
+
+            let total = logic.selector.text.len();
+            let searched = search.resume_offset.min(total);
+            ui.add(
+                ProgressBar::new(searched as f32 / total.max(1) as f32)
+                    .desired_width(240.0)
+                    .text(format!(
+                        "Highlighting matches… {} of {}",
+                        format_byte_count(searched),
+                        format_byte_count(total)
+                    )),
+            );
+        } else {
+            ui.colored_label(Color32::GREEN, "✓ Matches");
+        }
+
+        if logic.is_match && logic.match_count().is_some() {
+            let coverage = &logic.input_layout.coverage;
+            ui.weak(format!(
+                "Matched {} of {} ({:.1}%)",
+                format_byte_count(coverage.matched_bytes),
+                format_byte_count(coverage.total_bytes),
+                coverage.percentage(coverage.matched_bytes)
+            ));
+        }
+
+        if let Some(total) = logic.truncated_match_count {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!(
+                    "⚠ Showing first {} of {} matches",
+                    logic.selector.matches.len(),
+                    total
+                ),
+            )
+            .on_hover_text("The limit on how many matches are highlighted can be changed in the Regex Information tab");
+        }
+
+        if logic.matches_only {
+            ui.weak("Highlighting whole matches only");
+        }
+
+        if logic.byte_matching.is_some() {
+            ui.weak("Matching raw bytes");
+        }
+
+        #[cfg(feature = "backend-fancy")]
+        if let Some(fancy_matching) = &logic.fancy_matching {
+            ui.weak("Matching with fancy-regex");
+            if fancy_matching.partial_ast {
+                ui.weak("(Only capture groups are highlighted in the regex)")
+                    .on_hover_text("The pattern uses syntax that only fancy-regex supports, such as lookaround or backreferences, so the rest of it can't be highlighted");
+            }
+        }
+
+        if logic.line_mode {
+            ui.weak("Matching each line separately");
+
+            if logic.newline_in_pattern {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    "⚠ The pattern contains a newline, which never matches in line mode",
+                );
+            }
+        }
+    });
+}
+
+/// Displays a selector for how matches in the input text should be highlighted
+fn highlight_mode_selector(ui: &mut Ui, state: &mut AppState) {
+    let mut mode = state.widgets.highlight_mode;
+    ComboBox::from_id_source("highlight_mode")
+        .selected_text(mode.name())
+        .show_ui(ui, |ui| {
+            for option in [
+                HighlightMode::Auto,
+                HighlightMode::Captures,
+                HighlightMode::MatchesOnly,
+            ] {
+                ui.selectable_value(&mut mode, option, option.name());
+            }
+        })
+        .response
+        .on_hover_text(
+            "Highlighting only whole matches is much faster for large inputs, \
+            as capture groups are then only extracted for the match selected in the inspector",
+        );
+
+    if mode != state.widgets.highlight_mode {
+        state.set_highlight_mode(mode, ui.style());
+    }
+}
+
+/// Displays a selector for the style of the lines connecting capture groups to their matches
+fn line_style_selector(ui: &mut Ui, state: &mut AppState) {
+    ComboBox::from_id_source("line_style")
+        .selected_text(state.widgets.line_style.name())
+        .show_ui(ui, |ui| {
+            for option in [LineStyle::Curve, LineStyle::Elbow] {
+                ui.selectable_value(&mut state.widgets.line_style, option, option.name());
+            }
+        })
+        .response
+        .on_hover_text(
+            "The style of the lines connecting capture groups to their matches. \
+            Curves switch to elbows when the regex and input text are too close together",
+        );
+}
+
+/// Handles the input text and associated state
+fn input_editor(ui: &mut Ui, state: &mut AppState, idx: &mut Option<ShapeIdx>) -> TextEditOutput {
+    // If the text gets edited the layouter will be ran again; keep track of this to enable caching state
+    let mut input_changed = false;
+    Frame::canvas(ui.style())
+        .show(ui, |ui| {
+            TextEdit::multiline(&mut state.widgets.input_text)
+                .desired_width(f32::INFINITY)
+                .frame(false)
+                .layouter(&mut |ui, text, wrap_width| {
+                    *idx = Some(ui.painter().add(Shape::Noop));
+
+                    if input_changed && should_debounce(text) {
+                        PendingEdit::add(&mut state.pending_edit, ui.input().time, false, true);
+                    } else if input_changed {
+                        if let Ok(logic) = &mut state.logic {
+                            // Re-layout the text if it or the regex were changed
+                            logic.set_input_text(text.to_owned(), ui.style());
+
+                            // Make sure the match badge is updated, and any deferred layout is finished
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                    input_changed = true;
+
+                    // The previous layout is of different text if the input was edited since it was matched
+                    let pending =
+                        matches!(state.pending_edit, Some(PendingEdit { input: true, .. }));
+                    let mut layout_job =
+                        state.logic.as_ref().ok().filter(|_| !pending).map_or_else(
+                            || layout_plain_text(text.to_owned(), ui.style()),
+                            |state| {
+                                let mut formatting = state.input_layout.formatting.clone();
+
+                                // Only the formats of the selected match are changed, so the matches don't have to be
+                                // laid out again whenever the selection changes
+                                let selected = state
+                                    .selector
+                                    .matches
+                                    .get_current()
+                                    .and_then(|groups| groups.first()?.0.clone());
+                                let dark_mode = ui.visuals().dark_mode;
+                                if let Some(range) = selected {
+                                    formatting.override_range_format(range, |format| {
+                                        color::emphasised_format(format, dark_mode)
+                                    });
+                                }
+                                formatting.convert_to_layout_job()
+                            },
+                        );
+                    layout_job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(layout_job)
+                })
+                .show(ui)
+        })
+        .inner
+}
+
+/// Displays a segmented control for choosing which mode the result area is in
+fn result_mode_selector(ui: &mut Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        for mode in ResultModeState::defaults() {
+            let selected = state.widgets.result_mode.is_same_mode(&mode);
+            if ui.selectable_label(selected, mode.name()).clicked() {
+                state.widgets.switch_result_mode(mode);
+            }
+        }
+    });
+}
+
+/// Displays the options specific to the current result mode
+fn result_mode_options(ui: &mut Ui, state: &mut AppState) {
+    let logic = state.logic.as_ref().ok();
+    let mode = &mut state.widgets.result_mode;
+    let changed = match mode {
+        ResultModeState::Replace {
+            replace_text,
+            limit,
+            ..
+        } => {
+            replace_header(ui);
+            let edited = replace_editor(ui, replace_text, logic).response.changed();
+            replace_limit_selector(ui, limit) || edited
+        }
+        ResultModeState::Split { limit, .. } => split_limit_editor(ui, limit).changed(),
+        ResultModeState::Format { template, .. } => {
+            template_header(ui);
+            template_editor(ui, template).response.changed()
+        }
+        ResultModeState::StepReplace { replace_text, .. } => {
+            // The matches that were already replaced keep what they were replaced with, so no progress is lost
+            replace_header(ui);
+            replace_editor(ui, replace_text, logic);
+            false
+        }
+    };
+
+    if changed {
+        mode.invalidate();
+    }
+}
+
+/// Displays the match to decide on next in the step replace mode along with what it would be replaced with,
+/// and buttons for either replacing it or leaving it as it is
+fn replace_steps(ui: &mut Ui, state: &mut AppState) {
+    let (replace_text, steps) = match &mut state.widgets.result_mode {
+        ResultModeState::StepReplace {
+            replace_text,
+            steps: Some(steps),
+        } => (replace_text, steps),
+        _ => return,
+    };
+    let logic = match &state.logic {
+        Ok(logic) if !logic.empty_pattern => logic,
+        _ => return,
+    };
+
+    // The matches are of the input text from before any pending edits, and may not have all been found yet
+    if state.pending_edit.is_some() || logic.match_count().is_none() {
+        ui.weak("Waiting for every match to be found…");
+        return;
+    }
+
+    let count = logic.selector.matches.len();
+    if let Some(total) = logic.truncated_match_count {
+        ui.weak(format!(
+            "Only the first {} of {} matches can be stepped through",
+            count, total
+        ));
+    }
+
+    let mut select = None;
+    if steps.index < count {
+        let groups = logic.match_groups(steps.index);
+        let range = groups.first().cloned().flatten().unwrap_or_default();
+        let expansion =
+            expand_replacement(replace_text, &logic.regex, &groups, &logic.selector.text)
+                .into_iter()
+                .map(|part| match part {
+                    ExpansionPart::Text(text) => text,
+                    ExpansionPart::Empty(_) => "",
+                })
+                .collect::<String>();
+
+        if ui
+            .link(format!("Match {} of {}", steps.index + 1, count))
+            .on_hover_text("Click to select this match in the input text")
+            .clicked()
+        {
+            select = Some(steps.index);
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.monospace(logic.selector.text[range.clone()].replace('\n', "\\n"));
+            ui.label("→");
+            ui.monospace(expansion.replace('\n', "\\n"));
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("✔ Replace").clicked() {
+                steps.accept(range, &expansion);
+                select = Some(steps.index);
+            }
+            if ui.button("➡ Skip").clicked() {
+                steps.skip();
+                select = Some(steps.index);
+            }
+        });
+    } else {
+        ui.weak("Every match has been decided on");
+    }
+
+    if ui
+        .add_enabled(steps.index > 0, Button::new("↺ Start Over"))
+        .clicked()
+    {
+        *steps = ReplaceSteps::new(&state.widgets.input_text);
+        select = Some(0);
+    }
+
+    if let Some(index) = select.filter(|&index| index < count) {
+        select_group(state, index, 0);
+    }
+}
+
+/// Displays the header for the replace editor
+fn replace_header(ui: &mut Ui) {
+    ui.label("Replace With");
+}
+
+/// Handles the replace text, underlining any ambiguous references to capture groups in the given regex
+fn replace_editor(
+    ui: &mut Ui,
+    replace_text: &mut String,
+    logic: Option<&LogicState>,
+) -> TextEditOutput {
+    let output = Frame::canvas(ui.style())
+        .show(ui, |ui| {
+            TextEdit::singleline(replace_text)
+                .desired_width(f32::INFINITY)
+                .margin(Vec2::new(8.0, 4.0))
+                .hint_text(RichText::new("<Empty String>").monospace())
+                .layouter(&mut |ui, text, wrap_width| {
+                    let mut layout_job = match logic {
+                        Some(logic) => {
+                            let ambiguous = ambiguous_references(text, &logic.regex)
+                                .into_iter()
+                                .map(|reference| reference.span)
+                                .collect::<Vec<_>>();
+                            layout_replacement(
+                                text,
+                                ui.style(),
+                                &resolve_references(text, &logic.regex),
+                                &ambiguous,
+                                &logic.regex_layout.capture_group_colors,
+                            )
+                        }
+                        None => layout_plain_text(text.to_owned(), ui.style()),
+                    };
+                    layout_job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(layout_job)
+                })
+                .show(ui)
+        })
+        .inner;
+
+    if let Some(logic) = logic {
+        missing_group_tooltip(ui, &output, replace_text, &logic.regex);
+    }
+    output
+}
+
+/// Displays a selector for how many of the matches are replaced, returning whether it was changed
+fn replace_limit_selector(ui: &mut Ui, limit: &mut ReplaceLimit) -> bool {
+    let previous = *limit;
+    ui.horizontal(|ui| {
+        ui.label("Replace");
+        ComboBox::from_id_source("replace_limit")
+            .selected_text(limit.name())
+            .show_ui(ui, |ui| {
+                for option in [
+                    ReplaceLimit::All,
+                    ReplaceLimit::First,
+                    ReplaceLimit::FirstN(2),
+                ] {
+                    // Choosing the same option again keeps the number of matches that was already chosen
+                    let selected = discriminant(limit) == discriminant(&option);
+                    if ui.selectable_label(selected, option.name()).clicked() && !selected {
+                        *limit = option;
+                    }
+                }
+            });
+
+        if let ReplaceLimit::FirstN(n) = limit {
+            ui.add(
+                DragValue::new(n)
+                    .clamp_range(1..=usize::MAX)
+                    .suffix(" matches"),
+            );
+        }
+    });
+    *limit != previous
+}
+
+/// Shows a tooltip explaining why the reference under the pointer in the replace editor is replaced with nothing,
+/// if it refers to a group that doesn't exist
+fn missing_group_tooltip(ui: &Ui, output: &TextEditOutput, replace_text: &str, regex: &Regex) {
+    let offset = output
+        .response
+        .hover_pos()
+        .and_then(|pos| glyph_at(&output.galley.rows, pos - output.text_draw_pos.to_vec2()))
+        .and_then(|glyph| TextIndex::new(replace_text).glyph_to_byte(glyph));
+
+    let reference = offset.and_then(|offset| {
+        resolve_references(replace_text, regex)
+            .into_iter()
+            .find(|reference| reference.span.contains(&offset) && reference.group.is_none())
+    });
+
+    if let Some(reference) = reference {
+        show_tooltip_at_pointer(ui.ctx(), Id::new("missing_group"), |ui| {
+            ui.label(format!(
+                "The regex doesn't have the group that `{}` refers to, so it is replaced with nothing",
+                &replace_text[reference.span]
+            ));
+        });
+    }
+}
+
+/// Handles the limit on how many pieces the input text is split into
+fn split_limit_editor(ui: &mut Ui, limit: &mut usize) -> Response {
+    ui.horizontal(|ui| {
+        ui.label("Maximum Pieces");
+        ui.add(DragValue::new(limit).clamp_range(0..=usize::MAX))
+            .on_hover_text("Set to 0 to split the entire input text")
+    })
+    .inner
+}
+
+/// Displays the header for the template editor
+fn template_header(ui: &mut Ui) {
+    ui.label("Template").on_hover_text(
+        "Evaluated once for each match. Use {match}, {match.index}, {match.start}, {match.end}, \
+        {group:N} and {group.name} as placeholders, and \\n, \\t, \\\\, \\{ and \\} as escape sequences",
+    );
+}
+
+/// Handles the output template, highlighting the malformed part of it if it fails to parse
+fn template_editor(ui: &mut Ui, template: &mut String) -> TextEditOutput {
+    Frame::canvas(ui.style())
+        .show(ui, |ui| {
+            TextEdit::singleline(template)
+                .desired_width(f32::INFINITY)
+                .margin(Vec2::new(8.0, 4.0))
+                .hint_text(RichText::new("<Empty Template>").monospace())
+                .layouter(&mut |ui, text, wrap_width| {
+                    let mut layout_job = match parse_template(text) {
+                        Ok(_) => layout_plain_text(text.into(), ui.style()),
+                        Err(err) => layout_template_err(text, ui.style(), err.span),
+                    };
+                    layout_job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(layout_job)
+                })
+                .show(ui)
+        })
+        .inner
+}
+
+/// Displays the header for the result body
+fn result_header(ui: &mut Ui, state: &AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Result Text");
+        match &state.widgets.result_mode {
+            ResultModeState::Replace {
+                result: Some(_),
+                replaced_spans,
+                ..
+            } => {
+                ui.weak(match replaced_spans.len() {
+                    1 => "1 replacement".into(),
+                    replaced => format!("{} replacements", replaced),
+                });
+            }
+            ResultModeState::StepReplace {
+                steps: Some(steps), ..
+            } => {
+                ui.weak(format!(
+                    "{} replaced, {} skipped",
+                    steps.accepted, steps.skipped
+                ));
+            }
+            _ => {}
+        }
+    });
+}
+
+/// Displays a note explaining what `$0` expands to, if the replacement uses it in a commonly confusing way
+fn whole_match_note(ui: &mut Ui, state: &mut AppState) {
+    let replace_text = match &state.widgets.result_mode {
+        ResultModeState::Replace { replace_text, .. }
+        | ResultModeState::StepReplace { replace_text, .. } => replace_text,
+        _ => return,
+    };
+
+    if state.widgets.whole_match_note_dismissed {
+        return;
+    }
+
+    let note = match whole_match_usage(replace_text) {
+        Some(WholeMatchUsage::Unchanged) => {
+            "$0 expands to the whole of each match, so replacing each match with just $0 \
+            leaves the input text unchanged."
+        }
+        Some(WholeMatchUsage::AlongsideLiterals) => {
+            "$0 expands to the whole of each match, and only the matches are replaced; \
+            the text between matches is kept as-is, so the result is the input text with your text added around each match."
+        }
+        None => return,
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+            state.widgets.whole_match_note_dismissed = true;
+        }
+
+        ui.weak(note);
+
+        if ui.small_button("Replacement Syntax").clicked() {
+            state.widgets.tab_bar_state = TabBarState::SyntaxGuide;
+            state.widgets.reveal_replacement_syntax = true;
+        }
+    });
+}
+
+/// Displays a note about each reference in the replacement that expands to nothing because it refers to a group that
+/// doesn't exist, such as `$1_copy`, along with a button to brace the references so that they refer to the intended groups
+fn ambiguous_reference_note(ui: &mut Ui, state: &mut AppState) {
+    let (replace_text, regex) = match (&mut state.widgets.result_mode, &state.logic) {
+        (
+            ResultModeState::Replace { replace_text, .. }
+            | ResultModeState::StepReplace { replace_text, .. },
+            Ok(logic),
+        ) => (replace_text, &logic.regex),
+        _ => return,
+    };
+
+    let references = ambiguous_references(replace_text, regex);
+    if references.is_empty() {
+        return;
+    }
+
+    for reference in &references {
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!(
+                    "⚠ {} refers to a group named \"{}\", which doesn't exist, so it expands to nothing. Did you mean {}?",
+                    reference.written,
+                    &reference.written[1..],
+                    reference.intended
+                ),
+            );
+        });
+    }
+
+    if ui.small_button("Add Braces").clicked() {
+        *replace_text = brace_ambiguous_references(replace_text, &references);
+        state.widgets.result_mode.invalidate();
+    }
+}
+
+/// Displays a warning listing the capture groups that the replacement never refers to, where clicking on a group
+/// highlights it in the regex, along with a button to make them non-capturing,
+/// and a warning about each reference in the replacement to a group that doesn't exist
+fn group_reference_note(ui: &mut Ui, state: &mut AppState) {
+    let (replace_text, logic) = match (&state.widgets.result_mode, &mut state.logic) {
+        (
+            ResultModeState::Replace { replace_text, .. }
+            | ResultModeState::StepReplace { replace_text, .. },
+            Ok(logic),
+        ) => (replace_text, logic),
+        _ => return,
+    };
+
+    for span in missing_references(replace_text, &logic.regex) {
+        ui.colored_label(
+            Color32::YELLOW,
+            format!(
+                "⚠ The regex doesn't have the group that {} refers to, so it expands to nothing",
+                &replace_text[span]
+            ),
+        );
+    }
+
+    let unreferenced = unreferenced_groups(replace_text, &logic.regex);
+    if unreferenced.is_empty() {
+        return;
+    }
+
+    let error_id = Id::new("unreferenced_groups_error");
+    let mut fix = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.colored_label(Color32::YELLOW, "⚠ The replacement never uses groups");
+
+        let (_, spans) = ast_find_capture_groups(&logic.ast);
+        let names = logic.regex.capture_names().collect::<Vec<_>>();
+        for &group in &unreferenced {
+            let span = spans[group - 1].clone();
+            let label = match names[group] {
+                Some(name) => format!("{} ({})", group, name),
+                None => group.to_string(),
+            };
+
+            let selected = logic.selected_ast_span.as_ref() == Some(&span);
+            let response = ui
+                .selectable_label(selected, RichText::new(label).monospace())
+                .on_hover_text("Click to highlight the group in the regex");
+            if response.clicked() {
+                logic.selected_ast_span = (!selected).then_some(span);
+            }
+        }
+
+        fix = ui
+            .small_button("Make Non-Capturing")
+            .on_hover_text(
+                "Make the groups that are only used to structure the regex non-capturing, \
+                renumbering the references to the groups after them",
+            )
+            .clicked();
+    });
+
+    let error = ui.data().get_temp::<String>(error_id);
+    if let Some(error) = error {
+        ui.colored_label(Color32::RED, error);
+    }
+
+    if !fix {
+        return;
+    }
+
+    // Later groups are refactored first, so that the indexes of the earlier ones are unchanged
+    let widgets = &state.widgets;
+    let mut pattern = widgets.regex_text.clone();
+    let mut replacements = widgets
+        .replace_texts()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    for &group in unreferenced.iter().rev() {
+        let texts = replacements.iter().map(String::as_str).collect::<Vec<_>>();
+        match refactor_group(
+            &pattern,
+            &texts,
+            group,
+            &GroupRefactoring::MakeNonCapturing,
+            widgets.regex_flags,
+        ) {
+            Ok(refactored) => (pattern, replacements) = refactored,
+            Err(error) => {
+                ui.data().insert_temp(error_id, error);
+                return;
+            }
+        }
+    }
+
+    ui.data().remove::<String>(error_id);
+    state.widgets.regex_text = pattern;
+    for (replace_text, replacement) in state.widgets.replace_texts_mut().zip(replacements) {
+        *replace_text = replacement;
+    }
+    state.recompile(ui.style());
+}
+
+/// Displays the result of applying the current result mode to the input text
+fn result_body(ui: &mut Ui, state: &mut AppState) {
+    Frame::canvas(ui.style()).show(ui, |ui| match &mut state.widgets.result_mode {
+        ResultModeState::Replace {
+            result,
+            replaced_spans,
+            ..
+        } => {
+            TextEdit::multiline(&mut result.as_deref().unwrap_or_default())
+                .desired_width(f32::INFINITY)
+                .layouter(&mut |ui, text, wrap_width| {
+                    let mut layout_job = layout_result_text(
+                        text,
+                        ui.style(),
+                        replaced_spans,
+                        &state.widgets.custom_colors,
+                    );
+                    layout_job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(layout_job)
+                })
+                .show(ui);
+        }
+        ResultModeState::StepReplace { steps, .. } => {
+            let (text, replaced_spans) = match steps {
+                Some(steps) => (steps.text.as_str(), steps.replaced_spans.as_slice()),
+                None => ("", &[][..]),
+            };
+            TextEdit::multiline(&mut &*text)
+                .desired_width(f32::INFINITY)
+                .layouter(&mut |ui, text, wrap_width| {
+                    let mut layout_job = layout_result_text(
+                        text,
+                        ui.style(),
+                        replaced_spans,
+                        &state.widgets.custom_colors,
+                    );
+                    layout_job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(layout_job)
+                })
+                .show(ui);
+        }
+        ResultModeState::Split {
+            result, selected, ..
+        } => {
+            ui.with_layout(Layout::top_down(Align::Min), |ui| {
+                Grid::new("split_pieces").num_columns(3).show(ui, |ui| {
+                    for (index, piece) in result.iter().flatten().enumerate() {
+                        ui.label(index.to_string());
+
+                        // Empty pieces are marked, so that delimiters next to each other or at either end stand out
+                        let text = if piece.text.is_empty() {
+                            RichText::new("<empty>").monospace().weak()
+                        } else {
+                            RichText::new(&piece.text).monospace()
+                        };
+                        let is_selected = *selected == Some(index);
+                        if ui
+                            .selectable_label(is_selected, text)
+                            .on_hover_text("Click to highlight this piece in the input text")
+                            .clicked()
+                        {
+                            *selected = (!is_selected).then_some(index);
+                        }
+
+                        if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                            ui.output().copied_text = piece.text.clone();
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+        ResultModeState::Format {
+            template,
+            result: Some(Err(err)),
+        } => {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("Malformed template {}", err.location(template)),
+            );
+        }
+        ResultModeState::Format { result, .. } => {
+            // Only the matches that are highlighted are formatted
+            if let Ok(logic) = &state.logic {
+                if let Some(total) = logic.truncated_match_count {
+                    ui.weak(format!(
+                        "Only the first {} of {} matches are formatted",
+                        logic.selector.matches.len(),
+                        total
+                    ));
+                }
+            }
+
+            let output = result.as_ref().and_then(|result| result.as_deref().ok());
+            format_actions(
+                ui,
+                output.unwrap_or_default(),
+                &mut state.widgets.format_save_status,
+            );
+            TextEdit::multiline(&mut output.unwrap_or_default())
+                .desired_width(f32::INFINITY)
+                .font(TextStyle::Monospace)
+                .show(ui);
+        }
+    });
+}
+
+/// Displays buttons for copying and saving the formatted matches
+fn format_actions(ui: &mut Ui, output: &str, save_status: &mut Option<String>) {
+    ui.horizontal(|ui| {
+        if ui.button("📋 Copy").clicked() {
+            ui.output().copied_text = output.to_owned();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("💾 Save").clicked() {
+            *save_status = Some(
+                match crate::app::export::save_output(output, "regex_visualiser_matches.txt") {
+                    Ok(path) => format!("Saved to {}", path.display()),
+                    Err(err) => format!("Failed to save: {}", err),
+                },
+            );
+        }
+
+        if let Some(status) = save_status {
+            ui.weak(status.as_str());
+        }
+    });
+}
+
+/// The maximum number of matches to show connecting lines for at once,
+/// beyond which only the lines for the selected match are shown
+const MAX_FANNED_MATCHES: usize = 16;
+
+/// The fraction of the width of a capture group in the regex that lines for different matches are fanned out across
+const FAN_WIDTH: f32 = 0.8;
+
 /// Renders connecting lines between corresponding parts of the input text and regular expression text
 fn connecting_lines(
     ui: &mut Ui,
-    state: &AppState,
+    state: &mut AppState,
     idx: ShapeIdx,
     regex_result: &TextEditOutput,
     input_result: &TextEditOutput,
 ) {
+    // With so many matches that not all of them are highlighted, there would be too many lines to make sense of
     let logic = match &state.logic {
-        Ok(logic) => logic,
-        Err(_) => return,
+        Ok(logic) if logic.truncated_match_count.is_none() => logic,
+        _ => return,
     };
 
     let regex_ranges = &logic.regex_layout.capture_group_chars;
 
     // Each capture group in the regex is highlighted with the color at the corresponding index in `capture_group_colors`,
     // including the implicit capture group corresponding to the whole match that always occupies index 0
-    // (Which is represented in `capture_group_colors` with `Color32::TRANSPARENT`),
+    // (Which is represented in `capture_group_colors` with `GroupColor::NONE`),
     // so if `capture_group_colors` has 0 or 1 elements, that means the regex does not contain any real capture groups,
     // meaning there isn't anything to draw connecting lines between
     let regex_colors = match logic.regex_layout.capture_group_colors.as_slice() {
@@ -245,7 +2277,7 @@ fn connecting_lines(
         _ => return,
     };
 
-    assert_eq!(
+    debug_assert_eq!(
         regex_ranges.len(),
         regex_colors.len(),
         "Different number of char ranges and colors for regex capture groups (Ranges: {}, Colors: {})",
@@ -260,12 +2292,28 @@ fn connecting_lines(
     let regex_offset = regex_result.text_draw_pos.to_vec2();
     let input_offset = input_result.text_draw_pos.to_vec2();
 
-    let shapes = logic
-        .input_layout
-        .capture_group_chars
+    // The editor sections can be reordered, so lines go downwards or upwards depending on which text is above the other
+    let regex_above = regex_result.response.rect.center().y < input_result.response.rect.center().y;
+
+    // Lines for different matches of the same capture group are fanned out across the group in the regex,
+    // and alternate between solid and dashed, so they can be told apart; but with too many matches they would
+    // become indistinguishable anyway, so only the lines for the match selected in the inspector are shown instead
+    let matches = &logic.input_layout.capture_group_chars;
+    let degraded = logic.regex_layout.degraded;
+    let fanned = !degraded && matches.len() <= MAX_FANNED_MATCHES;
+    let selected = logic.selector.matches.index();
+
+    // If the regex has too many capture groups for their lines to be meaningful,
+    // only the line for the capture group selected in the inspector is shown
+    let selected_group = degraded.then(|| selected_group_index(logic)).flatten();
+    let line_style = state.widgets.line_style;
+
+    let lines = matches
         .iter()
-        .flat_map(|ranges| {
-            assert_eq!(
+        .enumerate()
+        .filter(|(match_index, _)| fanned || *match_index == selected)
+        .flat_map(|(match_index, ranges)| {
+            debug_assert_eq!(
                 regex_ranges.len(),
                 ranges.len(),
                 "Different number of char ranges for regex and input text (Regex: {}, Input: {})",
@@ -273,24 +2321,876 @@ fn connecting_lines(
                 ranges.len(),
             );
 
+            // Lines can't be matched up to capture groups reliably if the counts disagree, so skip the match entirely
+            let ranges = if ranges.len() == regex_ranges.len() {
+                ranges.as_slice()
+            } else {
+                &[]
+            };
+
             ranges
                 .iter()
                 .zip(regex_ranges)
                 .zip(regex_colors)
-                .filter_map(|((input_range, (depth, regex_range)), &color)| {
-                    Some(
-                        curve_between(
-                            glyph_bounds(regex_rows, regex_range)?.center_bottom() + regex_offset,
-                            glyph_bounds(input_rows, input_range.as_ref()?)?.center_top()
-                                + input_offset,
+                .enumerate()
+                .filter(move |(index, _)| !degraded || selected_group == Some(index + 1))
+                .filter_map(
+                    move |(index, ((input_range, (depth, regex_range)), group_color))| {
+                        // The backgrounds are too dark to stand out as lines against the editor's background
+                        let color = group_color.foreground;
+                        // If the capture group spans several rows of the regex, lines start from the row nearest to the input text
+                        let regex_bounds = glyph_bounds(regex_rows, regex_range);
+                        let regex_bounds = if regex_above {
+                            *regex_bounds.last()?
+                        } else {
+                            *regex_bounds.first()?
+                        };
+                        let fan_offset = if fanned {
+                            let fraction = (match_index as f32 + 0.5) / matches.len() as f32 - 0.5;
+                            regex_bounds.width() * FAN_WIDTH * fraction
+                        } else {
+                            0.0
+                        };
+
+                        // If the matched text wraps, the line goes to the row nearest to the regex,
+                        // and the rest of the rows are underlined instead
+                        let input_bounds = glyph_bounds(input_rows, input_range.as_ref()?);
+                        let (from, to, wrapped_rows) = if regex_above {
+                            let (nearest, rest) = input_bounds.split_first()?;
+                            (regex_bounds.center_bottom(), nearest.center_top(), rest)
+                        } else {
+                            let (nearest, rest) = input_bounds.split_last()?;
+                            (regex_bounds.center_top(), nearest.center_bottom(), rest)
+                        };
+                        let from = from + Vec2::X * fan_offset + regex_offset;
+                        let to = to + input_offset;
+                        let line = line_between(
+                            from,
+                            to,
                             (((*depth as f32) + 1.0) * 2.0, color),
-                            Orientation::Vertical,
-                        )
-                        .into(),
-                    )
-                })
+                            line_style,
+                            fanned && match_index % 2 == 1,
+                        );
+
+                        let underlines = wrapped_rows
+                            .iter()
+                            .map(|bounds| {
+                                let bounds = bounds.translate(input_offset);
+                                Shape::line_segment(
+                                    [bounds.left_bottom(), bounds.right_bottom()],
+                                    (WRAPPED_UNDERLINE_THICKNESS, color),
+                                )
+                            })
+                            .collect();
+
+                        // Skip index 0, as that is the implicit capture group corresponding to the whole match
+                        Some(DrawnLine {
+                            line,
+                            underlines,
+                            endpoints: (from, to),
+                            match_index,
+                            group_index: index + 1,
+                            color,
+                        })
+                    },
+                )
         })
         .collect::<Vec<_>>();
 
+    if state.widgets.line_labels_visible {
+        connecting_line_labels(ui, &logic.regex, &lines);
+    }
+
+    let clicked = connecting_line_interaction(ui, state, logic, &lines);
+
+    let shapes = lines
+        .into_iter()
+        .flat_map(|drawn| std::iter::once(drawn.line.shape).chain(drawn.underlines))
+        .collect::<Vec<_>>();
     ui.painter().set(idx, shapes);
+
+    if let Some((match_index, group_index)) = clicked {
+        select_group(state, match_index, group_index);
+    }
+}
+
+/// A connecting line between a capture group in the regex and the part of the input text it matched
+struct DrawnLine {
+    line: ConnectingLine,
+    /// Underlines across the rows of the matched text that the line doesn't go to, if the matched text wraps
+    underlines: Vec<Shape>,
+    /// The points on the regex and input text that the line connects
+    endpoints: (Pos2, Pos2),
+    match_index: usize,
+    group_index: usize,
+    color: Color32,
+}
+
+/// How thick the underlines across wrapped rows of matched text are, which connecting lines don't go to
+const WRAPPED_UNDERLINE_THICKNESS: f32 = 2.0;
+
+/// How far from a connecting line the pointer can be while still hovering over it
+const LINE_HIT_TOLERANCE: f32 = 6.0;
+
+/// How many characters of the matched text to show in the tooltip of a connecting line
+const LINE_SNIPPET_LENGTH: usize = 40;
+
+/// Shows a tooltip for the connecting line under the pointer, if there is one, and flashes its endpoints if it is clicked
+///
+/// Returns the indexes of the match and capture group of the clicked line, if one was clicked
+fn connecting_line_interaction(
+    ui: &Ui,
+    state: &AppState,
+    logic: &LogicState,
+    lines: &[DrawnLine],
+) -> Option<(usize, usize)> {
+    let pos = ui.input().pointer.hover_pos()?;
+    let hovered = lines
+        .iter()
+        .filter_map(|drawn| Some(drawn).zip(drawn.line.hit_distance(pos, LINE_HIT_TOLERANCE)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?
+        .0;
+
+    if ui.input().pointer.primary_clicked() {
+        ui.data().insert_temp(
+            Id::new("endpoint_flash"),
+            (hovered.endpoints, ui.input().time),
+        );
+        return Some((hovered.match_index, hovered.group_index));
+    }
+
+    let name = logic
+        .regex
+        .capture_names()
+        .nth(hovered.group_index)
+        .flatten();
+    let snippet = logic
+        .input_layout
+        .capture_group_chars
+        .get(hovered.match_index)
+        .and_then(|groups| groups.get(hovered.group_index - 1)?.clone())
+        .and_then(|range| {
+            let index = &logic.input_layout.text_index;
+            state
+                .widgets
+                .input_text
+                .get(index.char_to_byte(range.start)?..index.char_to_byte(range.end)?)
+        })
+        .unwrap_or_default();
+
+    show_tooltip_at_pointer(ui.ctx(), Id::new("connecting_line"), |ui| {
+        match name {
+            Some(name) => ui.strong(format!("Group {} ({})", hovered.group_index, name)),
+            None => ui.strong(format!("Group {}", hovered.group_index)),
+        };
+
+        let mut chars = snippet.chars();
+        let mut text = chars.by_ref().take(LINE_SNIPPET_LENGTH).collect::<String>();
+        if chars.next().is_some() {
+            text.push('…');
+        }
+        ui.monospace(text);
+        ui.weak(format!(
+            "Match {}, click to select",
+            hovered.match_index + 1
+        ));
+    });
+
+    None
+}
+
+/// Selects the match and capture group that was clicked on in the input editor in the inspector, if one was,
+/// selecting the whole match if the click wasn't on any of its capture groups
+fn select_clicked_match(state: &mut AppState, input_result: &TextEditOutput) {
+    let logic = match &state.logic {
+        Ok(logic) if input_result.response.clicked() => logic,
+        _ => return,
+    };
+
+    let glyph = match input_result
+        .response
+        .interact_pointer_pos()
+        .and_then(|pos| {
+            glyph_at(
+                &input_result.galley.rows,
+                pos - input_result.text_draw_pos.to_vec2(),
+            )
+        }) {
+        Some(glyph) => glyph,
+        None => return,
+    };
+
+    if let Some((match_index, group_index)) = logic.match_at_glyph(glyph) {
+        select_group(state, match_index, group_index);
+    }
+}
+
+/// Selects the given capture group of the given match in the inspector
+fn select_group(state: &mut AppState, match_index: usize, group_index: usize) {
+    let logic = match &mut state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    logic.selector.matches.try_set_index(match_index);
+    logic.selector.fill_current_captures(&logic.regex);
+    if let Some(groups) = logic.selector.matches.get_current_mut() {
+        groups.try_set_index(group_index);
+    }
+    state.widgets.match_selection_generation += 1;
+}
+
+/// How many seconds the endpoints of a clicked connecting line are flashed for
+const FLASH_DURATION: f64 = 0.5;
+
+/// Briefly draws expanding and fading circles around the endpoints of the most recently clicked connecting line
+///
+/// If motion is reduced, the circles are instead shown unchanged
+fn endpoint_flash(ui: &Ui, state: &AppState) {
+    let id = Id::new("endpoint_flash");
+    let ((from, to), start) = match ui.data().get_temp::<((Pos2, Pos2), f64)>(id) {
+        Some(flash) => flash,
+        None => return,
+    };
+
+    let progress = ((ui.input().time - start) / FLASH_DURATION) as f32;
+    if progress >= 1.0 {
+        ui.data().remove::<((Pos2, Pos2), f64)>(id);
+        return;
+    }
+    ui.ctx().request_repaint();
+
+    let radius = if state.widgets.reduce_motion {
+        6.0
+    } else {
+        4.0 + progress * 8.0
+    };
+
+    let stroke = ui.visuals().selection.stroke;
+    let stroke = Stroke::new(
+        stroke.width.max(2.0),
+        stroke.color.linear_multiply(1.0 - progress),
+    );
+    ui.painter().circle_stroke(from, radius, stroke);
+    ui.painter().circle_stroke(to, radius, stroke);
+}
+
+/// Returns the index of the capture group selected in the inspector, if one is selected
+fn selected_group_index(logic: &LogicState) -> Option<usize> {
+    // The whole match is always the first group of the selector, but isn't a capture group that lines are drawn for
+    let index = logic.selector.matches.get_current()?.index();
+    (index > 0).then_some(index)
+}
+
+/// Renders a label with the name or index of the capture group at the midpoint of each connecting line,
+/// skipping labels that are wider than their line or that would overlap other labels
+fn connecting_line_labels(ui: &Ui, regex: &Regex, lines: &[DrawnLine]) {
+    let font_id = FontId::monospace(9.0);
+    let names = regex.capture_names().collect::<Vec<_>>();
+
+    let mut placed: Vec<Rect> = Vec::new();
+    for drawn in lines {
+        let (line, index, color) = (&drawn.line, drawn.group_index, drawn.color);
+        let length = drawn.endpoints.0.distance(drawn.endpoints.1);
+        let text = match names.get(index).copied().flatten() {
+            Some(name) => name.to_owned(),
+            None => format!("#{}", index),
+        };
+
+        let galley = ui
+            .fonts()
+            .layout_no_wrap(text, font_id.clone(), Color32::WHITE);
+        if galley.size().x > length {
+            continue;
+        }
+
+        let rect =
+            Rect::from_center_size(line.midpoint, galley.size()).expand2(Vec2::new(3.0, 1.0));
+        if placed.iter().any(|other| other.intersects(rect)) {
+            continue;
+        }
+
+        let painter = ui.painter();
+        painter.rect_filled(rect, rect.height() / 2.0, color);
+        painter.galley(rect.center() - galley.size() / 2.0, galley);
+        placed.push(rect);
+    }
+}
+
+/// How many seconds the emphasis animation for a newly selected match lasts
+const EMPHASIS_DURATION: f32 = 1.0;
+
+/// How far the outline of a newly selected match expands by the end of the emphasis animation
+const EMPHASIS_EXPANSION: f32 = 8.0;
+
+/// The thickness of each underline when capture groups are shown as layers
+const LAYER_THICKNESS: f32 = 1.5;
+/// The distance between the centres of adjacent underlines when capture groups are shown as layers
+const LAYER_SPACING: f32 = 2.5;
+/// How many underlines are stacked beneath each row of the input text at most;
+/// capture groups nested more deeply than this share the lowest underline
+const MAX_LAYERS: usize = 3;
+
+/// Draws an underline beneath the text matched by each capture group that encloses other groups
+/// if capture groups are shown as layers, as only the innermost group's fill can be seen,
+/// with groups that are nested inside of other groups underlined further down
+fn layered_underlines(ui: &Ui, state: &AppState, input_result: &TextEditOutput) {
+    if !state.widgets.layered_highlighting {
+        return;
+    }
+
+    let logic = match &state.logic {
+        Ok(logic) if !logic.matches_only => logic,
+        _ => return,
+    };
+
+    let regex_layout = &logic.regex_layout;
+    let nesting = &regex_layout.group_nesting;
+    let layers = nesting
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| regex_layout.encloses_groups(index))
+        .map(|(_, depth)| depth)
+        .max()
+        .map_or(0, |deepest| (deepest + 1).min(MAX_LAYERS));
+    if layers == 0 {
+        return;
+    }
+
+    // The glyph offset at which each row starts, to find the rows that each capture group spans
+    let rows = &input_result.galley.rows;
+    let row_starts = rows
+        .iter()
+        .scan(0, |offset, row| {
+            let start = *offset;
+            *offset += row.glyphs.len();
+            Some(start)
+        })
+        .collect::<Vec<_>>();
+
+    let offset = input_result.text_draw_pos.to_vec2();
+    let clip = ui.clip_rect();
+    let painter = ui.painter();
+
+    for groups in &logic.input_layout.capture_group_chars {
+        for (index, range) in groups.iter().enumerate() {
+            let range = match range {
+                Some(range) if !range.is_empty() && regex_layout.encloses_groups(index) => range,
+                _ => continue,
+            };
+
+            let layer = nesting.get(index).map_or(0, |&depth| depth.min(layers - 1));
+            let color = regex_layout
+                .capture_group_colors
+                .get(index + 1)
+                .map_or(Color32::TRANSPARENT, |color| color.foreground);
+            let stroke = Stroke::new(LAYER_THICKNESS, color);
+
+            let first_row = row_starts.partition_point(|&start| start <= range.start) - 1;
+            for (row, &row_start) in rows.iter().zip(&row_starts).skip(first_row) {
+                if row_start >= range.end {
+                    break;
+                }
+
+                let glyphs = range.start.max(row_start) - row_start
+                    ..(range.end - row_start).min(row.glyphs.len());
+                if glyphs.is_empty() {
+                    continue;
+                }
+
+                // The outermost groups are underlined closest to the text, within the bottom of the row
+                let y = row.rect.bottom()
+                    - LAYER_THICKNESS / 2.0
+                    - (layers - 1 - layer) as f32 * LAYER_SPACING;
+                if !clip.y_range().contains(&(y + offset.y)) {
+                    continue;
+                }
+
+                let left = row.glyphs[glyphs.start].logical_rect().left();
+                let right = row.glyphs[glyphs.end - 1].logical_rect().right();
+                painter.line_segment(
+                    [Pos2::new(left, y) + offset, Pos2::new(right, y) + offset],
+                    stroke,
+                );
+            }
+        }
+    }
+}
+
+/// Briefly draws an expanding and fading outline around the match selected in the inspector when it changes,
+/// to draw attention to where it is in the input text
+///
+/// If motion is reduced, the outline is instead shown unchanged for a single frame
+fn selected_match_emphasis(ui: &Ui, state: &AppState, input_result: &TextEditOutput) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let id = Id::new("selected_match_emphasis");
+    let generation = state.widgets.match_selection_generation;
+    let previous = ui.data().get_temp::<u64>(id);
+    let changed = previous.is_some() && previous != Some(generation);
+    ui.data().insert_temp(id, generation);
+
+    let progress = if state.widgets.reduce_motion {
+        if !changed {
+            return;
+        }
+        0.0
+    } else {
+        // Each selection gets its own animation, which is started from the beginning when the selection changes
+        let animation_id = id.with(generation);
+        if changed {
+            ui.ctx()
+                .animate_value_with_time(animation_id, 0.0, EMPHASIS_DURATION);
+        }
+        ui.ctx()
+            .animate_value_with_time(animation_id, 1.0, EMPHASIS_DURATION)
+    };
+
+    if progress >= 1.0 {
+        return;
+    }
+
+    // A match that wraps is emphasised on each of the rows that it is on
+    let bounds = logic
+        .selector
+        .current_range()
+        .and_then(|range| {
+            TextIndex::new(&logic.selector.text).byte_range_to_glyph_range(range.clone())
+        })
+        .map_or_else(Vec::new, |range| {
+            glyph_bounds(&input_result.galley.rows, &range)
+        });
+
+    let stroke = ui.visuals().selection.stroke;
+    for bounds in bounds {
+        ui.painter().rect_stroke(
+            bounds
+                .translate(input_result.text_draw_pos.to_vec2())
+                .expand(progress * EMPHASIS_EXPANSION),
+            2.0,
+            Stroke::new(
+                stroke.width.max(2.0),
+                stroke.color.linear_multiply(1.0 - progress),
+            ),
+        );
+    }
+}
+
+/// Scrolls the input editor so that the selected match is in view, if the selection changed since it was last scrolled
+fn scroll_to_selected_match(ui: &Ui, state: &mut AppState, input_result: &TextEditOutput) {
+    let generation = state.widgets.match_selection_generation;
+    if state.scrolled_selection_generation == generation {
+        return;
+    }
+    state.scrolled_selection_generation = generation;
+
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let bounds = logic
+        .selector
+        .current_range()
+        .and_then(|range| {
+            TextIndex::new(&logic.selector.text).byte_range_to_glyph_range(range.clone())
+        })
+        .map_or_else(Vec::new, |range| {
+            glyph_bounds(&input_result.galley.rows, &range)
+        });
+
+    // A match that wraps onto several rows is scrolled to as a whole, as far as that fits
+    if let Some(rect) = bounds.into_iter().reduce(Rect::union) {
+        ui.scroll_to_rect(
+            rect.translate(input_result.text_draw_pos.to_vec2()),
+            Some(Align::Center),
+        );
+    }
+}
+
+/// Scrolls the input editor so that the range of the input text that was requested from another panel is in view
+fn scroll_to_target(ui: &Ui, state: &mut AppState, input_result: &TextEditOutput) {
+    let bounds = state
+        .input_scroll_target
+        .take()
+        .and_then(|range| {
+            TextIndex::new(&state.widgets.input_text).byte_range_to_glyph_range(range)
+        })
+        .map_or_else(Vec::new, |range| {
+            glyph_bounds(&input_result.galley.rows, &range)
+        });
+
+    if let Some(rect) = bounds.into_iter().reduce(Rect::union) {
+        ui.scroll_to_rect(
+            rect.translate(input_result.text_draw_pos.to_vec2()),
+            Some(Align::Center),
+        );
+    }
+}
+
+/// The height of each marker along the scroll bar, which is also the spacing that matches are bucketed to,
+/// so that at most one marker is drawn per bucket no matter how many matches there are
+const SCROLL_MARKER_HEIGHT: f32 = 2.0;
+/// The width of each marker along the scroll bar
+const SCROLL_MARKER_WIDTH: f32 = 6.0;
+
+/// Marks where each match is in the input text along the right edge of the scroll area, in proportion to the height
+/// of everything in it, where clicking a marker selects that match and so scrolls to it
+fn scroll_markers(ui: &Ui, state: &mut AppState, input_result: &TextEditOutput) {
+    let viewport = ui.clip_rect();
+    let content = ui.min_rect();
+    if content.height() <= viewport.height() {
+        return;
+    }
+
+    let logic = match &state.logic {
+        Ok(logic) if !logic.selector.matches.is_empty() => logic,
+        _ => return,
+    };
+
+    // The glyph offset at which each row starts, to find the row that each match starts in
+    let rows = &input_result.galley.rows;
+    let row_starts = rows
+        .iter()
+        .scan(0, |offset, row| {
+            let start = *offset;
+            *offset += row.glyphs.len();
+            Some(start)
+        })
+        .collect::<Vec<_>>();
+
+    let index = TextIndex::new(&logic.selector.text);
+    let fractions = logic.selector.matches.iter().map(|groups| {
+        let row = groups
+            .first()
+            .and_then(|(range, _)| index.glyph_offset(range.as_ref()?.start))
+            .and_then(|glyph| rows.get(row_starts.partition_point(|&start| start <= glyph) - 1));
+        row.map_or(0.0, |row| {
+            (input_result.text_draw_pos.y + row.rect.min.y - content.top()) / content.height()
+        })
+    });
+    let bucket_count = (viewport.height() / SCROLL_MARKER_HEIGHT) as usize;
+    let buckets = marker_buckets(fractions, bucket_count);
+
+    let color = match_marker_color(logic, ui.visuals().dark_mode);
+    let selected = logic.selector.matches.index();
+
+    let marker_rect = |bucket: usize| {
+        Rect::from_min_size(
+            Pos2::new(
+                viewport.right() - SCROLL_MARKER_WIDTH,
+                viewport.top() + bucket as f32 * SCROLL_MARKER_HEIGHT,
+            ),
+            Vec2::new(SCROLL_MARKER_WIDTH, SCROLL_MARKER_HEIGHT),
+        )
+    };
+
+    let painter = ui.painter();
+    let mut selected_bucket = None;
+    for (i, &(bucket, first)) in buckets.iter().enumerate() {
+        let next = buckets.get(i + 1).map_or(usize::MAX, |&(_, next)| next);
+        if (first..next).contains(&selected) {
+            selected_bucket = Some(bucket);
+        }
+        painter.rect_filled(marker_rect(bucket), 0.0, color);
+    }
+
+    // The selected match is drawn over the others so that it stands out
+    if let Some(bucket) = selected_bucket {
+        painter.rect_filled(
+            marker_rect(bucket).expand(1.0),
+            0.0,
+            ui.visuals().strong_text_color(),
+        );
+    }
+
+    let strip = Rect::from_x_y_ranges(
+        viewport.right() - SCROLL_MARKER_WIDTH..=viewport.right(),
+        viewport.y_range(),
+    );
+    let response = ui.interact(strip, Id::new("scroll_markers"), Sense::click());
+    let hovered = response.hover_pos().and_then(|pos| {
+        // The nearest marker is picked, as each one is too thin to reliably click on
+        buckets
+            .iter()
+            .map(|&(bucket, first)| ((marker_rect(bucket).center().y - pos.y).abs(), first))
+            .filter(|&(distance, _)| distance <= SCROLL_MARKER_HEIGHT * 2.0)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, first)| first)
+    });
+
+    if let Some(match_index) = hovered {
+        ui.output().cursor_icon = CursorIcon::PointingHand;
+        let response = response.on_hover_text(format!("Match {}", match_index + 1));
+        if response.clicked() {
+            select_group(state, match_index, 0);
+        }
+    }
+}
+
+/// Returns the color that marks where matches are, outside of the highlighting of the input text itself,
+/// which is the color of the first capture group, or of whole matches if there are no capture groups
+fn match_marker_color(logic: &LogicState, dark_mode: bool) -> Color32 {
+    logic
+        .regex_layout
+        .capture_group_colors
+        .get(1)
+        .copied()
+        .unwrap_or_else(|| logic.custom_colors.get(0, dark_mode))
+        .foreground
+}
+
+/// How far apart the carets of empty matches at the same position are drawn, so that each of them can be seen
+const EMPTY_MATCH_CARET_SPACING: f32 = 3.0;
+
+/// Draws a caret where each empty match in the input text is, as there is no text for them to be highlighted in,
+/// with the caret of the selected match drawn thicker
+fn empty_match_carets(ui: &Ui, state: &AppState, input_result: &TextEditOutput) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let empty_matches = logic
+        .selector
+        .matches
+        .iter()
+        .enumerate()
+        .filter_map(|(index, groups)| match groups.first() {
+            Some((Some(range), _)) if range.is_empty() => Some((index, range.start)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if empty_matches.is_empty() {
+        return;
+    }
+
+    let index = TextIndex::new(&logic.selector.text);
+    let rows = &input_result.galley.rows;
+    let offset = input_result.text_draw_pos.to_vec2();
+    let color = match_marker_color(logic, ui.visuals().dark_mode);
+    let selected = logic.selector.matches.index();
+    let painter = ui.painter();
+
+    let mut previous = None;
+    let mut stacked = 0;
+    for (match_index, start) in empty_matches {
+        stacked = if previous == Some(start) {
+            stacked + 1
+        } else {
+            0
+        };
+        previous = Some(start);
+
+        let caret = index
+            .byte_to_line_col(start)
+            .and_then(|(line, column)| caret_at(rows, line, column));
+        if let Some([top, bottom]) = caret {
+            let shift = Vec2::new(stacked as f32 * EMPTY_MATCH_CARET_SPACING, 0.0) + offset;
+            let width = if match_index == selected { 3.0 } else { 2.0 };
+            painter.line_segment([top + shift, bottom + shift], Stroke::new(width, color));
+        }
+    }
+}
+
+/// Outlines the piece of the input text that is selected in the split mode, if any,
+/// or marks where it is with a caret if it is empty
+fn split_piece_highlight(ui: &Ui, state: &AppState, input_result: &TextEditOutput) {
+    let range = match &state.widgets.result_mode {
+        ResultModeState::Split {
+            result: Some(pieces),
+            selected: Some(index),
+            ..
+        } => match pieces.get(*index) {
+            Some(piece) => piece.range.clone(),
+            None => return,
+        },
+        _ => return,
+    };
+
+    let glyphs = match TextIndex::new(&state.widgets.input_text).byte_range_to_glyph_range(range) {
+        Some(glyphs) => glyphs,
+        None => return,
+    };
+
+    let rows = &input_result.galley.rows;
+    let offset = input_result.text_draw_pos.to_vec2();
+    let stroke = ui.visuals().selection.stroke;
+    let stroke = Stroke::new(stroke.width.max(2.0), stroke.color);
+
+    if glyphs.is_empty() {
+        // The caret goes before the glyph after the piece, or after the glyph before it if it is at the end of the text
+        let caret = glyph_bounds(rows, &(glyphs.start..glyphs.start + 1))
+            .first()
+            .map(|rect| [rect.left_top(), rect.left_bottom()])
+            .or_else(|| {
+                glyph_bounds(rows, &(glyphs.start.saturating_sub(1)..glyphs.start))
+                    .first()
+                    .map(|rect| [rect.right_top(), rect.right_bottom()])
+            });
+
+        if let Some([top, bottom]) = caret {
+            ui.painter()
+                .line_segment([top + offset, bottom + offset], stroke);
+        }
+        return;
+    }
+
+    for bounds in glyph_bounds(rows, &glyphs) {
+        ui.painter()
+            .rect_stroke(bounds.translate(offset), 2.0, stroke);
+    }
+}
+
+/// Shows a tooltip with the description of the capture group that is under the pointer in the given text edit,
+/// if that capture group has a description
+///
+/// Returns whether the tooltip was shown
+fn group_description_tooltip(
+    ui: &Ui,
+    state: &AppState,
+    output: &TextEditOutput,
+    group_at_glyph: impl FnOnce(&LogicState, usize) -> Option<usize>,
+) -> bool {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return false,
+    };
+
+    let index = match output
+        .response
+        .hover_pos()
+        .and_then(|pos| glyph_at(&output.galley.rows, pos - output.text_draw_pos.to_vec2()))
+        .and_then(|glyph| group_at_glyph(logic, glyph))
+    {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let name = logic.regex.capture_names().nth(index).flatten();
+    let description = match state.widgets.group_descriptions.get(index, name) {
+        Some(description) => description,
+        None => return false,
+    };
+    show_tooltip_at_pointer(ui.ctx(), Id::new("group_description"), |ui| {
+        match name {
+            Some(name) => ui.strong(format!("Group {} ({})", index, name)),
+            None => ui.strong(format!("Group {}", index)),
+        };
+        ui.label(description);
+    });
+    true
+}
+
+/// Shows a tooltip identifying the match and capture group that the text under the pointer in the input editor is in,
+/// along with the description of that capture group, if it has one
+fn match_tooltip(ui: &Ui, state: &AppState, input_result: &TextEditOutput) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let (match_index, group_index) = match input_result
+        .response
+        .hover_pos()
+        .and_then(|pos| {
+            glyph_at(
+                &input_result.galley.rows,
+                pos - input_result.text_draw_pos.to_vec2(),
+            )
+        })
+        .and_then(|glyph| logic.match_at_glyph(glyph))
+    {
+        Some(selection) => selection,
+        None => return,
+    };
+
+    // Escape sequences make offsets in the text differ from offsets in the bytes that it is written with
+    let range = logic
+        .match_groups(match_index)
+        .get(group_index)
+        .cloned()
+        .flatten()
+        .map(|range| match &logic.byte_matching {
+            Some(byte_matching) => {
+                let input = &byte_matching.input;
+                input.to_byte_offset(range.start)..input.to_byte_offset(range.end)
+            }
+            None => range,
+        });
+
+    let name = logic.regex.capture_names().nth(group_index).flatten();
+    let description = state.widgets.group_descriptions.get(group_index, name);
+    show_tooltip_at_pointer(ui.ctx(), Id::new("match_tooltip"), |ui| {
+        let mut title = format!("Match {}", match_index + 1);
+        match (group_index, name) {
+            (0, _) => {}
+            (index, Some(name)) => title += &format!(", group {} ({})", index, name),
+            (index, None) => title += &format!(", group {}", index),
+        }
+        if let Some(range) = range {
+            title += &format!(", bytes {}..{}", range.start, range.end);
+        }
+        ui.strong(title);
+
+        if let Some(description) = description {
+            ui.label(description);
+        }
+    });
+}
+
+/// Shows a tooltip explaining the innermost part of the pattern that is under the pointer in the regex editor,
+/// along with the documentation of the token there, if there is one
+fn syntax_tooltip(ui: &Ui, state: &AppState, regex_result: &TextEditOutput) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let offset = match regex_result
+        .response
+        .hover_pos()
+        .and_then(|pos| {
+            glyph_at(
+                &regex_result.galley.rows,
+                pos - regex_result.text_draw_pos.to_vec2(),
+            )
+        })
+        .and_then(|glyph| TextIndex::new(&state.widgets.regex_text).glyph_to_byte(glyph))
+    {
+        Some(offset) => offset,
+        None => return,
+    };
+
+    let node = logic.ast_spans.node_at(offset);
+    let entry = logic.syntax_spans.entry_at(offset);
+    if node.is_none() && entry.is_none() {
+        return;
+    }
+
+    show_tooltip_at_pointer(ui.ctx(), Id::new("syntax_tooltip"), |ui| {
+        // The part of the pattern that the node was parsed from is shown as it is written, to help with debugging
+        if let Some((span, description)) = node {
+            ui.strong(description);
+            ui.weak(format!(
+                "{:?} at bytes {}..{}",
+                state
+                    .widgets
+                    .regex_text
+                    .get(span.clone())
+                    .unwrap_or_default(),
+                span.start,
+                span.end
+            ));
+        }
+
+        if let Some((_, entry)) = entry {
+            ui.separator();
+            ui.monospace(entry.syntax);
+            ui.label(entry.layout_description(TextStyle::Monospace.resolve(ui.style())));
+        }
+    });
 }
@@ -0,0 +1,112 @@
+use crate::app::state::{AppState, InputSource};
+use egui::{Align2, Area, Color32, Context, DroppedFile, Frame, Id, LayerId, Order, TextStyle};
+use std::time::Duration;
+
+/// How many seconds a message explaining why a dropped file was rejected is shown for
+const MESSAGE_DURATION: f64 = 6.0;
+
+/// Loads a file dropped onto the window as the input text, showing an overlay while one is hovered over the window,
+/// and a message if the drop was rejected
+pub fn file_drop(ctx: &Context, state: &mut AppState) {
+    hover_overlay(ctx);
+
+    let dropped = ctx.input().raw.dropped_files.clone();
+    let rejection = match dropped.as_slice() {
+        [] => None,
+        [file] => load_dropped_file(ctx, state, file).err(),
+        _ => Some("Only one file can be loaded at a time".into()),
+    };
+
+    let id = Id::new("file_drop_message");
+    let now = ctx.input().time;
+    if let Some(message) = rejection {
+        ctx.data()
+            .insert_temp(id, (message, now + MESSAGE_DURATION));
+    }
+    rejection_message(ctx, id, now);
+}
+
+/// Replaces the input text with the contents of a dropped file, or returns why it couldn't be
+fn load_dropped_file(
+    ctx: &Context,
+    state: &mut AppState,
+    file: &DroppedFile,
+) -> Result<(), String> {
+    let name = match (&file.path, file.name.as_str()) {
+        (Some(path), _) => path.display().to_string(),
+        (None, "") => "The dropped file".into(),
+        (None, name) => name.to_owned(),
+    };
+
+    // On the web the contents of the file are provided directly, whereas natively only the path is
+    let bytes = match (&file.bytes, &file.path) {
+        (Some(bytes), _) => bytes.to_vec(),
+        (None, Some(path)) => {
+            std::fs::read(path).map_err(|err| format!("Failed to read {}: {}", name, err))?
+        }
+        (None, None) => return Err(format!("The contents of {} aren't available", name)),
+    };
+
+    // Bytes that aren't valid UTF-8 can be escaped in byte mode, but are rejected otherwise rather than being mangled
+    if !state.widgets.byte_mode && std::str::from_utf8(&bytes).is_err() {
+        return Err(format!(
+            "{} isn't UTF-8 text, turn on byte mode to load it",
+            name
+        ));
+    }
+
+    state.load_input_bytes(bytes, InputSource::DroppedFile, &ctx.style());
+    Ok(())
+}
+
+/// Dims the window and explains what dropping will do, while files are hovered over it
+fn hover_overlay(ctx: &Context) {
+    let count = ctx.input().raw.hovered_files.len();
+    if count == 0 {
+        return;
+    }
+
+    let text = if count == 1 {
+        "Drop file to load as input"
+    } else {
+        "Only one file can be loaded at a time"
+    };
+
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("file_drop")));
+    let rect = ctx.input().screen_rect();
+    painter.rect_filled(rect, 0.0, Color32::from_black_alpha(192));
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        text,
+        TextStyle::Heading.resolve(&ctx.style()),
+        Color32::WHITE,
+    );
+}
+
+/// Shows the message explaining why the most recently dropped file was rejected, until it expires
+fn rejection_message(ctx: &Context, id: Id, now: f64) {
+    let (message, expiry) = match ctx.data().get_temp::<(String, f64)>(id) {
+        Some((message, expiry)) if expiry > now => (message, expiry),
+        _ => return,
+    };
+
+    // Make sure the message disappears on time, even if nothing else causes a repaint
+    ctx.request_repaint_after(Duration::from_secs_f64(expiry - now));
+
+    let mut dismissed = false;
+    Area::new("file_drop_message")
+        .anchor(Align2::CENTER_TOP, [0.0, 48.0])
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(ui.visuals().error_fg_color, message);
+                    dismissed |= ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                });
+            });
+        });
+
+    if dismissed {
+        ctx.data().remove::<(String, f64)>(id);
+    }
+}
@@ -1,32 +1,121 @@
 use crate::app::{
-    state::AppState,
-    text::{layout_plain_text, layout_regex_err},
+    export::{fit_cell, to_aligned_table, to_csv, to_json, ExportFormat, TableBorders},
+    lint::Severity,
+    replacement::{expand_replacement, ExpansionPart},
+    state::{AppState, LogicState, ResultModeState, ValueOrder},
+    text::{layout_error_annotation, layout_plain_text, layout_regex_err},
+    tutorial::TutorialTarget,
+    ui::{format_byte_count, tutorial::record_target, COMMAND_NAME},
 };
 use egui::{
-    text_edit::TextEditOutput, Button, Color32, ComboBox, Context, Frame, Grid, SidePanel, Stroke,
-    TextEdit, TextFormat, TextStyle, Ui,
+    text::LayoutJob, text_edit::TextEditOutput, Button, CollapsingHeader, Color32, ComboBox,
+    Context, DragValue, Frame, Grid, Key, Label, Modifiers, RichText, ScrollArea, SidePanel,
+    Stroke, TextEdit, TextFormat, TextStyle, Ui,
 };
+use std::ops::Range;
 
 /// Adds a container that displays an inspector that provides detailed breakdowns of the regex and its matches
 pub fn inspector(ctx: &Context, state: &mut AppState) {
-    SidePanel::right("inspector")
+    if ctx.input_mut().consume_key(Modifiers::COMMAND, Key::I) {
+        state.widgets.inspector.visible ^= true;
+    }
+
+    // When hidden, only a button to show the inspector again is displayed, so none of the work its widgets do is performed
+    if !state.widgets.inspector.visible {
+        SidePanel::right("inspector_collapsed")
+            .resizable(false)
+            .min_width(0.0)
+            .show(ctx, |ui| {
+                record_target(ctx, TutorialTarget::Inspector, ui.max_rect());
+                if ui
+                    .small_button("◀")
+                    .on_hover_text(format!("Show Inspector ({}+I)", COMMAND_NAME))
+                    .clicked()
+                {
+                    state.widgets.inspector.visible = true;
+                }
+            });
+        return;
+    }
+
+    let response = SidePanel::right("inspector")
         .max_width(ctx.available_rect().width() - 64.0)
+        .default_width(state.widgets.inspector.width)
         .show(ctx, |ui| inspector_ui(ui, state));
+
+    state.widgets.inspector.width = response.response.rect.width();
+    record_target(ctx, TutorialTarget::Inspector, response.response.rect);
 }
 
 /// Displays an inspector that provides detailed breakdowns of the regex and its matches
 pub fn inspector_ui(ui: &mut Ui, state: &mut AppState) {
-    ui.heading("Inspector");
+    ui.horizontal(|ui| {
+        if ui
+            .small_button("▶")
+            .on_hover_text(format!("Hide Inspector ({}+I)", COMMAND_NAME))
+            .clicked()
+        {
+            state.widgets.inspector.visible = false;
+        }
+
+        ui.heading("Inspector");
+    });
     ui.separator();
 
-    regular_expression(ui, state);
-    ui.add_space(16.0);
-    matches(ui, state);
+    let open = state.widgets.inspector.regex_open;
+    state.widgets.inspector.regex_open = section(ui, "Regular Expression", open, |ui| {
+        regular_expression(ui, state);
+    });
+
+    // The number of lints is shown even while the section is collapsed
+    let name = match &state.logic {
+        Ok(logic) => format!("Complexity Lints ({})", logic.lints.len()),
+        Err(_) => "Complexity Lints".into(),
+    };
+    let open = state.widgets.inspector.lints_open;
+    state.widgets.inspector.lints_open = section(ui, &name, open, |ui| {
+        lints(ui, state);
+    });
+
+    let open = state.widgets.inspector.matches_open;
+    state.widgets.inspector.matches_open = section(ui, "Matches", open, |ui| {
+        matches(ui, state);
+        replacement_preview(ui, state);
+        copy_as_table(ui, state);
+        export_matches(ui, state);
+    });
+
+    let open = state.widgets.inspector.match_list_open;
+    state.widgets.inspector.match_list_open = section(ui, "Match List", open, |ui| {
+        match_list(ui, state);
+    });
+
+    let open = state.widgets.inspector.statistics_open;
+    state.widgets.inspector.statistics_open = section(ui, "Statistics", open, |ui| {
+        statistics(ui, state);
+    });
+
+    let open = state.widgets.inspector.unique_values_open;
+    state.widgets.inspector.unique_values_open = section(ui, "Unique Values", open, |ui| {
+        unique_values(ui, state);
+    });
+
+    let open = state.widgets.inspector.descriptions_open;
+    state.widgets.inspector.descriptions_open = section(ui, "Group Descriptions", open, |ui| {
+        group_descriptions(ui, state);
+    });
 }
 
-fn regular_expression(ui: &mut Ui, state: &AppState) -> TextEditOutput {
-    ui.label("Regular Expression");
+/// Displays a collapsible section of the inspector that is expanded if `open` is true,
+/// returning whether it should be expanded on the next frame
+fn section(ui: &mut Ui, name: &str, open: bool, add_contents: impl FnOnce(&mut Ui)) -> bool {
+    let response = CollapsingHeader::new(name)
+        .open(Some(open))
+        .show(ui, add_contents);
+    open != response.header_response.clicked()
+}
 
+fn regular_expression(ui: &mut Ui, state: &AppState) -> TextEditOutput {
     let mut frame = Frame::canvas(ui.style());
     if state.logic.is_err() {
         frame = frame.stroke(Stroke::new(1.0, Color32::RED));
@@ -35,22 +124,84 @@ fn regular_expression(ui: &mut Ui, state: &AppState) -> TextEditOutput {
     frame
         .show(ui, |ui| {
             // Convert from a String to a &str to make the textedit immutable
-            TextEdit::singleline(&mut state.widgets.regex_text.as_str())
+            let output = TextEdit::singleline(&mut state.widgets.regex_text.as_str())
                 .desired_width(f32::INFINITY)
                 .layouter(&mut |ui, text, wrap_width| {
                     let mut layout_job = state.logic.as_ref().map_or_else(
-                        |err| layout_regex_err(text.into(), ui.style(), err).job,
+                        |err| {
+                            let previous = state.last_regex_layout.as_ref();
+                            layout_regex_err(text.into(), ui.style(), err, previous).job
+                        },
                         |state| state.regex_layout.job.clone(),
                     );
                     layout_job.wrap.max_width = wrap_width;
                     ui.fonts().layout_job(layout_job)
                 })
-                .show(ui)
+                .show(ui);
+
+            // Point out where the error is underneath the regex, lined up with the text of the text edit
+            let annotation = state.logic.as_ref().err().and_then(|err| {
+                layout_error_annotation(&state.widgets.regex_text, ui.style(), err)
+            });
+            if let Some(job) = annotation {
+                ui.horizontal(|ui| {
+                    ui.add_space(output.text_draw_pos.x - ui.min_rect().left());
+                    ui.add(Label::new(job).wrap(false));
+                });
+            }
+            output
         })
         .inner
 }
 
+/// Lists the parts of the regex that could make matching slow, where hovering over one highlights it in the regex
+fn lints(ui: &mut Ui, state: &mut AppState) {
+    let logic = match &mut state.logic {
+        Ok(logic) => logic,
+        Err(_) => {
+            ui.label("The regular expression is malformed.");
+            return;
+        }
+    };
+
+    if logic.lints.is_empty() {
+        ui.weak("Nothing in the regex is likely to make matching slow.");
+        return;
+    }
+
+    ui.weak(
+        "The regex engine used here never backtracks, but engines that do can be slow to match these. \
+        Hover over one to highlight it in the regex.",
+    );
+    for lint in &logic.lints {
+        let severity = lint.kind.severity();
+        let color = match severity {
+            Severity::High => Color32::RED,
+            Severity::Medium => Color32::YELLOW,
+            Severity::Low => ui.visuals().weak_text_color(),
+        };
+
+        let response = ui
+            .group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(color, severity.name());
+                    ui.monospace(lint.kind.id());
+                });
+                ui.label(&lint.description);
+            })
+            .response;
+        if response.hovered() {
+            logic.hovered_ast_span = lint.spans.first().cloned();
+        }
+    }
+}
+
 fn matches(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
+    // Capture groups may only be extracted once their match is selected
+    if let Ok(logic) = &mut state.logic {
+        logic.selector.fill_current_captures(&logic.regex);
+    }
+
     Grid::new("inspector").num_columns(5).show(ui, |ui| {
         whole_matches(ui, state);
         ui.label("Named groups");
@@ -60,8 +211,52 @@ fn matches(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
         ui.end_row();
     });
 
+    if let Ok(logic) = &state.logic {
+        if logic.empty_pattern {
+            ui.weak("Enter a pattern to begin inspecting matches");
+        }
+
+        // The text index is only built when capture groups are highlighted
+        let position = logic
+            .selector
+            .current_range()
+            .and_then(|range| logic.input_layout.text_index.byte_to_line_col(range.start));
+
+        if let Some((line, column)) = position {
+            ui.weak(format!("Line {}, Column {}", line + 1, column + 1));
+        }
+
+        // Escape sequences make offsets in the text differ from offsets in the bytes that it is written with
+        let byte_range = logic
+            .byte_matching
+            .as_ref()
+            .zip(logic.selector.current_range())
+            .map(|(byte_matching, range)| {
+                let input = &byte_matching.input;
+                input.to_byte_offset(range.start)..input.to_byte_offset(range.end)
+            });
+
+        if let Some(range) = byte_range {
+            ui.weak(format!("Bytes {}..{}", range.start, range.end));
+        }
+    }
+
     let logic = state.logic.as_mut().ok();
 
+    // A capture group that didn't participate in the selected match is selected
+    let not_participating = matches!(
+        &logic,
+        Some(logic) if logic.selector.matches.get_current().is_some()
+            && logic.selector.current_range().is_none()
+    );
+
+    // There is no text to show for an empty match, so where it is is shown instead
+    let empty_match = logic
+        .as_ref()
+        .and_then(|logic| logic.selector.matches.get_current()?.first()?.0.as_ref())
+        .filter(|range| range.is_empty())
+        .map(|range| empty_match_label(range.start));
+
     Frame::canvas(ui.style())
         .show(ui, |ui| {
             TextEdit::singleline(
@@ -71,6 +266,11 @@ fn matches(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
                     .unwrap_or_default(),
             )
             .desired_width(f32::INFINITY)
+            .hint_text(match (not_participating, empty_match) {
+                (true, _) => "<did not participate>".to_owned(),
+                (false, Some(label)) => label,
+                (false, None) => String::new(),
+            })
             .layouter(&mut |ui, text, wrap_width| {
                 let mut layout_job = logic
                     .as_ref()
@@ -93,9 +293,484 @@ fn matches(ui: &mut Ui, state: &mut AppState) -> TextEditOutput {
         .inner
 }
 
+/// Displays what the replacement string expands to for the selected match, while the replace mode is active,
+/// with any references to groups that didn't participate in the match struck through, as they expand to nothing
+fn replacement_preview(ui: &mut Ui, state: &AppState) {
+    if !matches!(state.widgets.result_mode, ResultModeState::Replace { .. }) {
+        return;
+    }
+
+    let logic = match &state.logic {
+        Ok(logic) if !logic.selector.matches.is_empty() => logic,
+        _ => return,
+    };
+
+    let groups = logic.match_groups(logic.selector.matches.index());
+    let parts = expand_replacement(
+        state.widgets.replace_text(),
+        &logic.regex,
+        &groups,
+        &logic.selector.text,
+    );
+
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let weak_color = ui.visuals().weak_text_color();
+    let mut job = LayoutJob::default();
+    for part in &parts {
+        match part {
+            ExpansionPart::Text(text) => {
+                for (index, line) in text.split('\n').enumerate() {
+                    if index > 0 {
+                        job.append(
+                            "\\n",
+                            0.0,
+                            TextFormat::simple(font_id.clone(), Color32::DARK_GRAY),
+                        );
+                    }
+                    job.append(line, 0.0, TextFormat::simple(font_id.clone(), text_color));
+                }
+            }
+            ExpansionPart::Empty(reference) => job.append(
+                reference,
+                0.0,
+                TextFormat {
+                    font_id: font_id.clone(),
+                    color: weak_color,
+                    strikethrough: Stroke::new(1.0, weak_color),
+                    ..Default::default()
+                },
+            ),
+        }
+    }
+
+    ui.label("Replaced with");
+    let response = Frame::canvas(ui.style())
+        .show(ui, |ui| {
+            TextEdit::singleline(&mut job.text.clone().as_str())
+                .desired_width(f32::INFINITY)
+                .layouter(&mut |ui, _, wrap_width| {
+                    let mut job = job.clone();
+                    job.wrap.max_width = wrap_width;
+                    ui.fonts().layout_job(job)
+                })
+                .show(ui)
+        })
+        .inner
+        .response;
+
+    if parts
+        .iter()
+        .any(|part| matches!(part, ExpansionPart::Empty(_)))
+    {
+        response.on_hover_text(
+            "References that are struck through are to groups that didn't participate in this match, \
+            so they are replaced with nothing",
+        );
+    }
+}
+
+/// Displays a button for copying every match as a plain text table, along with options for how the table is formatted
+fn copy_as_table(ui: &mut Ui, state: &mut AppState) {
+    let options = &mut state.widgets.table_options;
+    ui.horizontal_wrapped(|ui| {
+        let logic = state
+            .logic
+            .as_ref()
+            .ok()
+            .filter(|logic| !logic.selector.matches.is_empty());
+
+        if ui
+            .add_enabled(logic.is_some(), Button::new("📋 Copy as Table"))
+            .on_hover_text(
+                "Copy every match as a table of plain text, with a column for each capture group",
+            )
+            .clicked()
+        {
+            let logic = logic.unwrap();
+            ui.output().copied_text =
+                to_aligned_table(logic, *options, &state.widgets.group_descriptions);
+        }
+
+        ui.label("Max Width");
+        ui.add(DragValue::new(&mut options.max_width).clamp_range(1..=usize::MAX));
+
+        let mut box_drawing = options.borders == TableBorders::BoxDrawing;
+        if ui.checkbox(&mut box_drawing, "Borders").changed() {
+            options.borders = if box_drawing {
+                TableBorders::BoxDrawing
+            } else {
+                TableBorders::Spaces
+            };
+        }
+    });
+}
+
+/// Displays buttons for exporting every match and its capture groups as JSON or CSV,
+/// which is saved to the downloads directory on native and downloaded by the browser on the web
+fn export_matches(ui: &mut Ui, state: &mut AppState) {
+    // Exporting before every match has been found would silently leave some of them out
+    let logic = state
+        .logic
+        .as_ref()
+        .ok()
+        .filter(|logic| !logic.selector.matches.is_empty() && logic.match_count().is_some());
+
+    ui.horizontal_wrapped(|ui| {
+        let mut format = None;
+        if ui
+            .add_enabled(logic.is_some(), Button::new("💾 Export JSON"))
+            .on_hover_text("Export every match and its capture groups as JSON")
+            .clicked()
+        {
+            format = Some(ExportFormat::Json);
+        }
+        if ui
+            .add_enabled(logic.is_some(), Button::new("💾 Export CSV"))
+            .on_hover_text("Export every match as CSV, with a column for each capture group")
+            .clicked()
+        {
+            format = Some(ExportFormat::Csv);
+        }
+
+        if let (Some(format), Some(logic)) = (format, logic) {
+            let output = match format {
+                ExportFormat::Json => to_json(logic, &state.widgets.group_descriptions),
+                ExportFormat::Csv => to_csv(logic),
+            };
+            state.widgets.export_status = Some(save_export(&output, format.file_name()));
+        }
+
+        if let Some(status) = &state.widgets.export_status {
+            ui.weak(status.as_str());
+        }
+    });
+}
+
+/// Saves exported matches or lines to a file with the given name, returning a description of the outcome
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_export(output: &str, file_name: &str) -> String {
+    match crate::app::export::save_output(output, file_name) {
+        Ok(path) => format!("Saved to {}", path.display()),
+        Err(err) => format!("Failed to save: {}", err),
+    }
+}
+
+/// Has the browser download exported matches or lines as a file with the given name, returning a description of the outcome
+#[cfg(target_arch = "wasm32")]
+pub fn save_export(output: &str, file_name: &str) -> String {
+    match crate::app::export::download_output(output, file_name) {
+        Ok(()) => format!("Downloaded {}", file_name),
+        Err(err) => format!("Failed to download: {}", err),
+    }
+}
+
+/// How wide each cell of the match list can be, in columns of monospace text, before it is truncated
+const MATCH_LIST_CELL_WIDTH: usize = 24;
+
+/// Displays every match in a table, with a column for each capture group, where clicking a row selects that match
+fn match_list(ui: &mut Ui, state: &mut AppState) {
+    let logic = match &mut state.logic {
+        Ok(logic) if !logic.selector.matches.is_empty() => logic,
+        _ => {
+            ui.weak("There are no matches to list");
+            return;
+        }
+    };
+
+    let header = std::iter::once("#".to_owned())
+        .chain(std::iter::once("Bytes".to_owned()))
+        .chain(
+            logic
+                .regex
+                .capture_names()
+                .enumerate()
+                .map(|(index, name)| match (index, name) {
+                    (0, _) => "Match".to_owned(),
+                    (_, Some(name)) => name.to_owned(),
+                    (index, None) => index.to_string(),
+                }),
+        )
+        .collect::<Vec<_>>();
+
+    // Only the rows that are scrolled into view are laid out, and only their capture groups are found,
+    // as there can be thousands of matches; the header is shown above them, taking up one extra row
+    let row_height = ui.text_style_height(&TextStyle::Monospace) + ui.spacing().item_spacing.y;
+    let row_count = logic.selector.matches.len() + 1;
+    let mut clicked = None;
+    ScrollArea::both().max_height(MATCH_LIST_HEIGHT).show_rows(
+        ui,
+        row_height,
+        row_count,
+        |ui, rows| {
+            Grid::new("match_list")
+                .num_columns(header.len())
+                .striped(true)
+                .show(ui, |ui| {
+                    for name in &header {
+                        ui.strong(name);
+                    }
+                    ui.end_row();
+
+                    for index in rows.start..rows.end.saturating_sub(1).max(rows.start) {
+                        if match_list_row(ui, logic, index) {
+                            clicked = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+        },
+    );
+
+    if let Some(index) = clicked {
+        if logic.selector.matches.try_set_index(index) {
+            state.widgets.match_selection_generation += 1;
+        }
+    }
+}
+
+/// Displays how many matches there are, how long they are, and how many of them each capture group participated in
+fn statistics(ui: &mut Ui, state: &AppState) {
+    let logic = match &state.logic {
+        Ok(logic) if !logic.selector.matches.is_empty() => logic,
+        _ => {
+            ui.weak("There are no matches to summarize");
+            return;
+        }
+    };
+
+    // Matches beyond the limit are only counted, and matches that are still being searched for aren't counted yet
+    let stats = &logic.match_stats;
+    if logic.capture_search.is_some() {
+        ui.weak("Still searching for matches…");
+    } else if let Some(total) = logic.truncated_match_count {
+        ui.weak(format!(
+            "Only the first {} of {} matches are included",
+            stats.match_count, total
+        ));
+    }
+
+    let unit = if stats.in_bytes { "bytes" } else { "chars" };
+    Grid::new("statistics")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Matches");
+            ui.monospace(stats.match_count.to_string());
+            ui.end_row();
+
+            ui.label(format!("Total {}", unit));
+            ui.monospace(stats.total_length.to_string());
+            ui.end_row();
+
+            ui.label("Length (min/mean/max)");
+            ui.monospace(format!(
+                "{} / {:.1} / {}",
+                stats.min_length,
+                stats.mean_length().unwrap_or_default(),
+                stats.max_length
+            ));
+            ui.end_row();
+
+            let participation = match &stats.group_participation {
+                Some(participation) => participation,
+                None => return,
+            };
+            for ((index, name), count) in logic
+                .regex
+                .capture_names()
+                .enumerate()
+                .skip(1)
+                .zip(participation)
+            {
+                match name {
+                    Some(name) => ui.label(format!("Group {} ({})", index, name)),
+                    None => ui.label(format!("Group {}", index)),
+                };
+                let percentage = *count as f64 / stats.match_count.max(1) as f64 * 100.0;
+                ui.monospace(format!("{} ({:.0}%)", count, percentage));
+                ui.end_row();
+            }
+        });
+
+    if stats.group_participation.is_none() {
+        ui.weak("Capture groups aren't counted while only whole matches are highlighted");
+    }
+}
+
+/// The number of matches above which their unique values are only counted once asked to, as that may take a while
+const UNIQUE_VALUES_MATCH_LIMIT: usize = 20_000;
+
+/// The name of the file that the unique values are saved to
+const UNIQUE_VALUES_FILE_NAME: &str = "regex_visualiser_values.txt";
+
+/// Displays each distinct value that a chosen capture group matched, along with how many matches it had that value in
+fn unique_values(ui: &mut Ui, state: &mut AppState) {
+    let logic = match &mut state.logic {
+        Ok(logic) if !logic.selector.matches.is_empty() => logic,
+        _ => {
+            ui.weak("There are no matches to count");
+            return;
+        }
+    };
+    if logic.match_count().is_none() {
+        ui.weak("Still searching for matches…");
+        return;
+    }
+
+    let inspector = &mut state.widgets.inspector;
+    let names = logic.regex.capture_names().collect::<Vec<_>>();
+    if inspector.unique_values_group >= names.len() {
+        inspector.unique_values_group = 0;
+    }
+    let group_name = |index: usize| match (index, names[index]) {
+        (0, _) => "Whole Match".to_owned(),
+        (index, Some(name)) => format!("Group {} ({})", index, name),
+        (index, None) => format!("Group {}", index),
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        ComboBox::from_id_source("unique_values_group")
+            .selected_text(group_name(inspector.unique_values_group))
+            .show_ui(ui, |ui| {
+                for index in 0..names.len() {
+                    ui.selectable_value(
+                        &mut inspector.unique_values_group,
+                        index,
+                        group_name(index),
+                    );
+                }
+            });
+        for order in [ValueOrder::Count, ValueOrder::Alphabetical] {
+            ui.selectable_value(&mut inspector.unique_values_order, order, order.name());
+        }
+    });
+
+    let group = inspector.unique_values_group;
+    let counted = matches!(&logic.unique_values, Some(values) if values.group == group);
+    let match_count = logic.selector.matches.len();
+    if !counted && match_count > UNIQUE_VALUES_MATCH_LIMIT {
+        ui.weak(format!(
+            "Counting the values of {} matches may take a while",
+            match_count
+        ));
+        if !ui.button("Count Anyway").clicked() {
+            return;
+        }
+    }
+
+    let values = logic.unique_values(group, inspector.unique_values_order);
+    ui.weak(format!("{} distinct values", values.values.len()));
+    if values.missing > 0 {
+        ui.weak(format!(
+            "The group didn't participate in {} matches",
+            values.missing
+        ));
+    }
+
+    ui.horizontal(|ui| {
+        let output = || {
+            values
+                .values
+                .iter()
+                .map(|(value, _)| value.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        if ui
+            .button("📋 Copy")
+            .on_hover_text("Copy each distinct value on its own line")
+            .clicked()
+        {
+            ui.output().copied_text = output();
+        }
+        if ui.button("💾 Save").clicked() {
+            inspector.unique_values_status = Some(save_export(&output(), UNIQUE_VALUES_FILE_NAME));
+        }
+        if let Some(status) = &inspector.unique_values_status {
+            ui.weak(status.as_str());
+        }
+    });
+
+    // Only the values that are scrolled into view are laid out, as there may be very many of them
+    let count_width = values
+        .values
+        .iter()
+        .map(|(_, count)| count.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let row_height = ui.text_style_height(&TextStyle::Monospace) + ui.spacing().item_spacing.y;
+    ScrollArea::both().max_height(MATCH_LIST_HEIGHT).show_rows(
+        ui,
+        row_height,
+        values.values.len(),
+        |ui, rows| {
+            for (value, count) in &values.values[rows] {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("{:>1$}", count, count_width))
+                            .monospace()
+                            .weak(),
+                    );
+                    // Each value is kept to one row, so that the rows line up with where they're expected to be
+                    ui.monospace(value.replace('\n', "\\n"));
+                });
+            }
+        },
+    );
+}
+
+/// Displays the row of the match list for the match with the given index, returning whether it was clicked
+fn match_list_row(ui: &mut Ui, logic: &LogicState, index: usize) -> bool {
+    let selected = index == logic.selector.matches.index();
+    let clicked = ui
+        .selectable_label(selected, (index + 1).to_string())
+        .clicked();
+
+    let groups = logic.match_groups(index);
+    match groups.first().cloned().flatten() {
+        Some(range) => ui.monospace(format!("{}..{}", range.start, range.end)),
+        None => ui.label(""),
+    };
+
+    for (group, range) in groups.iter().enumerate() {
+        match range {
+            Some(range) if group == 0 && range.is_empty() => {
+                ui.weak(fit_cell(
+                    &empty_match_label(range.start),
+                    MATCH_LIST_CELL_WIDTH,
+                ));
+            }
+            range => {
+                let text = range
+                    .clone()
+                    .and_then(|range| logic.selector.text.get(range))
+                    .unwrap_or_default();
+                ui.monospace(fit_cell(text, MATCH_LIST_CELL_WIDTH));
+            }
+        }
+    }
+
+    clicked
+}
+
+/// Describes where an empty match is, as there is no text to show for it
+fn empty_match_label(offset: usize) -> String {
+    format!("<empty match at byte {}>", offset)
+}
+
+/// How tall the match list can grow before it scrolls
+const MATCH_LIST_HEIGHT: f32 = 240.0;
+
 fn whole_matches(ui: &mut Ui, state: &mut AppState) {
     ui.label("Whole Matches");
 
+    let truncated_total = state
+        .logic
+        .as_ref()
+        .ok()
+        .and_then(|logic| logic.truncated_match_count);
     let mut matches = state
         .logic
         .as_mut()
@@ -105,19 +780,45 @@ fn whole_matches(ui: &mut Ui, state: &mut AppState) {
         .as_ref()
         .map_or(false, |matches| !matches.is_empty());
 
-    if ui.add_enabled(enabled, Button::new("<")).clicked() {
+    if ui
+        .add_enabled(enabled, Button::new("<"))
+        .on_hover_text("Previous Match (Shift+F3)")
+        .clicked()
+    {
         matches.as_mut().unwrap().dec();
+        state.widgets.match_selection_generation += 1;
     }
 
     if enabled {
         let matches = matches.as_mut().unwrap();
-        ui.label(format!("{}/{}", matches.index() + 1, matches.len()));
+        match truncated_total {
+            Some(total) => {
+                ui.label(format!(
+                    "{}/{} of {}",
+                    matches.index() + 1,
+                    matches.len(),
+                    total
+                ))
+                .on_hover_text(format!(
+                    "Only the first {} matches are highlighted and can be inspected",
+                    matches.len()
+                ));
+            }
+            None => {
+                ui.label(format!("{}/{}", matches.index() + 1, matches.len()));
+            }
+        }
     } else {
         ui.label("-/-");
     }
 
-    if ui.add_enabled(enabled, Button::new(">")).clicked() {
+    if ui
+        .add_enabled(enabled, Button::new(">"))
+        .on_hover_text("Next Match (F3)")
+        .clicked()
+    {
         matches.unwrap().inc();
+        state.widgets.match_selection_generation += 1;
     }
 }
 
@@ -134,6 +835,7 @@ fn capture_groups(ui: &mut Ui, state: &mut AppState) {
 
     if ui.add_enabled(enabled, Button::new("<")).clicked() {
         groups.as_mut().unwrap().dec();
+        state.widgets.match_selection_generation += 1;
     }
 
     if enabled {
@@ -145,27 +847,95 @@ fn capture_groups(ui: &mut Ui, state: &mut AppState) {
 
     if ui.add_enabled(enabled, Button::new(">")).clicked() {
         groups.as_mut().unwrap().inc();
+        state.widgets.match_selection_generation += 1;
     }
 
     ComboBox::from_id_source("combobox")
         .selected_text(
             groups
                 .as_ref()
-                .and_then(|groups| groups.get_current())
-                .and_then(|(_, name)| name.as_deref())
-                .unwrap_or_default(),
+                .and_then(|groups| Some(groups.index()).zip(groups.get_current()))
+                .map_or_else(RichText::default, |(index, (range, name))| {
+                    group_label(name.as_deref(), index, range)
+                }),
         )
         .show_ui(ui, |ui| {
             if let Some(groups) = groups {
                 let mut new_index = groups.index();
-                for (index, name) in groups
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(index, (_, name))| Some(index).zip(name.as_ref()))
-                {
-                    ui.selectable_value(&mut new_index, index, name);
+                for (index, (range, name)) in groups.iter().enumerate() {
+                    ui.selectable_value(
+                        &mut new_index,
+                        index,
+                        group_label(name.as_deref(), index, range),
+                    );
+                }
+
+                if new_index != groups.index() && groups.try_set_index(new_index) {
+                    state.widgets.match_selection_generation += 1;
                 }
-                groups.try_set_index(new_index);
+            }
+        });
+}
+
+/// Returns how a capture group is listed in the group selector, given its name (If it has one), its index in the regex,
+/// and the range of the text that it matched, which is greyed out if it didn't participate in the match
+fn group_label(name: Option<&str>, index: usize, range: &Option<Range<usize>>) -> RichText {
+    let label = match name {
+        Some(name) => format!("{} (${})", name, index),
+        None => format!("${}", index),
+    };
+
+    match range {
+        Some(_) => RichText::new(label),
+        None => RichText::new(format!("{} <did not participate>", label)).weak(),
+    }
+}
+
+/// Displays an editable description for each capture group in the regex
+fn group_descriptions(ui: &mut Ui, state: &mut AppState) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    let descriptions = &mut state.widgets.group_descriptions;
+
+    if let Some(notice) = &descriptions.notice {
+        let mut dismissed = false;
+        ui.horizontal(|ui| {
+            dismissed = ui.small_button("✖").clicked();
+            ui.weak(notice);
+        });
+
+        if dismissed {
+            descriptions.notice = None;
+        }
+    }
+
+    Grid::new("group_descriptions")
+        .num_columns(2)
+        .show(ui, |ui| {
+            // The 0th capture group is the whole match, which isn't a real capture group
+            for (index, name) in logic.regex.capture_names().enumerate().skip(1) {
+                let response = match name {
+                    Some(name) => ui.label(format!("{} ({})", index, name)),
+                    None => ui.label(index.to_string()),
+                };
+
+                let coverage = &logic.input_layout.coverage;
+                if let Some(&bytes) = coverage.group_bytes.get(index) {
+                    response.on_hover_text(format!(
+                        "Matched {} of the input text ({:.1}%)",
+                        format_byte_count(bytes),
+                        coverage.percentage(bytes)
+                    ));
+                }
+
+                TextEdit::singleline(descriptions.get_mut(index, name))
+                    .desired_width(f32::INFINITY)
+                    .hint_text("Description")
+                    .show(ui);
+                ui.end_row();
             }
         });
 }
@@ -0,0 +1,88 @@
+use crate::app::state::AppState;
+use egui::{Context, Grid, ScrollArea, TextEdit, Ui, Window};
+
+/// Adds a window that lists the patterns recorded in the pattern journal
+pub fn journal(ctx: &Context, state: &mut AppState) {
+    if !state.widgets.journal_viewer.visible {
+        return;
+    }
+
+    let mut open = true;
+    let mut restored = None;
+    Window::new("Pattern Journal")
+        .open(&mut open)
+        .default_width(480.0)
+        .show(ctx, |ui| restored = journal_ui(ui, state));
+
+    if let Some(pattern) = restored {
+        state.widgets.regex_text = pattern;
+        state.recompile(&ctx.style());
+    }
+
+    state.widgets.journal_viewer.visible = open;
+}
+
+/// Displays the entries in the pattern journal, along with controls for managing it
+///
+/// Returns the pattern of the entry to restore into the editor, if one was chosen
+fn journal_ui(ui: &mut Ui, state: &mut AppState) -> Option<String> {
+    ui.checkbox(
+        &mut state.widgets.journal_enabled,
+        "Record every successfully compiled pattern",
+    );
+
+    let viewer = &mut state.widgets.journal_viewer;
+    ui.horizontal(|ui| {
+        TextEdit::singleline(&mut viewer.search)
+            .hint_text("Search")
+            .show(ui);
+
+        if viewer.confirm_clear {
+            ui.label("Clear every entry?");
+            if ui.button("Clear").clicked() {
+                state.journal.clear();
+                viewer.confirm_clear = false;
+            }
+            if ui.button("Cancel").clicked() {
+                viewer.confirm_clear = false;
+            }
+        } else if ui.button("Clear Journal").clicked() {
+            viewer.confirm_clear = true;
+        }
+    });
+    ui.separator();
+
+    let entries = state.journal.entries();
+    if entries.is_empty() {
+        ui.weak("The journal is empty.");
+        return None;
+    }
+
+    let mut restored = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        Grid::new("journal_entries")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                // List the most recent entries first
+                for entry in entries
+                    .iter()
+                    .rev()
+                    .filter(|entry| entry.pattern.contains(viewer.search.as_str()))
+                {
+                    ui.weak(entry.format_timestamp());
+                    match entry.match_count {
+                        Some(count) => ui.label(format!("{} matches", count)),
+                        None => ui.weak("Not counted"),
+                    };
+                    ui.monospace(&entry.pattern);
+                    if ui.small_button("Restore").clicked() {
+                        restored = Some(entry.pattern.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    restored
+}
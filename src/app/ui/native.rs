@@ -1,9 +1,15 @@
 mod about;
+mod files;
 mod menu_bar;
 
 use self::about::about;
+use self::files::large_file_prompt;
 use self::menu_bar::menu_bar;
-use super::{editor::editor, inspector::inspector, tab_bar::tab_bar};
+use super::{
+    clipboard_test::clipboard_test, editor::editor, file_drop::file_drop, inspector::inspector,
+    journal::journal, settings::settings, tab_bar::tab_bar, tutorial::tutorial,
+    undo_toast::undo_toast,
+};
 use crate::app::state::AppState;
 use egui::Context;
 
@@ -18,5 +24,12 @@ pub fn root(ctx: &Context, state: &mut AppState, close_fn: impl FnOnce()) {
         tab_bar(ctx, state);
         inspector(ctx, state);
         editor(ctx, state);
+        clipboard_test(ctx, state);
+        journal(ctx, state);
+        settings(ctx, state);
+        tutorial(ctx, state);
+        undo_toast(ctx, state);
+        large_file_prompt(ctx, state);
     }
+    file_drop(ctx, state);
 }
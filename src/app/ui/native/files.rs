@@ -0,0 +1,149 @@
+use crate::app::{
+    export::AstFormat,
+    state::{AppState, InputSource},
+    ui::format_byte_count,
+};
+use egui::{Align2, Context, Style, Window};
+use std::path::PathBuf;
+
+/// The size in bytes above which opening a file as the input text asks for confirmation first,
+/// as highlighting the matches in text that large may be slow
+const LARGE_FILE_THRESHOLD: u64 = 2_000_000;
+
+/// Asks for a file to open as the input text, and opens it,
+/// unless it is large, in which case confirmation is asked for first
+pub fn open_input_file(state: &mut AppState, style: &Style) {
+    let path = match rfd::FileDialog::new()
+        .set_title("Open Input File")
+        .pick_file()
+    {
+        Some(path) => path,
+        None => return,
+    };
+
+    match std::fs::metadata(&path) {
+        Ok(metadata) if metadata.len() > LARGE_FILE_THRESHOLD => {
+            state.widgets.pending_input_file = Some((path, metadata.len()));
+        }
+        _ => load_input_file(state, path, style),
+    }
+}
+
+/// Reads the given file and replaces the input text with its contents, reporting the outcome in the file status
+fn load_input_file(state: &mut AppState, path: PathBuf, style: &Style) {
+    let name = path.display().to_string();
+    state.widgets.file_status = Some(match std::fs::read(&path) {
+        Ok(bytes) => {
+            if state.load_input_bytes(bytes, InputSource::OpenedFile, style) {
+                format!(
+                    "{} isn't valid UTF-8, so the invalid parts were replaced with �",
+                    name
+                )
+            } else {
+                format!("Opened {}", name)
+            }
+        }
+        Err(err) => format!("Failed to open {}: {}", name, err),
+    });
+}
+
+/// Adds a window asking whether to open a large file as the input text, if one is awaiting confirmation
+pub fn large_file_prompt(ctx: &Context, state: &mut AppState) {
+    let (path, size) = match &state.widgets.pending_input_file {
+        Some(pending) => pending.clone(),
+        None => return,
+    };
+
+    let mut choice = None;
+    Window::new("Open Large File")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} is {}, so highlighting its matches may be slow.",
+                path.display(),
+                format_byte_count(size as usize)
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Open Anyway").clicked() {
+                    choice = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(false);
+                }
+            });
+        });
+
+    if let Some(open) = choice {
+        state.widgets.pending_input_file = None;
+        if open {
+            load_input_file(state, path, &ctx.style());
+        }
+    }
+}
+
+/// Asks where to save the output of the active result mode, and saves it there,
+/// reporting the outcome in the file status
+pub fn save_result_as(state: &mut AppState) {
+    // The result would be computed with a regex that only has the capture groups of the pattern
+    if let Some(reason) = state.widgets.results_unavailable() {
+        state.widgets.file_status = Some(reason.into());
+        return;
+    }
+
+    if let Ok(logic) = &state.logic {
+        state
+            .widgets
+            .result_mode
+            .update(logic, &state.widgets.input_text);
+    }
+
+    let output = match state.widgets.result_mode.output_text() {
+        Some(output) => output,
+        None => {
+            state.widgets.file_status = Some("There is no result to save".into());
+            return;
+        }
+    };
+
+    let path = match rfd::FileDialog::new()
+        .set_title("Save Result As")
+        .set_file_name("result.txt")
+        .save_file()
+    {
+        Some(path) => path,
+        None => return,
+    };
+
+    state.widgets.file_status = Some(match std::fs::write(&path, output) {
+        Ok(()) => format!("Saved the result to {}", path.display()),
+        Err(err) => format!("Failed to save to {}: {}", path.display(), err),
+    });
+}
+
+/// Asks where to save the AST of the regex in the given format, and saves it there,
+/// reporting the outcome in the file status
+pub fn export_ast_as(state: &mut AppState, format: AstFormat) {
+    let output = match &state.logic {
+        Ok(logic) => format.export(&logic.ast, &state.widgets.regex_text),
+        Err(_) => {
+            state.widgets.file_status = Some("The regex is malformed, so it has no AST".into());
+            return;
+        }
+    };
+
+    let path = match rfd::FileDialog::new()
+        .set_title(&format!("Export AST as {}", format.name()))
+        .set_file_name(format.file_name())
+        .save_file()
+    {
+        Some(path) => path,
+        None => return,
+    };
+
+    state.widgets.file_status = Some(match std::fs::write(&path, output) {
+        Ok(()) => format!("Exported the AST to {}", path.display()),
+        Err(err) => format!("Failed to save to {}: {}", path.display(), err),
+    });
+}
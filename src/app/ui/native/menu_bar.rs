@@ -1,5 +1,13 @@
-use crate::app::{state::AppState, ui::toggle_theme};
-use egui::{Context, Layout, TopBottomPanel, Ui};
+use super::files::{export_ast_as, open_input_file, save_result_as};
+use crate::app::{
+    export::AstFormat,
+    state::AppState,
+    ui::{
+        editor::{copy_regex_as_code, REGEX_EDITOR_ID},
+        toggle_theme, COMMAND_NAME,
+    },
+};
+use egui::{Button, Context, Id, Layout, TopBottomPanel, Ui};
 
 /// Adds a container that displays the menu bar (The thing that is usually toggled by pressing `alt`)
 ///
@@ -14,26 +22,137 @@ pub fn menu_bar(ctx: &Context, state: &mut AppState, close_fn: impl FnOnce()) {
 pub fn menu_bar_ui(ui: &mut Ui, state: &mut AppState, ctx: &Context, close_fn: impl FnOnce()) {
     egui::menu::bar(ui, |ui| {
         ui.menu_button("File", |ui| {
+            if ui.button("Open Input File…").clicked() {
+                ui.close_menu();
+                open_input_file(state, ui.style());
+            }
+
+            // Results aren't computed unless the input text is matched with the standard engine
+            let unavailable = state.widgets.results_unavailable();
+            if ui
+                .add_enabled(unavailable.is_none(), Button::new("Save Result As…"))
+                .on_disabled_hover_text(unavailable.unwrap_or_default())
+                .clicked()
+            {
+                ui.close_menu();
+                save_result_as(state);
+            }
+
+            ui.separator();
+            for format in [AstFormat::Dot, AstFormat::Json] {
+                if ui
+                    .add_enabled(
+                        state.logic.is_ok(),
+                        Button::new(format!("Export AST as {}…", format.name())),
+                    )
+                    .clicked()
+                {
+                    ui.close_menu();
+                    export_ast_as(state, format);
+                }
+            }
+
+            ui.separator();
             if ui.button("Quit").clicked() {
                 close_fn();
             }
         });
 
+        ui.menu_button("Edit", |ui| {
+            let enabled = state.input_history.can_undo();
+            if ui
+                .add_enabled(enabled, Button::new("Undo Input Change"))
+                .on_hover_text("Restore the input text from before it was last replaced")
+                .clicked()
+            {
+                state.undo_input_change(ui.style());
+                ui.close_menu();
+            }
+
+            ui.separator();
+            let has_matches = state.selected_match_text().is_some();
+            if ui
+                .add_enabled(has_matches, Button::new("Next Match"))
+                .on_hover_text(format!("F3 or {}+G", COMMAND_NAME))
+                .clicked()
+            {
+                state.select_adjacent_match(true);
+            }
+
+            if ui
+                .add_enabled(has_matches, Button::new("Previous Match"))
+                .on_hover_text(format!("Shift+F3 or {}+Shift+G", COMMAND_NAME))
+                .clicked()
+            {
+                state.select_adjacent_match(false);
+            }
+
+            if ui
+                .add_enabled(has_matches, Button::new("Copy Selected Match"))
+                .on_hover_text(format!("{}+Shift+C", COMMAND_NAME))
+                .clicked()
+            {
+                ui.output().copied_text = state.selected_match_text().unwrap_or_default().into();
+                ui.close_menu();
+            }
+
+            ui.menu_button("Copy Regex As", |ui| copy_regex_as_code(ui, state));
+
+            if ui
+                .button("Focus Regex")
+                .on_hover_text(format!("{}+L", COMMAND_NAME))
+                .clicked()
+            {
+                ui.memory().request_focus(Id::new(REGEX_EDITOR_ID));
+                ui.close_menu();
+            }
+        });
+
         ui.menu_button("View", |ui| {
-            if ui.button("Toggle Theme").clicked() {
-                ctx.set_visuals(toggle_theme(&ctx.style().visuals));
+            if ui
+                .button("Toggle Theme")
+                .on_hover_text(format!("{}+D", COMMAND_NAME))
+                .clicked()
+            {
+                toggle_theme(ctx, state);
             }
+
+            if ui.button("Settings").clicked() {
+                state.widgets.settings_visible = true;
+                ui.close_menu();
+            }
+
+            ui.checkbox(&mut state.widgets.reduce_motion, "Reduce Motion");
+            ui.checkbox(&mut state.widgets.inspector.visible, "Inspector")
+                .on_hover_text(format!("{}+I", COMMAND_NAME));
         });
 
         ui.menu_button("Help", |ui| {
+            if ui.button("Tutorial").clicked() {
+                state.begin_tutorial_step(state.widgets.tutorial.step, ui.style());
+                ui.close_menu();
+            }
+
+            if ui.button("Pattern Journal").clicked() {
+                state.widgets.journal_viewer.visible = true;
+                ui.close_menu();
+            }
+
             if ui.button("About").clicked() {
                 state.widgets.about_visible = true;
             }
         });
 
-        ui.with_layout(
-            Layout::right_to_left(egui::Align::Center),
-            egui::warn_if_debug_build,
-        );
+        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+            egui::warn_if_debug_build(ui);
+
+            if let Some(status) = &state.widgets.file_status {
+                if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                    state.widgets.file_status = None;
+                } else {
+                    ui.weak(status.as_str());
+                }
+            }
+        });
     });
 }
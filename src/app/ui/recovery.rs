@@ -0,0 +1,71 @@
+use crate::app::{crash::CrashRecovery, state::AppState, ui::apply_ui_prefs};
+use egui::{CentralPanel, CollapsingHeader, Context, ScrollArea, TextEdit, Ui, Vec2};
+
+/// Adds a container that offers to recover from the app having crashed the last time it was ran
+pub fn recovery(ctx: &Context, recovery: &mut Option<CrashRecovery>, state: &mut AppState) {
+    CentralPanel::default().show(ctx, |ui| recovery_ui(ui, recovery, state));
+}
+
+/// Displays an offer to recover from the app having crashed the last time it was ran
+pub fn recovery_ui(ui: &mut Ui, recovery: &mut Option<CrashRecovery>, state: &mut AppState) {
+    let crash = match recovery {
+        Some(crash) => crash,
+        None => return,
+    };
+
+    let mut rect = ui.available_rect_before_wrap();
+    let horizontal_shrink = rect.width() / 4.0;
+    let vertical_shrink = rect.height() / 8.0;
+    rect = rect.shrink2(Vec2::new(horizontal_shrink, vertical_shrink));
+
+    let mut close = false;
+    ui.allocate_ui_at_rect(rect, |ui| {
+        ui.heading("Regex Visualiser Crashed");
+        ui.separator();
+
+        ui.label(
+            "Sorry! Something went wrong the last time the app was running, causing it to crash.",
+        );
+
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.label("If you'd like to help fix it, please ");
+            ui.hyperlink_to("report the crash", crash.issue_url());
+            ui.label(" - the crash details below will be included automatically.");
+        });
+
+        ui.vertical_centered_justified(|ui| {
+            if let Some(snapshot) = crash.snapshot.take() {
+                if ui
+                    .button("Restore Last Session")
+                    .on_hover_text("Restore the regex and text from shortly before the crash")
+                    .clicked()
+                {
+                    state.widgets = snapshot;
+                    apply_ui_prefs(ui.ctx(), &state.widgets.ui_prefs);
+                    state.recompile(&ui.ctx().style());
+                    close = true;
+                } else {
+                    crash.snapshot = Some(snapshot);
+                }
+            }
+
+            if ui.button("Continue Without Restoring").clicked() {
+                close = true;
+            }
+        });
+
+        CollapsingHeader::new("Crash Details").show(ui, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                TextEdit::multiline(&mut crash.report.as_str())
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .show(ui);
+            });
+        });
+    });
+
+    if close {
+        *recovery = None;
+    }
+}
@@ -0,0 +1,143 @@
+use crate::app::state::AppState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::app::{
+    state::{UiPrefs, MAX_ZOOM, MIN_ZOOM, ZOOM_STEP},
+    ui::COMMAND_NAME,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use egui::Slider;
+use egui::{
+    color_picker::{color_edit_button_srgba, Alpha},
+    Button, Context, Grid, Ui, Window,
+};
+
+/// How many colors are listed in the settings at least, even if the regex has fewer capture groups
+const MIN_LISTED_COLORS: usize = 3;
+
+/// Adds a window for changing the settings, if it is visible
+pub fn settings(ctx: &Context, state: &mut AppState) {
+    if !state.widgets.settings_visible {
+        return;
+    }
+
+    let mut open = true;
+    let mut changed = false;
+    Window::new("Settings")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            // The browser's own zoom is used on the web instead
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                zoom(ui, &mut state.widgets.ui_prefs);
+                ui.separator();
+            }
+            changed = group_colors(ui, state);
+        });
+
+    // Recolor the regex, the input text and the connecting lines straight away
+    if changed {
+        state.recompile(&ctx.style());
+    }
+
+    state.widgets.settings_visible = open;
+}
+
+/// Displays a slider for the zoom factor that the UI is scaled by
+///
+/// The text doesn't have to be laid out again here, as that happens once the new scale takes effect
+#[cfg(not(target_arch = "wasm32"))]
+fn zoom(ui: &mut Ui, prefs: &mut UiPrefs) {
+    ui.heading("Appearance");
+    ui.horizontal(|ui| {
+        let mut zoom = prefs.zoom();
+        let slider = Slider::new(&mut zoom, MIN_ZOOM..=MAX_ZOOM)
+            .step_by(ZOOM_STEP.into())
+            .custom_formatter(|zoom, _| format!("{:.0}%", zoom * 100.0));
+        ui.label("Zoom");
+        if ui
+            .add(slider)
+            .on_hover_text(format!(
+                "{0}+= to zoom in, {0}+- to zoom out, and {0}+0 to reset",
+                COMMAND_NAME
+            ))
+            .changed()
+        {
+            prefs.set_zoom(zoom);
+        }
+        if ui
+            .add_enabled(zoom != 1.0, Button::new("↺").small())
+            .on_hover_text("Reset to 100%")
+            .clicked()
+        {
+            prefs.set_zoom(1.0);
+        }
+    });
+}
+
+/// Displays a color picker for each color of the palette that capture groups are highlighted with,
+/// along with the groups of the current regex that each one is used by
+///
+/// Returns whether any of the colors were changed
+fn group_colors(ui: &mut Ui, state: &mut AppState) -> bool {
+    ui.heading("Capture Group Colors");
+    ui.weak("Capture groups keep their colors when the regex is edited, so a color may be used by any group.");
+    ui.separator();
+
+    let dark_mode = ui.visuals().dark_mode;
+    let palette = match &state.logic {
+        Ok(logic) => logic.regex_layout.capture_group_palette.as_slice(),
+        Err(_) => &[],
+    };
+    let count = palette
+        .iter()
+        .map(|index| index + 1)
+        .chain([MIN_LISTED_COLORS, state.widgets.custom_colors.len()])
+        .max()
+        .unwrap_or_default();
+
+    let colors = &mut state.widgets.custom_colors;
+    let mut changed = false;
+    Grid::new("group_colors").num_columns(3).show(ui, |ui| {
+        for index in 0..count {
+            let groups = palette
+                .iter()
+                .enumerate()
+                .filter(|&(_, &color)| color == index)
+                .map(|(group, _)| format!("${}", group + 1))
+                .collect::<Vec<_>>();
+            if groups.is_empty() {
+                ui.weak(format!("Color {}", index + 1));
+            } else {
+                ui.label(format!("Color {} ({})", index + 1, groups.join(", ")));
+            }
+
+            let mut color = colors.get(index, dark_mode).background;
+            if color_edit_button_srgba(ui, &mut color, Alpha::Opaque).changed() {
+                colors.choose(index, Some(color));
+                changed = true;
+            }
+
+            let chosen = colors.chosen(index).is_some();
+            if ui
+                .add_enabled(chosen, Button::new("↺").small())
+                .on_hover_text("Reset to the default color")
+                .clicked()
+            {
+                colors.choose(index, None);
+                changed = true;
+            }
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    if ui
+        .add_enabled(!colors.is_empty(), Button::new("Reset to Defaults"))
+        .clicked()
+    {
+        *colors = Default::default();
+        changed = true;
+    }
+    changed
+}
@@ -0,0 +1,104 @@
+use crate::app::{
+    state::AppState,
+    ui::{editor::REGEX_EDITOR_ID, toggle_theme},
+};
+use egui::{Context, Event, Id, Key, Modifiers};
+
+/// Handles the keyboard shortcuts that act on the whole app rather than on a particular panel
+///
+/// None of them use keys that a focused text editor also handles, except for copying the selected match,
+/// which takes the place of the editor copying its own selection
+pub fn shortcuts(ctx: &Context, state: &mut AppState) {
+    let mut input = ctx.input_mut();
+    let next_match = input.consume_key(Modifiers::NONE, Key::F3)
+        || input.consume_key(Modifiers::COMMAND, Key::G);
+    let previous_match = input.consume_key(Modifiers::SHIFT, Key::F3)
+        || input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::G);
+    let focus_regex = input.consume_key(Modifiers::COMMAND, Key::L);
+    let toggle = input.consume_key(Modifiers::COMMAND, Key::D);
+
+    // The same keys are also sent as a copy event, which a focused editor would handle afterwards
+    let copy_match = input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::C);
+    if copy_match {
+        input.events.retain(|event| !matches!(event, Event::Copy));
+    }
+    drop(input);
+
+    if next_match {
+        state.select_adjacent_match(true);
+    }
+    if previous_match {
+        state.select_adjacent_match(false);
+    }
+    if focus_regex {
+        ctx.memory().request_focus(Id::new(REGEX_EDITOR_ID));
+    }
+    if copy_match {
+        if let Some(text) = state.selected_match_text() {
+            ctx.output().copied_text = text.to_owned();
+        }
+    }
+    if toggle {
+        toggle_theme(ctx, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::RawInput;
+
+    /// Runs a frame with the given events, returning the text that was copied and the events left unconsumed
+    fn run_frame(ctx: &Context, state: &mut AppState, events: Vec<Event>) -> (String, usize) {
+        let mut unconsumed = 0;
+        let output = ctx.run(
+            RawInput {
+                events,
+                ..Default::default()
+            },
+            |ctx| {
+                shortcuts(ctx, state);
+                unconsumed = ctx.input().events.len();
+            },
+        );
+        (output.platform_output.copied_text, unconsumed)
+    }
+
+    fn key(key: Key, modifiers: Modifiers) -> Event {
+        Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn match_shortcuts() {
+        let ctx = Context::default();
+        let mut state = AppState::default();
+        state.widgets.regex_text = r"\d+".into();
+        state.widgets.input_text = "a1 b22 c333".into();
+        state.recompile(&ctx.style());
+
+        run_frame(&ctx, &mut state, vec![key(Key::F3, Modifiers::NONE)]);
+        run_frame(&ctx, &mut state, vec![key(Key::G, Modifiers::COMMAND)]);
+        assert_eq!(state.selected_match_text(), Some("333"));
+
+        // Moving back from the first match wraps around to the last
+        for _ in 0..3 {
+            run_frame(&ctx, &mut state, vec![key(Key::F3, Modifiers::SHIFT)]);
+        }
+        assert_eq!(state.selected_match_text(), Some("333"));
+
+        // Copying the match replaces the copy event that a focused editor would handle
+        let copy = key(Key::C, Modifiers::COMMAND | Modifiers::SHIFT);
+        assert_eq!(
+            run_frame(&ctx, &mut state, vec![copy, Event::Copy]),
+            ("333".to_owned(), 0)
+        );
+
+        // Keys without the shortcuts' modifiers are left for the editors
+        let plain_keys = vec![key(Key::G, Modifiers::NONE), key(Key::D, Modifiers::NONE)];
+        assert_eq!(run_frame(&ctx, &mut state, plain_keys).1, 2);
+    }
+}
@@ -1,20 +1,101 @@
+mod explanation;
+mod matching_lines;
+mod pattern_library;
 mod syntax_guide;
 
-use self::syntax_guide::syntax_guide;
-use crate::app::state::{AppState, TabBarState};
-use egui::{Context, RichText, ScrollArea, SidePanel, Ui};
+use self::{
+    explanation::explanation, matching_lines::matching_lines, pattern_library::pattern_library,
+    syntax_guide::syntax_guide,
+};
+use crate::app::{
+    ast_tree::AstNode,
+    engine_info::{describe_literals, EngineInfo},
+    hir_tree::HirNode,
+    state::{AppState, LogicState, SyntaxTree, TabBarState},
+    ui::COMMAND_NAME,
+};
+use egui::{
+    collapsing_header::CollapsingState, CollapsingHeader, Context, DragValue, Grid, Id, Key,
+    Modifiers, RichText, ScrollArea, SidePanel, Ui,
+};
+use std::ops::Range;
+
+/// The tabs that can be opened from the tab bar, along with the icon and name of each one
+const TABS: [(TabBarState, char, &str); 5] = [
+    (TabBarState::Information, 'ℹ', "Regex Information"),
+    (TabBarState::SyntaxGuide, '📖', "Syntax Guide"),
+    (TabBarState::Library, '🗀', "Pattern Library"),
+    (TabBarState::Lines, '☰', "Matching Lines"),
+    (TabBarState::Explain, '💡', "Explain"),
+];
+
+/// The keys that toggle the tab at the corresponding index when pressed alongside the command modifier
+const TAB_KEYS: [Key; 9] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
 
 /// Adds a container that displays a tab bar of auxiliary information
 pub fn tab_bar(ctx: &Context, state: &mut AppState) {
+    // The AST tree and the explanation record which of their nodes is hovered over each time they are shown
+    if let Ok(logic) = &mut state.logic {
+        logic.hovered_ast_span = None;
+    }
+
+    for ((tab, ..), key) in TABS.iter().zip(TAB_KEYS) {
+        if ctx.input_mut().consume_key(Modifiers::COMMAND, key) {
+            state.widgets.tab_bar_state.toggle(*tab);
+        }
+    }
+
     SidePanel::left("tab_bar")
         .resizable(false)
         .min_width(0.0)
         .show(ctx, |ui| tab_bar_ui(ui, state));
 
+    // Animate the contents panel opening whenever a tab is opened, including when switching from a different tab
+    let mut openness = 1.0;
+    let animation_time = if state.widgets.reduce_motion {
+        0.0
+    } else {
+        0.2
+    };
+    for (tab, _, name) in TABS {
+        let is_open = state.widgets.tab_bar_state == tab;
+        let value = ctx.animate_bool_with_time(
+            Id::new("tab_bar_contents").with(name),
+            is_open,
+            animation_time,
+        );
+        if is_open {
+            openness = value;
+        }
+    }
+
     if state.widgets.tab_bar_state != TabBarState::Collapsed {
-        SidePanel::left("tab_bar_contents")
+        let mut panel = SidePanel::left("tab_bar_contents")
             .max_width(ctx.available_rect().width() - 64.0)
-            .show(ctx, |ui| tab_bar_contents(ui, state));
+            .default_width(state.widgets.tab_bar_width);
+
+        if openness < 1.0 {
+            // Grow the panel from slightly narrower than its full width
+            let width = state.widgets.tab_bar_width * (0.75 + 0.25 * openness);
+            panel = panel.resizable(false).width_range(width..=width);
+        }
+
+        let response = panel.show(ctx, |ui| tab_bar_contents(ui, state));
+
+        // Remember the width of the fully opened panel, so that resizing it isn't undone by the next animation
+        if openness >= 1.0 {
+            state.widgets.tab_bar_width = response.response.rect.width();
+        }
     }
 }
 
@@ -22,44 +103,261 @@ pub fn tab_bar(ctx: &Context, state: &mut AppState) {
 pub fn tab_bar_ui(ui: &mut Ui, state: &mut AppState) {
     ui.add_space(ui.style().spacing.item_spacing.y);
 
-    if ui
-        .button(RichText::new('ℹ').monospace().size(24.0))
-        .on_hover_text("Regex Information")
-        .clicked()
-    {
-        state.widgets.tab_bar_state.toggle(TabBarState::Information);
-    }
-
-    if ui
-        .button(RichText::new('📖').monospace().size(24.0))
-        .on_hover_text("Syntax Guide")
-        .clicked()
-    {
-        state.widgets.tab_bar_state.toggle(TabBarState::SyntaxGuide);
-    }
+    ScrollArea::vertical().show(ui, |ui| {
+        for (index, (tab, icon, name)) in TABS.into_iter().enumerate() {
+            let selected = state.widgets.tab_bar_state == tab;
+            if ui
+                .selectable_label(selected, RichText::new(icon).monospace().size(24.0))
+                .on_hover_text(format!("{} ({}+{})", name, COMMAND_NAME, index + 1))
+                .clicked()
+            {
+                state.widgets.tab_bar_state.toggle(tab);
+            }
+        }
+    });
 }
 
-fn tab_bar_contents(ui: &mut Ui, state: &AppState) {
+fn tab_bar_contents(ui: &mut Ui, state: &mut AppState) {
     ui.add_space(ui.style().spacing.item_spacing.y);
     match state.widgets.tab_bar_state {
         TabBarState::Collapsed => {}
-        TabBarState::SyntaxGuide => syntax_guide(ui),
+        TabBarState::SyntaxGuide => syntax_guide(ui, &mut state.widgets.reveal_replacement_syntax),
         TabBarState::Information => regex_info(ui, state),
+        TabBarState::Library => pattern_library(ui, state),
+        TabBarState::Lines => matching_lines(ui, state),
+        TabBarState::Explain => explanation(ui, state),
     }
 }
 
 /// Displays information about the regular expression
-fn regex_info(ui: &mut Ui, state: &AppState) {
+fn regex_info(ui: &mut Ui, state: &mut AppState) {
     let wrap = std::mem::replace(&mut ui.style_mut().wrap, Some(false));
     ui.heading("Regex Information");
     ui.separator();
     ui.style_mut().wrap = wrap;
 
-    ScrollArea::vertical().show(ui, |ui| {
-        if let Ok(l) = &state.logic {
-            ui.monospace(format!("{:#?}", l.ast))
-        } else {
-            ui.label("The regular expression is malformed. Hover over the red ⊗ to view the error.")
+    let mut threshold = state.widgets.degraded_group_threshold;
+    ui.horizontal(|ui| {
+        ui.label("Degrade Above");
+        ui.add(DragValue::new(&mut threshold).suffix(" groups"))
+            .on_hover_text(
+                "Regexes with more capture groups than this are visualised in a simplified form",
+            );
+    });
+
+    let mut match_limit = state.widgets.highlight_match_limit;
+    ui.horizontal(|ui| {
+        ui.label("Highlight Up To");
+        ui.add(
+            DragValue::new(&mut match_limit)
+                .clamp_range(1..=usize::MAX)
+                .suffix(" matches"),
+        )
+        .on_hover_text("Only this many matches in the input text are highlighted, though every match is still counted");
+    });
+
+    if threshold != state.widgets.degraded_group_threshold
+        || match_limit != state.widgets.highlight_match_limit
+    {
+        state.widgets.degraded_group_threshold = threshold;
+        state.widgets.highlight_match_limit = match_limit;
+        state.recompile(ui.style());
+    }
+
+    let logic = match &mut state.logic {
+        Ok(logic) => logic,
+        Err(_) => {
+            ui.label("The regular expression is malformed. Click the red ⊗ to view the error.");
+            return;
+        }
+    };
+
+    CollapsingHeader::new("Engine Info").show(ui, |ui| engine_info(ui, logic));
+
+    ui.horizontal(|ui| {
+        for tree in [SyntaxTree::Ast, SyntaxTree::Hir] {
+            ui.selectable_value(&mut state.widgets.syntax_tree, tree, tree.name());
         }
+    })
+    .response
+    .on_hover_text(
+        "The AST follows the regex as it is written, \
+        while the HIR is what the regex engine compiles, after classes and flags have been expanded",
+    );
+    if state.widgets.syntax_tree == SyntaxTree::Hir {
+        hir_tree(ui, logic);
+        return;
+    }
+
+    let tree = logic
+        .ast_tree
+        .get_or_insert_with(|| AstNode::from_ast(&logic.ast, &state.widgets.regex_text));
+
+    ui.weak("Hover over or click on a node to highlight it in the regex");
+    let mut clicked = None;
+    ScrollArea::both().show(ui, |ui| {
+        ast_tree_node(
+            ui,
+            tree,
+            Id::new("ast_tree"),
+            0,
+            &logic.selected_ast_span,
+            &mut logic.hovered_ast_span,
+            &mut clicked,
+        );
     });
+
+    // Clicking on the selected node again deselects it
+    if let Some(span) = clicked {
+        logic.selected_ast_span = (logic.selected_ast_span.as_ref() != Some(&span)).then_some(span);
+    }
+}
+
+/// Displays facts about how the regex engine sees the regex, finding them if they haven't been already
+fn engine_info(ui: &mut Ui, logic: &mut LogicState) {
+    let hir = match &logic.hir {
+        Some(hir) => hir,
+        None => {
+            ui.weak("Engine info is only available for patterns that regex-syntax can parse");
+            return;
+        }
+    };
+    let info = logic
+        .engine_info
+        .get_or_insert_with(|| EngineInfo::from_hir(hir));
+
+    let yes_no = |yes| if yes { "Yes" } else { "No" };
+    Grid::new("engine_info")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Compiled Size").on_hover_text(
+                "Roughly how much memory the compiled program of the regex takes up",
+            );
+            ui.monospace(match info.compiled_size {
+                Some(size) => format!("{} bytes", size),
+                None => "Unknown".into(),
+            });
+            ui.end_row();
+
+            ui.label("Anchored at Start")
+                .on_hover_text("Whether every match must start at the start of the text");
+            ui.monospace(yes_no(info.anchored_start));
+            ui.end_row();
+
+            ui.label("Anchored at End")
+                .on_hover_text("Whether every match must end at the end of the text");
+            ui.monospace(yes_no(info.anchored_end));
+            ui.end_row();
+
+            ui.label("Matches Empty String");
+            ui.monospace(yes_no(info.match_empty));
+            ui.end_row();
+
+            ui.label("Literal Prefixes").on_hover_text(
+                "Every match starts with one of these, so the engine can search for them first. \
+                Those marked with … are only the start of what a match has there",
+            );
+            ui.monospace(describe_literals(&info.prefixes, false));
+            ui.end_row();
+
+            ui.label("Literal Suffixes").on_hover_text(
+                "Every match ends with one of these. \
+                Those marked with … are only the end of what a match has there",
+            );
+            ui.monospace(describe_literals(&info.suffixes, true));
+            ui.end_row();
+        });
+}
+
+/// Displays a tree describing the HIR of the regex, building it if it hasn't been already
+fn hir_tree(ui: &mut Ui, logic: &mut LogicState) {
+    let hir = match &logic.hir {
+        Some(hir) => hir,
+        None => {
+            ui.weak("The HIR is only available for patterns that regex-syntax can parse");
+            return;
+        }
+    };
+    let tree = logic.hir_tree.get_or_insert_with(|| HirNode::from_hir(hir));
+
+    ui.weak("The HIR doesn't record which part of the regex each node came from, so nodes can't be highlighted in it");
+    ScrollArea::both().show(ui, |ui| {
+        hir_tree_node(ui, tree, Id::new("hir_tree"), 0);
+    });
+}
+
+/// How many levels of the AST and HIR trees are expanded when they are first shown
+const SYNTAX_TREE_OPEN_DEPTH: usize = 2;
+
+/// Displays a node of the AST tree along with all of its children, recording the span of the node that the pointer is
+/// over in `hovered`, and the span of the node that was clicked in `clicked`
+fn ast_tree_node(
+    ui: &mut Ui,
+    node: &AstNode,
+    id: Id,
+    depth: usize,
+    selected: &Option<Range<usize>>,
+    hovered: &mut Option<Range<usize>>,
+    clicked: &mut Option<Range<usize>>,
+) {
+    let is_selected = selected.as_ref() == Some(&node.span);
+    let mut label = |ui: &mut Ui| {
+        let response = ui.selectable_label(is_selected, RichText::new(&node.label).monospace());
+        if response.hovered() {
+            *hovered = Some(node.span.clone());
+        }
+        if response.clicked() {
+            *clicked = Some(node.span.clone());
+        }
+    };
+
+    if node.children.is_empty() {
+        ui.horizontal(|ui| {
+            // Line leaves up with the labels of nodes that have a button for collapsing them
+            ui.add_space(ui.spacing().indent);
+            label(ui);
+        });
+        return;
+    }
+
+    CollapsingState::load_with_default_open(ui.ctx(), id, depth < SYNTAX_TREE_OPEN_DEPTH)
+        .show_header(ui, label)
+        .body(|ui| {
+            for (index, child) in node.children.iter().enumerate() {
+                ast_tree_node(
+                    ui,
+                    child,
+                    id.with(index),
+                    depth + 1,
+                    selected,
+                    hovered,
+                    clicked,
+                );
+            }
+        });
+}
+
+/// Displays a node of the HIR tree along with all of its children
+fn hir_tree_node(ui: &mut Ui, node: &HirNode, id: Id, depth: usize) {
+    let label = |ui: &mut Ui| {
+        ui.label(RichText::new(&node.label).monospace());
+    };
+
+    if node.children.is_empty() {
+        ui.horizontal(|ui| {
+            // Line leaves up with the labels of nodes that have a button for collapsing them
+            ui.add_space(ui.spacing().indent);
+            label(ui);
+        });
+        return;
+    }
+
+    CollapsingState::load_with_default_open(ui.ctx(), id, depth < SYNTAX_TREE_OPEN_DEPTH)
+        .show_header(ui, label)
+        .body(|ui| {
+            for (index, child) in node.children.iter().enumerate() {
+                hir_tree_node(ui, child, id.with(index), depth + 1);
+            }
+        });
 }
@@ -0,0 +1,105 @@
+use crate::app::{
+    explain::{explain, ExplainLine},
+    state::AppState,
+};
+use egui::{text::LayoutJob, Color32, ScrollArea, TextFormat, TextStyle, Ui};
+use std::ops::Range;
+
+/// Displays a plain-English explanation of the regex, one part per line,
+/// where hovering over or clicking on a line highlights the part of the regex that it explains
+pub fn explanation(ui: &mut Ui, state: &mut AppState) {
+    let wrap = ui.style_mut().wrap.replace(false);
+    ui.heading("Explain");
+    ui.separator();
+    ui.style_mut().wrap = wrap;
+
+    let logic = match &mut state.logic {
+        Ok(logic) if logic.empty_pattern => {
+            ui.label("Enter a pattern to begin.");
+            return;
+        }
+        Ok(logic) => logic,
+        Err(_) => {
+            ui.label("The regular expression is malformed. Click the red ⊗ to view the error.");
+            return;
+        }
+    };
+
+    let lines = logic
+        .explanation
+        .get_or_insert_with(|| explain(&logic.ast, &logic.regex_layout.capture_group_colors));
+
+    ui.weak("Hover over or click on a line to highlight it in the regex");
+    ui.separator();
+
+    let mut clicked = None;
+    ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+        for line in lines.iter() {
+            explain_line(
+                ui,
+                line,
+                0,
+                &logic.selected_ast_span,
+                &mut logic.hovered_ast_span,
+                &mut clicked,
+            );
+        }
+    });
+
+    // Clicking on the selected line again deselects it
+    if let Some(span) = clicked {
+        logic.selected_ast_span = (logic.selected_ast_span.as_ref() != Some(&span)).then_some(span);
+    }
+}
+
+/// Displays a line of the explanation indented by its depth, followed by the lines inside of it,
+/// recording the span of the line that the pointer is over in `hovered`, and the span of the line that was clicked in `clicked`
+fn explain_line(
+    ui: &mut Ui,
+    line: &ExplainLine,
+    depth: usize,
+    selected: &Option<Range<usize>>,
+    hovered: &mut Option<Range<usize>>,
+    clicked: &mut Option<Range<usize>>,
+) {
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let mut job = LayoutJob::default();
+
+    // Capture groups are marked with a chip of the color that they are highlighted with
+    if let Some(color) = line.color {
+        job.append(
+            "  ",
+            0.0,
+            TextFormat {
+                font_id: font_id.clone(),
+                background: color,
+                ..Default::default()
+            },
+        );
+        job.append(
+            " ",
+            0.0,
+            TextFormat::simple(font_id.clone(), Color32::TRANSPARENT),
+        );
+    }
+    if line.bullet {
+        job.append("• ", 0.0, TextFormat::simple(font_id.clone(), text_color));
+    }
+    job.append(&line.text, 0.0, TextFormat::simple(font_id, text_color));
+
+    ui.horizontal(|ui| {
+        ui.add_space(ui.spacing().indent * depth as f32);
+        let response = ui.selectable_label(selected.as_ref() == Some(&line.span), job);
+        if response.hovered() {
+            *hovered = Some(line.span.clone());
+        }
+        if response.clicked() {
+            *clicked = Some(line.span.clone());
+        }
+    });
+
+    for child in &line.children {
+        explain_line(ui, child, depth + 1, selected, hovered, clicked);
+    }
+}
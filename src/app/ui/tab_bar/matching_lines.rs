@@ -0,0 +1,111 @@
+use crate::app::{state::AppState, ui::inspector::save_export};
+use egui::{Label, RichText, ScrollArea, Sense, TextStyle, Ui};
+
+/// The name of the file that the listed lines are saved to
+const LINES_FILE_NAME: &str = "regex_visualiser_lines.txt";
+
+/// Displays each line of the input text that the regex matches, or doesn't match if inverted, like `grep`,
+/// where clicking a line scrolls the input editor to it
+pub fn matching_lines(ui: &mut Ui, state: &mut AppState) {
+    let wrap = ui.style_mut().wrap.replace(false);
+    ui.heading("Matching Lines");
+    ui.separator();
+    ui.style_mut().wrap = wrap;
+
+    let tab = &mut state.widgets.lines_tab;
+    ui.checkbox(&mut tab.invert, "Invert")
+        .on_hover_text("List the lines that don't match instead, like grep -v");
+    let invert = tab.invert;
+
+    let logic = match &mut state.logic {
+        Ok(logic) if logic.empty_pattern => {
+            ui.label("Enter a pattern to begin.");
+            return;
+        }
+        Ok(logic) => logic,
+        Err(_) => {
+            ui.label("The regular expression is malformed. Click the red ⊗ to view the error.");
+            return;
+        }
+    };
+
+    // The lines are of the input text from before any pending edits, which may no longer be in bounds
+    if state.pending_edit.is_some() {
+        ui.weak("Waiting for typing to pause…");
+        return;
+    }
+
+    let input_text = &state.widgets.input_text;
+    let line_matches = logic.line_matches(input_text);
+    let lines = line_matches
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, matched))| *matched != invert)
+        .map(|(number, (range, _))| (number + 1, range.clone()))
+        .collect::<Vec<_>>();
+
+    let description = if invert { "don't match" } else { "match" };
+    ui.weak(format!(
+        "{} of {} lines {}",
+        lines.len(),
+        line_matches.len(),
+        description
+    ));
+
+    let tab = &mut state.widgets.lines_tab;
+    ui.horizontal(|ui| {
+        let output = || {
+            lines
+                .iter()
+                .map(|(_, range)| &input_text[range.clone()])
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        if ui.button("📋 Copy").clicked() {
+            ui.output().copied_text = output();
+        }
+        if ui.button("💾 Save").clicked() {
+            tab.save_status = Some(save_export(&output(), LINES_FILE_NAME));
+        }
+        if let Some(status) = &tab.save_status {
+            ui.weak(status.as_str());
+        }
+    });
+    ui.separator();
+
+    // Only the lines that are scrolled into view are laid out, as there may be very many of them
+    let number_width = lines
+        .last()
+        .map_or(1, |(number, _)| number.to_string().len());
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    let mut clicked = None;
+    ScrollArea::both().auto_shrink([false; 2]).show_rows(
+        ui,
+        row_height,
+        lines.len(),
+        |ui, rows| {
+            for (number, range) in &lines[rows] {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("{:>1$}", number, number_width))
+                            .monospace()
+                            .weak(),
+                    );
+                    let text = RichText::new(&input_text[range.clone()]).monospace();
+                    if ui
+                        .add(Label::new(text).wrap(false).sense(Sense::click()))
+                        .on_hover_text("Click to scroll to this line in the input text")
+                        .clicked()
+                    {
+                        clicked = Some(range.clone());
+                    }
+                });
+            }
+        },
+    );
+
+    if clicked.is_some() {
+        state.input_scroll_target = clicked;
+    }
+}
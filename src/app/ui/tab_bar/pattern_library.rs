@@ -0,0 +1,98 @@
+use crate::app::state::AppState;
+use egui::{Button, Grid, Label, RichText, ScrollArea, TextEdit, Ui};
+
+/// Displays the patterns saved in the pattern library, along with controls for saving the current pattern
+pub fn pattern_library(ui: &mut Ui, state: &mut AppState) {
+    let wrap = std::mem::replace(&mut ui.style_mut().wrap, Some(false));
+    ui.heading("Pattern Library");
+    ui.separator();
+    ui.style_mut().wrap = wrap;
+
+    save_current(ui, state);
+    ui.separator();
+
+    if state.widgets.pattern_library.is_empty() {
+        ui.weak("The library is empty. Save the current pattern to add it to the library.");
+        return;
+    }
+
+    let mut loaded = None;
+    let mut deleted = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        Grid::new("pattern_library")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, entry) in state.widgets.pattern_library.iter() {
+                    ui.vertical(|ui| {
+                        ui.strong(name);
+                        ui.add(Label::new(RichText::new(&entry.pattern).monospace()).wrap(false));
+                    });
+                    if ui
+                        .small_button("Load")
+                        .on_hover_text(
+                            "Replace the regex, replacement and input text with those of this entry",
+                        )
+                        .clicked()
+                    {
+                        loaded = Some(name.to_owned());
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        deleted = Some(name.to_owned());
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    if let Some(name) = loaded {
+        state.load_from_library(&name, ui.style());
+    }
+    if let Some(name) = deleted {
+        state.widgets.pattern_library.remove(&name);
+    }
+}
+
+/// Displays a text field for naming the current pattern and a button for saving it to the library,
+/// which asks for confirmation before replacing an entry that was already saved under the same name
+fn save_current(ui: &mut Ui, state: &mut AppState) {
+    let tab = &mut state.widgets.library_tab;
+    let name = tab.name.trim().to_owned();
+    let mut save = false;
+
+    ui.horizontal(|ui| {
+        let response = TextEdit::singleline(&mut tab.name)
+            .hint_text("Name")
+            .desired_width(160.0)
+            .show(ui)
+            .response;
+        if response.changed() {
+            tab.confirm_overwrite = false;
+        }
+
+        if tab.confirm_overwrite {
+            ui.label(format!("Replace \"{}\"?", name));
+            if ui.button("Replace").clicked() {
+                save = true;
+            }
+            if ui.button("Cancel").clicked() {
+                tab.confirm_overwrite = false;
+            }
+        } else if ui
+            .add_enabled(!name.is_empty(), Button::new("💾 Save Current"))
+            .on_hover_text("Save the regex, replacement and input text under this name")
+            .clicked()
+        {
+            if state.widgets.pattern_library.contains(&name) {
+                tab.confirm_overwrite = true;
+            } else {
+                save = true;
+            }
+        }
+    });
+
+    if save {
+        state.save_to_library(name);
+        state.widgets.library_tab = Default::default();
+    }
+}
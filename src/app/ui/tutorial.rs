@@ -0,0 +1,159 @@
+use crate::app::{
+    state::{AppState, ResultModeState},
+    tutorial::{TutorialAttempt, TutorialStep, TutorialTarget, TUTORIAL_STEPS},
+};
+use egui::{Button, Color32, Context, Id, LayerId, Order, ProgressBar, Rect, Stroke, Ui, Window};
+
+/// Records where a part of the UI that tutorial steps can draw attention to was displayed this frame
+pub fn record_target(ctx: &Context, target: TutorialTarget, rect: Rect) {
+    ctx.data().insert_temp(Id::new(target), rect);
+}
+
+/// Adds a window that guides the user through building a regex step by step
+pub fn tutorial(ctx: &Context, state: &mut AppState) {
+    if !state.widgets.tutorial.active {
+        return;
+    }
+
+    let mut open = true;
+    let response = Window::new("Tutorial")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| tutorial_ui(ui, state));
+
+    let target = TUTORIAL_STEPS
+        .get(state.widgets.tutorial.step)
+        .and_then(|step| step.target);
+
+    if let (Some(response), Some(target)) = (response, target) {
+        if state.widgets.tutorial.active {
+            callout(ctx, response.response.rect, target);
+        }
+    }
+
+    state.widgets.tutorial.active &= open;
+}
+
+/// Displays the instruction for the current step of the tutorial, along with controls for moving between steps
+fn tutorial_ui(ui: &mut Ui, state: &mut AppState) {
+    let index = state.widgets.tutorial.step.min(TUTORIAL_STEPS.len() - 1);
+    let step = &TUTORIAL_STEPS[index];
+
+    let outcome = check_step(state, step);
+    let tutorial = &mut state.widgets.tutorial;
+    if outcome.is_ok() {
+        tutorial.unlocked = tutorial.unlocked.max(index + 1);
+    }
+
+    let progress = tutorial.unlocked.min(TUTORIAL_STEPS.len()) as f32 / TUTORIAL_STEPS.len() as f32;
+    ui.add(ProgressBar::new(progress).text(format!(
+        "Step {} of {}",
+        index + 1,
+        TUTORIAL_STEPS.len()
+    )));
+
+    ui.heading(step.title);
+    ui.label(step.instruction);
+
+    match &outcome {
+        Ok(()) => ui.colored_label(Color32::GREEN, "✓ Done"),
+        Err(hint) => ui.weak(hint),
+    };
+
+    let unlocked = index < tutorial.unlocked;
+    ui.horizontal(|ui| {
+        if ui.add_enabled(index > 0, Button::new("◀ Back")).clicked() {
+            state.begin_tutorial_step(index - 1, ui.style());
+        }
+
+        if index + 1 < TUTORIAL_STEPS.len() {
+            let next = ui.add_enabled(unlocked, Button::new("Next ▶"));
+            if next
+                .on_disabled_hover_text("Complete this step first")
+                .clicked()
+            {
+                state.begin_tutorial_step(index + 1, ui.style());
+            }
+        } else if ui.add_enabled(unlocked, Button::new("Finish")).clicked() {
+            state.widgets.tutorial.active = false;
+        }
+
+        if ui
+            .button("Reload Input")
+            .on_hover_text("Replace the input text with the text for this step")
+            .clicked()
+        {
+            state.begin_tutorial_step(index, ui.style());
+        }
+
+        if ui.button("Show Answer").clicked() {
+            show_answer(ui, state, step);
+        }
+    });
+}
+
+/// Checks whether the regex and input text in the editor complete the given step
+fn check_step(state: &AppState, step: &TutorialStep) -> Result<(), String> {
+    if state.widgets.byte_mode {
+        return Err("Turn off Byte Mode, as the steps match text rather than bytes".into());
+    }
+    if state.widgets.results_unavailable().is_some() {
+        return Err("Switch back to the regex engine, as the steps are checked with it".into());
+    }
+
+    let logic = match &state.logic {
+        Ok(logic) if logic.empty_pattern => {
+            return Err("Type a pattern into the regex editor".into())
+        }
+        Ok(logic) => logic,
+        Err(_) => return Err("Fix the error in the pattern first".into()),
+    };
+
+    let replace_text = match &state.widgets.result_mode {
+        ResultModeState::Replace { replace_text, .. } => Some(replace_text.as_str()),
+        _ => None,
+    };
+
+    (step.check)(&TutorialAttempt {
+        regex: &logic.regex,
+        input: &state.widgets.input_text,
+        replace_text,
+    })
+}
+
+/// Puts the solution to the given step into the editor
+fn show_answer(ui: &Ui, state: &mut AppState, step: &TutorialStep) {
+    state.widgets.regex_text = step.solution.into();
+    state.recompile(ui.style());
+
+    if let Some(solution) = step.solution_replace_text {
+        state.widgets.switch_result_mode(ResultModeState::default());
+        if let ResultModeState::Replace { replace_text, .. } = &mut state.widgets.result_mode {
+            *replace_text = solution.into();
+        }
+        state.widgets.result_mode.invalidate();
+    }
+}
+
+/// Paints an arrow from the tutorial window to the part of the UI that the current step is about,
+/// if that part of the UI was displayed this frame
+fn callout(ctx: &Context, window: Rect, target: TutorialTarget) {
+    let id = Id::new(target);
+    let rect = match ctx.data().get_temp::<Rect>(id) {
+        Some(rect) => rect.expand(4.0),
+        None => return,
+    };
+
+    // Forget the position, so that the arrow isn't drawn to where the target was if it stops being displayed
+    ctx.data().remove::<Rect>(id);
+
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("tutorial_callout")));
+    let stroke = Stroke::new(3.0, ctx.style().visuals.selection.bg_fill);
+    painter.rect_stroke(rect, 4.0, stroke);
+
+    let origin = window.clamp(rect.center());
+    let tip = rect.clamp(window.center());
+    if origin.distance(tip) > 16.0 {
+        painter.arrow(origin, tip - origin, stroke);
+    }
+}
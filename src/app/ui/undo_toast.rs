@@ -0,0 +1,47 @@
+use crate::app::state::AppState;
+use egui::{Align2, Area, Context, Frame, Id, Vec2};
+use std::time::Duration;
+
+/// How many seconds the offer to undo a replacement of the input text is shown for
+const TOAST_DURATION: f64 = 6.0;
+
+/// Adds a notification offering to undo the most recent replacement of the input text, shortly after it happens
+pub fn undo_toast(ctx: &Context, state: &mut AppState) {
+    let id = Id::new("undo_toast");
+    let now = ctx.input().time;
+
+    if state.input_history.unannounced {
+        state.input_history.unannounced = false;
+        ctx.data().insert_temp(id, now + TOAST_DURATION);
+    }
+
+    let expiry = match ctx.data().get_temp::<f64>(id) {
+        Some(expiry) if expiry > now && state.input_history.can_undo() => expiry,
+        _ => return,
+    };
+
+    // Make sure the notification disappears on time, even if nothing else causes a repaint
+    ctx.request_repaint_after(Duration::from_secs_f64(expiry - now));
+
+    let mut dismissed = false;
+    Area::new("undo_toast")
+        .anchor(Align2::CENTER_BOTTOM, Vec2::new(0.0, -16.0))
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("The input text was replaced");
+
+                    if ui.button("Undo").clicked() {
+                        state.undo_input_change(ui.style());
+                        dismissed = true;
+                    }
+
+                    dismissed |= ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                });
+            });
+        });
+
+    if dismissed {
+        ctx.data().remove::<f64>(id);
+    }
+}
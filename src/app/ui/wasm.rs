@@ -1,5 +1,9 @@
 use self::banner::banner;
-use super::{editor::editor, inspector::inspector, tab_bar::tab_bar};
+use super::{
+    clipboard_test::clipboard_test, editor::editor, file_drop::file_drop, inspector::inspector,
+    journal::journal, settings::settings, tab_bar::tab_bar, tutorial::tutorial,
+    undo_toast::undo_toast,
+};
 use crate::app::state::AppState;
 use egui::Context;
 
@@ -7,8 +11,14 @@ mod banner;
 
 /// Displays and updates the entire ui
 pub fn root(ctx: &Context, state: &mut AppState) {
-    banner(ctx);
+    banner(ctx, state);
     tab_bar(ctx, state);
     inspector(ctx, state);
     editor(ctx, state);
+    clipboard_test(ctx, state);
+    journal(ctx, state);
+    settings(ctx, state);
+    tutorial(ctx, state);
+    undo_toast(ctx, state);
+    file_drop(ctx, state);
 }
@@ -1,13 +1,18 @@
-use crate::app::ui::toggle_theme;
-use egui::{Align, Context, Frame, Layout, RichText, TopBottomPanel, Ui};
+use crate::app::{
+    export::{download_output, AstFormat},
+    share::{session_link, SharedSession},
+    state::AppState,
+    ui::{toggle_theme, COMMAND_NAME},
+};
+use egui::{Align, Button, Context, Frame, Layout, RichText, TopBottomPanel, Ui};
 
 /// Adds a container that displays a banner at the top of the window
-pub fn banner(ctx: &Context) {
-    TopBottomPanel::top("banner").show(ctx, |ui| banner_ui(ui, ctx));
+pub fn banner(ctx: &Context, state: &mut AppState) {
+    TopBottomPanel::top("banner").show(ctx, |ui| banner_ui(ui, ctx, state));
 }
 
 /// Displays a banner at the top of the window
-pub fn banner_ui(ui: &mut Ui, ctx: &Context) {
+pub fn banner_ui(ui: &mut Ui, ctx: &Context, state: &mut AppState) {
     Frame::none().inner_margin(8.0).show(ui, |ui| {
         egui::menu::bar(ui, |ui| {
             ui.heading("Regex Visualiser");
@@ -32,10 +37,78 @@ pub fn banner_ui(ui: &mut Ui, ctx: &Context) {
                     '🌙'
                 };
 
-                if ui.button(RichText::new(icon).size(20.0)).clicked() {
-                    ctx.set_visuals(toggle_theme(&ctx.style().visuals));
+                if ui
+                    .button(RichText::new(icon).size(20.0))
+                    .on_hover_text(format!("Toggle Theme ({}+D)", COMMAND_NAME))
+                    .clicked()
+                {
+                    toggle_theme(ctx, state);
+                }
+
+                if ui
+                    .button(RichText::new("⚙").size(20.0))
+                    .on_hover_text("Settings")
+                    .clicked()
+                {
+                    state.widgets.settings_visible = true;
+                }
+
+                ui.checkbox(&mut state.widgets.reduce_motion, "Reduce Motion");
+                ui.checkbox(&mut state.widgets.inspector.visible, "Inspector")
+                    .on_hover_text(format!("{}+I", COMMAND_NAME));
+
+                let enabled = state.input_history.can_undo();
+                if ui
+                    .add_enabled(enabled, Button::new("↶ Undo Input Change"))
+                    .on_hover_text("Restore the input text from before it was last replaced")
+                    .clicked()
+                {
+                    state.undo_input_change(ui.style());
+                }
+
+                if ui
+                    .button("🔗 Copy Link")
+                    .on_hover_text(
+                        "Copy a link that opens the current regex, input and replacement text",
+                    )
+                    .clicked()
+                {
+                    let session = SharedSession::from_widgets(&state.widgets);
+                    if let Some(link) = session_link(&session) {
+                        ui.output().copied_text = link;
+                    }
+                }
+
+                ui.add_enabled_ui(state.logic.is_ok(), |ui| {
+                    ui.menu_button("⬇ Export AST", |ui| export_ast_menu(ui, state));
+                });
+
+                if ui.button("📓 Pattern Journal").clicked() {
+                    state.widgets.journal_viewer.visible = true;
+                }
+
+                if ui.button("🎓 Tutorial").clicked() {
+                    state.begin_tutorial_step(state.widgets.tutorial.step, ui.style());
                 }
             });
         });
     });
 }
+
+/// Offers a download of the AST of the regex in each of the formats that it can be exported in
+fn export_ast_menu(ui: &mut Ui, state: &AppState) {
+    let logic = match &state.logic {
+        Ok(logic) => logic,
+        Err(_) => return,
+    };
+
+    for format in [AstFormat::Dot, AstFormat::Json] {
+        if ui.button(format!("As {}", format.name())).clicked() {
+            ui.close_menu();
+            let output = format.export(&logic.ast, &state.widgets.regex_text);
+            // The browser only refuses the download if it is unable to download anything at all,
+            // which there is nowhere in the banner to report
+            let _ = download_output(&output, format.file_name());
+        }
+    }
+}
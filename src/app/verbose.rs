@@ -0,0 +1,368 @@
+use super::{
+    parsing::{parse_regex, RegexError, RegexFlags},
+    text::GetRangeExt,
+};
+use regex_syntax::ast::{Ast, Group, GroupKind};
+use std::iter::Peekable;
+
+/// Pretty-prints the given pattern in verbose mode (With the `x` flag set), putting each group and each alternative
+/// of an alternation on its own line, indented by how deeply it is nested, with a comment naming each capture group
+///
+/// Any whitespace and comments that the pattern already has are removed first, see `compact_verbose`
+pub fn format_verbose(pattern: &str, flags: RegexFlags) -> Result<String, RegexError> {
+    let pattern = compact_verbose(pattern, flags.ignore_whitespace);
+    let flags = RegexFlags {
+        ignore_whitespace: false,
+        ..flags
+    };
+    let ast = parse_regex(&pattern, flags)?;
+
+    let mut formatter = VerboseFormatter {
+        pattern: &pattern,
+        lines: vec!["(?x)".into()],
+        line: String::new(),
+    };
+    formatter.write(&ast, 0);
+    formatter.end_line(0, None);
+    Ok(formatter.lines.join("\n"))
+}
+
+/// Removes the whitespace and comments that verbose mode ignores from the given pattern, along with the `x` flag itself,
+/// so that the pattern means the same without verbose mode
+///
+/// `verbose` is whether verbose mode is enabled for the whole pattern, rather than only by flags within it
+pub fn compact_verbose(pattern: &str, verbose: bool) -> String {
+    let mut compact = String::with_capacity(pattern.len());
+    let mut verbose = verbose;
+    // Whether verbose mode was enabled outside of each group that the scan is inside of
+    let mut outer = Vec::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if verbose && c.is_whitespace() => {}
+            '#' if verbose => skip_comment(&mut chars),
+            '\\' => compact_escape(&mut chars, verbose, &mut compact),
+            '[' => compact_class(&mut chars, verbose, &mut compact),
+            '(' => {
+                outer.push(verbose);
+                compact.push(c);
+                if verbose {
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                }
+                if chars.peek() == Some(&'?') {
+                    compact_flags(&mut chars, &mut verbose, &mut outer, &mut compact);
+                }
+            }
+            ')' => {
+                compact.push(c);
+                if let Some(previous) = outer.pop() {
+                    verbose = previous;
+                }
+            }
+            c => compact.push(c),
+        }
+    }
+    compact
+}
+
+/// Lays out the nodes of an AST over indented lines
+struct VerboseFormatter<'a> {
+    /// The pattern that the AST was parsed from, without verbose mode
+    pattern: &'a str,
+    lines: Vec<String>,
+    /// The text of the line being written, before it is indented
+    line: String,
+}
+
+impl VerboseFormatter<'_> {
+    fn write(&mut self, ast: &Ast, depth: usize) {
+        match ast {
+            Ast::Concat(concat) => {
+                for ast in &concat.asts {
+                    self.write(ast, depth);
+                }
+            }
+            // Alternatives are lined up after the `|` that separates them
+            Ast::Alternation(alternation) => {
+                for (index, ast) in alternation.asts.iter().enumerate() {
+                    self.end_line(depth, None);
+                    self.line.push_str(if index == 0 { "  " } else { "| " });
+                    self.write(ast, depth);
+                }
+                self.end_line(depth, None);
+            }
+            Ast::Group(group) => self.write_group(group, "", depth),
+            Ast::Repetition(repetition) => match &*repetition.ast {
+                Ast::Group(group) => {
+                    let op = &self.pattern[repetition.op.span.range()];
+                    self.write_group(group, op, depth)
+                }
+                _ => self.write_source(ast),
+            },
+            _ => self.write_source(ast),
+        }
+    }
+
+    /// Writes a group over at least two lines, with the parentheses on their own lines and the contents between them,
+    /// followed by the given repetition operator
+    fn write_group(&mut self, group: &Group, op: &str, depth: usize) {
+        let comment = match &group.kind {
+            GroupKind::CaptureIndex(index) => Some(format!("group {}", index)),
+            GroupKind::CaptureName(name) => Some(format!("group {}: {}", name.index, name.name)),
+            GroupKind::NonCapturing(_) => None,
+        };
+
+        // A group at the start of an alternative stays on the same line as the `|` before it
+        if !matches!(self.line.as_str(), "  " | "| ") {
+            self.end_line(depth, None);
+        }
+        self.line
+            .push_str(&self.pattern[group.span.start.offset..group.ast.span().start.offset]);
+        self.end_line(depth, comment);
+        self.write(&group.ast, depth + 1);
+        self.end_line(depth + 1, None);
+        self.line.push(')');
+        self.line.push_str(op);
+        self.end_line(depth, None);
+    }
+
+    /// Writes the source of the given node, escaping anything in it that verbose mode would ignore
+    fn write_source(&mut self, ast: &Ast) {
+        let mut chars = self.pattern[ast.span().range()].chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    self.line.push(c);
+                    self.line.extend(chars.next());
+                }
+                ' ' | '#' => {
+                    self.line.push('\\');
+                    self.line.push(c);
+                }
+                '\n' => self.line.push_str(r"\n"),
+                '\t' => self.line.push_str(r"\t"),
+                '\r' => self.line.push_str(r"\r"),
+                c if c.is_whitespace() => self.line.push_str(&format!(r"\x{{{:X}}}", c as u32)),
+                c => self.line.push(c),
+            }
+        }
+    }
+
+    /// Adds the line being written to the lines at the given depth, with the given comment after it,
+    /// unless nothing has been written to it
+    fn end_line(&mut self, depth: usize, comment: Option<String>) {
+        let line = std::mem::take(&mut self.line);
+        if line.trim().is_empty() {
+            return;
+        }
+        // Spaces are escaped everywhere but after a `|` that starts an empty alternative
+        let line = if line == "| " { "|" } else { &line };
+
+        let indent = "    ".repeat(depth);
+        self.lines.push(match comment {
+            Some(comment) => format!("{}{}  # {}", indent, line, comment),
+            None => format!("{}{}", indent, line),
+        });
+    }
+}
+
+/// Skips the rest of a verbose mode comment, up to and including the newline that ends it
+fn skip_comment(chars: &mut impl Iterator<Item = char>) {
+    for c in chars {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// Copies an escape sequence from `chars` into `compact`, after its `\\` has been consumed,
+/// unescaping whitespace if verbose mode is enabled, as it can only be escaped in verbose mode
+fn compact_escape(chars: &mut impl Iterator<Item = char>, verbose: bool, compact: &mut String) {
+    match chars.next() {
+        Some(c) if verbose && c.is_whitespace() => compact.push(c),
+        c => {
+            compact.push('\\');
+            compact.extend(c);
+        }
+    }
+}
+
+/// Copies the flags of a group that starts with `(?` from `chars` into `compact`, leaving out the `x` flag,
+/// and updates whether verbose mode is enabled accordingly
+///
+/// A group that only sets flags, such as `(?x)`, isn't a group that is ever closed,
+/// so it is removed from `outer`, and it is removed entirely if it only set the `x` flag
+fn compact_flags(
+    chars: &mut Peekable<impl Iterator<Item = char> + Clone>,
+    verbose: &mut bool,
+    outer: &mut Vec<bool>,
+    compact: &mut String,
+) {
+    let flags = chars
+        .clone()
+        .skip(1)
+        .take_while(|c| c.is_ascii_alphabetic() || *c == '-')
+        .collect::<String>();
+    let end = chars.clone().nth(flags.len() + 1);
+    if flags.is_empty() || !matches!(end, Some(':' | ')')) {
+        // Not a group with flags, such as `(?:` or `(?P<name>`
+        return;
+    }
+    chars.nth(flags.len());
+
+    let mut kept = String::new();
+    let mut negated = false;
+    for flag in flags.chars() {
+        match flag {
+            '-' => negated = true,
+            'x' => *verbose = !negated,
+            _ => {}
+        }
+        if flag != 'x' {
+            kept.push(flag);
+        }
+    }
+    if kept.ends_with('-') {
+        kept.pop();
+    }
+
+    if end == Some(')') {
+        outer.pop();
+        chars.next();
+        if kept.is_empty() {
+            compact.pop();
+        } else {
+            compact.push('?');
+            compact.push_str(&kept);
+            compact.push(')');
+        }
+    } else {
+        compact.push('?');
+        compact.push_str(&kept);
+    }
+}
+
+/// Copies a character class from `chars` into `compact`, after its opening `[` has been copied,
+/// removing the whitespace and comments in it if verbose mode is enabled
+fn compact_class(
+    chars: &mut Peekable<impl Iterator<Item = char>>,
+    verbose: bool,
+    compact: &mut String,
+) {
+    compact.push('[');
+    if let Some(c) = chars.next_if_eq(&'^') {
+        compact.push(c);
+    }
+
+    // A `]` at the start of a class is a literal
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        match c {
+            c if verbose && c.is_whitespace() => continue,
+            '#' if verbose => {
+                skip_comment(chars);
+                continue;
+            }
+            '\\' => compact_escape(chars, verbose, compact),
+            // An ASCII class, such as `[:alpha:]`
+            '[' if chars.peek() == Some(&':') => {
+                compact.push(c);
+                for c in chars.by_ref() {
+                    compact.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '[' => compact_class(chars, verbose, compact),
+            ']' if !first => {
+                compact.push(c);
+                return;
+            }
+            c => compact.push(c),
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{parsing::compile_regex, text::find_captures};
+
+    /// Verbose mode is enabled within the formatted pattern, and turned off along with compacting it,
+    /// neither of which changes what the pattern captures
+    #[test]
+    fn formatting_and_compacting_preserve_captures() {
+        let verbose = RegexFlags {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let cases: &[(&str, RegexFlags, &[&str])] = &[
+            (
+                r"^(?P<year>\d{4})-(?P<month>\d{2})(?:-(\d{2}))?$",
+                Default::default(),
+                &["2024-05-06", "2024-05", "2024-5"],
+            ),
+            (
+                "a b#c|[ #x]+|(a|b||c)+?",
+                Default::default(),
+                &["a b#c", " # x", "aab", "ccc", ""],
+            ),
+            (
+                r"(?i)foo(?-i:BAR) z\t",
+                Default::default(),
+                &["FOOBAR z\t", "fooBAR z\t", "foobar z\t"],
+            ),
+            (
+                "(?x) a b # A comment\n [c d\\ ] ( ?-x: e ) (?i-x)f g",
+                Default::default(),
+                &["abc e f g", "ab  e F g", "abd e fg"],
+            ),
+            ("a b (c) [ d ]", verbose, &["abcd", "a b c d"]),
+            ("\u{a0}(\n)", Default::default(), &["\u{a0}\n", " \n"]),
+        ];
+
+        let compile = |pattern: &str, flags| compile_regex(pattern, flags).unwrap().1;
+        for &(pattern, flags, inputs) in cases {
+            let original = compile(pattern, flags);
+            let formatted = format_verbose(pattern, flags).unwrap();
+            let compacted = compact_verbose(&formatted, flags.ignore_whitespace);
+            assert!(
+                !compacted.contains('\n'),
+                "{:?} was compacted to {:?}",
+                formatted,
+                compacted
+            );
+
+            let without_verbose = RegexFlags {
+                ignore_whitespace: false,
+                ..flags
+            };
+            for regex in [
+                compile(&formatted, flags),
+                compile(&compacted, without_verbose),
+            ] {
+                for input in inputs {
+                    assert_eq!(
+                        find_captures(&regex, input),
+                        find_captures(&original, input),
+                        "{:?} as {:?} in {:?}",
+                        pattern,
+                        regex.as_str(),
+                        input
+                    );
+                }
+            }
+        }
+    }
+
+    /// Groups and alternatives are put on their own lines, indented by how deeply they're nested
+    #[test]
+    fn nesting_is_indented() {
+        assert_eq!(
+            format_verbose("a(?P<b>c|d)+e", Default::default()).unwrap(),
+            "(?x)\na\n(?P<b>  # group 1: b\n      c\n    | d\n)+\ne"
+        );
+    }
+}
@@ -1,4 +1,8 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
-pub use app::Application;
+pub use app::{install_panic_hook, Application};
+#[cfg(all(feature = "render-test", not(target_arch = "wasm32")))]
+pub use app::{run_render_test, RenderTestFixture, RENDER_TEST_FIXTURES};
+#[cfg(feature = "self-test")]
+pub use app::{run_self_test, run_self_test_case, SelfTestCase, SELF_TEST_CASES};
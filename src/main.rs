@@ -4,9 +4,27 @@
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    // Run the pipeline against representative inputs without opening a window, as a sanity check of the build
+    #[cfg(feature = "self-test")]
+    if std::env::args().any(|arg| arg == "--self-test") {
+        let success = regex_visualiser::run_self_test();
+        std::process::exit(if success { 0 } else { 1 });
+    }
+
+    // Render the editor off-screen and compare its layout against the goldens, or replace them with `--update-goldens`
+    #[cfg(feature = "render-test")]
+    if std::env::args().any(|arg| arg == "--render-test") {
+        let update = std::env::args().any(|arg| arg == "--update-goldens");
+        let success = regex_visualiser::run_render_test(update);
+        std::process::exit(if success { 0 } else { 1 });
+    }
+
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    // Record panics so that they can be reported the next time the app is launched
+    regex_visualiser::install_panic_hook();
+
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Regex Visualiser",
@@ -21,6 +39,9 @@ fn main() {
     // Make sure panics are logged using `console.error`.
     console_error_panic_hook::set_once();
 
+    // Record panics so that they can be reported the next time the app is launched
+    regex_visualiser::install_panic_hook();
+
     // Redirect tracing to console.log and friends:
     tracing_wasm::set_as_global_default();
 
@@ -0,0 +1,14 @@
+#!/usr/bin/env bash
+# This script reports the size of the compiled `.wasm` file with none of the optional features enabled, as the web app
+# is built, and with each of them enabled on its own, so that features that bloat the web app are easy to spot.
+set -eu
+
+wasm="target/wasm32-unknown-unknown/release/regex_visualiser.wasm"
+
+report() {
+    cargo build --quiet --release --bin regex_visualiser --target wasm32-unknown-unknown --no-default-features --features "$1"
+    printf '%-40s %10d bytes\n' "${2:-$1}" "$(wc -c < "$wasm")"
+}
+
+report "" "(none, as in the web app)"
+report "backend-fancy"